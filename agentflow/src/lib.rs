@@ -17,4 +17,4 @@ pub use workflow::{
 
 pub use config::{load_config, save_config, AgentFlowConfig, GlobalConfig};
 
-pub use llm_provider::{LLMInvokeOptions, LLMProvider};
+pub use llm_provider::{LLMInvokeOptions, LLMMessage, LLMMessageRole, LLMProvider};
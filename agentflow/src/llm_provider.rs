@@ -1,4 +1,5 @@
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
@@ -7,6 +8,12 @@ pub struct LLMInvokeOptions {
     pub model: Option<String>,
     pub temperature: Option<f32>,
     pub max_tokens: Option<u32>,
+    pub top_p: Option<f32>,
+    pub stop: Option<Vec<String>>,
+    pub seed: Option<u64>,
+    pub presence_penalty: Option<f32>,
+    pub frequency_penalty: Option<f32>,
+    pub n: Option<u32>,
     pub metadata: HashMap<String, serde_json::Value>,
 }
 
@@ -17,14 +24,87 @@ impl Default for LLMInvokeOptions {
             model: None,
             temperature: None,
             max_tokens: None,
+            top_p: None,
+            stop: None,
+            seed: None,
+            presence_penalty: None,
+            frequency_penalty: None,
+            n: None,
             metadata: HashMap::new(),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LLMMessageRole {
+    System,
+    User,
+    Assistant,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LLMMessage {
+    pub role: LLMMessageRole,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl LLMMessage {
+    pub fn new(role: LLMMessageRole, content: impl Into<String>) -> Self {
+        Self {
+            role,
+            content: content.into(),
+            name: None,
+        }
+    }
+
+    pub fn system(content: impl Into<String>) -> Self {
+        Self::new(LLMMessageRole::System, content)
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self::new(LLMMessageRole::User, content)
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self::new(LLMMessageRole::Assistant, content)
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+}
+
 #[async_trait]
 pub trait LLMProvider: Send + Sync {
     async fn invoke(&self, prompt: &str, options: &LLMInvokeOptions) -> anyhow::Result<String>;
+
+    /// 多轮对话调用，未原生支持消息列表的实现会退化为拼接后的单个 prompt
+    async fn chat(
+        &self,
+        messages: &[LLMMessage],
+        options: &LLMInvokeOptions,
+    ) -> anyhow::Result<String> {
+        let prompt = messages
+            .iter()
+            .map(|m| {
+                let role = match m.role {
+                    LLMMessageRole::System => "system",
+                    LLMMessageRole::User => "user",
+                    LLMMessageRole::Assistant => "assistant",
+                };
+                match &m.name {
+                    Some(name) => format!("[{}] {}: {}", role, name, m.content),
+                    None => format!("[{}] {}", role, m.content),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        self.invoke(&prompt, options).await
+    }
     
     fn name(&self) -> &str;
 }
@@ -0,0 +1,106 @@
+use crate::error::AdapterError;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug)]
+pub struct AdaptiveConfig {
+    pub min_limit: usize,
+    pub max_limit: usize,
+    /// 延迟超过该值视为拥塞；为 None 时只根据 429/5xx/超时调整
+    pub latency_threshold: Option<Duration>,
+    pub decrease_factor: f64,
+    /// 两次收缩之间的最短间隔，同一波错误只收缩一次
+    pub cooldown: Duration,
+}
+
+impl Default for AdaptiveConfig {
+    fn default() -> Self {
+        Self {
+            min_limit: 1,
+            max_limit: 100,
+            latency_threshold: None,
+            decrease_factor: 0.75,
+            cooldown: Duration::from_secs(1),
+        }
+    }
+}
+
+struct LimitState {
+    limit: f64,
+    last_decrease: Option<Instant>,
+}
+
+/// 加性增、乘性减：正常完成时上限每轮加 1，拥塞时按系数收缩
+pub struct AdaptiveLimit {
+    config: AdaptiveConfig,
+    state: Mutex<LimitState>,
+}
+
+impl AdaptiveLimit {
+    pub fn new(config: AdaptiveConfig, initial: usize) -> Self {
+        let initial = initial.clamp(config.min_limit, config.max_limit.max(config.min_limit));
+        Self {
+            config,
+            state: Mutex::new(LimitState {
+                limit: initial as f64,
+                last_decrease: None,
+            }),
+        }
+    }
+
+    pub fn config(&self) -> &AdaptiveConfig {
+        &self.config
+    }
+
+    pub fn limit(&self) -> usize {
+        self.state.lock().unwrap().limit as usize
+    }
+
+    /// 记录一次上游调用，上限变化时返回新值。
+    /// 只有用满一半以上名额时才扩容，避免空闲期把上限推到最大
+    pub fn record(&self, latency: Duration, overloaded: bool, in_flight: usize) -> Option<usize> {
+        let congested = overloaded
+            || self
+                .config
+                .latency_threshold
+                .is_some_and(|threshold| latency > threshold);
+
+        let mut state = self.state.lock().unwrap();
+        let before = state.limit as usize;
+        let max_limit = self.config.max_limit.max(self.config.min_limit) as f64;
+
+        if congested {
+            let now = Instant::now();
+            if state
+                .last_decrease
+                .is_some_and(|at| now.duration_since(at) < self.config.cooldown)
+            {
+                return None;
+            }
+            state.last_decrease = Some(now);
+            state.limit = (state.limit * self.config.decrease_factor)
+                .floor()
+                .max(self.config.min_limit as f64);
+        } else if in_flight * 2 >= before {
+            state.limit = (state.limit + 1.0 / state.limit).min(max_limit);
+        }
+
+        let after = state.limit as usize;
+        (after != before).then_some(after)
+    }
+}
+
+/// 上游限流、5xx 与超时说明提供商已接近容量上限
+pub(crate) fn is_overload<T>(result: &anyhow::Result<T>) -> bool {
+    match result {
+        Ok(_) => false,
+        Err(e) => matches!(
+            e.downcast_ref::<AdapterError>(),
+            Some(
+                AdapterError::RateLimited { .. }
+                    | AdapterError::Upstream5xx { .. }
+                    | AdapterError::Timeout(_)
+            )
+        ),
+    }
+}
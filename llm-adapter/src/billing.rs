@@ -1,46 +1,98 @@
+use crate::billing_report::in_range;
+use crate::billing_store::{BillingStore, RecordStream};
+use crate::pricing::{default_currency, ModelPrice, PricingCatalog};
+use crate::response::Usage;
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BillingConfig {
     pub input_price_per_1k: f64,
     pub output_price_per_1k: f64,
+    /// 命中缓存的输入 token 单价，未设置时按普通输入价格计费
+    #[serde(default)]
+    pub cached_input_price_per_1k: Option<f64>,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    /// 单次调用最少计费的 token 数
     pub min_charge_tokens: u64,
+    /// 每个适配器在内存中保留的最近记录数，完整历史在 `BillingStore` 中
+    #[serde(default = "default_max_records")]
+    pub max_records: usize,
     pub enabled: bool,
 }
 
+fn default_max_records() -> usize {
+    1000
+}
+
 impl Default for BillingConfig {
     fn default() -> Self {
         Self {
             input_price_per_1k: 0.001,
             output_price_per_1k: 0.002,
+            cached_input_price_per_1k: None,
+            currency: default_currency(),
             min_charge_tokens: 0,
+            max_records: default_max_records(),
             enabled: true,
         }
     }
 }
 
+impl BillingConfig {
+    /// 价格目录中没有匹配项时使用的统一价格
+    fn flat_price(&self) -> ModelPrice {
+        ModelPrice {
+            adapter: "*".to_string(),
+            model: "*".to_string(),
+            input_price_per_1k: self.input_price_per_1k,
+            output_price_per_1k: self.output_price_per_1k,
+            cached_input_price_per_1k: self.cached_input_price_per_1k,
+            currency: self.currency.clone(),
+            effective_from: None,
+            effective_until: None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct UsageRecord {
     pub adapter_name: String,
     pub user_id: Option<String>,
     pub request_id: String,
+    #[serde(default)]
+    pub model: Option<String>,
     pub input_tokens: u64,
     pub output_tokens: u64,
+    #[serde(default)]
+    pub cached_tokens: u64,
     pub total_cost: f64,
+    #[serde(default = "default_currency")]
+    pub currency: String,
     pub timestamp: DateTime<Utc>,
     pub metadata: serde_json::Value,
 }
 
+impl UsageRecord {
+    /// 调用方传入的租户，记录在 `metadata.tenant_id` 中
+    pub fn tenant_id(&self) -> Option<&str> {
+        self.metadata.get("tenant_id").and_then(|v| v.as_str())
+    }
+}
+
 pub struct BillingTracker {
     config: Arc<RwLock<BillingConfig>>,
-    records: Arc<DashMap<String, Vec<UsageRecord>>>, // adapter_name -> records
-    user_stats: Arc<DashMap<String, UserBillingStats>>, // user_id -> stats
+    records: Arc<DashMap<String, VecDeque<UsageRecord>>>, // adapter_name -> records
+    user_stats: Arc<DashMap<String, UserBillingStats>>,   // user_id -> stats
     adapter_stats: Arc<DashMap<String, AdapterBillingStats>>, // adapter_name -> stats
+    store: Option<Arc<dyn BillingStore>>,
+    pricing: Option<Arc<PricingCatalog>>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -49,6 +101,8 @@ pub struct UserBillingStats {
     pub total_requests: u64,
     pub total_input_tokens: u64,
     pub total_output_tokens: u64,
+    #[serde(default)]
+    pub total_cached_tokens: u64,
     pub total_cost: f64,
     pub last_updated: DateTime<Utc>,
 }
@@ -59,6 +113,8 @@ pub struct AdapterBillingStats {
     pub total_requests: u64,
     pub total_input_tokens: u64,
     pub total_output_tokens: u64,
+    #[serde(default)]
+    pub total_cached_tokens: u64,
     pub total_cost: f64,
     pub last_updated: DateTime<Utc>,
 }
@@ -70,9 +126,73 @@ impl BillingTracker {
             records: Arc::new(DashMap::new()),
             user_stats: Arc::new(DashMap::new()),
             adapter_stats: Arc::new(DashMap::new()),
+            store: None,
+            pricing: None,
         }
     }
 
+    pub fn with_pricing(mut self, pricing: Arc<PricingCatalog>) -> Self {
+        self.pricing = Some(pricing);
+        self
+    }
+
+    /// `at` 时刻某个模型的价格，价格目录中没有时使用 `BillingConfig` 中的统一价格
+    pub async fn price_at(
+        &self,
+        adapter_name: &str,
+        model: Option<&str>,
+        at: DateTime<Utc>,
+    ) -> ModelPrice {
+        let config = self.config.read().await;
+        self.lookup_price(&config, adapter_name, model, at)
+    }
+
+    fn lookup_price(
+        &self,
+        config: &BillingConfig,
+        adapter_name: &str,
+        model: Option<&str>,
+        at: DateTime<Utc>,
+    ) -> ModelPrice {
+        self.pricing
+            .as_ref()
+            .and_then(|pricing| pricing.lookup(adapter_name, model, at))
+            .unwrap_or_else(|| config.flat_price())
+    }
+
+    pub fn with_store(mut self, store: Arc<dyn BillingStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    pub fn store(&self) -> Option<Arc<dyn BillingStore>> {
+        self.store.clone()
+    }
+
+    /// 从存储中读取历史记录重建统计，返回恢复的记录数
+    pub async fn rehydrate(&self, adapter_name: &str) -> anyhow::Result<usize> {
+        let Some(store) = &self.store else {
+            return Ok(0);
+        };
+
+        let records = store.load(adapter_name).await?;
+        let max_records = self.config.read().await.max_records;
+        let count = records.len();
+        for record in records {
+            self.apply(record, max_records);
+        }
+
+        if count > 0 {
+            info!(
+                adapter = %adapter_name,
+                records = count,
+                store = store.kind(),
+                "Billing history restored"
+            );
+        }
+        Ok(count)
+    }
+
     pub async fn record_usage(
         &self,
         adapter_name: String,
@@ -82,32 +202,146 @@ impl BillingTracker {
         output_tokens: u64,
         metadata: serde_json::Value,
     ) {
+        self.record_token_usage(
+            adapter_name,
+            user_id,
+            request_id,
+            &Usage::new(input_tokens, output_tokens),
+            metadata,
+        )
+        .await;
+    }
+
+    /// 按用量计费并返回账单记录，计费关闭时返回 None
+    pub async fn record_token_usage(
+        &self,
+        adapter_name: String,
+        user_id: Option<String>,
+        request_id: String,
+        usage: &Usage,
+        metadata: serde_json::Value,
+    ) -> Option<UsageRecord> {
+        self.record_model_usage(adapter_name, None, user_id, request_id, usage, metadata)
+            .await
+    }
+
+    /// 按调用时生效的模型价格计费
+    pub async fn record_model_usage(
+        &self,
+        adapter_name: String,
+        model: Option<String>,
+        user_id: Option<String>,
+        request_id: String,
+        usage: &Usage,
+        metadata: serde_json::Value,
+    ) -> Option<UsageRecord> {
         let config = self.config.read().await;
         if !config.enabled {
-            return;
+            return None;
         }
 
-        let input_cost = (input_tokens as f64 / 1000.0) * config.input_price_per_1k;
-        let output_cost = (output_tokens as f64 / 1000.0) * config.output_price_per_1k;
-        let total_cost = input_cost + output_cost;
+        let input_tokens = usage.prompt_tokens;
+        let output_tokens = usage.completion_tokens;
+        let cached_tokens = usage.cached_tokens.min(input_tokens);
+
+        let timestamp = Utc::now();
+        let price = self.lookup_price(&config, &adapter_name, model.as_deref(), timestamp);
+        let total_cost = price.cost(
+            input_tokens,
+            output_tokens,
+            cached_tokens,
+            config.min_charge_tokens,
+        );
 
         let record = UsageRecord {
-            adapter_name: adapter_name.clone(),
-            user_id: user_id.clone(),
+            adapter_name,
+            user_id,
             request_id,
+            model,
             input_tokens,
             output_tokens,
+            cached_tokens,
             total_cost,
-            timestamp: Utc::now(),
+            currency: price.currency,
+            timestamp,
             metadata,
         };
 
-        self.records
-            .entry(adapter_name.clone())
-            .or_insert_with(Vec::new)
-            .push(record);
+        let max_records = config.max_records;
+        drop(config);
+        Some(self.commit(record, max_records).await)
+    }
+
+    /// 记录命中响应缓存的调用：保留原响应的用量，费用为 0
+    pub async fn record_cache_hit(
+        &self,
+        adapter_name: String,
+        model: Option<String>,
+        user_id: Option<String>,
+        request_id: String,
+        usage: &Usage,
+        metadata: serde_json::Value,
+    ) -> Option<UsageRecord> {
+        let config = self.config.read().await;
+        if !config.enabled {
+            return None;
+        }
+
+        let timestamp = Utc::now();
+        let price = self.lookup_price(&config, &adapter_name, model.as_deref(), timestamp);
+        let record = UsageRecord {
+            adapter_name,
+            user_id,
+            request_id,
+            model,
+            input_tokens: usage.prompt_tokens,
+            output_tokens: usage.completion_tokens,
+            cached_tokens: usage.cached_tokens.min(usage.prompt_tokens),
+            total_cost: 0.0,
+            currency: price.currency,
+            timestamp,
+            metadata,
+        };
+
+        let max_records = config.max_records;
+        drop(config);
+        Some(self.commit(record, max_records).await)
+    }
+
+    async fn commit(&self, record: UsageRecord, max_records: usize) -> UsageRecord {
+        self.apply(record.clone(), max_records);
+
+        // 写入失败只记录日志，不影响本次调用
+        if let Some(store) = &self.store {
+            if let Err(e) = store.append(&record).await {
+                warn!(adapter = %record.adapter_name, "Failed to persist billing record: {}", e);
+            }
+        }
+
+        debug!(
+            adapter = %record.adapter_name,
+            input_tokens = record.input_tokens,
+            output_tokens = record.output_tokens,
+            cached_tokens = record.cached_tokens,
+            cost = record.total_cost,
+            "Billing recorded"
+        );
 
-        if let Some(uid) = user_id {
+        record
+    }
+
+    /// 计入统计并保留在最近记录中，超出 `max_records` 时丢弃最旧的记录
+    fn apply(&self, record: UsageRecord, max_records: usize) {
+        let UsageRecord {
+            input_tokens,
+            output_tokens,
+            cached_tokens,
+            total_cost,
+            timestamp,
+            ..
+        } = record;
+
+        if let Some(uid) = &record.user_id {
             let mut stats =
                 self.user_stats
                     .entry(uid.clone())
@@ -116,42 +350,86 @@ impl BillingTracker {
                         total_requests: 0,
                         total_input_tokens: 0,
                         total_output_tokens: 0,
+                        total_cached_tokens: 0,
                         total_cost: 0.0,
-                        last_updated: Utc::now(),
+                        last_updated: timestamp,
                     });
 
             stats.total_requests += 1;
             stats.total_input_tokens += input_tokens;
             stats.total_output_tokens += output_tokens;
+            stats.total_cached_tokens += cached_tokens;
             stats.total_cost += total_cost;
-            stats.last_updated = Utc::now();
+            stats.last_updated = timestamp;
         }
 
         let mut adapter_stats = self
             .adapter_stats
-            .entry(adapter_name.clone())
+            .entry(record.adapter_name.clone())
             .or_insert_with(|| AdapterBillingStats {
-                adapter_name: adapter_name.clone(),
+                adapter_name: record.adapter_name.clone(),
                 total_requests: 0,
                 total_input_tokens: 0,
                 total_output_tokens: 0,
+                total_cached_tokens: 0,
                 total_cost: 0.0,
-                last_updated: Utc::now(),
+                last_updated: timestamp,
             });
 
         adapter_stats.total_requests += 1;
         adapter_stats.total_input_tokens += input_tokens;
         adapter_stats.total_output_tokens += output_tokens;
+        adapter_stats.total_cached_tokens += cached_tokens;
         adapter_stats.total_cost += total_cost;
-        adapter_stats.last_updated = Utc::now();
+        adapter_stats.last_updated = timestamp;
+        drop(adapter_stats);
 
-        debug!(
-            adapter = %adapter_name,
-            input_tokens = input_tokens,
-            output_tokens = output_tokens,
-            cost = total_cost,
-            "Billing recorded"
-        );
+        let mut records = self.records.entry(record.adapter_name.clone()).or_default();
+        records.push_back(record);
+        while records.len() > max_records {
+            records.pop_front();
+        }
+    }
+
+    /// `[from, to)` 内的记录；配置了存储时读取完整历史，否则只有内存中的最近记录
+    pub async fn records(
+        &self,
+        adapter_name: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<Vec<UsageRecord>> {
+        if let Some(store) = &self.store {
+            return store.query(adapter_name, from, to).await;
+        }
+
+        Ok(self
+            .recent_records(adapter_name)
+            .into_iter()
+            .filter(|record| in_range(record.timestamp, from, to))
+            .collect())
+    }
+
+    /// 与 `records` 相同的范围，配置了存储时逐条读取
+    pub async fn record_stream(
+        &self,
+        adapter_name: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<RecordStream> {
+        if let Some(store) = &self.store {
+            return store.stream(adapter_name, from, to).await;
+        }
+
+        let records = self.records(adapter_name, from, to).await?;
+        Ok(Box::pin(futures::stream::iter(records.into_iter().map(Ok))))
+    }
+
+    /// 内存中保留的最近记录，按时间顺序
+    pub fn recent_records(&self, adapter_name: &str) -> Vec<UsageRecord> {
+        self.records
+            .get(adapter_name)
+            .map(|records| records.iter().cloned().collect())
+            .unwrap_or_default()
     }
 
     pub fn get_user_stats(&self, user_id: &str) -> Option<UserBillingStats> {
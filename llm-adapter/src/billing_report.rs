@@ -0,0 +1,155 @@
+use crate::billing::UsageRecord;
+use chrono::{DateTime, Datelike, TimeZone, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 报表时间粒度，按 UTC 划分
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportGranularity {
+    Hour,
+    #[default]
+    Day,
+    Month,
+}
+
+impl ReportGranularity {
+    pub fn bucket_start(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let (day, hour) = match self {
+            ReportGranularity::Hour => (at.day(), at.hour()),
+            ReportGranularity::Day => (at.day(), 0),
+            ReportGranularity::Month => (1, 0),
+        };
+        Utc.with_ymd_and_hms(at.year(), at.month(), day, hour, 0, 0)
+            .unwrap()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportGroupBy {
+    #[default]
+    Adapter,
+    Model,
+    User,
+    Tenant,
+}
+
+impl ReportGroupBy {
+    fn key(&self, record: &UsageRecord) -> Option<String> {
+        match self {
+            ReportGroupBy::Adapter => Some(record.adapter_name.clone()),
+            ReportGroupBy::Model => record.model.clone(),
+            ReportGroupBy::User => record.user_id.clone(),
+            ReportGroupBy::Tenant => record.tenant_id().map(str::to_string),
+        }
+    }
+}
+
+/// 报表查询条件，时间范围为 `[from, to)`
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ReportQuery {
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub granularity: ReportGranularity,
+    #[serde(default)]
+    pub group_by: ReportGroupBy,
+    /// 只统计该适配器
+    #[serde(default)]
+    pub adapter: Option<String>,
+}
+
+/// 一个时间段内某个分组的用量，不同币种分开统计
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ReportBucket {
+    pub period_start: DateTime<Utc>,
+    /// 分组值，记录中缺少该字段时为 None（如未带用户的请求）
+    pub key: Option<String>,
+    pub currency: String,
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cached_tokens: u64,
+    pub total_cost: f64,
+}
+
+/// 按时间段和分组汇总账单记录，结果按时间段、分组、币种排序
+pub fn aggregate(
+    records: &[UsageRecord],
+    granularity: ReportGranularity,
+    group_by: ReportGroupBy,
+) -> Vec<ReportBucket> {
+    let mut buckets = BTreeMap::new();
+    for record in records {
+        let period_start = granularity.bucket_start(record.timestamp);
+        let key = group_by.key(record);
+        let bucket = buckets
+            .entry((period_start, key.clone(), record.currency.clone()))
+            .or_insert_with(|| ReportBucket {
+                period_start,
+                key,
+                currency: record.currency.clone(),
+                requests: 0,
+                input_tokens: 0,
+                output_tokens: 0,
+                cached_tokens: 0,
+                total_cost: 0.0,
+            });
+
+        bucket.requests += 1;
+        bucket.input_tokens += record.input_tokens;
+        bucket.output_tokens += record.output_tokens;
+        bucket.cached_tokens += record.cached_tokens;
+        bucket.total_cost += record.total_cost;
+    }
+    buckets.into_values().collect()
+}
+
+pub const CSV_HEADER: &str = "timestamp,adapter,model,user_id,tenant_id,request_id,\
+input_tokens,output_tokens,cached_tokens,total_cost,currency\n";
+
+/// 一条记录对应的 CSV 行（含换行）
+pub fn csv_row(record: &UsageRecord) -> String {
+    let fields = [
+        record.timestamp.to_rfc3339(),
+        record.adapter_name.clone(),
+        record.model.clone().unwrap_or_default(),
+        record.user_id.clone().unwrap_or_default(),
+        record.tenant_id().unwrap_or_default().to_string(),
+        record.request_id.clone(),
+        record.input_tokens.to_string(),
+        record.output_tokens.to_string(),
+        record.cached_tokens.to_string(),
+        record.total_cost.to_string(),
+        record.currency.clone(),
+    ];
+
+    let mut row = fields.map(|field| csv_escape(&field)).join(",");
+    row.push('\n');
+    row
+}
+
+fn csv_escape(field: &str) -> String {
+    let mut field = field.to_string();
+    // 电子表格会把这些字符开头的单元格当作公式执行
+    if field.starts_with(['=', '+', '-', '@']) {
+        field.insert(0, '\'');
+    }
+
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+pub(crate) fn in_range(
+    at: DateTime<Utc>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> bool {
+    from.is_none_or(|from| from <= at) && to.is_none_or(|to| at < to)
+}
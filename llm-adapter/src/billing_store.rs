@@ -0,0 +1,361 @@
+use crate::billing::UsageRecord;
+use crate::billing_report::in_range;
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::stream::{self, Stream, TryStreamExt};
+use rusqlite::{params, Connection};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tracing::warn;
+
+pub type RecordStream = Pin<Box<dyn Stream<Item = anyhow::Result<UsageRecord>> + Send>>;
+
+/// 计费记录的持久化存储，只追加不修改
+#[async_trait]
+pub trait BillingStore: Send + Sync {
+    fn kind(&self) -> &'static str;
+
+    async fn append(&self, record: &UsageRecord) -> anyhow::Result<()>;
+
+    /// 按写入顺序读取某个适配器的全部记录，用于启动时恢复统计
+    async fn load(&self, adapter_name: &str) -> anyhow::Result<Vec<UsageRecord>>;
+
+    /// 读取某个适配器在 `[from, to)` 内的记录，用于生成报表与导出
+    async fn query(
+        &self,
+        adapter_name: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<Vec<UsageRecord>> {
+        let records = self.load(adapter_name).await?;
+        Ok(records
+            .into_iter()
+            .filter(|record| in_range(record.timestamp, from, to))
+            .collect())
+    }
+
+    /// 逐条读取 `[from, to)` 内的记录，导出大量历史时不整体载入内存
+    async fn stream(
+        &self,
+        adapter_name: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<RecordStream> {
+        let records = self.query(adapter_name, from, to).await?;
+        Ok(Box::pin(stream::iter(records.into_iter().map(Ok))))
+    }
+}
+
+/// 每行一条 JSON 记录，多个适配器可以共用同一个文件
+pub struct JsonlBillingStore {
+    path: PathBuf,
+    file: tokio::sync::Mutex<tokio::fs::File>,
+}
+
+impl JsonlBillingStore {
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+
+        Ok(Self {
+            path,
+            file: tokio::sync::Mutex::new(tokio::fs::File::from_std(file)),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[async_trait]
+impl BillingStore for JsonlBillingStore {
+    fn kind(&self) -> &'static str {
+        "jsonl"
+    }
+
+    async fn append(&self, record: &UsageRecord) -> anyhow::Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+
+        // 整行一次写入，避免并发写入时交错
+        let mut file = self.file.lock().await;
+        file.write_all(&line).await?;
+        file.flush().await?;
+        Ok(())
+    }
+
+    async fn load(&self, adapter_name: &str) -> anyhow::Result<Vec<UsageRecord>> {
+        self.stream(adapter_name, None, None)
+            .await?
+            .try_collect()
+            .await
+    }
+
+    async fn stream(
+        &self,
+        adapter_name: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<RecordStream> {
+        let lines = BufReader::new(tokio::fs::File::open(&self.path).await?).lines();
+        let path = self.path.clone();
+        let adapter_name = adapter_name.to_string();
+
+        let records = stream::try_unfold((lines, 0), |(mut lines, number)| async move {
+            let line = lines.next_line().await?;
+            Ok::<_, anyhow::Error>(line.map(|line| ((number + 1, line), (lines, number + 1))))
+        })
+        .try_filter_map(move |(number, line)| {
+            let record = parse_line(&path, number, &line).filter(|record| {
+                record.adapter_name == adapter_name && in_range(record.timestamp, from, to)
+            });
+            async move { Ok(record) }
+        });
+        Ok(Box::pin(records))
+    }
+}
+
+fn parse_line(path: &Path, number: usize, line: &str) -> Option<UsageRecord> {
+    if line.trim().is_empty() {
+        return None;
+    }
+    // 进程崩溃时最后一行可能不完整，跳过无法解析的行
+    serde_json::from_str(line)
+        .inspect_err(|e| {
+            warn!(
+                path = %path.display(),
+                line = number,
+                "Skipping malformed billing record: {}",
+                e
+            )
+        })
+        .ok()
+}
+
+const SQLITE_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS usage_records (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    adapter_name TEXT NOT NULL,
+    user_id TEXT,
+    request_id TEXT NOT NULL,
+    model TEXT,
+    input_tokens INTEGER NOT NULL,
+    output_tokens INTEGER NOT NULL,
+    cached_tokens INTEGER NOT NULL DEFAULT 0,
+    total_cost REAL NOT NULL,
+    currency TEXT NOT NULL DEFAULT 'USD',
+    timestamp TEXT NOT NULL,
+    metadata TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_usage_records_adapter
+    ON usage_records (adapter_name, timestamp);
+";
+
+/// 为旧版本创建的表补充后来增加的列
+fn migrate(connection: &Connection) -> rusqlite::Result<()> {
+    let mut statement = connection.prepare("PRAGMA table_info(usage_records)")?;
+    let columns = statement
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    for (column, definition) in [
+        ("model", "TEXT"),
+        ("currency", "TEXT NOT NULL DEFAULT 'USD'"),
+    ] {
+        if !columns.iter().any(|c| c == column) {
+            connection.execute_batch(&format!(
+                "ALTER TABLE usage_records ADD COLUMN {} {}",
+                column, definition
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+/// SQLite 存储，数据库操作在阻塞线程池中执行
+pub struct SqliteBillingStore {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteBillingStore {
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> anyhow::Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> anyhow::Result<Self> {
+        // 多个适配器各自打开同一个数据库文件时等待写锁
+        connection.busy_timeout(Duration::from_secs(5))?;
+        connection.execute_batch(SQLITE_SCHEMA)?;
+        migrate(&connection)?;
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    async fn run<T, F>(&self, f: F) -> anyhow::Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> anyhow::Result<T> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+        tokio::task::spawn_blocking(move || f(&connection.lock().unwrap())).await?
+    }
+}
+
+#[async_trait]
+impl BillingStore for SqliteBillingStore {
+    fn kind(&self) -> &'static str {
+        "sqlite"
+    }
+
+    async fn append(&self, record: &UsageRecord) -> anyhow::Result<()> {
+        let record = record.clone();
+        self.run(move |connection| {
+            connection.execute(
+                "INSERT INTO usage_records (adapter_name, user_id, request_id, model, \
+                 input_tokens, output_tokens, cached_tokens, total_cost, currency, timestamp, \
+                 metadata) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    record.adapter_name,
+                    record.user_id,
+                    record.request_id,
+                    record.model,
+                    record.input_tokens as i64,
+                    record.output_tokens as i64,
+                    record.cached_tokens as i64,
+                    record.total_cost,
+                    record.currency,
+                    record
+                        .timestamp
+                        .to_rfc3339_opts(SecondsFormat::Micros, true),
+                    record.metadata.to_string(),
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn load(&self, adapter_name: &str) -> anyhow::Result<Vec<UsageRecord>> {
+        self.query(adapter_name, None, None).await
+    }
+
+    async fn query(
+        &self,
+        adapter_name: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<Vec<UsageRecord>> {
+        self.stream(adapter_name, from, to)
+            .await?
+            .try_collect()
+            .await
+    }
+
+    async fn stream(
+        &self,
+        adapter_name: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<RecordStream> {
+        let connection = self.connection.clone();
+        let adapter_name = adapter_name.to_string();
+        // 时间统一存为 UTC 的 RFC3339 字符串，可以直接按字典序比较
+        let from = from.map(|at| at.to_rfc3339_opts(SecondsFormat::Micros, true));
+        let to = to.map(|at| at.to_rfc3339_opts(SecondsFormat::Micros, true));
+        let (tx, rx) = tokio::sync::mpsc::channel(SQLITE_PAGE_SIZE as usize);
+
+        tokio::task::spawn_blocking(move || {
+            let mut after = 0;
+            loop {
+                // 按页持有连接锁，下游读取缓慢时不阻塞写入
+                let page = select_page(
+                    &connection.lock().unwrap(),
+                    &adapter_name,
+                    from.as_deref(),
+                    to.as_deref(),
+                    after,
+                );
+                let page = match page {
+                    Ok(page) if page.is_empty() => return,
+                    Ok(page) => page,
+                    Err(e) => {
+                        let _ = tx.blocking_send(Err(e));
+                        return;
+                    }
+                };
+                for (id, record) in page {
+                    after = id;
+                    if tx.blocking_send(Ok(record)).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(Box::pin(stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|record| (record, rx))
+        })))
+    }
+}
+
+const SQLITE_PAGE_SIZE: i64 = 512;
+
+/// 读取 id 大于 `after` 的一页记录
+fn select_page(
+    connection: &Connection,
+    adapter_name: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+    after: i64,
+) -> anyhow::Result<Vec<(i64, UsageRecord)>> {
+    let mut statement = connection.prepare_cached(
+        "SELECT id, adapter_name, user_id, request_id, model, input_tokens, output_tokens, \
+         cached_tokens, total_cost, currency, timestamp, metadata \
+         FROM usage_records WHERE adapter_name = ?1 \
+         AND (?2 IS NULL OR timestamp >= ?2) AND (?3 IS NULL OR timestamp < ?3) \
+         AND id > ?4 ORDER BY id LIMIT ?5",
+    )?;
+    let mut rows = statement.query(params![adapter_name, from, to, after, SQLITE_PAGE_SIZE])?;
+
+    let mut page = Vec::new();
+    while let Some(row) = rows.next()? {
+        let timestamp: String = row.get(10)?;
+        let metadata: String = row.get(11)?;
+        page.push((
+            row.get(0)?,
+            UsageRecord {
+                adapter_name: row.get(1)?,
+                user_id: row.get(2)?,
+                request_id: row.get(3)?,
+                model: row.get(4)?,
+                input_tokens: row.get::<_, i64>(5)? as u64,
+                output_tokens: row.get::<_, i64>(6)? as u64,
+                cached_tokens: row.get::<_, i64>(7)? as u64,
+                total_cost: row.get(8)?,
+                currency: row.get(9)?,
+                timestamp: DateTime::parse_from_rfc3339(&timestamp)?.with_timezone(&Utc),
+                metadata: serde_json::from_str(&metadata)?,
+            },
+        ));
+    }
+    Ok(page)
+}
@@ -0,0 +1,526 @@
+use crate::billing::UsageRecord;
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, TimeZone, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+/// 预算周期，按 UTC 自然日/自然月重置
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetPeriod {
+    Daily,
+    Monthly,
+}
+
+impl BudgetPeriod {
+    pub fn start(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let day = match self {
+            BudgetPeriod::Daily => now.day(),
+            BudgetPeriod::Monthly => 1,
+        };
+        Utc.with_ymd_and_hms(now.year(), now.month(), day, 0, 0, 0)
+            .unwrap()
+    }
+
+    pub fn end(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let start = self.start(now);
+        match self {
+            BudgetPeriod::Daily => start + ChronoDuration::days(1),
+            BudgetPeriod::Monthly => {
+                let (year, month) = if start.month() == 12 {
+                    (start.year() + 1, 1)
+                } else {
+                    (start.year(), start.month() + 1)
+                };
+                Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).unwrap()
+            }
+        }
+    }
+}
+
+/// 预算作用对象，用户与租户的 id 为 "*" 时对每个用户/租户分别计算
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "id", rename_all = "snake_case")]
+pub enum BudgetScope {
+    User(String),
+    Tenant(String),
+    Adapter(String),
+}
+
+impl BudgetScope {
+    fn subject(&self, subject: &BudgetSubject) -> Option<String> {
+        let (id, actual) = match self {
+            BudgetScope::User(id) => (id, subject.user_id.as_deref()),
+            BudgetScope::Tenant(id) => (id, subject.tenant_id.as_deref()),
+            BudgetScope::Adapter(id) => (id, Some(subject.adapter.as_str())),
+        };
+        actual
+            .filter(|actual| id == "*" || id == actual)
+            .map(str::to_string)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Budget {
+    pub name: String,
+    pub scope: BudgetScope,
+    pub period: BudgetPeriod,
+    #[serde(default)]
+    pub max_cost: Option<f64>,
+    #[serde(default)]
+    pub max_tokens: Option<u64>,
+    #[serde(default = "default_warn_ratio")]
+    pub warn_ratio: f64,
+    /// 为 false 时超出上限只发出事件，不拒绝请求
+    #[serde(default = "default_hard_stop")]
+    pub hard_stop: bool,
+}
+
+fn default_warn_ratio() -> f64 {
+    0.8
+}
+
+fn default_hard_stop() -> bool {
+    true
+}
+
+impl Budget {
+    /// 已用比例，取金额与 token 中较高的一项
+    fn ratio(&self, cost: f64, tokens: u64) -> f64 {
+        let cost_ratio = self
+            .max_cost
+            .filter(|max| *max > 0.0)
+            .map_or(0.0, |max| cost / max);
+        let token_ratio = self
+            .max_tokens
+            .filter(|max| *max > 0)
+            .map_or(0.0, |max| tokens as f64 / max as f64);
+        cost_ratio.max(token_ratio)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BudgetSubject {
+    pub adapter: String,
+    pub user_id: Option<String>,
+    pub tenant_id: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetEventKind {
+    Warning,
+    Exceeded,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BudgetEvent {
+    pub kind: BudgetEventKind,
+    pub budget: String,
+    pub subject: String,
+    pub period: BudgetPeriod,
+    pub period_start: DateTime<Utc>,
+    pub spent_cost: f64,
+    pub spent_tokens: u64,
+    pub max_cost: Option<f64>,
+    pub max_tokens: Option<u64>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BudgetUsage {
+    pub budget: String,
+    pub subject: String,
+    pub period_start: DateTime<Utc>,
+    pub spent_cost: f64,
+    pub spent_tokens: u64,
+    pub max_cost: Option<f64>,
+    pub max_tokens: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetExceeded {
+    pub budget: String,
+    pub subject: String,
+    pub reset_at: DateTime<Utc>,
+}
+
+impl BudgetExceeded {
+    pub fn retry_after(&self) -> Duration {
+        (self.reset_at - Utc::now()).to_std().unwrap_or_default()
+    }
+}
+
+impl std::fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Budget {} exceeded for {} until {}",
+            self.budget,
+            self.subject,
+            self.reset_at.to_rfc3339()
+        )
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+struct Spend {
+    period_start: DateTime<Utc>,
+    cost: f64,
+    tokens: u64,
+    /// 进行中的调用预留的额度，结算或放弃时释放
+    reserved_cost: f64,
+    reserved_tokens: u64,
+    warned: bool,
+    exceeded: bool,
+}
+
+impl Spend {
+    fn new(period_start: DateTime<Utc>) -> Self {
+        Self {
+            period_start,
+            cost: 0.0,
+            tokens: 0,
+            reserved_cost: 0.0,
+            reserved_tokens: 0,
+            warned: false,
+            exceeded: false,
+        }
+    }
+}
+
+struct Hold {
+    key: (String, String),
+    period_start: DateTime<Utc>,
+    cost: f64,
+    tokens: u64,
+}
+
+/// 调用前在硬性预算中预留的额度，`settle` 按实际费用结算，未结算时在丢弃时释放
+pub struct BudgetReservation {
+    manager: Arc<BudgetManager>,
+    subject: BudgetSubject,
+    holds: Vec<Hold>,
+}
+
+impl BudgetReservation {
+    pub fn settle(mut self, cost: f64, tokens: u64) {
+        self.manager.release(std::mem::take(&mut self.holds));
+        self.manager.record(&self.subject, cost, tokens);
+    }
+}
+
+impl Drop for BudgetReservation {
+    fn drop(&mut self) {
+        self.manager.release(std::mem::take(&mut self.holds));
+    }
+}
+
+/// 跨适配器共享的预算，调用前检查、计费后累计，越过阈值时广播事件
+pub struct BudgetManager {
+    budgets: RwLock<Vec<Budget>>,
+    spend: DashMap<(String, String), Spend>, // (budget, subject) -> spend
+    /// 检查与预留在同一把锁内完成，并发请求不会同时越过上限
+    reserving: Mutex<()>,
+    /// 各预算开始生效的时间，之后的调用由 `record` 计入
+    installed: Mutex<HashMap<String, DateTime<Utc>>>,
+    /// 已从账单记录恢复过用量的（预算，适配器）
+    restored: Mutex<HashSet<(String, String)>>,
+    events: broadcast::Sender<BudgetEvent>,
+}
+
+impl BudgetManager {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(256);
+        Self {
+            budgets: RwLock::new(Vec::new()),
+            spend: DashMap::new(),
+            reserving: Mutex::new(()),
+            installed: Mutex::new(HashMap::new()),
+            restored: Mutex::new(HashSet::new()),
+            events,
+        }
+    }
+
+    /// 替换全部预算定义，保留名称未变的预算在当前周期的用量；
+    /// 新增预算此前的用量需用 `restore` 从账单记录补上
+    pub fn set_budgets(&self, budgets: Vec<Budget>) {
+        let now = Utc::now();
+        let kept = |name: &String| budgets.iter().any(|budget| &budget.name == name);
+        self.spend.retain(|(name, _), _| kept(name));
+        self.restored.lock().unwrap().retain(|(name, _)| kept(name));
+        {
+            let mut installed = self.installed.lock().unwrap();
+            installed.retain(|name, _| kept(name));
+            for budget in &budgets {
+                installed.entry(budget.name.clone()).or_insert(now);
+            }
+        }
+        info!(count = budgets.len(), "Budgets updated");
+        *self.budgets.write().unwrap() = budgets;
+    }
+
+    pub fn budgets(&self) -> Vec<Budget> {
+        self.budgets.read().unwrap().clone()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<BudgetEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn check(&self, subject: &BudgetSubject) -> Result<(), BudgetExceeded> {
+        let _reserving = self.reserving.lock().unwrap();
+        self.exhausted(subject, 0.0, 0).map(|_| ())
+    }
+
+    pub fn reserve(
+        self: &Arc<Self>,
+        subject: &BudgetSubject,
+        cost: f64,
+        tokens: u64,
+    ) -> Result<BudgetReservation, BudgetExceeded> {
+        let _reserving = self.reserving.lock().unwrap();
+        let holds = self.exhausted(subject, cost, tokens)?;
+        for hold in &holds {
+            let mut spend = self
+                .spend
+                .entry(hold.key.clone())
+                .or_insert_with(|| Spend::new(hold.period_start));
+            if spend.period_start != hold.period_start {
+                *spend = Spend::new(hold.period_start);
+            }
+            spend.reserved_cost += hold.cost;
+            spend.reserved_tokens += hold.tokens;
+        }
+
+        Ok(BudgetReservation {
+            manager: self.clone(),
+            subject: subject.clone(),
+            holds,
+        })
+    }
+
+    fn exhausted(
+        &self,
+        subject: &BudgetSubject,
+        cost: f64,
+        tokens: u64,
+    ) -> Result<Vec<Hold>, BudgetExceeded> {
+        let now = Utc::now();
+        let mut holds = Vec::new();
+        for budget in self.budgets.read().unwrap().iter() {
+            if !budget.hard_stop {
+                continue;
+            }
+            let Some(key) = budget.scope.subject(subject) else {
+                continue;
+            };
+
+            let period_start = budget.period.start(now);
+            let key = (budget.name.clone(), key);
+            let (spent_cost, spent_tokens) = self
+                .spend
+                .get(&key)
+                .filter(|spend| spend.period_start == period_start)
+                .map_or((0.0, 0), |spend| {
+                    (
+                        spend.cost + spend.reserved_cost,
+                        spend.tokens + spend.reserved_tokens,
+                    )
+                });
+            if budget.ratio(spent_cost, spent_tokens) >= 1.0
+                || budget.ratio(spent_cost + cost, spent_tokens + tokens) > 1.0
+            {
+                return Err(BudgetExceeded {
+                    budget: budget.name.clone(),
+                    subject: key.1,
+                    reset_at: budget.period.end(now),
+                });
+            }
+
+            holds.push(Hold {
+                key,
+                period_start,
+                cost,
+                tokens,
+            });
+        }
+        Ok(holds)
+    }
+
+    fn release(&self, holds: Vec<Hold>) {
+        for hold in holds {
+            if let Some(mut spend) = self.spend.get_mut(&hold.key) {
+                // 跨周期后旧的预留已随用量一起清零
+                if spend.period_start == hold.period_start {
+                    spend.reserved_cost = (spend.reserved_cost - hold.cost).max(0.0);
+                    spend.reserved_tokens = spend.reserved_tokens.saturating_sub(hold.tokens);
+                }
+            }
+        }
+    }
+
+    pub fn needs_restore(&self, adapter_name: &str) -> bool {
+        let restored = self.restored.lock().unwrap();
+        self.budgets
+            .read()
+            .unwrap()
+            .iter()
+            .any(|budget| !restored.contains(&(budget.name.clone(), adapter_name.to_string())))
+    }
+
+    /// 用账单记录重建当前周期的用量，不发出事件。每个预算对每个适配器只恢复一次，
+    /// 只计入预算生效之前的记录，之后的调用已由 `record` 计入；命中响应缓存的记录不计入
+    pub fn restore(&self, adapter_name: &str, records: &[UsageRecord]) -> usize {
+        let now = Utc::now();
+        let budgets: Vec<(Budget, DateTime<Utc>)> = {
+            let mut restored = self.restored.lock().unwrap();
+            let installed = self.installed.lock().unwrap();
+            self.budgets
+                .read()
+                .unwrap()
+                .iter()
+                .filter(|budget| restored.insert((budget.name.clone(), adapter_name.to_string())))
+                .map(|budget| {
+                    let since = installed.get(&budget.name).copied().unwrap_or(now);
+                    (budget.clone(), since)
+                })
+                .collect()
+        };
+
+        let mut count = 0;
+        for record in records {
+            if record.metadata.get("cache_hit").and_then(|v| v.as_bool()) == Some(true) {
+                continue;
+            }
+            let subject = BudgetSubject {
+                adapter: record.adapter_name.clone(),
+                user_id: record.user_id.clone(),
+                tenant_id: record.tenant_id().map(str::to_string),
+            };
+
+            let mut applied = false;
+            for (budget, installed_at) in &budgets {
+                let period_start = budget.period.start(now);
+                if record.timestamp < period_start || record.timestamp >= *installed_at {
+                    continue;
+                }
+                let Some(key) = budget.scope.subject(&subject) else {
+                    continue;
+                };
+
+                let mut spend = self
+                    .spend
+                    .entry((budget.name.clone(), key))
+                    .or_insert_with(|| Spend::new(period_start));
+                if spend.period_start != period_start {
+                    *spend = Spend::new(period_start);
+                }
+                spend.cost += record.total_cost;
+                spend.tokens += record.input_tokens + record.output_tokens;
+
+                // 重启前已发出过的事件不再重复发出
+                let ratio = budget.ratio(spend.cost, spend.tokens);
+                spend.exceeded |= ratio >= 1.0;
+                spend.warned |= ratio >= budget.warn_ratio;
+                applied = true;
+            }
+            count += applied as usize;
+        }
+
+        if count > 0 {
+            info!(adapter = %adapter_name, records = count, "Budget spend restored");
+        }
+        count
+    }
+
+    /// 计入一次调用的费用，每个周期内预警与超限事件各发出一次
+    pub fn record(&self, subject: &BudgetSubject, cost: f64, tokens: u64) {
+        let now = Utc::now();
+        for budget in self.budgets.read().unwrap().iter() {
+            let Some(key) = budget.scope.subject(subject) else {
+                continue;
+            };
+
+            let period_start = budget.period.start(now);
+            let mut spend = self
+                .spend
+                .entry((budget.name.clone(), key.clone()))
+                .or_insert_with(|| Spend::new(period_start));
+            if spend.period_start != period_start {
+                *spend = Spend::new(period_start);
+            }
+            spend.cost += cost;
+            spend.tokens += tokens;
+
+            let ratio = budget.ratio(spend.cost, spend.tokens);
+            let kind = if ratio >= 1.0 && !spend.exceeded {
+                spend.exceeded = true;
+                spend.warned = true;
+                BudgetEventKind::Exceeded
+            } else if ratio >= budget.warn_ratio && !spend.warned {
+                spend.warned = true;
+                BudgetEventKind::Warning
+            } else {
+                continue;
+            };
+
+            warn!(
+                budget = %budget.name,
+                subject = %key,
+                cost = spend.cost,
+                tokens = spend.tokens,
+                "Budget threshold crossed: {:?}",
+                kind
+            );
+            let _ = self.events.send(BudgetEvent {
+                kind,
+                budget: budget.name.clone(),
+                subject: key,
+                period: budget.period,
+                period_start,
+                spent_cost: spend.cost,
+                spent_tokens: spend.tokens,
+                max_cost: budget.max_cost,
+                max_tokens: budget.max_tokens,
+            });
+        }
+    }
+
+    pub fn usage(&self) -> Vec<BudgetUsage> {
+        let budgets = self.budgets.read().unwrap();
+        let period_starts: Vec<_> = budgets
+            .iter()
+            .map(|budget| budget.period.start(Utc::now()))
+            .collect();
+
+        self.spend
+            .iter()
+            .filter_map(|entry| {
+                let (name, subject) = entry.key();
+                let index = budgets.iter().position(|budget| &budget.name == name)?;
+                let budget = &budgets[index];
+                let current = entry.period_start == period_starts[index];
+                Some(BudgetUsage {
+                    budget: name.clone(),
+                    subject: subject.clone(),
+                    period_start: period_starts[index],
+                    spent_cost: if current { entry.cost } else { 0.0 },
+                    spent_tokens: if current { entry.tokens } else { 0 },
+                    max_cost: budget.max_cost,
+                    max_tokens: budget.max_tokens,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Default for BudgetManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
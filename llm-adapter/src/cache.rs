@@ -0,0 +1,430 @@
+use crate::chat::{ChatRequest, ChatRole};
+use crate::embedding::EmbeddingAdapter;
+use crate::registry::InvokeOptions;
+use crate::response::InvokeResponse;
+use async_trait::async_trait;
+use dashmap::DashMap;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// `InvokeOptions::metadata` 中控制缓存的键，取值同 HTTP `Cache-Control`：
+/// `no-cache` 跳过读取但写入新结果，`no-store` 既不读取也不写入
+pub const CACHE_CONTROL: &str = "cache_control";
+
+#[derive(Clone, Debug)]
+pub struct CacheConfig {
+    pub ttl: Duration,
+    pub max_entries: usize,
+    /// 设置后对未精确命中的请求按向量相似度查找，取值 0~1
+    pub similarity_threshold: Option<f32>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(3600),
+            max_entries: 10_000,
+            similarity_threshold: None,
+        }
+    }
+}
+
+/// 响应缓存的存储后端，读写失败时按未命中处理
+#[async_trait]
+pub trait CacheStore: Send + Sync {
+    async fn get(&self, key: &str) -> anyhow::Result<Option<InvokeResponse>>;
+    async fn set(&self, key: &str, response: &InvokeResponse, ttl: Duration) -> anyhow::Result<()>;
+}
+
+/// 进程内 LRU 缓存，超出 `max_entries` 时淘汰最久未访问的条目
+pub struct InMemoryCacheStore {
+    max_entries: usize,
+    state: Mutex<LruState>,
+}
+
+#[derive(Default)]
+struct LruState {
+    entries: HashMap<String, LruEntry>,
+    /// 访问序号 → key，最小的序号最久未访问
+    order: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+struct LruEntry {
+    response: InvokeResponse,
+    expires_at: Instant,
+    tick: u64,
+}
+
+impl LruState {
+    fn touch(&mut self, key: &str) {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some(entry) = self.entries.get_mut(key) {
+            self.order.remove(&entry.tick);
+            entry.tick = tick;
+            self.order.insert(tick, key.to_string());
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.order.remove(&entry.tick);
+        }
+    }
+}
+
+impl InMemoryCacheStore {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            max_entries: max_entries.max(1),
+            state: Mutex::new(LruState::default()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait]
+impl CacheStore for InMemoryCacheStore {
+    async fn get(&self, key: &str) -> anyhow::Result<Option<InvokeResponse>> {
+        let mut state = self.state.lock().unwrap();
+        let expired = match state.entries.get(key) {
+            Some(entry) => entry.expires_at <= Instant::now(),
+            None => return Ok(None),
+        };
+        if expired {
+            state.remove(key);
+            return Ok(None);
+        }
+
+        state.touch(key);
+        Ok(state.entries.get(key).map(|entry| entry.response.clone()))
+    }
+
+    async fn set(&self, key: &str, response: &InvokeResponse, ttl: Duration) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.remove(key);
+        while state.entries.len() >= self.max_entries {
+            let Some((_, oldest)) = state.order.pop_first() else {
+                break;
+            };
+            state.entries.remove(&oldest);
+        }
+
+        state.tick += 1;
+        let tick = state.tick;
+        state.entries.insert(
+            key.to_string(),
+            LruEntry {
+                response: response.clone(),
+                expires_at: Instant::now() + ttl,
+                tick,
+            },
+        );
+        state.order.insert(tick, key.to_string());
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    /// 其中按向量相似度命中的次数
+    pub semantic_hits: u64,
+    pub misses: u64,
+}
+
+enum Embedder {
+    Fixed(Arc<dyn EmbeddingAdapter>),
+    /// 每次查询时按名称从注册表取，不依赖适配器的注册顺序
+    Registry {
+        adapters: Arc<DashMap<String, Arc<dyn EmbeddingAdapter>>>,
+        name: String,
+    },
+}
+
+impl Embedder {
+    fn get(&self) -> Option<Arc<dyn EmbeddingAdapter>> {
+        match self {
+            Embedder::Fixed(embedder) => Some(embedder.clone()),
+            Embedder::Registry { adapters, name } => adapters.get(name).map(|e| e.value().clone()),
+        }
+    }
+}
+
+struct SemanticEntry {
+    scope: String,
+    key: String,
+    embedding: Vec<f32>,
+}
+
+pub enum CacheLookup {
+    Hit(CacheHit),
+    Miss(CacheMiss),
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheHit {
+    pub response: InvokeResponse,
+    /// 近似匹配命中时的相似度，精确命中为 None
+    pub similarity: Option<f32>,
+}
+
+pub struct CacheMiss {
+    key: String,
+    semantic: Option<(String, Vec<f32>)>,
+}
+
+/// 按 (适配器, 模型, 规范化后的消息, 采样参数) 缓存成功的响应
+pub struct ResponseCache {
+    adapter_name: String,
+    config: CacheConfig,
+    store: Arc<dyn CacheStore>,
+    embedder: Option<Embedder>,
+    semantic_index: Mutex<VecDeque<SemanticEntry>>,
+    hits: AtomicU64,
+    semantic_hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ResponseCache {
+    pub fn new(adapter_name: impl Into<String>, config: CacheConfig) -> Self {
+        let store = Arc::new(InMemoryCacheStore::new(config.max_entries));
+        Self::with_store(adapter_name, config, store)
+    }
+
+    pub fn with_store(
+        adapter_name: impl Into<String>,
+        config: CacheConfig,
+        store: Arc<dyn CacheStore>,
+    ) -> Self {
+        Self {
+            adapter_name: adapter_name.into(),
+            config,
+            store,
+            embedder: None,
+            semantic_index: Mutex::new(VecDeque::new()),
+            hits: AtomicU64::new(0),
+            semantic_hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn with_embedder(mut self, embedder: Arc<dyn EmbeddingAdapter>) -> Self {
+        self.embedder = Some(Embedder::Fixed(embedder));
+        self
+    }
+
+    pub(crate) fn with_registry_embedder(
+        mut self,
+        adapters: Arc<DashMap<String, Arc<dyn EmbeddingAdapter>>>,
+        name: String,
+    ) -> Self {
+        self.embedder = Some(Embedder::Registry { adapters, name });
+        self
+    }
+
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            semantic_hits: self.semantic_hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 查找缓存；`no-store` 的请求返回 None，不参与缓存
+    pub async fn lookup(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> Option<CacheLookup> {
+        let control = options.metadata.get(CACHE_CONTROL).and_then(|v| v.as_str());
+        if control == Some("no-store") {
+            return None;
+        }
+
+        let scope = self.scope(request, options);
+        let key = cache_key(&[scope.as_str(), &normalize(last_user_text(request))]);
+        let mut miss = CacheMiss {
+            key,
+            semantic: None,
+        };
+        if control == Some("no-cache") {
+            return Some(CacheLookup::Miss(miss));
+        }
+
+        if let Some(response) = self.get(&miss.key).await {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Some(CacheLookup::Hit(CacheHit {
+                response,
+                similarity: None,
+            }));
+        }
+
+        if let Some(threshold) = self.config.similarity_threshold {
+            if let Some(embedding) = self.embed(request).await {
+                if let Some((key, similarity)) = self.nearest(&scope, &embedding, threshold) {
+                    if let Some(response) = self.get(&key).await {
+                        debug!(adapter = %self.adapter_name, similarity, "Semantic cache hit");
+                        self.hits.fetch_add(1, Ordering::Relaxed);
+                        self.semantic_hits.fetch_add(1, Ordering::Relaxed);
+                        return Some(CacheLookup::Hit(CacheHit {
+                            response,
+                            similarity: Some(similarity),
+                        }));
+                    }
+                }
+                miss.semantic = Some((scope, embedding));
+            }
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        Some(CacheLookup::Miss(miss))
+    }
+
+    pub async fn store(&self, miss: CacheMiss, response: &InvokeResponse) {
+        if let Err(e) = self.store.set(&miss.key, response, self.config.ttl).await {
+            warn!(adapter = %self.adapter_name, "Failed to write response cache: {}", e);
+            return;
+        }
+
+        if let Some((scope, embedding)) = miss.semantic {
+            let mut index = self.semantic_index.lock().unwrap();
+            index.retain(|entry| entry.key != miss.key);
+            if index.len() >= self.config.max_entries.max(1) {
+                index.pop_front();
+            }
+            index.push_back(SemanticEntry {
+                scope,
+                key: miss.key,
+                embedding,
+            });
+        }
+    }
+
+    async fn get(&self, key: &str) -> Option<InvokeResponse> {
+        match self.store.get(key).await {
+            Ok(response) => response,
+            Err(e) => {
+                warn!(adapter = %self.adapter_name, "Failed to read response cache: {}", e);
+                None
+            }
+        }
+    }
+
+    /// 除最后一条用户消息外的所有内容，近似匹配只在同一范围内进行
+    fn scope(&self, request: &ChatRequest, options: &InvokeOptions) -> String {
+        let last_user = request
+            .messages
+            .iter()
+            .rposition(|m| m.role == ChatRole::User);
+        let messages: Vec<serde_json::Value> = request
+            .messages
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let mut value = serde_json::to_value(m).unwrap_or_default();
+                value["content"] = if Some(i) == last_user {
+                    serde_json::Value::Null
+                } else {
+                    serde_json::Value::String(normalize(&m.content))
+                };
+                value
+            })
+            .collect();
+
+        let scope = serde_json::json!({
+            "adapter": self.adapter_name,
+            "model": options.model,
+            "messages": messages,
+            "tools": request.tools,
+            "tool_choice": request.tool_choice,
+            "temperature": options.temperature,
+            "max_tokens": options.max_tokens,
+            "top_p": options.top_p,
+            "stop": options.stop,
+            "seed": options.seed,
+            "presence_penalty": options.presence_penalty,
+            "frequency_penalty": options.frequency_penalty,
+            "n": options.n,
+        });
+        cache_key(&[&scope.to_string()])
+    }
+
+    /// 为最后一条用户消息生成向量，带图片或音频的消息不做近似匹配
+    async fn embed(&self, request: &ChatRequest) -> Option<Vec<f32>> {
+        let message = request.last_user_message()?;
+        if message.has_media() || message.content.trim().is_empty() {
+            return None;
+        }
+        let embedder = self.embedder.as_ref()?.get()?;
+
+        match embedder.embed(&[normalize(&message.content)], None).await {
+            Ok(response) => response.embeddings.into_iter().next(),
+            Err(e) => {
+                warn!(adapter = %self.adapter_name, "Failed to embed prompt for cache lookup: {}", e);
+                None
+            }
+        }
+    }
+
+    fn nearest(&self, scope: &str, embedding: &[f32], threshold: f32) -> Option<(String, f32)> {
+        let index = self.semantic_index.lock().unwrap();
+        index
+            .iter()
+            .filter(|entry| entry.scope == scope)
+            .map(|entry| (entry, cosine_similarity(&entry.embedding, embedding)))
+            .filter(|(_, similarity)| *similarity >= threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(entry, similarity)| (entry.key.clone(), similarity))
+    }
+}
+
+fn last_user_text(request: &ChatRequest) -> &str {
+    request
+        .last_user_message()
+        .map(|m| m.content.as_str())
+        .unwrap_or_default()
+}
+
+/// 去掉首尾空白并合并连续空白，空白差异不影响命中
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn cache_key(parts: &[&str]) -> String {
+    let mut context = md5::Context::new();
+    for part in parts {
+        context.consume(part.as_bytes());
+        context.consume([0u8]);
+    }
+    format!("{:x}", context.finalize())
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
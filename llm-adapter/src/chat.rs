@@ -0,0 +1,400 @@
+use crate::error::AdapterError;
+use crate::tool::{ToolCall, ToolChoice, ToolDefinition};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatRole {
+    System,
+    User,
+    Assistant,
+    Tool,
+}
+
+impl ChatRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChatRole::System => "system",
+            ChatRole::User => "user",
+            ChatRole::Assistant => "assistant",
+            ChatRole::Tool => "tool",
+        }
+    }
+}
+
+/// 图片或音频的来源：可公开访问的 URL，或 base64 编码的数据
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum MediaSource {
+    Url {
+        url: String,
+    },
+    Base64 {
+        /// 如 `image/png`、`audio/wav`
+        media_type: String,
+        data: String,
+    },
+}
+
+impl MediaSource {
+    /// URL 原样返回，base64 数据转为 data URL
+    pub fn to_url(&self) -> String {
+        match self {
+            MediaSource::Url { url } => url.clone(),
+            MediaSource::Base64 { media_type, data } => {
+                format!("data:{};base64,{}", media_type, data)
+            }
+        }
+    }
+}
+
+/// 消息中的一段内容，用于向视觉、语音模型发送图片与音频
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ContentPart {
+    Text {
+        text: String,
+    },
+    Image {
+        source: MediaSource,
+        /// OpenAI 的图片精度：`low`、`high` 或 `auto`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        detail: Option<String>,
+    },
+    Audio {
+        source: MediaSource,
+    },
+}
+
+impl ContentPart {
+    pub fn text(text: impl Into<String>) -> Self {
+        ContentPart::Text { text: text.into() }
+    }
+
+    pub fn image_url(url: impl Into<String>) -> Self {
+        ContentPart::Image {
+            source: MediaSource::Url { url: url.into() },
+            detail: None,
+        }
+    }
+
+    pub fn image_base64(media_type: impl Into<String>, data: impl Into<String>) -> Self {
+        ContentPart::Image {
+            source: MediaSource::Base64 {
+                media_type: media_type.into(),
+                data: data.into(),
+            },
+            detail: None,
+        }
+    }
+
+    pub fn audio_url(url: impl Into<String>) -> Self {
+        ContentPart::Audio {
+            source: MediaSource::Url { url: url.into() },
+        }
+    }
+
+    pub fn audio_base64(media_type: impl Into<String>, data: impl Into<String>) -> Self {
+        ContentPart::Audio {
+            source: MediaSource::Base64 {
+                media_type: media_type.into(),
+                data: data.into(),
+            },
+        }
+    }
+
+    /// 设置图片精度，其他内容不受影响
+    pub fn with_detail(mut self, value: impl Into<String>) -> Self {
+        if let ContentPart::Image { detail, .. } = &mut self {
+            *detail = Some(value.into());
+        }
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: ChatRole,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// assistant 消息中模型发起的工具调用
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// tool 消息对应的调用 ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    /// 正文之后附带的图片、音频等内容，按顺序发送
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parts: Vec<ContentPart>,
+}
+
+impl ChatMessage {
+    pub fn new(role: ChatRole, content: impl Into<String>) -> Self {
+        Self {
+            role,
+            content: content.into(),
+            name: None,
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+            parts: Vec::new(),
+        }
+    }
+
+    pub fn system(content: impl Into<String>) -> Self {
+        Self::new(ChatRole::System, content)
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self::new(ChatRole::User, content)
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self::new(ChatRole::Assistant, content)
+    }
+
+    /// 回传给模型的工具调用结果
+    pub fn tool(tool_call_id: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            tool_call_id: Some(tool_call_id.into()),
+            ..Self::new(ChatRole::Tool, content)
+        }
+    }
+
+    pub fn with_tool_calls(mut self, tool_calls: Vec<ToolCall>) -> Self {
+        self.tool_calls = tool_calls;
+        self
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn with_parts(mut self, parts: Vec<ContentPart>) -> Self {
+        self.parts = parts;
+        self
+    }
+
+    pub fn with_part(mut self, part: ContentPart) -> Self {
+        self.parts.push(part);
+        self
+    }
+
+    /// 是否包含图片或音频
+    pub fn has_media(&self) -> bool {
+        self.parts
+            .iter()
+            .any(|part| !matches!(part, ContentPart::Text { .. }))
+    }
+
+    /// 正文与附带内容按顺序合并，正文为空时省略
+    pub fn content_parts(&self, text: String) -> Vec<ContentPart> {
+        let mut parts = Vec::with_capacity(self.parts.len() + 1);
+        if !text.is_empty() {
+            parts.push(ContentPart::Text { text });
+        }
+        parts.extend(self.parts.iter().cloned());
+        parts
+    }
+
+    /// 不支持 name 字段的接口将发言者名称并入正文
+    pub fn content_with_name(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{}: {}", name, self.content),
+            None => self.content.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatRequest {
+    pub messages: Vec<ChatMessage>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<ToolDefinition>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+}
+
+impl ChatRequest {
+    pub fn new(messages: Vec<ChatMessage>) -> Self {
+        Self {
+            messages,
+            ..Default::default()
+        }
+    }
+
+    pub fn from_prompt(prompt: impl Into<String>) -> Self {
+        Self::new(vec![ChatMessage::user(prompt)])
+    }
+
+    pub fn with_message(mut self, message: ChatMessage) -> Self {
+        self.messages.push(message);
+        self
+    }
+
+    pub fn with_tools(mut self, tools: Vec<ToolDefinition>) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    pub fn with_tool_choice(mut self, tool_choice: ToolChoice) -> Self {
+        self.tool_choice = Some(tool_choice);
+        self
+    }
+
+    pub fn has_media(&self) -> bool {
+        self.messages.iter().any(ChatMessage::has_media)
+    }
+
+    /// 不支持图片与音频的适配器拒绝多模态请求，避免静默丢弃内容
+    pub fn ensure_text_only(&self, provider: &str) -> Result<(), AdapterError> {
+        if self.has_media() {
+            return Err(AdapterError::InvalidRequest(format!(
+                "{} does not support image or audio input",
+                provider
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn last_user_message(&self) -> Option<&ChatMessage> {
+        self.messages
+            .iter()
+            .rev()
+            .find(|m| m.role == ChatRole::User)
+    }
+
+    /// 为只接受单个 prompt 的适配器拼接消息；单条无名用户消息原样返回
+    pub fn to_prompt(&self) -> String {
+        if let [message] = self.messages.as_slice() {
+            if message.role == ChatRole::User && message.name.is_none() {
+                return message.content.clone();
+            }
+        }
+
+        self.messages
+            .iter()
+            .map(|m| match &m.name {
+                Some(name) => format!("[{}] {}: {}", m.role.as_str(), name, m.content),
+                None => format!("[{}] {}", m.role.as_str(), m.content),
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// OpenAI 兼容接口的消息内容：纯文本或内容数组
+#[derive(Serialize)]
+#[serde(untagged)]
+pub(crate) enum OpenAIContent {
+    Text(String),
+    Parts(Vec<OpenAIContentPart>),
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum OpenAIContentPart {
+    Text { text: String },
+    ImageUrl { image_url: OpenAIImageUrl },
+    InputAudio { input_audio: OpenAIInputAudio },
+}
+
+#[derive(Serialize)]
+pub(crate) struct OpenAIImageUrl {
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct OpenAIInputAudio {
+    data: String,
+    format: String,
+}
+
+/// 附带内容在 OpenAI 兼容接口中的表示方式，各厂商支持的范围不同
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct OpenAIMediaSupport {
+    pub audio: bool,
+    /// 智谱的 base64 图片不带 `data:` 前缀
+    pub raw_base64_image: bool,
+}
+
+impl OpenAIMediaSupport {
+    pub const OPENAI: Self = Self {
+        audio: true,
+        raw_base64_image: false,
+    };
+    pub const IMAGES_ONLY: Self = Self {
+        audio: false,
+        raw_base64_image: false,
+    };
+}
+
+impl OpenAIContent {
+    /// 没有附带内容时保持纯文本，兼容不支持内容数组的接口
+    pub(crate) fn new(
+        provider: &str,
+        message: &ChatMessage,
+        text: String,
+        support: OpenAIMediaSupport,
+    ) -> Result<Self, AdapterError> {
+        if message.parts.is_empty() {
+            return Ok(OpenAIContent::Text(text));
+        }
+
+        message
+            .content_parts(text)
+            .into_iter()
+            .map(|part| OpenAIContentPart::new(provider, part, support))
+            .collect::<Result<_, _>>()
+            .map(OpenAIContent::Parts)
+    }
+}
+
+impl OpenAIContentPart {
+    fn new(
+        provider: &str,
+        part: ContentPart,
+        support: OpenAIMediaSupport,
+    ) -> Result<Self, AdapterError> {
+        match part {
+            ContentPart::Text { text } => Ok(OpenAIContentPart::Text { text }),
+            ContentPart::Image { source, detail } => {
+                let url = match source {
+                    MediaSource::Base64 { data, .. } if support.raw_base64_image => data,
+                    source => source.to_url(),
+                };
+                Ok(OpenAIContentPart::ImageUrl {
+                    image_url: OpenAIImageUrl { url, detail },
+                })
+            }
+            ContentPart::Audio { source } if support.audio => match source {
+                MediaSource::Base64 { media_type, data } => Ok(OpenAIContentPart::InputAudio {
+                    input_audio: OpenAIInputAudio {
+                        data,
+                        format: audio_format(&media_type),
+                    },
+                }),
+                MediaSource::Url { .. } => Err(AdapterError::InvalidRequest(format!(
+                    "{} only accepts base64 audio input",
+                    provider
+                ))),
+            },
+            ContentPart::Audio { .. } => Err(AdapterError::InvalidRequest(format!(
+                "{} does not support audio input",
+                provider
+            ))),
+        }
+    }
+}
+
+/// `audio/mpeg` → `mp3`，其余取子类型（如 `audio/wav` → `wav`）
+fn audio_format(media_type: &str) -> String {
+    match media_type.rsplit('/').next().unwrap_or(media_type) {
+        "mpeg" => "mp3".to_string(),
+        "x-wav" => "wav".to_string(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,257 @@
+use crate::error::AdapterError;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+#[derive(Clone, Debug)]
+pub struct CircuitBreakerConfig {
+    pub failure_threshold: u32,
+    /// 最近 `window_size` 次调用的失败率达到该值时熔断
+    pub failure_rate_threshold: f64,
+    pub min_requests: u32,
+    pub window_size: usize,
+    pub cool_down: Duration,
+    /// 半开状态下同时放行的探测请求数
+    pub half_open_max_probes: u32,
+    /// 半开状态下连续成功多少次后恢复
+    pub success_threshold: u32,
+    pub enabled: bool,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            failure_rate_threshold: 0.5,
+            min_requests: 10,
+            window_size: 20,
+            cool_down: Duration::from_secs(30),
+            half_open_max_probes: 1,
+            success_threshold: 1,
+            enabled: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half_open",
+        }
+    }
+
+    /// 用于监控指标：0 关闭，1 半开，2 熔断
+    pub fn as_gauge(&self) -> f64 {
+        match self {
+            CircuitState::Closed => 0.0,
+            CircuitState::HalfOpen => 1.0,
+            CircuitState::Open => 2.0,
+        }
+    }
+}
+
+struct BreakerState {
+    state: CircuitState,
+    consecutive_failures: u32,
+    /// 最近调用结果，true 表示失败
+    window: VecDeque<bool>,
+    opened_at: Option<Instant>,
+    probes_in_flight: u32,
+    probe_successes: u32,
+}
+
+pub struct CircuitBreaker {
+    name: String,
+    config: CircuitBreakerConfig,
+    inner: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: impl Into<String>, config: CircuitBreakerConfig) -> Self {
+        Self {
+            name: name.into(),
+            config,
+            inner: Mutex::new(BreakerState {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                window: VecDeque::new(),
+                opened_at: None,
+                probes_in_flight: 0,
+                probe_successes: 0,
+            }),
+        }
+    }
+
+    pub fn config(&self) -> &CircuitBreakerConfig {
+        &self.config
+    }
+
+    /// 当前状态，冷却期已过的熔断状态报告为半开
+    pub fn state(&self) -> CircuitState {
+        let inner = self.inner.lock().unwrap();
+        match inner.state {
+            CircuitState::Open if self.cool_down_remaining(&inner).is_none() => {
+                CircuitState::HalfOpen
+            }
+            state => state,
+        }
+    }
+
+    /// 放行一次调用；半开状态下超出探测名额的请求被拒绝
+    pub fn acquire(&self) -> Result<CircuitGuard<'_>, AdapterError> {
+        if !self.config.enabled {
+            return Ok(CircuitGuard {
+                breaker: self,
+                probe: false,
+                recorded: true,
+            });
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state == CircuitState::Open {
+            if let Some(remaining) = self.cool_down_remaining(&inner) {
+                return Err(self.open_error(Some(remaining)));
+            }
+            info!(adapter = %self.name, "Circuit half-open, probing");
+            inner.state = CircuitState::HalfOpen;
+            inner.probe_successes = 0;
+        }
+
+        let probe = inner.state == CircuitState::HalfOpen;
+        if probe {
+            if inner.probes_in_flight >= self.config.half_open_max_probes {
+                return Err(self.open_error(None));
+            }
+            inner.probes_in_flight += 1;
+        }
+
+        Ok(CircuitGuard {
+            breaker: self,
+            probe,
+            recorded: false,
+        })
+    }
+
+    fn record(&self, probe: bool, failed: bool) {
+        if !self.config.enabled {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        if probe {
+            inner.probes_in_flight = inner.probes_in_flight.saturating_sub(1);
+        }
+
+        if failed {
+            inner.consecutive_failures += 1;
+        } else {
+            inner.consecutive_failures = 0;
+        }
+        inner.window.push_back(failed);
+        while inner.window.len() > self.config.window_size {
+            inner.window.pop_front();
+        }
+
+        match inner.state {
+            CircuitState::HalfOpen if failed => self.open(&mut inner),
+            CircuitState::HalfOpen => {
+                inner.probe_successes += 1;
+                if inner.probe_successes >= self.config.success_threshold {
+                    info!(adapter = %self.name, "Circuit closed");
+                    inner.state = CircuitState::Closed;
+                    inner.opened_at = None;
+                    inner.window.clear();
+                }
+            }
+            CircuitState::Closed if failed && self.should_open(&inner) => self.open(&mut inner),
+            _ => {}
+        }
+    }
+
+    fn release_probe(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.probes_in_flight = inner.probes_in_flight.saturating_sub(1);
+    }
+
+    fn should_open(&self, inner: &BreakerState) -> bool {
+        if inner.consecutive_failures >= self.config.failure_threshold {
+            return true;
+        }
+
+        let total = inner.window.len();
+        if total < self.config.min_requests as usize || total == 0 {
+            return false;
+        }
+        let failures = inner.window.iter().filter(|f| **f).count();
+        failures as f64 / total as f64 >= self.config.failure_rate_threshold
+    }
+
+    fn open(&self, inner: &mut BreakerState) {
+        warn!(
+            adapter = %self.name,
+            consecutive_failures = inner.consecutive_failures,
+            "Circuit opened"
+        );
+        inner.state = CircuitState::Open;
+        inner.opened_at = Some(Instant::now());
+        inner.probe_successes = 0;
+    }
+
+    fn cool_down_remaining(&self, inner: &BreakerState) -> Option<Duration> {
+        let opened_at = inner.opened_at?;
+        self.config
+            .cool_down
+            .checked_sub(opened_at.elapsed())
+            .filter(|d| !d.is_zero())
+    }
+
+    fn open_error(&self, retry_after: Option<Duration>) -> AdapterError {
+        AdapterError::CircuitOpen {
+            retry_after,
+            message: format!("Circuit open for adapter {}", self.name),
+        }
+    }
+}
+
+/// 一次被放行的调用，需通过 [`CircuitGuard::record`] 报告结果；未报告即丢弃时只释放探测名额
+pub struct CircuitGuard<'a> {
+    breaker: &'a CircuitBreaker,
+    probe: bool,
+    recorded: bool,
+}
+
+impl CircuitGuard<'_> {
+    /// 只有上游不可用类错误计入失败，请求本身的错误说明服务仍然可用
+    pub fn record<T>(mut self, result: &anyhow::Result<T>) {
+        self.recorded = true;
+        self.breaker.record(self.probe, is_failure(result));
+    }
+}
+
+pub(crate) fn is_failure<T>(result: &anyhow::Result<T>) -> bool {
+    match result {
+        Ok(_) => false,
+        Err(e) => e
+            .downcast_ref::<AdapterError>()
+            .map(|err| err.is_retryable())
+            .unwrap_or(true),
+    }
+}
+
+impl Drop for CircuitGuard<'_> {
+    fn drop(&mut self) {
+        if !self.recorded && self.probe {
+            self.breaker.release_probe();
+        }
+    }
+}
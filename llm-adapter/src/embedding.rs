@@ -0,0 +1,149 @@
+use crate::error::{check_response, AdapterError};
+use crate::registry::{Adapter, InvokeOptions};
+use crate::response::{OpenAIUsage, Usage};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// 一批文本的向量，顺序与输入一致
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddingResponse {
+    pub embeddings: Vec<Vec<f32>>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub usage: Option<Usage>,
+}
+
+impl EmbeddingResponse {
+    pub fn new(embeddings: Vec<Vec<f32>>) -> Self {
+        Self {
+            embeddings,
+            model: None,
+            usage: None,
+        }
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    pub fn with_usage(mut self, usage: Option<Usage>) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    /// 向量维度，没有结果时为 0
+    pub fn dimensions(&self) -> usize {
+        self.embeddings.first().map(Vec::len).unwrap_or(0)
+    }
+
+    /// 合并分批请求的结果，用量累加
+    pub(crate) fn append(&mut self, other: EmbeddingResponse) {
+        self.embeddings.extend(other.embeddings);
+        if self.model.is_none() {
+            self.model = other.model;
+        }
+        self.usage = match (self.usage.take(), other.usage) {
+            (Some(a), Some(b)) => Some(
+                Usage::new(
+                    a.prompt_tokens + b.prompt_tokens,
+                    a.completion_tokens + b.completion_tokens,
+                )
+                .with_cached_tokens(a.cached_tokens + b.cached_tokens),
+            ),
+            (a, b) => a.or(b),
+        };
+    }
+}
+
+/// 生成文本向量的适配器，`model` 为空时使用适配器配置的向量模型
+#[async_trait]
+pub trait EmbeddingAdapter: Adapter {
+    async fn embed(
+        &self,
+        texts: &[String],
+        model: Option<&str>,
+    ) -> anyhow::Result<EmbeddingResponse>;
+
+    /// 带用户、租户等调用信息，包装后的适配器据此限流与计费
+    async fn embed_with_options(
+        &self,
+        texts: &[String],
+        options: &InvokeOptions,
+    ) -> anyhow::Result<EmbeddingResponse> {
+        self.embed(texts, options.model.as_deref()).await
+    }
+}
+
+#[derive(Serialize)]
+struct OpenAIEmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct OpenAIEmbeddingResponse {
+    #[serde(default)]
+    data: Vec<OpenAIEmbedding>,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    usage: Option<OpenAIUsage>,
+}
+
+#[derive(Deserialize)]
+struct OpenAIEmbedding {
+    #[serde(default)]
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+/// OpenAI 兼容的 embeddings 接口，超过 `batch_size` 条时分批请求后合并
+pub(crate) async fn openai_embeddings(
+    provider: &str,
+    request: impl Fn() -> reqwest::RequestBuilder,
+    model: &str,
+    texts: &[String],
+    batch_size: usize,
+) -> anyhow::Result<EmbeddingResponse> {
+    let mut result = EmbeddingResponse::default();
+
+    for batch in texts.chunks(batch_size.max(1)) {
+        let response = request()
+            .json(&OpenAIEmbeddingRequest {
+                model,
+                input: batch,
+            })
+            .send()
+            .await
+            .map_err(AdapterError::from)?;
+        let mut body: OpenAIEmbeddingResponse = check_response(provider, response)
+            .await?
+            .json()
+            .await
+            .map_err(AdapterError::from)?;
+
+        if body.data.len() != batch.len() {
+            return Err(AdapterError::Decode(format!(
+                "{} returned {} embeddings for {} inputs",
+                provider,
+                body.data.len(),
+                batch.len()
+            ))
+            .into());
+        }
+        body.data.sort_by_key(|item| item.index);
+
+        result.append(EmbeddingResponse {
+            embeddings: body.data.into_iter().map(|item| item.embedding).collect(),
+            model: body.model,
+            usage: body.usage.map(Usage::from),
+        });
+    }
+
+    if result.model.is_none() {
+        result.model = Some(model.to_string());
+    }
+    Ok(result)
+}
@@ -0,0 +1,276 @@
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use serde_json::Value;
+use std::time::Duration;
+
+/// 适配器调用失败的分类，以 `anyhow::Error` 形式返回，调用方可通过 `downcast_ref` 取回
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdapterError {
+    /// API Key 无效或无权限（401/403）
+    Auth(String),
+    /// 提供商限流，或本地限流器拒绝
+    RateLimited {
+        retry_after: Option<Duration>,
+        message: String,
+    },
+    /// 输入超出模型上下文长度
+    ContextLengthExceeded(String),
+    /// 输入或输出触发了内容审核
+    ContentFiltered(String),
+    InvalidRequest(String),
+    Upstream5xx {
+        status: u16,
+        message: String,
+    },
+    Timeout(String),
+    Network(String),
+    /// 响应无法解析
+    Decode(String),
+    /// 熔断器打开，请求未发出
+    CircuitOpen {
+        retry_after: Option<Duration>,
+        message: String,
+    },
+    /// 本地排队过长，请求被丢弃
+    Overloaded {
+        retry_after: Option<Duration>,
+        message: String,
+    },
+    /// 预算已用尽，`retry_after` 为距离周期重置的时间
+    BudgetExceeded {
+        retry_after: Option<Duration>,
+        message: String,
+    },
+}
+
+impl AdapterError {
+    /// 根据 HTTP 状态码和响应体分类，响应体中的错误信息按常见字段提取
+    pub fn from_status(status: u16, body: &str, retry_after: Option<Duration>) -> Self {
+        let message = error_message(body).unwrap_or_else(|| body.trim().to_string());
+        Self::classify(status, body, message, retry_after)
+    }
+
+    pub(crate) fn classify(
+        status: u16,
+        body: &str,
+        message: String,
+        retry_after: Option<Duration>,
+    ) -> Self {
+        let message = if message.is_empty() {
+            format!("HTTP {}", status)
+        } else {
+            message
+        };
+
+        match status {
+            401 | 403 => AdapterError::Auth(message),
+            429 => AdapterError::RateLimited {
+                retry_after,
+                message,
+            },
+            408 | 504 => AdapterError::Timeout(message),
+            500..=599 => AdapterError::Upstream5xx { status, message },
+            _ if is_context_length_error(body) => AdapterError::ContextLengthExceeded(message),
+            _ if is_content_filter_error(body) => AdapterError::ContentFiltered(message),
+            413 => AdapterError::ContextLengthExceeded(message),
+            _ => AdapterError::InvalidRequest(message),
+        }
+    }
+
+    pub(crate) fn no_choices(provider: &str) -> Self {
+        AdapterError::Decode(format!("{} API returned no choices", provider))
+    }
+
+    /// 限流、上游 5xx、超时、网络错误、熔断与过载可以重试（或切换到其他适配器），其余重试也不会成功
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            AdapterError::RateLimited { .. }
+                | AdapterError::Upstream5xx { .. }
+                | AdapterError::Timeout(_)
+                | AdapterError::Network(_)
+                | AdapterError::CircuitOpen { .. }
+                | AdapterError::Overloaded { .. }
+        )
+    }
+
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            AdapterError::RateLimited { retry_after, .. }
+            | AdapterError::CircuitOpen { retry_after, .. }
+            | AdapterError::Overloaded { retry_after, .. }
+            | AdapterError::BudgetExceeded { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// 稳定的错误码，用于 API 响应
+    pub fn code(&self) -> &'static str {
+        match self {
+            AdapterError::Auth(_) => "auth_error",
+            AdapterError::RateLimited { .. } => "rate_limited",
+            AdapterError::ContextLengthExceeded(_) => "context_length_exceeded",
+            AdapterError::ContentFiltered(_) => "content_filtered",
+            AdapterError::InvalidRequest(_) => "invalid_request",
+            AdapterError::Upstream5xx { .. } => "upstream_error",
+            AdapterError::Timeout(_) => "timeout",
+            AdapterError::Network(_) => "network_error",
+            AdapterError::Decode(_) => "decode_error",
+            AdapterError::CircuitOpen { .. } => "circuit_open",
+            AdapterError::Overloaded { .. } => "overloaded",
+            AdapterError::BudgetExceeded { .. } => "budget_exceeded",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            AdapterError::Auth(message)
+            | AdapterError::RateLimited { message, .. }
+            | AdapterError::ContextLengthExceeded(message)
+            | AdapterError::ContentFiltered(message)
+            | AdapterError::InvalidRequest(message)
+            | AdapterError::Upstream5xx { message, .. }
+            | AdapterError::Timeout(message)
+            | AdapterError::Network(message)
+            | AdapterError::Decode(message)
+            | AdapterError::CircuitOpen { message, .. }
+            | AdapterError::Overloaded { message, .. }
+            | AdapterError::BudgetExceeded { message, .. } => message,
+        }
+    }
+}
+
+impl std::fmt::Display for AdapterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdapterError::Auth(msg) => write!(f, "Authentication failed: {}", msg),
+            AdapterError::RateLimited {
+                retry_after: Some(after),
+                message,
+            } => write!(
+                f,
+                "Rate limited: {} (retry after {}s)",
+                message,
+                after.as_secs()
+            ),
+            AdapterError::RateLimited { message, .. } => write!(f, "Rate limited: {}", message),
+            AdapterError::ContextLengthExceeded(msg) => {
+                write!(f, "Context length exceeded: {}", msg)
+            }
+            AdapterError::ContentFiltered(msg) => write!(f, "Content filtered: {}", msg),
+            AdapterError::InvalidRequest(msg) => write!(f, "Invalid request: {}", msg),
+            AdapterError::Upstream5xx { status, message } => {
+                write!(f, "Upstream error ({}): {}", status, message)
+            }
+            AdapterError::Timeout(msg) => write!(f, "Request timed out: {}", msg),
+            AdapterError::Network(msg) => write!(f, "Network error: {}", msg),
+            AdapterError::Decode(msg) => write!(f, "Failed to decode response: {}", msg),
+            AdapterError::CircuitOpen { message, .. } => write!(f, "{}", message),
+            AdapterError::Overloaded { message, .. } => write!(f, "Overloaded: {}", message),
+            AdapterError::BudgetExceeded { message, .. } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for AdapterError {}
+
+impl From<reqwest::Error> for AdapterError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            AdapterError::Timeout(e.to_string())
+        } else if e.is_decode() {
+            AdapterError::Decode(e.to_string())
+        } else if let Some(status) = e.status() {
+            AdapterError::classify(status.as_u16(), "", e.to_string(), None)
+        } else {
+            AdapterError::Network(e.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for AdapterError {
+    fn from(e: serde_json::Error) -> Self {
+        AdapterError::Decode(e.to_string())
+    }
+}
+
+/// 检查响应状态，失败时读取响应体并转换为 [`AdapterError`]
+pub(crate) async fn check_response(
+    provider: &str,
+    response: reqwest::Response,
+) -> Result<reqwest::Response, AdapterError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let retry_after = parse_retry_after(response.headers());
+    let text = response.text().await.unwrap_or_default();
+    tracing::error!("{} API error ({}): {}", provider, status, text);
+
+    let message = error_message(&text).unwrap_or_else(|| status.to_string());
+    Err(AdapterError::classify(
+        status.as_u16(),
+        &text,
+        format!("{} API error: {}", provider, message),
+        retry_after,
+    ))
+}
+
+/// Retry-After 只支持秒数格式
+pub(crate) fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// 按 OpenAI（error.message）、千问（message）等常见格式提取错误信息
+pub(crate) fn error_message(body: &str) -> Option<String> {
+    let value: Value = serde_json::from_str(body).ok()?;
+    let candidates = [
+        value.pointer("/error/message"),
+        value.get("message"),
+        value.get("error_msg"),
+        value.get("msg"),
+        value.get("error"),
+    ];
+
+    let message = candidates.into_iter().flatten().find_map(|v| v.as_str())?;
+    Some(message.to_string())
+}
+
+fn is_context_length_error(body: &str) -> bool {
+    let body = body.to_lowercase();
+    [
+        "context_length_exceeded",
+        "maximum context length",
+        "context length",
+        "too many tokens",
+        "range of input length",
+        "input length",
+        "prompt is too long",
+        "超长",
+        "超过最大长度",
+    ]
+    .iter()
+    .any(|marker| body.contains(marker))
+}
+
+fn is_content_filter_error(body: &str) -> bool {
+    let body = body.to_lowercase();
+    [
+        "content_filter",
+        "content_policy",
+        "datainspectionfailed",
+        "sensitivecontentdetected",
+        "inappropriate content",
+        "\"1301\"",
+        "敏感内容",
+    ]
+    .iter()
+    .any(|marker| body.contains(marker))
+}
@@ -1,51 +1,131 @@
+use crate::adaptive::AdaptiveConfig;
+use crate::billing_store::{BillingStore, JsonlBillingStore, SqliteBillingStore};
+use crate::cache::CacheConfig;
+use crate::circuit_breaker::CircuitBreaker;
 use crate::config::AdapterConfig;
 use crate::generic::{AuthType, GenericAdapter, RequestConfig};
+use crate::guard::ConcurrencyConfig;
+use crate::health::{HealthCheckConfig, HealthMonitor, HealthProbe};
+use crate::pricing::PricingCatalog;
 use crate::providers::{
-    DeepSeekAdapter, DoubaoAdapter, OpenAIAdapter, QianwenAdapter, ZhipuAdapter,
+    AnthropicAdapter, DeepSeekAdapter, DoubaoAdapter, GeminiAdapter, OllamaAdapter, OpenAIAdapter,
+    QianwenAdapter, ZhipuAdapter,
 };
+use crate::rate_limit::{InMemoryRateLimitBackend, RateLimitBackend, RateLimitConfig};
+use crate::redis_backend::{RedisConcurrencyBackend, RedisRateLimitBackend, RedisStore};
 use crate::registry::Adapter;
-use crate::{BillingTracker, ConcurrencyGuard, RateLimiter};
+use crate::{BillingTracker, ConcurrencyGuard, RateLimiter, RetryPolicy};
 use std::sync::Arc;
-use tracing::info;
+use tracing::{info, warn};
 
 pub struct AdapterFactory;
 
+/// 本地部署的模型服务，不要求 API Key，默认不计费
+const LOCAL_PROVIDERS: [&str; 3] = ["ollama", "llamacpp", "vllm"];
+
 impl AdapterFactory {
+    pub fn is_local_provider(name: &str) -> bool {
+        LOCAL_PROVIDERS.contains(&name)
+    }
+
     pub fn create_adapter(config: AdapterConfig) -> anyhow::Result<Arc<dyn Adapter + Send + Sync>> {
-        let api_key = config
-            .api_key
-            .clone()
-            .ok_or_else(|| anyhow::anyhow!("API key is required for adapter: {}", config.name))?;
+        let health_probe = Self::health_probe(&config.metadata);
+        if Self::is_local_provider(&config.name) {
+            return Ok(Self::create_local_adapter(&config, health_probe));
+        }
+
+        // 内置提供商必须配置 Key，通用适配器由 create_generic_adapter 按认证方式检查
+        let api_key = || {
+            config
+                .api_key
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("API key is required for adapter: {}", config.name))
+        };
+
+        let embedding_model = Self::embedding_model(&config.metadata);
 
         let adapter: Arc<dyn Adapter + Send + Sync> = match config.name.as_str() {
             "openai" => {
                 info!("Creating built-in OpenAI adapter");
-                Arc::new(OpenAIAdapter::new(api_key, config.model.clone()))
+                let mut adapter = OpenAIAdapter::new(api_key()?, config.model.clone());
+                if let Some(model) = embedding_model {
+                    adapter = adapter.with_embedding_model(model);
+                }
+                Arc::new(adapter.with_health_probe(health_probe))
+            }
+            "anthropic" => {
+                info!("Creating built-in Anthropic adapter");
+                let adapter = match &config.base_url {
+                    Some(base_url) => AnthropicAdapter::new_with_base(
+                        api_key()?,
+                        config
+                            .model
+                            .clone()
+                            .unwrap_or_else(|| "claude-sonnet-4-5".to_string()),
+                        base_url.clone(),
+                    ),
+                    None => AnthropicAdapter::new(api_key()?, config.model.clone()),
+                };
+                Arc::new(adapter.with_health_probe(health_probe))
+            }
+            "gemini" => {
+                info!("Creating built-in Gemini adapter");
+                let adapter = match &config.base_url {
+                    Some(base_url) => GeminiAdapter::new_with_base(
+                        api_key()?,
+                        config
+                            .model
+                            .clone()
+                            .unwrap_or_else(|| "gemini-2.0-flash".to_string()),
+                        base_url.clone(),
+                    ),
+                    None => GeminiAdapter::new(api_key()?, config.model.clone()),
+                };
+                let query_auth =
+                    config.metadata.get("auth_type").and_then(|v| v.as_str()) == Some("query");
+                Arc::new(
+                    adapter
+                        .with_query_auth(query_auth)
+                        .with_health_probe(health_probe),
+                )
             }
             "deepseek" => {
                 info!("Creating built-in DeepSeek adapter");
-                Arc::new(DeepSeekAdapter::new(api_key, config.model.clone()))
+                Arc::new(
+                    DeepSeekAdapter::new(api_key()?, config.model.clone())
+                        .with_health_probe(health_probe),
+                )
             }
             "zhipu" => {
                 info!("Creating built-in Zhipu adapter");
-                Arc::new(ZhipuAdapter::new(api_key, config.model.clone()))
+                let mut adapter = ZhipuAdapter::new(api_key()?, config.model.clone());
+                if let Some(model) = embedding_model {
+                    adapter = adapter.with_embedding_model(model);
+                }
+                Arc::new(adapter.with_health_probe(health_probe))
             }
             "doubao" => {
                 info!("Creating built-in Doubao adapter");
-                Arc::new(DoubaoAdapter::new(api_key, config.model.clone()))
+                Arc::new(
+                    DoubaoAdapter::new(api_key()?, config.model.clone())
+                        .with_health_probe(health_probe),
+                )
             }
             "qianwen" => {
-                if let Some(base_url) = &config.base_url {
-                    if base_url.contains("compatible-mode") {
-                        info!("Creating generic adapter for Qianwen (OpenAI-compatible mode)");
-                        Self::create_generic_adapter(config)?
-                    } else {
-                        info!("Creating built-in Qianwen adapter (native API)");
-                        Arc::new(QianwenAdapter::new(api_key, config.model.clone()))
-                    }
+                let compatible_mode = config
+                    .base_url
+                    .as_ref()
+                    .is_some_and(|base_url| base_url.contains("compatible-mode"));
+                if compatible_mode {
+                    info!("Creating generic adapter for Qianwen (OpenAI-compatible mode)");
+                    Self::create_generic_adapter(config)?
                 } else {
                     info!("Creating built-in Qianwen adapter (native API)");
-                    Arc::new(QianwenAdapter::new(api_key, config.model.clone()))
+                    let mut adapter = QianwenAdapter::new(api_key()?, config.model.clone());
+                    if let Some(model) = embedding_model {
+                        adapter = adapter.with_embedding_model(model);
+                    }
+                    Arc::new(adapter.with_health_probe(health_probe))
                 }
             }
             _ => {
@@ -57,12 +137,78 @@ impl AdapterFactory {
         Ok(adapter)
     }
 
+    fn create_local_adapter(
+        config: &AdapterConfig,
+        health_probe: HealthProbe,
+    ) -> Arc<dyn Adapter + Send + Sync> {
+        let model = config.model.clone();
+        let embedding_model = Self::embedding_model(&config.metadata);
+        match config.name.as_str() {
+            "ollama" => {
+                info!("Creating built-in Ollama adapter");
+                let mut adapter = match &config.base_url {
+                    Some(base_url) => OllamaAdapter::new_with_base(
+                        model.unwrap_or_else(|| "llama3.2".to_string()),
+                        base_url.clone(),
+                    ),
+                    None => OllamaAdapter::new(model),
+                };
+                if let Some(keep_alive) = config.metadata.get("keep_alive") {
+                    adapter = adapter.with_keep_alive(keep_alive.clone());
+                }
+                if let Some(model) = embedding_model {
+                    adapter = adapter.with_embedding_model(model);
+                }
+                Arc::new(adapter.with_health_probe(health_probe))
+            }
+            name => {
+                // llama.cpp 与 vLLM 提供 OpenAI 兼容接口，启动时指定了 --api-key 才需要配置 Key
+                info!("Creating OpenAI-compatible local adapter: {}", name);
+                let default_base_url = match name {
+                    "vllm" => "http://localhost:8000",
+                    _ => "http://localhost:8080",
+                };
+                let model = model.unwrap_or_else(|| "default".to_string());
+                // 本地服务通常只加载一个模型，未单独配置时向量请求也使用它
+                let embedding_model = embedding_model.unwrap_or_else(|| model.clone());
+                Arc::new(
+                    OpenAIAdapter::new_with_base(
+                        config.api_key.clone().unwrap_or_default(),
+                        model,
+                        config
+                            .base_url
+                            .clone()
+                            .unwrap_or_else(|| default_base_url.to_string()),
+                    )
+                    .with_name(name.to_string())
+                    .with_embedding_model(embedding_model)
+                    .with_health_probe(health_probe),
+                )
+            }
+        }
+    }
+
+    /// 元数据 `embedding_model` 指定向量接口默认使用的模型
+    fn embedding_model(
+        metadata: &std::collections::HashMap<String, serde_json::Value>,
+    ) -> Option<String> {
+        metadata
+            .get("embedding_model")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    }
+
     pub fn create_generic_adapter(
         config: AdapterConfig,
     ) -> anyhow::Result<Arc<dyn Adapter + Send + Sync>> {
-        let api_key = config
-            .api_key
-            .ok_or_else(|| anyhow::anyhow!("API key is required for adapter: {}", config.name))?;
+        let request_config = Self::parse_request_config(&config.metadata)?;
+
+        // auth_type 为 none 的自建服务可以不配置 API Key
+        let api_key = match config.api_key {
+            Some(api_key) => api_key,
+            None if matches!(request_config.auth_type, AuthType::None) => String::new(),
+            None => anyhow::bail!("API key is required for adapter: {}", config.name),
+        };
 
         let base_url = config
             .base_url
@@ -70,15 +216,22 @@ impl AdapterFactory {
 
         let model = config.model.unwrap_or_else(|| "default".to_string());
 
-        let request_config = Self::parse_request_config(&config.metadata)?;
-
-        let adapter = GenericAdapter::new(
+        let mut adapter = GenericAdapter::new(
             config.name.clone(),
             api_key,
             model,
             base_url,
             request_config,
-        );
+        )
+        .with_health_probe(Self::health_probe(&config.metadata));
+
+        if let Some(endpoint) = config
+            .metadata
+            .get("health_endpoint")
+            .and_then(|v| v.as_str())
+        {
+            adapter = adapter.with_health_endpoint(endpoint.to_string());
+        }
 
         info!("Created generic adapter: {}", config.name);
         Ok(Arc::new(adapter) as Arc<dyn Adapter + Send + Sync>)
@@ -104,6 +257,11 @@ impl AdapterFactory {
             model_field: "model".to_string(),
             message_field: "messages".to_string(),
             response_path: "choices.0.message.content".to_string(),
+            stream_response_path: "choices.0.delta.content".to_string(),
+            finish_reason_path: "choices.0.finish_reason".to_string(),
+            usage_path: "usage".to_string(),
+            tool_calls_path: "choices.0.message.tool_calls".to_string(),
+            error_path: None,
         };
 
         if let Some(endpoint) = metadata.get("endpoint_template").and_then(|v| v.as_str()) {
@@ -151,14 +309,100 @@ impl AdapterFactory {
             config.response_path = response_path.to_string();
         }
 
+        if let Some(path) = metadata
+            .get("stream_response_path")
+            .and_then(|v| v.as_str())
+        {
+            config.stream_response_path = path.to_string();
+        }
+
+        if let Some(path) = metadata.get("finish_reason_path").and_then(|v| v.as_str()) {
+            config.finish_reason_path = path.to_string();
+        }
+
+        if let Some(path) = metadata.get("usage_path").and_then(|v| v.as_str()) {
+            config.usage_path = path.to_string();
+        }
+
+        if let Some(path) = metadata.get("tool_calls_path").and_then(|v| v.as_str()) {
+            config.tool_calls_path = path.to_string();
+        }
+
+        if let Some(path) = metadata.get("error_path").and_then(|v| v.as_str()) {
+            config.error_path = Some(path.to_string());
+        }
+
         Ok(config)
     }
 
     pub fn create_rate_limiter(
         metadata: &std::collections::HashMap<String, serde_json::Value>,
-    ) -> Arc<RateLimiter> {
-        use crate::rate_limit::RateLimitConfig;
+    ) -> anyhow::Result<Arc<RateLimiter>> {
+        Ok(Arc::new(RateLimiter::with_backend(
+            Self::rate_limit_config(metadata),
+            Self::create_rate_limit_backend(metadata)?,
+        )))
+    }
+
+    /// 元数据 `rate_limit_backend` 为 "redis" 时多副本共享限流状态
+    pub fn create_rate_limit_backend(
+        metadata: &std::collections::HashMap<String, serde_json::Value>,
+    ) -> anyhow::Result<Arc<dyn RateLimitBackend>> {
+        Ok(match Self::shared_store(metadata, "rate_limit_backend")? {
+            Some(store) => Arc::new(RedisRateLimitBackend::new(store)),
+            None => Arc::new(InMemoryRateLimitBackend::new()),
+        })
+    }
+
+    /// 限流、并发或计费存储的后端类型，默认 "memory"
+    pub fn backend_kind<'a>(
+        metadata: &'a std::collections::HashMap<String, serde_json::Value>,
+        key: &str,
+    ) -> &'a str {
+        metadata
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or("memory")
+    }
+
+    /// 限流或并发控制的共享存储，"memory" 时为 None；
+    /// 请求了 Redis 却没有可用的地址时报错，不会悄悄退回进程内实现
+    fn shared_store(
+        metadata: &std::collections::HashMap<String, serde_json::Value>,
+        key: &str,
+    ) -> anyhow::Result<Option<Arc<RedisStore>>> {
+        match Self::backend_kind(metadata, key) {
+            "memory" => Ok(None),
+            "redis" => Self::redis_store(metadata)
+                .map(Some)
+                .map_err(|e| anyhow::anyhow!("{} is redis but {}", key, e)),
+            other => anyhow::bail!("Unknown {}: {}", key, other),
+        }
+    }
+
+    /// 读取 `redis_url`（未设置时使用环境变量 REDIS_URL）和 `redis_key_prefix`
+    fn redis_store(
+        metadata: &std::collections::HashMap<String, serde_json::Value>,
+    ) -> anyhow::Result<Arc<RedisStore>> {
+        let url = metadata
+            .get("redis_url")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .or_else(|| std::env::var("REDIS_URL").ok())
+            .ok_or_else(|| anyhow::anyhow!("neither redis_url nor REDIS_URL is set"))?;
+
+        let prefix = metadata
+            .get("redis_key_prefix")
+            .and_then(|v| v.as_str())
+            .unwrap_or("llm_adapter");
+        let store = RedisStore::open(&url, prefix)
+            .map_err(|e| anyhow::anyhow!("redis_url is invalid: {}", e))?;
+        Ok(Arc::new(store))
+    }
 
+    pub fn rate_limit_config(
+        metadata: &std::collections::HashMap<String, serde_json::Value>,
+    ) -> RateLimitConfig {
         let mut config = RateLimitConfig::default();
 
         if let Some(rps) = metadata.get("rate_limit_rps").and_then(|v| v.as_u64()) {
@@ -173,24 +417,60 @@ impl AdapterFactory {
             config.requests_per_hour = rph as u32;
         }
 
+        if let Some(burst) = metadata.get("rate_limit_burst").and_then(|v| v.as_u64()) {
+            config.burst = Some(burst as u32);
+        }
+
         if let Some(enabled) = metadata.get("rate_limit_enabled").and_then(|v| v.as_bool()) {
             config.enabled = enabled;
         }
 
-        Arc::new(RateLimiter::new(config))
+        config
     }
 
     pub fn create_concurrency_guard(
+        name: &str,
         metadata: &std::collections::HashMap<String, serde_json::Value>,
-    ) -> Arc<ConcurrencyGuard> {
-        use crate::guard::ConcurrencyConfig;
+    ) -> anyhow::Result<Arc<ConcurrencyGuard>> {
+        let config = Self::concurrency_config(metadata);
+
+        if let Some(store) = Self::shared_store(metadata, "concurrency_backend")? {
+            let mut backend = RedisConcurrencyBackend::new(store, name, config.max_concurrent);
+            if let Some(ms) = metadata
+                .get("concurrency_lease_ms")
+                .and_then(|v| v.as_u64())
+            {
+                backend = backend.with_lease(std::time::Duration::from_millis(ms.max(1)));
+            }
+            return Ok(Arc::new(ConcurrencyGuard::with_backend(
+                config,
+                Arc::new(backend),
+            )));
+        }
 
+        Ok(Arc::new(ConcurrencyGuard::new(config)))
+    }
+
+    pub fn concurrency_config(
+        metadata: &std::collections::HashMap<String, serde_json::Value>,
+    ) -> ConcurrencyConfig {
         let mut config = ConcurrencyConfig::default();
 
         if let Some(max) = metadata.get("max_concurrent").and_then(|v| v.as_u64()) {
             config.max_concurrent = max as usize;
         }
 
+        if let Some(ms) = metadata.get("acquire_timeout_ms").and_then(|v| v.as_u64()) {
+            config.acquire_timeout = std::time::Duration::from_millis(ms);
+        }
+
+        if let Some(fail_fast) = metadata
+            .get("concurrency_fail_fast")
+            .and_then(|v| v.as_bool())
+        {
+            config.fail_fast = fail_fast;
+        }
+
         if let Some(enabled) = metadata
             .get("concurrency_enabled")
             .and_then(|v| v.as_bool())
@@ -198,15 +478,270 @@ impl AdapterFactory {
             config.enabled = enabled;
         }
 
-        Arc::new(ConcurrencyGuard::new(config))
+        if let Some(max_queue) = metadata
+            .get("concurrency_max_queue")
+            .and_then(|v| v.as_u64())
+        {
+            config.max_queue = Some(max_queue as usize);
+        }
+
+        if metadata
+            .get("concurrency_adaptive")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+        {
+            let mut adaptive = AdaptiveConfig::default();
+            if let Some(min) = metadata
+                .get("concurrency_min_limit")
+                .and_then(|v| v.as_u64())
+            {
+                adaptive.min_limit = min as usize;
+            }
+            if let Some(max) = metadata
+                .get("concurrency_max_limit")
+                .and_then(|v| v.as_u64())
+            {
+                adaptive.max_limit = max as usize;
+            }
+            if let Some(ms) = metadata
+                .get("concurrency_latency_threshold_ms")
+                .and_then(|v| v.as_u64())
+            {
+                adaptive.latency_threshold = Some(std::time::Duration::from_millis(ms));
+            }
+            if let Some(factor) = metadata
+                .get("concurrency_decrease_factor")
+                .and_then(|v| v.as_f64())
+                .filter(|factor| *factor > 0.0 && *factor < 1.0)
+            {
+                adaptive.decrease_factor = factor;
+            }
+            if let Some(ms) = metadata
+                .get("concurrency_cooldown_ms")
+                .and_then(|v| v.as_u64())
+            {
+                adaptive.cooldown = std::time::Duration::from_millis(ms);
+            }
+            config.adaptive = Some(adaptive);
+        }
+
+        config
     }
 
+    pub fn create_retry_policy(
+        metadata: &std::collections::HashMap<String, serde_json::Value>,
+    ) -> Arc<RetryPolicy> {
+        use crate::retry::RetryConfig;
+        use std::time::Duration;
+
+        let mut config = RetryConfig::default();
+
+        if let Some(attempts) = metadata.get("retry_max_attempts").and_then(|v| v.as_u64()) {
+            config.max_attempts = attempts.max(1) as u32;
+        }
+
+        if let Some(ms) = metadata.get("retry_base_delay_ms").and_then(|v| v.as_u64()) {
+            config.base_delay = Duration::from_millis(ms);
+        }
+
+        if let Some(ms) = metadata.get("retry_max_delay_ms").and_then(|v| v.as_u64()) {
+            config.max_delay = Duration::from_millis(ms);
+        }
+
+        if let Some(jitter) = metadata.get("retry_jitter").and_then(|v| v.as_bool()) {
+            config.jitter = jitter;
+        }
+
+        if let Some(respect) = metadata
+            .get("retry_respect_retry_after")
+            .and_then(|v| v.as_bool())
+        {
+            config.respect_retry_after = respect;
+        }
+
+        if let Some(classes) = metadata.get("retry_on").and_then(|v| v.as_array()) {
+            config.retry_on = classes
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(|s| s.to_string())
+                .collect();
+        }
+
+        if let Some(enabled) = metadata.get("retry_enabled").and_then(|v| v.as_bool()) {
+            config.enabled = enabled;
+        }
+
+        Arc::new(RetryPolicy::new(config))
+    }
+
+    pub fn create_circuit_breaker(
+        name: &str,
+        metadata: &std::collections::HashMap<String, serde_json::Value>,
+    ) -> Arc<CircuitBreaker> {
+        use crate::circuit_breaker::CircuitBreakerConfig;
+        use std::time::Duration;
+
+        let mut config = CircuitBreakerConfig::default();
+
+        if let Some(threshold) = metadata
+            .get("circuit_failure_threshold")
+            .and_then(|v| v.as_u64())
+        {
+            config.failure_threshold = threshold.max(1) as u32;
+        }
+
+        if let Some(rate) = metadata
+            .get("circuit_failure_rate_threshold")
+            .and_then(|v| v.as_f64())
+        {
+            config.failure_rate_threshold = rate;
+        }
+
+        if let Some(min) = metadata
+            .get("circuit_min_requests")
+            .and_then(|v| v.as_u64())
+        {
+            config.min_requests = min as u32;
+        }
+
+        if let Some(size) = metadata.get("circuit_window_size").and_then(|v| v.as_u64()) {
+            config.window_size = size.max(1) as usize;
+        }
+
+        if let Some(ms) = metadata
+            .get("circuit_cool_down_ms")
+            .and_then(|v| v.as_u64())
+        {
+            config.cool_down = Duration::from_millis(ms);
+        }
+
+        if let Some(probes) = metadata
+            .get("circuit_half_open_probes")
+            .and_then(|v| v.as_u64())
+        {
+            config.half_open_max_probes = probes.max(1) as u32;
+        }
+
+        if let Some(successes) = metadata
+            .get("circuit_success_threshold")
+            .and_then(|v| v.as_u64())
+        {
+            config.success_threshold = successes.max(1) as u32;
+        }
+
+        if let Some(enabled) = metadata
+            .get("circuit_breaker_enabled")
+            .and_then(|v| v.as_bool())
+        {
+            config.enabled = enabled;
+        }
+
+        Arc::new(CircuitBreaker::new(name, config))
+    }
+
+    /// 读取 `health_check`（none / models / completion），未设置或无法识别时不主动探测
+    pub fn health_probe(
+        metadata: &std::collections::HashMap<String, serde_json::Value>,
+    ) -> HealthProbe {
+        let Some(value) = metadata.get("health_check") else {
+            return HealthProbe::default();
+        };
+        value
+            .as_str()
+            .and_then(HealthProbe::parse)
+            .unwrap_or_else(|| {
+                warn!("Unknown health_check {}, using passive health only", value);
+                HealthProbe::default()
+            })
+    }
+
+    pub fn create_health_monitor(
+        metadata: &std::collections::HashMap<String, serde_json::Value>,
+    ) -> Arc<HealthMonitor> {
+        use std::time::Duration;
+
+        let mut config = HealthCheckConfig::default();
+
+        if let Some(ms) = metadata.get("health_check_ttl_ms").and_then(|v| v.as_u64()) {
+            config.ttl = Duration::from_millis(ms);
+        }
+
+        if let Some(ms) = metadata
+            .get("health_check_timeout_ms")
+            .and_then(|v| v.as_u64())
+        {
+            config.timeout = Duration::from_millis(ms);
+        }
+
+        if let Some(rate) = metadata
+            .get("health_error_rate_threshold")
+            .and_then(|v| v.as_f64())
+        {
+            config.error_rate_threshold = rate;
+        }
+
+        if let Some(min) = metadata.get("health_min_requests").and_then(|v| v.as_u64()) {
+            config.min_requests = min as u32;
+        }
+
+        if let Some(ms) = metadata.get("health_window_ms").and_then(|v| v.as_u64()) {
+            config.window = Duration::from_millis(ms);
+        }
+
+        if let Some(enabled) = metadata
+            .get("health_check_enabled")
+            .and_then(|v| v.as_bool())
+        {
+            config.enabled = enabled;
+        }
+
+        Arc::new(HealthMonitor::new(config))
+    }
+
+    /// `cache` 为 true 时开启响应缓存
+    pub fn cache_config(
+        metadata: &std::collections::HashMap<String, serde_json::Value>,
+    ) -> Option<CacheConfig> {
+        use std::time::Duration;
+
+        if metadata.get("cache").and_then(|v| v.as_bool()) != Some(true) {
+            return None;
+        }
+
+        let mut config = CacheConfig::default();
+
+        if let Some(secs) = metadata.get("cache_ttl_secs").and_then(|v| v.as_u64()) {
+            config.ttl = Duration::from_secs(secs);
+        }
+
+        if let Some(max) = metadata.get("cache_max_entries").and_then(|v| v.as_u64()) {
+            config.max_entries = max as usize;
+        }
+
+        if let Some(threshold) = metadata
+            .get("cache_similarity_threshold")
+            .and_then(|v| v.as_f64())
+        {
+            config.similarity_threshold = Some(threshold as f32);
+        }
+
+        Some(config)
+    }
+
+    /// 价格目录中没有匹配项时按元数据中的统一价格计费，本地模型默认价格为 0；
+    /// 配置的账单存储打不开时返回错误
     pub fn create_billing_tracker(
+        name: &str,
         metadata: &std::collections::HashMap<String, serde_json::Value>,
-    ) -> Arc<BillingTracker> {
+        pricing: Arc<PricingCatalog>,
+    ) -> anyhow::Result<Arc<BillingTracker>> {
         use crate::billing::BillingConfig;
 
         let mut config = BillingConfig::default();
+        if Self::is_local_provider(name) {
+            config.input_price_per_1k = 0.0;
+            config.output_price_per_1k = 0.0;
+        }
 
         if let Some(price) = metadata.get("input_price_per_1k").and_then(|v| v.as_f64()) {
             config.input_price_per_1k = price;
@@ -216,10 +751,54 @@ impl AdapterFactory {
             config.output_price_per_1k = price;
         }
 
+        if let Some(price) = metadata
+            .get("cached_input_price_per_1k")
+            .and_then(|v| v.as_f64())
+        {
+            config.cached_input_price_per_1k = Some(price);
+        }
+
+        if let Some(currency) = metadata.get("currency").and_then(|v| v.as_str()) {
+            config.currency = currency.to_string();
+        }
+
+        if let Some(tokens) = metadata.get("min_charge_tokens").and_then(|v| v.as_u64()) {
+            config.min_charge_tokens = tokens;
+        }
+
+        if let Some(max) = metadata.get("billing_max_records").and_then(|v| v.as_u64()) {
+            config.max_records = max as usize;
+        }
+
         if let Some(enabled) = metadata.get("billing_enabled").and_then(|v| v.as_bool()) {
             config.enabled = enabled;
         }
 
-        Arc::new(BillingTracker::new(config))
+        let tracker = BillingTracker::new(config).with_pricing(pricing);
+        let tracker = match Self::create_billing_store(metadata)
+            .map_err(|e| anyhow::anyhow!("Failed to open billing store for {}: {}", name, e))?
+        {
+            Some(store) => tracker.with_store(store),
+            None => tracker,
+        };
+        Ok(Arc::new(tracker))
+    }
+
+    /// `billing_store` 为 `jsonl` 或 `sqlite` 时按 `billing_path` 打开持久化存储，默认只保存在内存中
+    pub fn create_billing_store(
+        metadata: &std::collections::HashMap<String, serde_json::Value>,
+    ) -> anyhow::Result<Option<Arc<dyn BillingStore>>> {
+        let kind = Self::backend_kind(metadata, "billing_store");
+        let path = metadata.get("billing_path").and_then(|v| v.as_str());
+
+        let store: Arc<dyn BillingStore> = match kind {
+            "memory" => return Ok(None),
+            "jsonl" => Arc::new(JsonlBillingStore::open(
+                path.unwrap_or("data/billing.jsonl"),
+            )?),
+            "sqlite" => Arc::new(SqliteBillingStore::open(path.unwrap_or("data/billing.db"))?),
+            other => return Err(anyhow::anyhow!("Unknown billing store: {}", other)),
+        };
+        Ok(Some(store))
     }
 }
@@ -0,0 +1,221 @@
+use crate::chat::ChatRequest;
+use crate::config::AdapterConfig;
+use crate::error::AdapterError;
+use crate::registry::{Adapter, InvokeOptions};
+use crate::response::InvokeResponse;
+use crate::stream::ChatStream;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+type AdapterMap = Arc<RwLock<HashMap<String, Arc<dyn Adapter + Send + Sync>>>>;
+
+/// 按顺序尝试多个适配器，只有请求本身有问题时才不再切换成员
+///
+/// 成员通常是注册表中已包装的适配器，各自的限流、并发控制与计费保持独立。
+pub struct FallbackAdapter {
+    name: String,
+    members: Members,
+}
+
+enum Members {
+    Fixed(Vec<Arc<dyn Adapter + Send + Sync>>),
+    /// 每次调用时按名称从注册表取，成员重新注册或注销后立即生效
+    Registry {
+        adapters: AdapterMap,
+        chain: Vec<String>,
+    },
+}
+
+impl FallbackAdapter {
+    pub fn new(name: String, members: Vec<Arc<dyn Adapter + Send + Sync>>) -> Self {
+        Self {
+            name,
+            members: Members::Fixed(members),
+        }
+    }
+
+    pub(crate) fn from_registry(name: String, chain: Vec<String>, adapters: AdapterMap) -> Self {
+        Self {
+            name,
+            members: Members::Registry { adapters, chain },
+        }
+    }
+
+    pub fn member_names(&self) -> Vec<&str> {
+        match &self.members {
+            Members::Fixed(members) => members.iter().map(|m| m.name()).collect(),
+            Members::Registry { chain, .. } => chain.iter().map(String::as_str).collect(),
+        }
+    }
+
+    /// 当前的成员及其名称，已注销的成员跳过
+    async fn resolve(&self) -> Vec<(String, Arc<dyn Adapter + Send + Sync>)> {
+        match &self.members {
+            Members::Fixed(members) => members
+                .iter()
+                .map(|m| (m.name().to_string(), m.clone()))
+                .collect(),
+            Members::Registry { adapters, chain } => {
+                let adapters = adapters.read().await;
+                chain
+                    .iter()
+                    .filter_map(|name| match adapters.get(name) {
+                        Some(adapter) => Some((name.clone(), adapter.clone())),
+                        None => {
+                            warn!(fallback = %self.name, member = %name, "Fallback member is not registered, skipping");
+                            None
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// 读取 `{"type": "fallback", "chain": [...]}` 形式的元数据，非 fallback 配置返回 None
+    pub fn chain_from_config(config: &AdapterConfig) -> Option<Vec<String>> {
+        let is_fallback = config
+            .metadata
+            .get("type")
+            .and_then(|v| v.as_str())
+            .is_some_and(|t| t == "fallback");
+        if !is_fallback {
+            return None;
+        }
+
+        let chain = config
+            .metadata
+            .get("chain")
+            .and_then(|v| v.as_array())
+            .map(|chain| {
+                chain
+                    .iter()
+                    .filter_map(|v| v.as_str())
+                    .map(|s| s.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(chain)
+    }
+
+    async fn first_success<T, F, Fut>(&self, call: F) -> anyhow::Result<(T, String)>
+    where
+        F: Fn(Arc<dyn Adapter + Send + Sync>) -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let mut last_error = None;
+
+        for (name, member) in self.resolve().await {
+            match call(member).await {
+                Ok(value) => return Ok((value, name)),
+                Err(e) => {
+                    if e.downcast_ref::<AdapterError>()
+                        .is_some_and(caused_by_request)
+                    {
+                        return Err(e);
+                    }
+                    warn!(
+                        fallback = %self.name,
+                        member = %name,
+                        error = %e,
+                        "Fallback member failed, trying next"
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error
+            .unwrap_or_else(|| anyhow::anyhow!("Fallback adapter {} has no members", self.name)))
+    }
+}
+
+/// 换成员也无法成功的错误；预算超限、认证失败等只与当前成员有关
+fn caused_by_request(error: &AdapterError) -> bool {
+    matches!(
+        error,
+        AdapterError::InvalidRequest(_)
+            | AdapterError::ContextLengthExceeded(_)
+            | AdapterError::ContentFiltered(_)
+    )
+}
+
+#[async_trait]
+impl Adapter for FallbackAdapter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn describe(&self) -> String {
+        format!(
+            "Fallback adapter {}: {}",
+            self.name,
+            self.member_names().join(" -> ")
+        )
+    }
+
+    async fn invoke(&self, prompt: &str) -> anyhow::Result<String> {
+        self.chat(&ChatRequest::from_prompt(prompt), &InvokeOptions::default())
+            .await
+            .map(|response| response.content)
+    }
+
+    async fn invoke_with_options(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<String> {
+        self.chat(&ChatRequest::from_prompt(prompt), options)
+            .await
+            .map(|response| response.content)
+    }
+
+    async fn invoke_stream(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        self.chat_stream(&ChatRequest::from_prompt(prompt), options)
+            .await
+    }
+
+    async fn chat(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<InvokeResponse> {
+        let (mut response, member) = self
+            .first_success(|m| async move { m.chat(request, options).await })
+            .await?;
+        info!(fallback = %self.name, member = %member, "Fallback request served");
+
+        // 嵌套的 fallback 保留最内层的实际成员
+        response.served_by.get_or_insert(member);
+        Ok(response)
+    }
+
+    /// 只在建立流之前切换成员，已开始输出的流不再切换
+    async fn chat_stream(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let (stream, member) = self
+            .first_success(|m| async move { m.chat_stream(request, options).await })
+            .await?;
+        info!(fallback = %self.name, member = %member, "Fallback stream served");
+        Ok(stream)
+    }
+
+    async fn health(&self) -> bool {
+        for (_, member) in self.resolve().await {
+            if member.health().await {
+                return true;
+            }
+        }
+        false
+    }
+}
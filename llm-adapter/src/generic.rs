@@ -1,7 +1,14 @@
-use crate::registry::Adapter;
+use crate::chat::{ChatRequest, OpenAIContent, OpenAIMediaSupport};
+use crate::error::{error_message, parse_retry_after, AdapterError};
+use crate::health::HealthProbe;
+use crate::registry::{Adapter, InvokeOptions};
+use crate::response::{InvokeResponse, OpenAIUsage, Usage};
+use crate::stream::{sse_stream, ChatStream, StreamChunk};
+use crate::tool::{tool_choice_value, OpenAITool, OpenAIToolCall, ToolCall};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 use tracing::{error, info};
 
 #[derive(Clone)]
@@ -13,6 +20,9 @@ pub struct GenericAdapter {
     endpoint: String,
     client: reqwest::Client,
     request_config: RequestConfig,
+    health_probe: HealthProbe,
+    /// 模型列表探测使用的路径，未设置时不主动探测
+    health_endpoint: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -25,6 +35,33 @@ pub struct RequestConfig {
     pub model_field: String,
     pub message_field: String,
     pub response_path: String,
+    #[serde(default = "default_stream_response_path")]
+    pub stream_response_path: String,
+    #[serde(default = "default_finish_reason_path")]
+    pub finish_reason_path: String,
+    #[serde(default = "default_usage_path")]
+    pub usage_path: String,
+    #[serde(default = "default_tool_calls_path")]
+    pub tool_calls_path: String,
+    /// 错误响应中错误信息的路径，未设置时按常见格式提取
+    #[serde(default)]
+    pub error_path: Option<String>,
+}
+
+fn default_stream_response_path() -> String {
+    "choices.0.delta.content".to_string()
+}
+
+fn default_finish_reason_path() -> String {
+    "choices.0.finish_reason".to_string()
+}
+
+fn default_usage_path() -> String {
+    "usage".to_string()
+}
+
+fn default_tool_calls_path() -> String {
+    "choices.0.message.tool_calls".to_string()
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -51,11 +88,23 @@ impl GenericAdapter {
             endpoint: request_config.endpoint_template.clone(),
             client: reqwest::Client::new(),
             request_config,
+            health_probe: HealthProbe::default(),
+            health_endpoint: None,
         }
     }
 
-    fn build_url(&self) -> String {
-        let endpoint = self.endpoint.replace("{model}", &self.model);
+    pub fn with_health_probe(mut self, health_probe: HealthProbe) -> Self {
+        self.health_probe = health_probe;
+        self
+    }
+
+    pub fn with_health_endpoint(mut self, health_endpoint: String) -> Self {
+        self.health_endpoint = Some(health_endpoint);
+        self
+    }
+
+    fn build_url(&self, model: &str) -> String {
+        let endpoint = self.endpoint.replace("{model}", model);
         format!("{}{}", self.base_url, endpoint)
     }
 
@@ -96,87 +145,63 @@ impl GenericAdapter {
         headers
     }
 
-    fn build_body(&self, prompt: &str) -> anyhow::Result<Value> {
+    fn build_body(&self, request: &ChatRequest, options: &InvokeOptions) -> anyhow::Result<Value> {
+        let model = options.model.as_deref().unwrap_or(&self.model);
+        let prompt = request.to_prompt();
+        let prompt = prompt.as_str();
+
         if let Some(ref template) = self.request_config.body_template {
             let mut body = template.clone();
 
-            fn replace_in_value(value: &mut Value, model: &str, prompt: &str) {
-                match value {
-                    Value::String(s) => {
-                        if s == "{model}" {
-                            *s = model.to_string();
-                        } else if s == "{prompt}" || s == "{message}" {
-                            *s = prompt.to_string();
-                        }
-                    }
-                    Value::Array(arr) => {
-                        for item in arr {
-                            replace_in_value(item, model, prompt);
-                        }
-                    }
-                    Value::Object(obj) => {
-                        for (_key, val) in obj {
-                            replace_in_value(val, model, prompt);
-                        }
-                    }
-                    _ => {}
-                }
-            }
-
-            replace_in_value(&mut body, &self.model, prompt);
+            replace_in_value(&mut body, model, prompt, &option_placeholders(options));
 
             if let Some(obj) = body.as_object_mut() {
                 if !obj.contains_key(&self.request_config.model_field) {
                     obj.insert(
                         self.request_config.model_field.clone(),
-                        Value::String(self.model.clone()),
+                        Value::String(model.to_string()),
                     );
                 }
 
                 if let Some(msg_field) = obj.get_mut(&self.request_config.message_field) {
                     match msg_field {
                         Value::Array(arr) => {
-                            arr.push(Value::Object({
-                                let mut msg = serde_json::Map::new();
-                                msg.insert("role".to_string(), Value::String("user".to_string()));
-                                msg.insert(
-                                    "content".to_string(),
-                                    Value::String(prompt.to_string()),
-                                );
-                                msg
-                            }));
+                            arr.extend(message_values(&self.name, request)?);
                         }
                         _ => {
+                            // 拼接为单个 prompt 时无法携带图片与音频
+                            request.ensure_text_only(&self.name)?;
                             *msg_field = Value::String(prompt.to_string());
                         }
                     }
                 } else {
-                    let mut messages = serde_json::Map::new();
-                    messages.insert("role".to_string(), Value::String("user".to_string()));
-                    messages.insert("content".to_string(), Value::String(prompt.to_string()));
                     obj.insert(
                         self.request_config.message_field.clone(),
-                        Value::Array(vec![Value::Object(messages)]),
+                        Value::Array(message_values(&self.name, request)?),
                     );
                 }
+
+                insert_tools(obj, request);
             }
 
             Ok(body)
         } else {
+            request.ensure_text_only(&self.name)?;
             let mut body = serde_json::Map::new();
             body.insert(
                 self.request_config.model_field.clone(),
-                serde_json::Value::String(self.model.clone()),
+                serde_json::Value::String(model.to_string()),
             );
             body.insert(
                 self.request_config.message_field.clone(),
                 serde_json::Value::String(prompt.to_string()),
             );
+            insert_tools(&mut body, request);
             Ok(serde_json::Value::Object(body))
         }
     }
 
-    fn extract_response(&self, response: Value) -> anyhow::Result<String> {
+    fn extract_response(&self, response: Value) -> Result<String, AdapterError> {
         let path_parts: Vec<&str> = self.request_config.response_path.split('.').collect();
         let mut current = &response;
 
@@ -185,46 +210,36 @@ impl GenericAdapter {
                 Value::Object(map) => {
                     current = map
                         .get(part)
-                        .ok_or_else(|| anyhow::anyhow!("Path {} not found", part))?;
+                        .ok_or_else(|| AdapterError::Decode(format!("Path {} not found", part)))?;
                 }
                 Value::Array(arr) => {
-                    let idx: usize = part
-                        .parse()
-                        .map_err(|_| anyhow::anyhow!("Invalid array index: {}", part))?;
-                    current = arr
-                        .get(idx)
-                        .ok_or_else(|| anyhow::anyhow!("Array index {} out of bounds", idx))?;
+                    let idx: usize = part.parse().map_err(|_| {
+                        AdapterError::Decode(format!("Invalid array index: {}", part))
+                    })?;
+                    current = arr.get(idx).ok_or_else(|| {
+                        AdapterError::Decode(format!("Array index {} out of bounds", idx))
+                    })?;
                 }
                 _ => {
-                    return Err(anyhow::anyhow!("Cannot navigate path at: {}", part));
+                    return Err(AdapterError::Decode(format!(
+                        "Cannot navigate path at: {}",
+                        part
+                    )));
                 }
             }
         }
 
         match current {
             Value::String(s) => Ok(s.clone()),
-            _ => serde_json::to_string(current)
-                .map_err(|e| anyhow::anyhow!("Failed to serialize response value: {}", e)),
+            _ => serde_json::to_string(current).map_err(|e| {
+                AdapterError::Decode(format!("Failed to serialize response value: {}", e))
+            }),
         }
     }
-}
 
-#[async_trait]
-impl Adapter for GenericAdapter {
-    fn name(&self) -> &str {
-        &self.name
-    }
-
-    async fn describe(&self) -> String {
-        format!("Generic {} adapter for model {}", self.name, self.model)
-    }
-
-    async fn invoke(&self, prompt: &str) -> anyhow::Result<String> {
-        info!("Calling {} with model: {}", self.name, self.model);
-
-        let url = self.build_url();
+    async fn send(&self, model: &str, body: &Value) -> anyhow::Result<reqwest::Response> {
+        let url = self.build_url(model);
         let headers = self.build_headers();
-        let body = self.build_body(prompt)?;
 
         let mut request = match self.request_config.method.as_str() {
             "GET" => self.client.get(&url),
@@ -240,19 +255,89 @@ impl Adapter for GenericAdapter {
         }
 
         if self.request_config.method != "GET" {
-            request = request.json(&body);
+            request = request.json(body);
         }
 
-        let response = request.send().await?;
+        let response = request.send().await.map_err(AdapterError::from)?;
         let status = response.status();
 
         if !status.is_success() {
+            let retry_after = parse_retry_after(response.headers());
             let text = response.text().await.unwrap_or_default();
             error!("{} API error ({}): {}", self.name, status, text);
-            anyhow::bail!("{} API error: {}", self.name, status);
+
+            let message = self
+                .request_config
+                .error_path
+                .as_deref()
+                .and_then(|path| {
+                    let value: Value = serde_json::from_str(&text).ok()?;
+                    let message = lookup_path(&value, path)?.as_str()?.to_string();
+                    Some(message)
+                })
+                .or_else(|| error_message(&text))
+                .unwrap_or_else(|| status.to_string());
+
+            return Err(AdapterError::classify(
+                status.as_u16(),
+                &text,
+                format!("{} API error: {}", self.name, message),
+                retry_after,
+            )
+            .into());
         }
 
-        let response_text = response.text().await?;
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl Adapter for GenericAdapter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn describe(&self) -> String {
+        format!("Generic {} adapter for model {}", self.name, self.model)
+    }
+
+    async fn invoke(&self, prompt: &str) -> anyhow::Result<String> {
+        self.chat(&ChatRequest::from_prompt(prompt), &InvokeOptions::default())
+            .await
+            .map(|response| response.content)
+    }
+
+    async fn invoke_with_options(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<String> {
+        self.chat(&ChatRequest::from_prompt(prompt), options)
+            .await
+            .map(|response| response.content)
+    }
+
+    async fn invoke_stream(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        self.chat_stream(&ChatRequest::from_prompt(prompt), options)
+            .await
+    }
+
+    async fn chat(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<InvokeResponse> {
+        let model = options.model.as_deref().unwrap_or(&self.model);
+        info!("Calling {} with model: {}", self.name, model);
+
+        let body = self.build_body(request, options)?;
+        let response = self.send(model, &body).await?;
+
+        let response_text = response.text().await.map_err(AdapterError::from)?;
 
         let result: Value = serde_json::from_str(&response_text).map_err(|e| {
             error!("{} JSON parse error: {}", self.name, e);
@@ -260,13 +345,210 @@ impl Adapter for GenericAdapter {
                 "Response preview (first 200 chars): {}",
                 &response_text.chars().take(200).collect::<String>()
             );
-            anyhow::anyhow!("Failed to parse response as JSON: {}", e)
+            AdapterError::Decode(format!("Failed to parse response as JSON: {}", e))
         })?;
 
-        self.extract_response(result)
+        let usage = extract_usage(&result, &self.request_config.usage_path);
+        let finish_reason = lookup_path(&result, &self.request_config.finish_reason_path)
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        let model = result
+            .get("model")
+            .and_then(|v| v.as_str())
+            .unwrap_or(model)
+            .to_string();
+
+        let tool_calls: Vec<ToolCall> = lookup_path(&result, &self.request_config.tool_calls_path)
+            .and_then(|v| serde_json::from_value::<Vec<OpenAIToolCall>>(v.clone()).ok())
+            .map(|calls| calls.into_iter().map(ToolCall::from).collect())
+            .unwrap_or_default();
+
+        // 只返回工具调用时 content 为 null
+        let content = match lookup_path(&result, &self.request_config.response_path) {
+            Some(Value::Null) if !tool_calls.is_empty() => String::new(),
+            _ => self.extract_response(result)?,
+        };
+
+        Ok(InvokeResponse::new(content)
+            .with_tool_calls(tool_calls)
+            .with_usage(usage)
+            .with_model(model)
+            .with_finish_reason(finish_reason))
+    }
+
+    async fn chat_stream(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let model = options.model.as_deref().unwrap_or(&self.model);
+        info!("Streaming {} with model: {}", self.name, model);
+
+        let mut body = self.build_body(request, options)?;
+        if let Some(obj) = body.as_object_mut() {
+            obj.insert("stream".to_string(), Value::Bool(true));
+        }
+
+        let response = self.send(model, &body).await?;
+
+        let content_path = self.request_config.stream_response_path.clone();
+        let finish_reason_path = self.request_config.finish_reason_path.clone();
+        let usage_path = self.request_config.usage_path.clone();
+
+        Ok(sse_stream(response, move |data| {
+            let data = data.trim();
+            if data.is_empty() || data == "[DONE]" {
+                return Ok(None);
+            }
+
+            let value: Value = serde_json::from_str(data).map_err(|e| {
+                AdapterError::Decode(format!("Failed to parse stream chunk: {}", e))
+            })?;
+
+            Ok(Some(StreamChunk {
+                delta: lookup_path(&value, &content_path)
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                finish_reason: lookup_path(&value, &finish_reason_path)
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string()),
+                usage: extract_usage(&value, &usage_path),
+            }))
+        }))
     }
 
     async fn health(&self) -> bool {
-        true
+        let models = self.health_endpoint.as_ref().map(|endpoint| {
+            let request = self
+                .client
+                .get(format!("{}{}", self.base_url, endpoint))
+                .headers(self.build_headers());
+            match &self.request_config.auth_type {
+                AuthType::Query(param_name) => request.query(&[(param_name, &self.api_key)]),
+                _ => request,
+            }
+        });
+
+        self.health_probe.run(self, &self.name, models).await
     }
 }
+
+/// body_template 中的采样参数占位符，未设置的选项会连同其字段一起移除
+fn option_placeholders(options: &InvokeOptions) -> HashMap<&'static str, Option<Value>> {
+    HashMap::from([
+        ("{temperature}", options.temperature.map(f32_value)),
+        ("{max_tokens}", options.max_tokens.map(Value::from)),
+        ("{top_p}", options.top_p.map(f32_value)),
+        ("{stop}", options.stop.clone().map(Value::from)),
+        ("{seed}", options.seed.map(Value::from)),
+        (
+            "{presence_penalty}",
+            options.presence_penalty.map(f32_value),
+        ),
+        (
+            "{frequency_penalty}",
+            options.frequency_penalty.map(f32_value),
+        ),
+        ("{n}", options.n.map(Value::from)),
+    ])
+}
+
+// 经十进制字符串转换，避免 0.7f32 变成 0.699999988079071
+fn f32_value(value: f32) -> Value {
+    value
+        .to_string()
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+        .map(Value::Number)
+        .unwrap_or(Value::Null)
+}
+
+fn is_unset_placeholder(value: &Value, options: &HashMap<&'static str, Option<Value>>) -> bool {
+    matches!(value, Value::String(s) if matches!(options.get(s.as_str()), Some(None)))
+}
+
+fn replace_in_value(
+    value: &mut Value,
+    model: &str,
+    prompt: &str,
+    options: &HashMap<&'static str, Option<Value>>,
+) {
+    match value {
+        Value::String(s) => {
+            if s == "{model}" {
+                *s = model.to_string();
+            } else if s == "{prompt}" || s == "{message}" {
+                *s = prompt.to_string();
+            } else if let Some(Some(option)) = options.get(s.as_str()) {
+                *value = option.clone();
+            }
+        }
+        Value::Array(arr) => {
+            arr.retain(|item| !is_unset_placeholder(item, options));
+            for item in arr {
+                replace_in_value(item, model, prompt, options);
+            }
+        }
+        Value::Object(obj) => {
+            obj.retain(|_, val| !is_unset_placeholder(val, options));
+            for (_key, val) in obj {
+                replace_in_value(val, model, prompt, options);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn message_values(provider: &str, request: &ChatRequest) -> anyhow::Result<Vec<Value>> {
+    request
+        .messages
+        .iter()
+        .map(|m| {
+            let mut value = serde_json::to_value(m)?;
+            if !m.parts.is_empty() {
+                let content =
+                    OpenAIContent::new(provider, m, m.content.clone(), OpenAIMediaSupport::OPENAI)?;
+                value["content"] = serde_json::to_value(content)?;
+                if let Some(obj) = value.as_object_mut() {
+                    obj.remove("parts");
+                }
+            }
+            if !m.tool_calls.is_empty() {
+                let tool_calls: Vec<OpenAIToolCall> =
+                    m.tool_calls.iter().map(OpenAIToolCall::from).collect();
+                value["tool_calls"] = serde_json::to_value(tool_calls)?;
+            }
+            Ok(value)
+        })
+        .collect()
+}
+
+fn insert_tools(body: &mut serde_json::Map<String, Value>, request: &ChatRequest) {
+    if !request.tools.is_empty() {
+        let tools: Vec<OpenAITool> = request.tools.iter().map(OpenAITool::from).collect();
+        body.insert(
+            "tools".to_string(),
+            serde_json::to_value(tools).unwrap_or_default(),
+        );
+    }
+    if let Some(tool_choice) = &request.tool_choice {
+        body.insert("tool_choice".to_string(), tool_choice_value(tool_choice));
+    }
+}
+
+fn extract_usage(value: &Value, path: &str) -> Option<Usage> {
+    lookup_path(value, path)
+        .and_then(|v| serde_json::from_value::<OpenAIUsage>(v.clone()).ok())
+        .map(Usage::from)
+}
+
+fn lookup_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(value, |current, part| match current {
+            Value::Object(map) => map.get(part),
+            Value::Array(arr) => part.parse::<usize>().ok().and_then(|idx| arr.get(idx)),
+            _ => None,
+        })
+}
@@ -1,11 +1,57 @@
-use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::Semaphore;
+use crate::adaptive::{AdaptiveConfig, AdaptiveLimit};
+use async_trait::async_trait;
+use serde::Serialize;
+use std::any::Any;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
 use tracing::debug;
 
+/// 请求优先级，名额不足时高优先级的等待者先获得许可
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    Interactive,
+    #[default]
+    Normal,
+    Batch,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 3] = [Priority::Interactive, Priority::Normal, Priority::Batch];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "interactive" => Some(Priority::Interactive),
+            "normal" => Some(Priority::Normal),
+            "batch" => Some(Priority::Batch),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::Interactive => "interactive",
+            Priority::Normal => "normal",
+            Priority::Batch => "batch",
+        }
+    }
+
+    pub(crate) fn lane(self) -> usize {
+        self as usize
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ConcurrencyConfig {
     pub max_concurrent: usize,
+    pub acquire_timeout: Duration,
+    pub fail_fast: bool,
+    /// 排队请求达到该数量时直接丢弃新请求
+    pub max_queue: Option<usize>,
+    /// 设置后按上游延迟与错误自动调整上限，`max_concurrent` 为初始值
+    pub adaptive: Option<AdaptiveConfig>,
     pub enabled: bool,
 }
 
@@ -13,70 +59,413 @@ impl Default for ConcurrencyConfig {
     fn default() -> Self {
         Self {
             max_concurrent: 10,
+            acquire_timeout: Duration::from_secs(30),
+            fail_fast: false,
+            max_queue: None,
+            adaptive: None,
             enabled: true,
         }
     }
 }
 
+/// 并发名额的存储后端
+#[async_trait]
+pub trait ConcurrencyBackend: Send + Sync {
+    fn kind(&self) -> &'static str;
+
+    async fn acquire(&self, priority: Priority) -> Result<ConcurrencyPermit, ConcurrencyError>;
+
+    async fn try_acquire(&self) -> Result<Option<ConcurrencyPermit>, ConcurrencyError>;
+
+    /// 调整名额上限；缩容时已发出的许可不受影响，归还后才生效
+    fn resize(&self, max_concurrent: usize);
+
+    fn available_permits(&self) -> usize;
+}
+
+struct SemaphoreState {
+    limit: usize,
+    in_use: usize,
+    next_id: u64,
+    waiters: [VecDeque<(u64, oneshot::Sender<()>)>; 3],
+}
+
+impl SemaphoreState {
+    fn dispatch(&mut self) {
+        while self.in_use < self.limit {
+            let Some((_, waiter)) = self.waiters.iter_mut().find_map(|lane| lane.pop_front())
+            else {
+                break;
+            };
+            if waiter.send(()).is_ok() {
+                self.in_use += 1;
+            }
+        }
+    }
+
+    fn release(&mut self) {
+        self.in_use -= 1;
+        self.dispatch();
+    }
+}
+
+/// 进程内的优先级信号量，上限可在运行时调整
+pub struct InMemoryConcurrencyBackend {
+    state: Arc<Mutex<SemaphoreState>>,
+}
+
+impl InMemoryConcurrencyBackend {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(SemaphoreState {
+                limit: max_concurrent,
+                in_use: 0,
+                next_id: 0,
+                waiters: Default::default(),
+            })),
+        }
+    }
+
+    fn permit(&self) -> ConcurrencyPermit {
+        ConcurrencyPermit::new(MemoryPermit {
+            state: self.state.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl ConcurrencyBackend for InMemoryConcurrencyBackend {
+    fn kind(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn acquire(&self, priority: Priority) -> Result<ConcurrencyPermit, ConcurrencyError> {
+        let mut waiting = {
+            let mut state = self.state.lock().unwrap();
+            if state.in_use < state.limit {
+                state.in_use += 1;
+                return Ok(self.permit());
+            }
+
+            let (sender, receiver) = oneshot::channel();
+            let id = state.next_id;
+            state.next_id += 1;
+            state.waiters[priority.lane()].push_back((id, sender));
+            Waiting {
+                state: self.state.clone(),
+                lane: priority.lane(),
+                id,
+                receiver,
+                granted: false,
+            }
+        };
+
+        (&mut waiting.receiver)
+            .await
+            .map_err(|_| ConcurrencyError::Closed)?;
+        waiting.granted = true;
+        Ok(self.permit())
+    }
+
+    async fn try_acquire(&self) -> Result<Option<ConcurrencyPermit>, ConcurrencyError> {
+        let mut state = self.state.lock().unwrap();
+        if state.in_use >= state.limit {
+            return Ok(None);
+        }
+        state.in_use += 1;
+        Ok(Some(self.permit()))
+    }
+
+    fn resize(&self, max_concurrent: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.limit = max_concurrent;
+        state.dispatch();
+    }
+
+    fn available_permits(&self) -> usize {
+        let state = self.state.lock().unwrap();
+        state.limit.saturating_sub(state.in_use)
+    }
+}
+
+struct MemoryPermit {
+    state: Arc<Mutex<SemaphoreState>>,
+}
+
+impl Drop for MemoryPermit {
+    fn drop(&mut self) {
+        self.state.lock().unwrap().release();
+    }
+}
+
+/// 排队中的请求；超时或被取消时退出队列，已分到的名额归还
+struct Waiting {
+    state: Arc<Mutex<SemaphoreState>>,
+    lane: usize,
+    id: u64,
+    receiver: oneshot::Receiver<()>,
+    granted: bool,
+}
+
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        if self.granted {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let lane = &mut state.waiters[self.lane];
+        if let Some(index) = lane.iter().position(|(id, _)| *id == self.id) {
+            lane.remove(index);
+        } else if self.receiver.try_recv().is_ok() {
+            state.release();
+        }
+    }
+}
+
+/// 并发控制的运行统计
+#[derive(Debug, Clone, Serialize)]
+pub struct ConcurrencyStats {
+    pub limit: usize,
+    pub available: usize,
+    pub queued: BTreeMap<&'static str, usize>,
+    pub acquired: u64,
+    pub rejected: u64,
+    pub shed: u64,
+    pub adaptive: bool,
+    pub wait_seconds_total: f64,
+    /// 等待时间分布：（分桶上限秒数，不超过该值的累计次数），总次数即 `acquired`
+    pub wait_buckets: Vec<(f64, u64)>,
+}
+
+impl ConcurrencyStats {
+    pub fn queue_depth(&self) -> usize {
+        self.queued.values().sum()
+    }
+}
+
+pub const WAIT_BUCKETS: [f64; 12] = [
+    0.001, 0.005, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
 pub struct ConcurrencyGuard {
-    semaphore: Arc<Semaphore>,
-    config: ConcurrencyConfig,
+    backend: Arc<dyn ConcurrencyBackend>,
+    config: RwLock<ConcurrencyConfig>,
+    adaptive: RwLock<Option<AdaptiveLimit>>,
+    queued: [AtomicUsize; 3],
+    acquired: AtomicU64,
+    rejected: AtomicU64,
+    shed: AtomicU64,
+    wait_micros: AtomicU64,
+    wait_buckets: [AtomicU64; WAIT_BUCKETS.len()],
 }
 
 impl ConcurrencyGuard {
     pub fn new(config: ConcurrencyConfig) -> Self {
-        let permits = if config.enabled {
-            config.max_concurrent
-        } else {
-            usize::MAX // 无限制
-        };
+        let backend = Arc::new(InMemoryConcurrencyBackend::new(config.max_concurrent));
+        Self::with_backend(config, backend)
+    }
+
+    pub fn with_backend(config: ConcurrencyConfig, backend: Arc<dyn ConcurrencyBackend>) -> Self {
+        let adaptive = Self::adaptive_limit(&config);
+        if let Some(adaptive) = &adaptive {
+            backend.resize(adaptive.limit());
+        }
 
         Self {
-            semaphore: Arc::new(Semaphore::new(permits)),
-            config,
+            backend,
+            config: RwLock::new(config),
+            adaptive: RwLock::new(adaptive),
+            queued: Default::default(),
+            acquired: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+            shed: AtomicU64::new(0),
+            wait_micros: AtomicU64::new(0),
+            wait_buckets: Default::default(),
         }
     }
 
-    pub async fn acquire(&self) -> Result<ConcurrencyPermit, ConcurrencyError> {
-        if !self.config.enabled {
+    fn adaptive_limit(config: &ConcurrencyConfig) -> Option<AdaptiveLimit> {
+        config
+            .adaptive
+            .clone()
+            .map(|adaptive| AdaptiveLimit::new(adaptive, config.max_concurrent))
+    }
+
+    pub fn config(&self) -> ConcurrencyConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    pub fn backend_kind(&self) -> &'static str {
+        self.backend.kind()
+    }
+
+    pub async fn acquire(&self, priority: Priority) -> Result<ConcurrencyPermit, ConcurrencyError> {
+        let config = self.config();
+        if !config.enabled {
             return Ok(ConcurrencyPermit::unlimited());
         }
 
-        let permit = tokio::time::timeout(
-            Duration::from_secs(30),
-            self.semaphore.clone().acquire_owned(),
-        )
-        .await
-        .map_err(|_| ConcurrencyError::Timeout)?
-        .map_err(|_| ConcurrencyError::Closed)?;
+        if config.fail_fast {
+            return match self.backend.try_acquire().await? {
+                Some(permit) => {
+                    self.acquired.fetch_add(1, Ordering::Relaxed);
+                    self.record_wait(Duration::ZERO);
+                    Ok(permit)
+                }
+                None => {
+                    self.rejected.fetch_add(1, Ordering::Relaxed);
+                    Err(ConcurrencyError::Busy)
+                }
+            };
+        }
+
+        if config.max_queue.is_some_and(|max_queue| {
+            self.available_permits() == 0 && self.queue_depth() >= max_queue
+        }) {
+            self.shed.fetch_add(1, Ordering::Relaxed);
+            return Err(ConcurrencyError::QueueFull);
+        }
+
+        let start = Instant::now();
+        let result = {
+            let _queued = QueueSlot::enter(&self.queued[priority.lane()]);
+            tokio::time::timeout(config.acquire_timeout, self.backend.acquire(priority)).await
+        };
+
+        match result {
+            Ok(Ok(permit)) => {
+                self.acquired.fetch_add(1, Ordering::Relaxed);
+                self.record_wait(start.elapsed());
+                Ok(permit)
+            }
+            Ok(Err(e)) => Err(e),
+            Err(_) => {
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                Err(ConcurrencyError::Timeout)
+            }
+        }
+    }
+
+    fn record_wait(&self, wait: Duration) {
+        self.wait_micros
+            .fetch_add(wait.as_micros() as u64, Ordering::Relaxed);
 
-        Ok(ConcurrencyPermit::new(permit))
+        let seconds = wait.as_secs_f64();
+        if let Some(bucket) = WAIT_BUCKETS.iter().position(|bound| seconds <= *bound) {
+            self.wait_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        }
     }
 
     pub fn available_permits(&self) -> usize {
-        self.semaphore.available_permits()
+        self.backend.available_permits()
     }
 
-    pub fn update_config(&mut self, config: ConcurrencyConfig) {
-        self.config = config.clone();
+    pub fn limit(&self) -> usize {
+        match &*self.adaptive.read().unwrap() {
+            Some(adaptive) => adaptive.limit(),
+            None => self.config().max_concurrent,
+        }
+    }
 
+    fn queue_depth(&self) -> usize {
+        self.queued
+            .iter()
+            .map(|queued| queued.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    pub fn record(&self, latency: Duration, overloaded: bool) {
+        let adaptive = self.adaptive.read().unwrap();
+        let Some(adaptive) = adaptive.as_ref() else {
+            return;
+        };
+
+        let in_flight = adaptive.limit().saturating_sub(self.available_permits());
+        if let Some(limit) = adaptive.record(latency, overloaded, in_flight) {
+            debug!(limit, overloaded, "Adaptive concurrency limit changed");
+            self.backend.resize(limit);
+        }
+    }
+
+    pub fn stats(&self) -> ConcurrencyStats {
+        ConcurrencyStats {
+            limit: self.limit(),
+            available: self.available_permits(),
+            queued: Priority::ALL
+                .iter()
+                .map(|p| (p.as_str(), self.queued[p.lane()].load(Ordering::Relaxed)))
+                .collect(),
+            acquired: self.acquired.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            shed: self.shed.load(Ordering::Relaxed),
+            adaptive: self.adaptive.read().unwrap().is_some(),
+            wait_seconds_total: self.wait_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0,
+            wait_buckets: WAIT_BUCKETS
+                .iter()
+                .zip(&self.wait_buckets)
+                .scan(0, |total, (bound, count)| {
+                    *total += count.load(Ordering::Relaxed);
+                    Some((*bound, *total))
+                })
+                .collect(),
+        }
+    }
+
+    pub fn update_config(&self, config: ConcurrencyConfig) {
         if config.enabled {
             debug!(
                 max_concurrent = config.max_concurrent,
                 "Concurrency config updated"
             );
         }
+
+        // 已在自适应模式下时从当前学到的上限继续调整
+        let mut adaptive = self.adaptive.write().unwrap();
+        let initial = match adaptive.as_ref() {
+            Some(current) => current.limit(),
+            None => config.max_concurrent,
+        };
+        *adaptive = config
+            .adaptive
+            .clone()
+            .map(|config| AdaptiveLimit::new(config, initial));
+
+        let limit = match adaptive.as_ref() {
+            Some(adaptive) => adaptive.limit(),
+            None => config.max_concurrent,
+        };
+        self.backend.resize(limit);
+        *self.config.write().unwrap() = config;
+    }
+}
+
+/// 计入排队数，离开作用域（包括被取消）时减去
+struct QueueSlot<'a>(&'a AtomicUsize);
+
+impl<'a> QueueSlot<'a> {
+    fn enter(counter: &'a AtomicUsize) -> Self {
+        counter.fetch_add(1, Ordering::Relaxed);
+        Self(counter)
+    }
+}
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
     }
 }
 
+/// 并发许可，drop 时归还名额
 pub struct ConcurrencyPermit {
-    permit: Option<tokio::sync::OwnedSemaphorePermit>,
+    permit: Option<Box<dyn Any + Send + Sync>>,
 }
 
 impl ConcurrencyPermit {
-    fn new(permit: tokio::sync::OwnedSemaphorePermit) -> Self {
+    /// 包装后端持有的名额，`held` 被 drop 时名额归还
+    pub fn new<T: Any + Send + Sync>(held: T) -> Self {
         Self {
-            permit: Some(permit),
+            permit: Some(Box::new(held)),
         }
     }
 
@@ -94,6 +483,8 @@ impl Drop for ConcurrencyPermit {
 #[derive(Debug, Clone)]
 pub enum ConcurrencyError {
     Timeout,
+    Busy,
+    QueueFull,
     Closed,
 }
 
@@ -101,6 +492,8 @@ impl std::fmt::Display for ConcurrencyError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ConcurrencyError::Timeout => write!(f, "Concurrency limit timeout"),
+            ConcurrencyError::Busy => write!(f, "No concurrency slot available"),
+            ConcurrencyError::QueueFull => write!(f, "Concurrency queue is full"),
             ConcurrencyError::Closed => write!(f, "Semaphore closed"),
         }
     }
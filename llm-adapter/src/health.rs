@@ -0,0 +1,238 @@
+use crate::cache::CACHE_CONTROL;
+use crate::chat::ChatRequest;
+use crate::error::check_response;
+use crate::guard::Priority;
+use crate::registry::{Adapter, InvokeOptions};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HealthProbe {
+    /// 不主动探测，只看被动统计
+    #[default]
+    None,
+    /// 请求模型列表，不消耗 token；提供商没有模型列表接口时只看被动统计
+    Models,
+    /// 发送 max_tokens 为 1 的补全请求，需显式配置；经注册表包装后走限流、预算与计费
+    Completion,
+}
+
+impl HealthProbe {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "none" => Some(HealthProbe::None),
+            "models" => Some(HealthProbe::Models),
+            "completion" => Some(HealthProbe::Completion),
+            _ => None,
+        }
+    }
+
+    /// 执行探测；`models` 为 None 表示提供商没有模型列表接口
+    pub(crate) async fn run<A>(
+        self,
+        adapter: &A,
+        provider: &str,
+        models: Option<reqwest::RequestBuilder>,
+    ) -> bool
+    where
+        A: Adapter + ?Sized,
+    {
+        let result = match (self, models) {
+            (HealthProbe::None, _) | (HealthProbe::Models, None) => return true,
+            (HealthProbe::Models, Some(request)) => match request.send().await {
+                Ok(response) => check_response(provider, response)
+                    .await
+                    .map(|_| ())
+                    .map_err(anyhow::Error::from),
+                Err(e) => Err(e.into()),
+            },
+            (HealthProbe::Completion, _) => adapter
+                .chat(
+                    &ChatRequest::from_prompt("ping"),
+                    &Self::completion_options(),
+                )
+                .await
+                .map(|_| ()),
+        };
+
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                warn!(provider, error = %e, "Health probe failed");
+                false
+            }
+        }
+    }
+
+    /// 补全探测的调用参数：只生成 1 个 token，按批量优先级排队，不读写响应缓存
+    pub(crate) fn completion_options() -> InvokeOptions {
+        let mut options = InvokeOptions {
+            max_tokens: Some(1),
+            priority: Priority::Batch,
+            ..Default::default()
+        };
+        options
+            .metadata
+            .insert(CACHE_CONTROL.to_string(), serde_json::json!("no-store"));
+        options
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HealthCheckConfig {
+    pub ttl: Duration,
+    /// 单次主动探测的超时时间，超时视为不健康
+    pub timeout: Duration,
+    pub error_rate_threshold: f64,
+    pub min_requests: u32,
+    /// 被动统计只看这段时间内的调用，流量被切走后可自行恢复
+    pub window: Duration,
+    pub enabled: bool,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(60),
+            timeout: Duration::from_secs(5),
+            error_rate_threshold: 0.5,
+            min_requests: 5,
+            window: Duration::from_secs(60),
+            enabled: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct ProbeResult {
+    healthy: bool,
+    at: Instant,
+    checked_at: DateTime<Utc>,
+}
+
+pub struct HealthMonitor {
+    config: HealthCheckConfig,
+    last_probe: Mutex<Option<ProbeResult>>,
+    /// 保证同一时间只有一个探测在进行
+    probing: tokio::sync::Mutex<()>,
+    /// 最近调用结果，true 表示失败
+    outcomes: Mutex<VecDeque<(Instant, bool)>>,
+}
+
+impl HealthMonitor {
+    pub fn new(config: HealthCheckConfig) -> Self {
+        Self {
+            config,
+            last_probe: Mutex::new(None),
+            probing: tokio::sync::Mutex::new(()),
+            outcomes: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn config(&self) -> &HealthCheckConfig {
+        &self.config
+    }
+
+    pub fn record(&self, failed: bool) {
+        if !self.config.enabled {
+            return;
+        }
+
+        let mut outcomes = self.outcomes.lock().unwrap();
+        outcomes.push_back((Instant::now(), failed));
+        self.prune(&mut outcomes);
+    }
+
+    /// 统计窗口内的错误率，调用次数不足时返回 None
+    pub fn error_rate(&self) -> Option<f64> {
+        let mut outcomes = self.outcomes.lock().unwrap();
+        self.prune(&mut outcomes);
+
+        let total = outcomes.len();
+        if total == 0 || total < self.config.min_requests as usize {
+            return None;
+        }
+        let failures = outcomes.iter().filter(|(_, failed)| *failed).count();
+        Some(failures as f64 / total as f64)
+    }
+
+    pub fn passive_healthy(&self) -> bool {
+        self.error_rate()
+            .is_none_or(|rate| rate < self.config.error_rate_threshold)
+    }
+
+    /// 返回缓存的探测结果，过期时调用 `check` 重新探测
+    pub async fn probe<F, Fut>(&self, check: F) -> bool
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = bool>,
+    {
+        if !self.config.enabled {
+            return true;
+        }
+
+        let _probing = self.probing.lock().await;
+        if let Some(result) = self.fresh_probe() {
+            return result.healthy;
+        }
+
+        let healthy = tokio::time::timeout(self.config.timeout, check())
+            .await
+            .unwrap_or(false);
+        *self.last_probe.lock().unwrap() = Some(ProbeResult {
+            healthy,
+            at: Instant::now(),
+            checked_at: Utc::now(),
+        });
+        healthy
+    }
+
+    pub fn invalidate(&self) {
+        *self.last_probe.lock().unwrap() = None;
+    }
+
+    pub fn last_probe(&self) -> Option<(bool, DateTime<Utc>)> {
+        self.last_probe
+            .lock()
+            .unwrap()
+            .map(|result| (result.healthy, result.checked_at))
+    }
+
+    fn fresh_probe(&self) -> Option<ProbeResult> {
+        self.last_probe
+            .lock()
+            .unwrap()
+            .filter(|result| result.at.elapsed() < self.config.ttl)
+    }
+
+    fn prune(&self, outcomes: &mut VecDeque<(Instant, bool)>) {
+        while outcomes
+            .front()
+            .is_some_and(|(at, _)| at.elapsed() > self.config.window)
+        {
+            outcomes.pop_front();
+        }
+    }
+}
+
+impl Default for HealthMonitor {
+    fn default() -> Self {
+        Self::new(HealthCheckConfig::default())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub adapter: String,
+    pub healthy: bool,
+    pub probe_healthy: Option<bool>,
+    pub probe_checked_at: Option<DateTime<Utc>>,
+    pub passive_healthy: bool,
+    pub error_rate: Option<f64>,
+    pub circuit: Option<String>,
+}
@@ -1,20 +1,53 @@
+pub mod adaptive;
+pub mod cache;
+pub mod chat;
+pub mod circuit_breaker;
 pub mod config;
+pub mod embedding;
+pub mod error;
 pub mod factory;
+pub mod fallback;
 pub mod generic;
+pub mod health;
 pub mod providers;
 pub mod registry;
+pub mod response;
+pub mod retry;
+pub mod stream;
+pub mod tool;
 pub mod wrapper;
 
 pub mod billing;
+pub mod billing_report;
+pub mod billing_store;
+pub mod budget;
 pub mod guard;
+pub mod pricing;
 pub mod rate_limit;
+pub mod redis_backend;
 
+pub use adaptive::AdaptiveConfig;
+pub use cache::{CacheConfig, CacheStore, InMemoryCacheStore, ResponseCache};
+pub use chat::{ChatMessage, ChatRequest, ChatRole, ContentPart, MediaSource};
+pub use circuit_breaker::{CircuitBreaker, CircuitState};
 pub use config::AdapterConfig;
+pub use embedding::{EmbeddingAdapter, EmbeddingResponse};
+pub use error::AdapterError;
 pub use factory::AdapterFactory;
+pub use fallback::FallbackAdapter;
 pub use generic::{AuthType, GenericAdapter, RequestConfig};
+pub use health::{HealthCheckConfig, HealthMonitor, HealthProbe, HealthReport};
 pub use registry::{Adapter, AdapterRegistry, InvokeOptions};
+pub use response::{InvokeResponse, Usage};
+pub use retry::{RetryConfig, RetryPolicy};
+pub use stream::{ChatStream, StreamChunk};
+pub use tool::{ToolCall, ToolChoice, ToolDefinition};
 pub use wrapper::WrappedAdapter;
 
 pub use billing::BillingTracker;
-pub use guard::ConcurrencyGuard;
+pub use billing_report::{ReportBucket, ReportGranularity, ReportGroupBy, ReportQuery};
+pub use billing_store::{BillingStore, JsonlBillingStore, RecordStream, SqliteBillingStore};
+pub use budget::{Budget, BudgetManager, BudgetPeriod, BudgetReservation, BudgetScope};
+pub use guard::{ConcurrencyGuard, Priority};
+pub use pricing::{ModelPrice, PricingCatalog};
 pub use rate_limit::RateLimiter;
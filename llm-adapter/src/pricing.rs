@@ -0,0 +1,138 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use tracing::info;
+
+/// 某个适配器上某个模型在一段时间内的价格
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    /// 适配器名称，"*" 匹配所有适配器
+    #[serde(default = "wildcard")]
+    pub adapter: String,
+    /// 模型名称，"*" 匹配所有模型，以 "*" 结尾时按前缀匹配（如 "gpt-4o*"）
+    #[serde(default = "wildcard")]
+    pub model: String,
+    pub input_price_per_1k: f64,
+    pub output_price_per_1k: f64,
+    /// 命中缓存的输入 token 单价，未设置时按普通输入价格计费
+    #[serde(default)]
+    pub cached_input_price_per_1k: Option<f64>,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    /// 生效时间，未设置表示一直有效
+    #[serde(default)]
+    pub effective_from: Option<DateTime<Utc>>,
+    /// 失效时间（不含）
+    #[serde(default)]
+    pub effective_until: Option<DateTime<Utc>>,
+}
+
+fn wildcard() -> String {
+    "*".to_string()
+}
+
+pub(crate) fn default_currency() -> String {
+    "USD".to_string()
+}
+
+impl ModelPrice {
+    /// 按用量计算费用，总 token 不足 `min_charge_tokens` 时差额按输入价格补足
+    pub fn cost(
+        &self,
+        input_tokens: u64,
+        output_tokens: u64,
+        cached_tokens: u64,
+        min_charge_tokens: u64,
+    ) -> f64 {
+        let cached_tokens = cached_tokens.min(input_tokens);
+        let shortfall = min_charge_tokens.saturating_sub(input_tokens + output_tokens);
+        let uncached_tokens = input_tokens - cached_tokens + shortfall;
+
+        let cached_price = self
+            .cached_input_price_per_1k
+            .unwrap_or(self.input_price_per_1k);
+        (uncached_tokens as f64 / 1000.0) * self.input_price_per_1k
+            + (cached_tokens as f64 / 1000.0) * cached_price
+            + (output_tokens as f64 / 1000.0) * self.output_price_per_1k
+    }
+
+    fn is_effective(&self, at: DateTime<Utc>) -> bool {
+        self.effective_from.is_none_or(|from| from <= at)
+            && self.effective_until.is_none_or(|until| at < until)
+    }
+
+    /// 匹配时返回具体程度，越具体优先级越高
+    fn specificity(&self, adapter: &str, model: Option<&str>) -> Option<(usize, bool)> {
+        let adapter_exact = match self.adapter.as_str() {
+            "*" => false,
+            name if name == adapter => true,
+            _ => return None,
+        };
+
+        let model_rank = match (self.model.as_str(), model) {
+            ("*", _) => 0,
+            (pattern, Some(model)) if pattern == model => usize::MAX,
+            (pattern, Some(model)) => {
+                let prefix = pattern.strip_suffix('*')?;
+                if !model.starts_with(prefix) {
+                    return None;
+                }
+                prefix.len() + 1
+            }
+            (_, None) => return None,
+        };
+
+        Some((model_rank, adapter_exact))
+    }
+}
+
+/// 按（适配器，模型）查找价格，同一模型可按生效时间配置多条价格
+pub struct PricingCatalog {
+    prices: RwLock<Vec<ModelPrice>>,
+}
+
+impl PricingCatalog {
+    pub fn new(prices: Vec<ModelPrice>) -> Self {
+        Self {
+            prices: RwLock::new(prices),
+        }
+    }
+
+    pub fn set_prices(&self, prices: Vec<ModelPrice>) {
+        info!(count = prices.len(), "Pricing catalog updated");
+        *self.prices.write().unwrap() = prices;
+    }
+
+    pub fn prices(&self) -> Vec<ModelPrice> {
+        self.prices.read().unwrap().clone()
+    }
+
+    /// 查找 `at` 时刻生效的价格：模型精确匹配优先，其次是最长前缀，再按适配器精确匹配，最后取生效时间最晚的一条
+    pub fn lookup(
+        &self,
+        adapter: &str,
+        model: Option<&str>,
+        at: DateTime<Utc>,
+    ) -> Option<ModelPrice> {
+        self.prices
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|price| price.is_effective(at))
+            .filter_map(|price| {
+                price
+                    .specificity(adapter, model)
+                    .map(|(model_rank, adapter_exact)| {
+                        ((model_rank, adapter_exact, price.effective_from), price)
+                    })
+            })
+            .max_by_key(|(rank, _)| *rank)
+            .map(|(_, price)| price.clone())
+    }
+}
+
+impl Default for PricingCatalog {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
@@ -0,0 +1,459 @@
+use crate::chat::{ChatMessage, ChatRequest, ChatRole};
+use crate::error::{check_response, AdapterError};
+use crate::health::HealthProbe;
+use crate::registry::{Adapter, InvokeOptions};
+use crate::response::{InvokeResponse, Usage};
+use crate::stream::{sse_stream, ChatStream, StreamChunk};
+use crate::tool::{ToolCall, ToolChoice, ToolDefinition};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Mutex;
+use tracing::info;
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+/// Messages API 要求必须指定 max_tokens
+const DEFAULT_MAX_TOKENS: u32 = 4096;
+
+#[derive(Clone)]
+pub struct AnthropicAdapter {
+    api_key: String,
+    model: String,
+    base_url: String,
+    client: reqwest::Client,
+    health_probe: HealthProbe,
+}
+
+#[derive(Serialize)]
+struct AnthropicRequest {
+    model: String,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
+    messages: Vec<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<AnthropicTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<Value>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+}
+
+#[derive(Serialize)]
+struct Message {
+    role: &'static str,
+    content: Vec<ContentBlock>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlock {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        #[serde(default)]
+        input: Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+    },
+    /// 思考过程等暂不支持的内容块，解析响应时忽略
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Serialize)]
+struct AnthropicTool {
+    name: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    description: String,
+    input_schema: Value,
+}
+
+impl From<&ToolDefinition> for AnthropicTool {
+    fn from(tool: &ToolDefinition) -> Self {
+        Self {
+            name: tool.name.clone(),
+            description: tool.description.clone(),
+            input_schema: tool.parameters.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct AnthropicResponse {
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    content: Vec<ContentBlock>,
+    #[serde(default)]
+    stop_reason: Option<String>,
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Deserialize, Default, Clone)]
+struct AnthropicUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    cache_creation_input_tokens: u64,
+    #[serde(default)]
+    cache_read_input_tokens: u64,
+}
+
+impl From<AnthropicUsage> for Usage {
+    fn from(usage: AnthropicUsage) -> Self {
+        // input_tokens 不含缓存部分，统一折算为包含缓存的 prompt_tokens
+        let prompt_tokens =
+            usage.input_tokens + usage.cache_creation_input_tokens + usage.cache_read_input_tokens;
+        Usage::new(prompt_tokens, usage.output_tokens)
+            .with_cached_tokens(usage.cache_read_input_tokens)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamEvent {
+    MessageStart {
+        message: StreamMessage,
+    },
+    ContentBlockDelta {
+        delta: StreamDelta,
+    },
+    MessageDelta {
+        delta: MessageDelta,
+        #[serde(default)]
+        usage: Option<AnthropicUsage>,
+    },
+    Error {
+        error: StreamError,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct StreamMessage {
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamDelta {
+    TextDelta {
+        text: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct MessageDelta {
+    #[serde(default)]
+    stop_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StreamError {
+    #[serde(rename = "type", default)]
+    kind: String,
+    #[serde(default)]
+    message: String,
+}
+
+#[async_trait]
+impl Adapter for AnthropicAdapter {
+    fn name(&self) -> &str {
+        "anthropic"
+    }
+
+    async fn describe(&self) -> String {
+        format!("Anthropic {} 模型适配器", self.model)
+    }
+
+    async fn invoke(&self, prompt: &str) -> anyhow::Result<String> {
+        self.chat(&ChatRequest::from_prompt(prompt), &InvokeOptions::default())
+            .await
+            .map(|response| response.content)
+    }
+
+    async fn invoke_with_options(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<String> {
+        self.chat(&ChatRequest::from_prompt(prompt), options)
+            .await
+            .map(|response| response.content)
+    }
+
+    async fn invoke_stream(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        self.chat_stream(&ChatRequest::from_prompt(prompt), options)
+            .await
+    }
+
+    async fn chat(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<InvokeResponse> {
+        request.ensure_text_only("Anthropic")?;
+        let req = self.build_request(request, options, false);
+        info!("Calling Anthropic with model: {}", req.model);
+        let response = self.send(&req).await?;
+
+        let result: AnthropicResponse = response.json().await.map_err(AdapterError::from)?;
+
+        let mut content = String::new();
+        let mut tool_calls = Vec::new();
+        for block in result.content {
+            match block {
+                ContentBlock::Text { text } => content.push_str(&text),
+                ContentBlock::ToolUse { id, name, input } => {
+                    tool_calls.push(ToolCall::new(id, name, input.to_string()))
+                }
+                _ => {}
+            }
+        }
+
+        Ok(InvokeResponse::new(content)
+            .with_tool_calls(tool_calls)
+            .with_usage(result.usage.map(Usage::from))
+            .with_model(result.model.unwrap_or(req.model))
+            .with_finish_reason(result.stop_reason.as_deref().map(finish_reason)))
+    }
+
+    async fn chat_stream(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        request.ensure_text_only("Anthropic")?;
+        let req = self.build_request(request, options, true);
+        info!("Streaming Anthropic with model: {}", req.model);
+
+        let response = self.send(&req).await?;
+        // 输入用量在 message_start 中，输出用量在 message_delta 中，合并后随结束分片返回
+        let usage = Mutex::new(AnthropicUsage::default());
+        Ok(sse_stream(response, move |data| {
+            parse_stream_event(data, &usage)
+        }))
+    }
+
+    async fn health(&self) -> bool {
+        let models = Some(
+            self.client
+                .get(format!("{}/v1/models", self.base_url))
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", ANTHROPIC_VERSION),
+        );
+        self.health_probe.run(self, "Anthropic", models).await
+    }
+}
+
+impl AnthropicAdapter {
+    pub fn new(api_key: String, model: Option<String>) -> Self {
+        Self::new_with_base(
+            api_key,
+            model.unwrap_or_else(|| "claude-sonnet-4-5".to_string()),
+            "https://api.anthropic.com".to_string(),
+        )
+    }
+
+    pub fn new_with_base(api_key: String, model: String, base_url: String) -> Self {
+        Self {
+            api_key,
+            model,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client: reqwest::Client::new(),
+            health_probe: HealthProbe::default(),
+        }
+    }
+
+    pub fn with_health_probe(mut self, health_probe: HealthProbe) -> Self {
+        self.health_probe = health_probe;
+        self
+    }
+
+    fn build_request(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+        stream: bool,
+    ) -> AnthropicRequest {
+        let system: Vec<&str> = request
+            .messages
+            .iter()
+            .filter(|m| m.role == ChatRole::System)
+            .map(|m| m.content.as_str())
+            .collect();
+
+        AnthropicRequest {
+            model: options.model.clone().unwrap_or_else(|| self.model.clone()),
+            max_tokens: options.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+            system: (!system.is_empty()).then(|| system.join("\n\n")),
+            messages: build_messages(&request.messages),
+            temperature: options.temperature,
+            top_p: options.top_p,
+            stop_sequences: options.stop.clone(),
+            tools: request.tools.iter().map(AnthropicTool::from).collect(),
+            tool_choice: request.tool_choice.as_ref().map(tool_choice_value),
+            stream,
+        }
+    }
+
+    async fn send(&self, req: &AnthropicRequest) -> anyhow::Result<reqwest::Response> {
+        let response = self
+            .client
+            .post(format!("{}/v1/messages", self.base_url))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(req)
+            .send()
+            .await
+            .map_err(AdapterError::from)?;
+
+        Ok(check_response("Anthropic", response).await?)
+    }
+}
+
+/// system 消息单独放在请求的 system 字段；工具结果以 user 消息回传，相邻同角色消息合并
+fn build_messages(messages: &[ChatMessage]) -> Vec<Message> {
+    let mut result: Vec<Message> = Vec::new();
+    for message in messages {
+        let (role, blocks) = match message.role {
+            ChatRole::System => continue,
+            ChatRole::User => (
+                "user",
+                vec![ContentBlock::Text {
+                    text: message.content_with_name(),
+                }],
+            ),
+            ChatRole::Assistant => {
+                let mut blocks = Vec::new();
+                if !message.content.is_empty() {
+                    blocks.push(ContentBlock::Text {
+                        text: message.content.clone(),
+                    });
+                }
+                blocks.extend(message.tool_calls.iter().map(|call| {
+                    ContentBlock::ToolUse {
+                        id: call.id.clone(),
+                        name: call.name.clone(),
+                        input: call
+                            .parse_arguments()
+                            .unwrap_or_else(|_| Value::Object(Default::default())),
+                    }
+                }));
+                ("assistant", blocks)
+            }
+            ChatRole::Tool => (
+                "user",
+                vec![ContentBlock::ToolResult {
+                    tool_use_id: message.tool_call_id.clone().unwrap_or_default(),
+                    content: message.content.clone(),
+                }],
+            ),
+        };
+
+        match result.last_mut() {
+            Some(last) if last.role == role => last.content.extend(blocks),
+            _ => result.push(Message {
+                role,
+                content: blocks,
+            }),
+        }
+    }
+    result
+}
+
+fn tool_choice_value(choice: &ToolChoice) -> Value {
+    match choice {
+        ToolChoice::Auto => serde_json::json!({"type": "auto"}),
+        ToolChoice::None => serde_json::json!({"type": "none"}),
+        ToolChoice::Required => serde_json::json!({"type": "any"}),
+        ToolChoice::Tool(name) => serde_json::json!({"type": "tool", "name": name}),
+    }
+}
+
+/// 将 stop_reason 转换为与其他提供商一致的 finish_reason
+fn finish_reason(stop_reason: &str) -> String {
+    match stop_reason {
+        "end_turn" | "stop_sequence" => "stop",
+        "max_tokens" => "length",
+        "tool_use" => "tool_calls",
+        "refusal" => "content_filter",
+        other => other,
+    }
+    .to_string()
+}
+
+fn parse_stream_event(
+    data: &str,
+    usage: &Mutex<AnthropicUsage>,
+) -> anyhow::Result<Option<StreamChunk>> {
+    let event: StreamEvent = serde_json::from_str(data).map_err(|e| {
+        AdapterError::Decode(format!("Failed to parse Anthropic stream event: {}", e))
+    })?;
+
+    match event {
+        StreamEvent::MessageStart { message } => {
+            if let Some(start) = message.usage {
+                *usage.lock().unwrap() = start;
+            }
+            Ok(None)
+        }
+        StreamEvent::ContentBlockDelta {
+            delta: StreamDelta::TextDelta { text },
+        } => Ok(Some(StreamChunk::text(text))),
+        StreamEvent::MessageDelta { delta, usage: end } => {
+            let mut usage = usage.lock().unwrap();
+            if let Some(end) = end {
+                usage.output_tokens = end.output_tokens;
+            }
+            Ok(Some(StreamChunk {
+                delta: String::new(),
+                finish_reason: delta.stop_reason.as_deref().map(finish_reason),
+                usage: Some(Usage::from(usage.clone())),
+            }))
+        }
+        StreamEvent::Error { error } => {
+            let status = match error.kind.as_str() {
+                "invalid_request_error" => 400,
+                "authentication_error" => 401,
+                "permission_error" => 403,
+                "not_found_error" => 404,
+                "request_too_large" => 413,
+                "rate_limit_error" => 429,
+                "overloaded_error" => 529,
+                _ => 500,
+            };
+            Err(AdapterError::classify(
+                status,
+                data,
+                format!("Anthropic API error: {}", error.message),
+                None,
+            )
+            .into())
+        }
+        _ => Ok(None),
+    }
+}
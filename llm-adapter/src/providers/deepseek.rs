@@ -1,7 +1,13 @@
-use crate::registry::Adapter;
+use crate::chat::{ChatMessage, ChatRequest};
+use crate::error::{check_response, AdapterError};
+use crate::health::HealthProbe;
+use crate::registry::{Adapter, InvokeOptions};
+use crate::response::{InvokeResponse, OpenAIUsage, Usage};
+use crate::stream::{parse_openai_chunk, sse_stream, ChatStream, StreamOptions};
+use crate::tool::{tool_choice_value, OpenAITool, OpenAIToolCall, ToolCall};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use tracing::{error, info};
+use tracing::info;
 
 #[derive(Clone)]
 pub struct DeepSeekAdapter {
@@ -9,6 +15,7 @@ pub struct DeepSeekAdapter {
     model: String,
     base_url: String,
     client: reqwest::Client,
+    health_probe: HealthProbe,
 }
 
 #[derive(Serialize)]
@@ -16,27 +23,76 @@ struct DeepSeekRequest {
     model: String,
     messages: Vec<Message>,
     temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    presence_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frequency_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<OpenAITool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
 }
 
 #[derive(Serialize)]
 struct Message {
     role: String,
     content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<OpenAIToolCall>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+}
+
+impl From<&ChatMessage> for Message {
+    fn from(message: &ChatMessage) -> Self {
+        Self {
+            role: message.role.as_str().to_string(),
+            content: message.content.clone(),
+            name: message.name.clone(),
+            tool_calls: message
+                .tool_calls
+                .iter()
+                .map(OpenAIToolCall::from)
+                .collect(),
+            tool_call_id: message.tool_call_id.clone(),
+        }
+    }
 }
 
 #[derive(Deserialize)]
 struct DeepSeekResponse {
     choices: Vec<Choice>,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    usage: Option<OpenAIUsage>,
 }
 
 #[derive(Deserialize)]
 struct Choice {
     message: MessageResponse,
+    #[serde(default)]
+    finish_reason: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct MessageResponse {
-    content: String,
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<OpenAIToolCall>,
 }
 
 #[async_trait]
@@ -50,38 +106,82 @@ impl Adapter for DeepSeekAdapter {
     }
 
     async fn invoke(&self, prompt: &str) -> anyhow::Result<String> {
-        info!("Calling DeepSeek with model: {}", self.model);
+        self.chat(&ChatRequest::from_prompt(prompt), &InvokeOptions::default())
+            .await
+            .map(|response| response.content)
+    }
 
-        let req = DeepSeekRequest {
-            model: self.model.clone(),
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: prompt.to_string(),
-            }],
-            temperature: 0.7,
-        };
+    async fn invoke_with_options(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<String> {
+        self.chat(&ChatRequest::from_prompt(prompt), options)
+            .await
+            .map(|response| response.content)
+    }
 
-        let response = self
-            .client
-            .post(format!("{}/v1/chat/completions", self.base_url))
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&req)
-            .send()
-            .await?;
+    async fn invoke_stream(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        self.chat_stream(&ChatRequest::from_prompt(prompt), options)
+            .await
+    }
 
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            error!("DeepSeek API error: {}", text);
-            anyhow::bail!("DeepSeek API error: {}", status);
-        }
+    async fn chat(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<InvokeResponse> {
+        request.ensure_text_only("DeepSeek")?;
+        let req = self.build_request(request, options, false);
+        info!("Calling DeepSeek with model: {}", req.model);
+        let response = self.send(&req).await?;
+
+        let result: DeepSeekResponse = response.json().await.map_err(AdapterError::from)?;
+        let choice = result
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| AdapterError::no_choices("DeepSeek"))?;
+
+        let tool_calls = choice
+            .message
+            .tool_calls
+            .into_iter()
+            .map(ToolCall::from)
+            .collect();
+        let content = choice.message.content.unwrap_or_default();
 
-        let result: DeepSeekResponse = response.json().await?;
-        Ok(result.choices[0].message.content.clone())
+        Ok(InvokeResponse::new(content)
+            .with_tool_calls(tool_calls)
+            .with_usage(result.usage.map(Usage::from))
+            .with_model(result.model.unwrap_or(req.model))
+            .with_finish_reason(choice.finish_reason))
+    }
+
+    async fn chat_stream(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        request.ensure_text_only("DeepSeek")?;
+        let req = self.build_request(request, options, true);
+        info!("Streaming DeepSeek with model: {}", req.model);
+
+        let response = self.send(&req).await?;
+        Ok(sse_stream(response, parse_openai_chunk))
     }
 
     async fn health(&self) -> bool {
-        true
+        let models = Some(
+            self.client
+                .get(format!("{}/v1/models", self.base_url))
+                .bearer_auth(&self.api_key),
+        );
+        self.health_probe.run(self, "DeepSeek", models).await
     }
 }
 
@@ -92,6 +192,49 @@ impl DeepSeekAdapter {
             model: model.unwrap_or_else(|| "deepseek-chat".to_string()),
             base_url: "https://api.deepseek.com".to_string(),
             client: reqwest::Client::new(),
+            health_probe: HealthProbe::default(),
+        }
+    }
+
+    pub fn with_health_probe(mut self, health_probe: HealthProbe) -> Self {
+        self.health_probe = health_probe;
+        self
+    }
+
+    fn build_request(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+        stream: bool,
+    ) -> DeepSeekRequest {
+        DeepSeekRequest {
+            model: options.model.clone().unwrap_or_else(|| self.model.clone()),
+            messages: request.messages.iter().map(Message::from).collect(),
+            temperature: options.temperature.unwrap_or(0.7),
+            max_tokens: options.max_tokens,
+            top_p: options.top_p,
+            stop: options.stop.clone(),
+            presence_penalty: options.presence_penalty,
+            frequency_penalty: options.frequency_penalty,
+            tools: request.tools.iter().map(OpenAITool::from).collect(),
+            tool_choice: request.tool_choice.as_ref().map(tool_choice_value),
+            stream,
+            stream_options: stream.then_some(StreamOptions {
+                include_usage: true,
+            }),
         }
     }
+
+    async fn send(&self, req: &DeepSeekRequest) -> anyhow::Result<reqwest::Response> {
+        let response = self
+            .client
+            .post(format!("{}/v1/chat/completions", self.base_url))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .json(req)
+            .send()
+            .await
+            .map_err(AdapterError::from)?;
+
+        Ok(check_response("DeepSeek", response).await?)
+    }
 }
@@ -1,7 +1,13 @@
-use crate::registry::Adapter;
+use crate::chat::{ChatMessage, ChatRequest, OpenAIContent, OpenAIMediaSupport};
+use crate::error::{check_response, AdapterError};
+use crate::health::HealthProbe;
+use crate::registry::{Adapter, InvokeOptions};
+use crate::response::{InvokeResponse, OpenAIUsage, Usage};
+use crate::stream::{parse_openai_chunk, sse_stream, ChatStream, StreamOptions};
+use crate::tool::{tool_choice_value, OpenAITool, OpenAIToolCall, ToolCall};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use tracing::{error, info};
+use tracing::info;
 
 #[derive(Clone)]
 pub struct DoubaoAdapter {
@@ -9,6 +15,7 @@ pub struct DoubaoAdapter {
     model: String,
     base_url: String,
     client: reqwest::Client,
+    health_probe: HealthProbe,
 }
 
 #[derive(Serialize)]
@@ -16,27 +23,78 @@ struct DoubaoRequest {
     model: String,
     messages: Vec<Message>,
     temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    presence_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frequency_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<OpenAITool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
 }
 
 #[derive(Serialize)]
 struct Message {
     role: String,
-    content: String,
+    content: OpenAIContent,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<OpenAIToolCall>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+}
+
+impl Message {
+    fn new(message: &ChatMessage) -> Result<Self, AdapterError> {
+        Ok(Self {
+            role: message.role.as_str().to_string(),
+            content: OpenAIContent::new(
+                "Doubao",
+                message,
+                message.content_with_name(),
+                OpenAIMediaSupport::IMAGES_ONLY,
+            )?,
+            tool_calls: message
+                .tool_calls
+                .iter()
+                .map(OpenAIToolCall::from)
+                .collect(),
+            tool_call_id: message.tool_call_id.clone(),
+        })
+    }
 }
 
 #[derive(Deserialize)]
 struct DoubaoResponse {
     choices: Vec<Choice>,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    usage: Option<OpenAIUsage>,
 }
 
 #[derive(Deserialize)]
 struct Choice {
     message: MessageResponse,
+    #[serde(default)]
+    finish_reason: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct MessageResponse {
-    content: String,
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<OpenAIToolCall>,
 }
 
 #[async_trait]
@@ -50,48 +108,141 @@ impl Adapter for DoubaoAdapter {
     }
 
     async fn invoke(&self, prompt: &str) -> anyhow::Result<String> {
-        info!("Calling Doubao with model: {}", self.model);
+        self.chat(&ChatRequest::from_prompt(prompt), &InvokeOptions::default())
+            .await
+            .map(|response| response.content)
+    }
 
-        let req = DoubaoRequest {
-            model: self.model.clone(),
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: prompt.to_string(),
-            }],
-            temperature: 0.7,
-        };
+    async fn invoke_with_options(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<String> {
+        self.chat(&ChatRequest::from_prompt(prompt), options)
+            .await
+            .map(|response| response.content)
+    }
 
-        let response = self
-            .client
-            .post(format!("{}/v1/chat/completions", self.base_url))
-            .bearer_auth(&self.api_key)
-            .json(&req)
-            .send()
-            .await?;
+    async fn invoke_stream(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        self.chat_stream(&ChatRequest::from_prompt(prompt), options)
+            .await
+    }
 
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            error!("Doubao API error: {}", text);
-            anyhow::bail!("Doubao API error: {}", status);
-        }
+    async fn chat(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<InvokeResponse> {
+        let req = self.build_request(request, options, false)?;
+        info!("Calling Doubao with model: {}", req.model);
+        let response = self.send(&req).await?;
+
+        let result: DoubaoResponse = response.json().await.map_err(AdapterError::from)?;
+        let choice = result
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| AdapterError::no_choices("Doubao"))?;
+
+        let tool_calls = choice
+            .message
+            .tool_calls
+            .into_iter()
+            .map(ToolCall::from)
+            .collect();
+        let content = choice.message.content.unwrap_or_default();
+
+        Ok(InvokeResponse::new(content)
+            .with_tool_calls(tool_calls)
+            .with_usage(result.usage.map(Usage::from))
+            .with_model(result.model.unwrap_or(req.model))
+            .with_finish_reason(choice.finish_reason))
+    }
 
-        let result: DoubaoResponse = response.json().await?;
-        Ok(result.choices[0].message.content.clone())
+    async fn chat_stream(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let req = self.build_request(request, options, true)?;
+        info!("Streaming Doubao with model: {}", req.model);
+
+        let response = self.send(&req).await?;
+        Ok(sse_stream(response, parse_openai_chunk))
     }
 
     async fn health(&self) -> bool {
-        true
+        // 没有模型列表接口，models 方式也使用最小补全请求探测
+        self.health_probe.run(self, "Doubao", None).await
     }
 }
 
 impl DoubaoAdapter {
     pub fn new(api_key: String, model: Option<String>) -> Self {
+        Self::new_with_base(
+            api_key,
+            model.unwrap_or_else(|| "doubao-pro-4k".to_string()),
+            "https://ark.cn-beijing.volces.com/api/v3".to_string(),
+        )
+    }
+
+    pub fn new_with_base(api_key: String, model: String, base_url: String) -> Self {
         Self {
             api_key,
-            model: model.unwrap_or_else(|| "doubao-pro-4k".to_string()),
-            base_url: "https://ark.cn-beijing.volces.com/api/v3".to_string(),
+            model,
+            base_url,
             client: reqwest::Client::new(),
+            health_probe: HealthProbe::default(),
         }
     }
+
+    pub fn with_health_probe(mut self, health_probe: HealthProbe) -> Self {
+        self.health_probe = health_probe;
+        self
+    }
+
+    fn build_request(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+        stream: bool,
+    ) -> Result<DoubaoRequest, AdapterError> {
+        Ok(DoubaoRequest {
+            model: options.model.clone().unwrap_or_else(|| self.model.clone()),
+            messages: request
+                .messages
+                .iter()
+                .map(Message::new)
+                .collect::<Result<_, _>>()?,
+            temperature: options.temperature.unwrap_or(0.7),
+            max_tokens: options.max_tokens,
+            top_p: options.top_p,
+            stop: options.stop.clone(),
+            presence_penalty: options.presence_penalty,
+            frequency_penalty: options.frequency_penalty,
+            tools: request.tools.iter().map(OpenAITool::from).collect(),
+            tool_choice: request.tool_choice.as_ref().map(tool_choice_value),
+            stream,
+            stream_options: stream.then_some(StreamOptions {
+                include_usage: true,
+            }),
+        })
+    }
+
+    async fn send(&self, req: &DoubaoRequest) -> anyhow::Result<reqwest::Response> {
+        let response = self
+            .client
+            .post(format!("{}/v1/chat/completions", self.base_url))
+            .bearer_auth(&self.api_key)
+            .json(req)
+            .send()
+            .await
+            .map_err(AdapterError::from)?;
+
+        Ok(check_response("Doubao", response).await?)
+    }
 }
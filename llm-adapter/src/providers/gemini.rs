@@ -0,0 +1,532 @@
+use crate::chat::{ChatMessage, ChatRequest, ChatRole};
+use crate::error::{check_response, AdapterError};
+use crate::health::HealthProbe;
+use crate::registry::{Adapter, InvokeOptions};
+use crate::response::{InvokeResponse, Usage};
+use crate::stream::{sse_stream, ChatStream, StreamChunk};
+use crate::tool::{ToolCall, ToolChoice, ToolDefinition};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::info;
+
+#[derive(Clone)]
+pub struct GeminiAdapter {
+    api_key: String,
+    model: String,
+    base_url: String,
+    /// 通过 `?key=` 传递 API Key，默认使用 x-goog-api-key 请求头
+    query_auth: bool,
+    client: reqwest::Client,
+    health_probe: HealthProbe,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiRequest {
+    contents: Vec<Content>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system_instruction: Option<Content>,
+    generation_config: GenerationConfig,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<GeminiTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_config: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Content {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    role: Option<String>,
+    #[serde(default)]
+    parts: Vec<Part>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct Part {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    function_call: Option<FunctionCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    function_response: Option<FunctionResponse>,
+    /// 思考过程，不计入输出内容
+    #[serde(default, skip_serializing)]
+    thought: bool,
+}
+
+impl Part {
+    fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: Some(text.into()),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct FunctionCall {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    name: String,
+    #[serde(default)]
+    args: Value,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FunctionResponse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    name: String,
+    response: Value,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerationConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_output_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    presence_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frequency_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    candidate_count: Option<u32>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiTool {
+    function_declarations: Vec<FunctionDeclaration>,
+}
+
+#[derive(Serialize)]
+struct FunctionDeclaration {
+    name: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    description: String,
+    parameters: Value,
+}
+
+impl From<&ToolDefinition> for FunctionDeclaration {
+    fn from(tool: &ToolDefinition) -> Self {
+        Self {
+            name: tool.name.clone(),
+            description: tool.description.clone(),
+            parameters: tool.parameters.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiResponse {
+    #[serde(default)]
+    candidates: Vec<Candidate>,
+    #[serde(default)]
+    prompt_feedback: Option<PromptFeedback>,
+    #[serde(default)]
+    usage_metadata: Option<UsageMetadata>,
+    #[serde(default)]
+    model_version: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Candidate {
+    #[serde(default)]
+    content: Option<Content>,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PromptFeedback {
+    #[serde(default)]
+    block_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageMetadata {
+    #[serde(default)]
+    prompt_token_count: u64,
+    #[serde(default)]
+    candidates_token_count: u64,
+    #[serde(default)]
+    thoughts_token_count: u64,
+    #[serde(default)]
+    cached_content_token_count: u64,
+}
+
+impl From<UsageMetadata> for Usage {
+    fn from(usage: UsageMetadata) -> Self {
+        // 思考 token 按输出计费
+        Usage::new(
+            usage.prompt_token_count,
+            usage.candidates_token_count + usage.thoughts_token_count,
+        )
+        .with_cached_tokens(usage.cached_content_token_count)
+    }
+}
+
+/// 一次响应（或流式分片）中解析出的内容
+struct Output {
+    text: String,
+    tool_calls: Vec<ToolCall>,
+    finish_reason: Option<String>,
+    usage: Option<Usage>,
+    model: Option<String>,
+}
+
+impl GeminiResponse {
+    /// 提示词或输出被安全策略拦截且没有任何内容时返回 `ContentFiltered`
+    fn into_output(self) -> Result<Output, AdapterError> {
+        if let Some(reason) = self.prompt_feedback.and_then(|f| f.block_reason) {
+            return Err(AdapterError::ContentFiltered(format!(
+                "Gemini blocked the prompt: {}",
+                reason
+            )));
+        }
+
+        let usage = self.usage_metadata.map(Usage::from);
+        let Some(candidate) = self.candidates.into_iter().next() else {
+            return Ok(Output {
+                text: String::new(),
+                tool_calls: Vec::new(),
+                finish_reason: None,
+                usage,
+                model: self.model_version,
+            });
+        };
+
+        let mut text = String::new();
+        let mut tool_calls = Vec::new();
+        for part in candidate.content.map(|c| c.parts).unwrap_or_default() {
+            if part.thought {
+                continue;
+            }
+            if let Some(t) = part.text {
+                text.push_str(&t);
+            }
+            if let Some(call) = part.function_call {
+                // 旧版接口不返回调用 ID，按序号生成
+                let id = call
+                    .id
+                    .unwrap_or_else(|| format!("call_{}", tool_calls.len()));
+                tool_calls.push(ToolCall::new(id, call.name, call.args.to_string()));
+            }
+        }
+
+        let blocked = candidate.finish_reason.as_deref().is_some_and(is_blocked);
+        if blocked && text.is_empty() && tool_calls.is_empty() {
+            return Err(AdapterError::ContentFiltered(format!(
+                "Gemini blocked the response: {}",
+                candidate.finish_reason.unwrap_or_default()
+            )));
+        }
+
+        let finish_reason = candidate
+            .finish_reason
+            .map(|reason| finish_reason(&reason, !tool_calls.is_empty()));
+        Ok(Output {
+            text,
+            tool_calls,
+            finish_reason,
+            usage,
+            model: self.model_version,
+        })
+    }
+}
+
+#[async_trait]
+impl Adapter for GeminiAdapter {
+    fn name(&self) -> &str {
+        "gemini"
+    }
+
+    async fn describe(&self) -> String {
+        format!("Gemini {} 模型适配器", self.model)
+    }
+
+    async fn invoke(&self, prompt: &str) -> anyhow::Result<String> {
+        self.chat(&ChatRequest::from_prompt(prompt), &InvokeOptions::default())
+            .await
+            .map(|response| response.content)
+    }
+
+    async fn invoke_with_options(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<String> {
+        self.chat(&ChatRequest::from_prompt(prompt), options)
+            .await
+            .map(|response| response.content)
+    }
+
+    async fn invoke_stream(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        self.chat_stream(&ChatRequest::from_prompt(prompt), options)
+            .await
+    }
+
+    async fn chat(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<InvokeResponse> {
+        request.ensure_text_only("Gemini")?;
+        let model = options.model.clone().unwrap_or_else(|| self.model.clone());
+        let req = self.build_request(request, options);
+        info!("Calling Gemini with model: {}", model);
+
+        let response = self
+            .send(&format!("models/{}:generateContent", model), &req)
+            .await?;
+        let result: GeminiResponse = response.json().await.map_err(AdapterError::from)?;
+        let output = result.into_output()?;
+
+        Ok(InvokeResponse::new(output.text)
+            .with_tool_calls(output.tool_calls)
+            .with_usage(output.usage)
+            .with_model(output.model.unwrap_or(model))
+            .with_finish_reason(output.finish_reason))
+    }
+
+    async fn chat_stream(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        request.ensure_text_only("Gemini")?;
+        let model = options.model.clone().unwrap_or_else(|| self.model.clone());
+        let req = self.build_request(request, options);
+        info!("Streaming Gemini with model: {}", model);
+
+        let response = self
+            .send(
+                &format!("models/{}:streamGenerateContent?alt=sse", model),
+                &req,
+            )
+            .await?;
+        Ok(sse_stream(response, parse_stream_chunk))
+    }
+
+    async fn health(&self) -> bool {
+        let models =
+            Some(self.authorize(self.client.get(format!("{}/v1beta/models", self.base_url))));
+        self.health_probe.run(self, "Gemini", models).await
+    }
+}
+
+impl GeminiAdapter {
+    pub fn new(api_key: String, model: Option<String>) -> Self {
+        Self::new_with_base(
+            api_key,
+            model.unwrap_or_else(|| "gemini-2.0-flash".to_string()),
+            "https://generativelanguage.googleapis.com".to_string(),
+        )
+    }
+
+    pub fn new_with_base(api_key: String, model: String, base_url: String) -> Self {
+        Self {
+            api_key,
+            model,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            query_auth: false,
+            client: reqwest::Client::new(),
+            health_probe: HealthProbe::default(),
+        }
+    }
+
+    pub fn with_query_auth(mut self, query_auth: bool) -> Self {
+        self.query_auth = query_auth;
+        self
+    }
+
+    pub fn with_health_probe(mut self, health_probe: HealthProbe) -> Self {
+        self.health_probe = health_probe;
+        self
+    }
+
+    fn build_request(&self, request: &ChatRequest, options: &InvokeOptions) -> GeminiRequest {
+        let system: Vec<Part> = request
+            .messages
+            .iter()
+            .filter(|m| m.role == ChatRole::System)
+            .map(|m| Part::text(m.content.clone()))
+            .collect();
+
+        GeminiRequest {
+            contents: build_contents(&request.messages),
+            system_instruction: (!system.is_empty()).then_some(Content {
+                role: None,
+                parts: system,
+            }),
+            generation_config: GenerationConfig {
+                temperature: options.temperature,
+                top_p: options.top_p,
+                max_output_tokens: options.max_tokens,
+                stop_sequences: options.stop.clone(),
+                seed: options.seed,
+                presence_penalty: options.presence_penalty,
+                frequency_penalty: options.frequency_penalty,
+                candidate_count: options.n,
+            },
+            tools: if request.tools.is_empty() {
+                Vec::new()
+            } else {
+                vec![GeminiTool {
+                    function_declarations: request
+                        .tools
+                        .iter()
+                        .map(FunctionDeclaration::from)
+                        .collect(),
+                }]
+            },
+            tool_config: request.tool_choice.as_ref().map(tool_config),
+        }
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if self.query_auth {
+            request.query(&[("key", &self.api_key)])
+        } else {
+            request.header("x-goog-api-key", &self.api_key)
+        }
+    }
+
+    async fn send(&self, path: &str, req: &GeminiRequest) -> anyhow::Result<reqwest::Response> {
+        let response = self
+            .authorize(
+                self.client
+                    .post(format!("{}/v1beta/{}", self.base_url, path)),
+            )
+            .json(req)
+            .send()
+            .await
+            .map_err(AdapterError::from)?;
+
+        Ok(check_response("Gemini", response).await?)
+    }
+}
+
+/// assistant 对应 model 角色；工具结果以 functionResponse 回传，需要按调用 ID 找回函数名
+fn build_contents(messages: &[ChatMessage]) -> Vec<Content> {
+    let mut contents: Vec<Content> = Vec::new();
+    for message in messages {
+        let (role, parts) = match message.role {
+            ChatRole::System => continue,
+            ChatRole::User => ("user", vec![Part::text(message.content_with_name())]),
+            ChatRole::Assistant => {
+                let mut parts = Vec::new();
+                if !message.content.is_empty() {
+                    parts.push(Part::text(message.content.clone()));
+                }
+                parts.extend(message.tool_calls.iter().map(|call| {
+                    Part {
+                        function_call: Some(FunctionCall {
+                            id: None,
+                            name: call.name.clone(),
+                            args: call
+                                .parse_arguments()
+                                .unwrap_or_else(|_| Value::Object(Default::default())),
+                        }),
+                        ..Default::default()
+                    }
+                }));
+                ("model", parts)
+            }
+            ChatRole::Tool => {
+                let call_id = message.tool_call_id.clone().unwrap_or_default();
+                let name = messages
+                    .iter()
+                    .flat_map(|m| &m.tool_calls)
+                    .find(|call| call.id == call_id)
+                    .map(|call| call.name.clone())
+                    .unwrap_or_else(|| call_id.clone());
+                // response 必须是对象，非 JSON 对象的结果包装为 {"result": ...}
+                let response = match serde_json::from_str::<Value>(&message.content) {
+                    Ok(value @ Value::Object(_)) => value,
+                    _ => serde_json::json!({ "result": message.content }),
+                };
+                (
+                    "user",
+                    vec![Part {
+                        function_response: Some(FunctionResponse {
+                            id: None,
+                            name,
+                            response,
+                        }),
+                        ..Default::default()
+                    }],
+                )
+            }
+        };
+
+        match contents.last_mut() {
+            Some(last) if last.role.as_deref() == Some(role) => last.parts.extend(parts),
+            _ => contents.push(Content {
+                role: Some(role.to_string()),
+                parts,
+            }),
+        }
+    }
+    contents
+}
+
+fn tool_config(choice: &ToolChoice) -> Value {
+    let config = match choice {
+        ToolChoice::Auto => serde_json::json!({"mode": "AUTO"}),
+        ToolChoice::None => serde_json::json!({"mode": "NONE"}),
+        ToolChoice::Required => serde_json::json!({"mode": "ANY"}),
+        ToolChoice::Tool(name) => {
+            serde_json::json!({"mode": "ANY", "allowedFunctionNames": [name]})
+        }
+    };
+    serde_json::json!({ "functionCallingConfig": config })
+}
+
+fn is_blocked(finish_reason: &str) -> bool {
+    matches!(
+        finish_reason,
+        "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII" | "IMAGE_SAFETY"
+    )
+}
+
+/// 将 finishReason 转换为与其他提供商一致的 finish_reason；发起函数调用时 Gemini 仍返回 STOP
+fn finish_reason(reason: &str, has_tool_calls: bool) -> String {
+    match reason {
+        "STOP" if has_tool_calls => "tool_calls".to_string(),
+        "STOP" => "stop".to_string(),
+        "MAX_TOKENS" => "length".to_string(),
+        reason if is_blocked(reason) => "content_filter".to_string(),
+        other => other.to_lowercase(),
+    }
+}
+
+fn parse_stream_chunk(data: &str) -> anyhow::Result<Option<StreamChunk>> {
+    let chunk: GeminiResponse = serde_json::from_str(data)
+        .map_err(|e| AdapterError::Decode(format!("Failed to parse Gemini stream chunk: {}", e)))?;
+    let output = chunk.into_output()?;
+
+    Ok(Some(StreamChunk {
+        delta: output.text,
+        finish_reason: output.finish_reason,
+        usage: output.usage,
+    }))
+}
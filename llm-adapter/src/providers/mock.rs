@@ -1,4 +1,8 @@
-use crate::registry::Adapter;
+use crate::chat::{ChatRequest, ChatRole};
+use crate::registry::{Adapter, InvokeOptions};
+use crate::response::InvokeResponse;
+use crate::stream::{ChatStream, StreamChunk};
+use crate::tool::{ToolCall, ToolChoice};
 use async_trait::async_trait;
 use tracing::info;
 
@@ -21,6 +25,48 @@ impl Adapter for MockAdapter {
         Ok(format!("Mock response to: {}", prompt))
     }
 
+    async fn invoke_stream(
+        &self,
+        prompt: &str,
+        _options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        info!("Mock adapter streaming: {}", prompt);
+        let response = format!("Mock response to: {}", prompt);
+
+        let mut chunks: Vec<anyhow::Result<StreamChunk>> = response
+            .split_inclusive(' ')
+            .map(|part| Ok(StreamChunk::text(part)))
+            .collect();
+        chunks.push(Ok(StreamChunk::finished("stop")));
+
+        Ok(Box::pin(futures::stream::iter(chunks)))
+    }
+
+    async fn chat(
+        &self,
+        request: &ChatRequest,
+        _options: &InvokeOptions,
+    ) -> anyhow::Result<InvokeResponse> {
+        if let Some(tool_call) = mock_tool_call(request) {
+            info!("Mock adapter calling tool: {}", tool_call.name);
+            return Ok(InvokeResponse::new("")
+                .with_tool_calls(vec![tool_call])
+                .with_finish_reason(Some("tool_calls".to_string())));
+        }
+
+        let content = self.invoke(&last_user_content(request)).await?;
+        Ok(InvokeResponse::new(content).with_finish_reason(Some("stop".to_string())))
+    }
+
+    async fn chat_stream(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        self.invoke_stream(&last_user_content(request), options)
+            .await
+    }
+
     async fn health(&self) -> bool {
         true
     }
@@ -31,3 +77,33 @@ impl MockAdapter {
         Self { name }
     }
 }
+
+/// 附带图片或音频时在末尾注明数量，便于测试确认内容已传入
+fn last_user_content(request: &ChatRequest) -> String {
+    let Some(message) = request.last_user_message() else {
+        return String::new();
+    };
+    match message.parts.len() {
+        0 => message.content.clone(),
+        n => format!("{} [{} attachments]", message.content, n),
+    }
+}
+
+/// 提供了工具且最后一条是用户消息时，模拟模型调用工具（默认第一个）
+fn mock_tool_call(request: &ChatRequest) -> Option<ToolCall> {
+    if request.messages.last().map(|m| m.role) != Some(ChatRole::User) {
+        return None;
+    }
+
+    let tool = match &request.tool_choice {
+        Some(ToolChoice::None) => None,
+        Some(ToolChoice::Tool(name)) => request.tools.iter().find(|t| &t.name == name),
+        _ => request.tools.first(),
+    }?;
+
+    Some(ToolCall::new(
+        format!("call_mock_{}", tool.name),
+        tool.name.clone(),
+        "{}",
+    ))
+}
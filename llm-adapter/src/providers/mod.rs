@@ -1,16 +1,25 @@
+pub mod anthropic;
 pub mod deepseek;
 pub mod doubao;
+pub mod gemini;
 pub mod mock;
+pub mod ollama;
 pub mod openai;
 pub mod qianwen;
 pub mod zhipu;
 
+#[allow(unused_imports)]
+pub use anthropic::AnthropicAdapter;
 #[allow(unused_imports)]
 pub use deepseek::DeepSeekAdapter;
 #[allow(unused_imports)]
 pub use doubao::DoubaoAdapter;
+#[allow(unused_imports)]
+pub use gemini::GeminiAdapter;
 pub use mock::MockAdapter;
 #[allow(unused_imports)]
+pub use ollama::OllamaAdapter;
+#[allow(unused_imports)]
 pub use openai::OpenAIAdapter;
 #[allow(unused_imports)]
 pub use qianwen::QianwenAdapter;
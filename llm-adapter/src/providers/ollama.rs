@@ -0,0 +1,435 @@
+use crate::chat::{ChatMessage, ChatRequest, ChatRole};
+use crate::embedding::{EmbeddingAdapter, EmbeddingResponse};
+use crate::error::{check_response, AdapterError};
+use crate::health::HealthProbe;
+use crate::registry::{Adapter, InvokeOptions};
+use crate::response::{InvokeResponse, Usage};
+use crate::stream::{ndjson_stream, ChatStream, StreamChunk};
+use crate::tool::{OpenAITool, OpenAIToolCall, ToolCall};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::info;
+
+/// Ollama 原生 `/api/chat` 接口，本地部署无需 API Key
+#[derive(Clone)]
+pub struct OllamaAdapter {
+    model: String,
+    embedding_model: String,
+    base_url: String,
+    /// 请求结束后模型保留在内存中的时长，如 "10m"、"-1"（常驻）或秒数
+    keep_alive: Option<Value>,
+    client: reqwest::Client,
+    health_probe: HealthProbe,
+}
+
+#[derive(Serialize)]
+struct OllamaRequest {
+    model: String,
+    messages: Vec<Message>,
+    stream: bool,
+    options: ModelOptions,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<OpenAITool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<Value>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    role: &'static str,
+    content: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<MessageToolCall>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_name: Option<String>,
+}
+
+/// Ollama 要求工具参数为 JSON 对象而不是字符串
+#[derive(Serialize)]
+struct MessageToolCall {
+    function: MessageFunction,
+}
+
+#[derive(Serialize)]
+struct MessageFunction {
+    name: String,
+    arguments: Value,
+}
+
+#[derive(Serialize)]
+struct ModelOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_predict: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    presence_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frequency_penalty: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct OllamaResponse {
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    message: Option<MessageResponse>,
+    #[serde(default)]
+    done: bool,
+    #[serde(default)]
+    done_reason: Option<String>,
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    eval_count: Option<u64>,
+    /// 流式响应中途出错时返回
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MessageResponse {
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    tool_calls: Vec<OpenAIToolCall>,
+}
+
+impl OllamaResponse {
+    /// token 计数只在最后一个分片（done 为 true）中返回
+    fn usage(&self) -> Option<Usage> {
+        if !self.done {
+            return None;
+        }
+        Some(Usage::new(
+            self.prompt_eval_count.unwrap_or(0),
+            self.eval_count.unwrap_or(0),
+        ))
+    }
+
+    fn finish_reason(&self, has_tool_calls: bool) -> Option<String> {
+        if !self.done {
+            return None;
+        }
+        Some(match self.done_reason.as_deref() {
+            _ if has_tool_calls => "tool_calls".to_string(),
+            None | Some("stop") => "stop".to_string(),
+            Some(other) => other.to_string(),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct TagsResponse {
+    #[serde(default)]
+    models: Vec<ModelTag>,
+}
+
+#[derive(Deserialize)]
+struct ModelTag {
+    name: String,
+}
+
+#[async_trait]
+impl Adapter for OllamaAdapter {
+    fn name(&self) -> &str {
+        "ollama"
+    }
+
+    async fn describe(&self) -> String {
+        format!("Ollama {} 本地模型适配器", self.model)
+    }
+
+    async fn invoke(&self, prompt: &str) -> anyhow::Result<String> {
+        self.chat(&ChatRequest::from_prompt(prompt), &InvokeOptions::default())
+            .await
+            .map(|response| response.content)
+    }
+
+    async fn invoke_with_options(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<String> {
+        self.chat(&ChatRequest::from_prompt(prompt), options)
+            .await
+            .map(|response| response.content)
+    }
+
+    async fn invoke_stream(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        self.chat_stream(&ChatRequest::from_prompt(prompt), options)
+            .await
+    }
+
+    async fn chat(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<InvokeResponse> {
+        request.ensure_text_only("Ollama")?;
+        let req = self.build_request(request, options, false);
+        info!("Calling Ollama with model: {}", req.model);
+        let response = self.send(&req).await?;
+
+        let mut result: OllamaResponse = response.json().await.map_err(AdapterError::from)?;
+        let message = result
+            .message
+            .take()
+            .ok_or_else(|| AdapterError::Decode("Ollama returned no message".to_string()))?;
+
+        // Ollama 不返回工具调用 ID，按序号生成
+        let tool_calls: Vec<ToolCall> = message
+            .tool_calls
+            .into_iter()
+            .map(ToolCall::from)
+            .enumerate()
+            .map(|(index, mut call)| {
+                if call.id.is_empty() {
+                    call.id = format!("call_{}", index);
+                }
+                call
+            })
+            .collect();
+        let finish_reason = result.finish_reason(!tool_calls.is_empty());
+
+        Ok(InvokeResponse::new(message.content)
+            .with_tool_calls(tool_calls)
+            .with_usage(result.usage())
+            .with_model(result.model.unwrap_or(req.model))
+            .with_finish_reason(finish_reason))
+    }
+
+    async fn chat_stream(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        request.ensure_text_only("Ollama")?;
+        let req = self.build_request(request, options, true);
+        info!("Streaming Ollama with model: {}", req.model);
+
+        let response = self.send(&req).await?;
+        Ok(ndjson_stream(response, parse_stream_chunk))
+    }
+
+    async fn health(&self) -> bool {
+        let models = Some(self.client.get(format!("{}/api/tags", self.base_url)));
+        self.health_probe.run(self, "Ollama", models).await
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<String>> {
+        let response = self
+            .client
+            .get(format!("{}/api/tags", self.base_url))
+            .send()
+            .await
+            .map_err(AdapterError::from)?;
+        let tags: TagsResponse = check_response("Ollama", response)
+            .await?
+            .json()
+            .await
+            .map_err(AdapterError::from)?;
+        Ok(tags.models.into_iter().map(|tag| tag.name).collect())
+    }
+
+    fn as_embedding(&self) -> Option<&dyn EmbeddingAdapter> {
+        Some(self)
+    }
+}
+
+#[derive(Serialize)]
+struct EmbedRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct EmbedResponse {
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    embeddings: Vec<Vec<f32>>,
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+}
+
+#[async_trait]
+impl EmbeddingAdapter for OllamaAdapter {
+    async fn embed(
+        &self,
+        texts: &[String],
+        model: Option<&str>,
+    ) -> anyhow::Result<EmbeddingResponse> {
+        let model = model.unwrap_or(&self.embedding_model);
+        info!(
+            "Embedding {} texts with Ollama model: {}",
+            texts.len(),
+            model
+        );
+
+        let response = self
+            .client
+            .post(format!("{}/api/embed", self.base_url))
+            .json(&EmbedRequest {
+                model,
+                input: texts,
+                keep_alive: self.keep_alive.clone(),
+            })
+            .send()
+            .await
+            .map_err(AdapterError::from)?;
+        let result: EmbedResponse = check_response("Ollama", response)
+            .await?
+            .json()
+            .await
+            .map_err(AdapterError::from)?;
+
+        Ok(EmbeddingResponse::new(result.embeddings)
+            .with_model(result.model.unwrap_or_else(|| model.to_string()))
+            .with_usage(result.prompt_eval_count.map(|tokens| Usage::new(tokens, 0))))
+    }
+}
+
+impl OllamaAdapter {
+    pub fn new(model: Option<String>) -> Self {
+        Self::new_with_base(
+            model.unwrap_or_else(|| "llama3.2".to_string()),
+            "http://localhost:11434".to_string(),
+        )
+    }
+
+    pub fn new_with_base(model: String, base_url: String) -> Self {
+        Self {
+            model,
+            embedding_model: "nomic-embed-text".to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            keep_alive: None,
+            client: reqwest::Client::new(),
+            health_probe: HealthProbe::default(),
+        }
+    }
+
+    pub fn with_embedding_model(mut self, embedding_model: String) -> Self {
+        self.embedding_model = embedding_model;
+        self
+    }
+
+    pub fn with_keep_alive(mut self, keep_alive: Value) -> Self {
+        self.keep_alive = Some(keep_alive);
+        self
+    }
+
+    pub fn with_health_probe(mut self, health_probe: HealthProbe) -> Self {
+        self.health_probe = health_probe;
+        self
+    }
+
+    fn build_request(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+        stream: bool,
+    ) -> OllamaRequest {
+        OllamaRequest {
+            model: options.model.clone().unwrap_or_else(|| self.model.clone()),
+            messages: request
+                .messages
+                .iter()
+                .map(|message| build_message(message, &request.messages))
+                .collect(),
+            stream,
+            options: ModelOptions {
+                temperature: options.temperature,
+                top_p: options.top_p,
+                num_predict: options.max_tokens,
+                stop: options.stop.clone(),
+                seed: options.seed,
+                presence_penalty: options.presence_penalty,
+                frequency_penalty: options.frequency_penalty,
+            },
+            tools: request.tools.iter().map(OpenAITool::from).collect(),
+            keep_alive: self.keep_alive.clone(),
+        }
+    }
+
+    async fn send(&self, req: &OllamaRequest) -> anyhow::Result<reqwest::Response> {
+        let response = self
+            .client
+            .post(format!("{}/api/chat", self.base_url))
+            .json(req)
+            .send()
+            .await
+            .map_err(AdapterError::from)?;
+
+        Ok(check_response("Ollama", response).await?)
+    }
+}
+
+/// 工具结果通过 tool_name 关联调用，按调用 ID 找回函数名
+fn build_message(message: &ChatMessage, messages: &[ChatMessage]) -> Message {
+    let tool_name = message.tool_call_id.as_ref().and_then(|id| {
+        messages
+            .iter()
+            .flat_map(|m| &m.tool_calls)
+            .find(|call| &call.id == id)
+            .map(|call| call.name.clone())
+    });
+
+    Message {
+        role: match message.role {
+            ChatRole::System => "system",
+            ChatRole::User => "user",
+            ChatRole::Assistant => "assistant",
+            ChatRole::Tool => "tool",
+        },
+        content: message.content.clone(),
+        tool_calls: message
+            .tool_calls
+            .iter()
+            .map(|call| MessageToolCall {
+                function: MessageFunction {
+                    name: call.name.clone(),
+                    arguments: call
+                        .parse_arguments()
+                        .unwrap_or_else(|_| Value::Object(Default::default())),
+                },
+            })
+            .collect(),
+        tool_name,
+    }
+}
+
+fn parse_stream_chunk(data: &str) -> anyhow::Result<Option<StreamChunk>> {
+    let chunk: OllamaResponse = serde_json::from_str(data)
+        .map_err(|e| AdapterError::Decode(format!("Failed to parse Ollama stream chunk: {}", e)))?;
+    if let Some(error) = chunk.error {
+        return Err(AdapterError::Upstream5xx {
+            status: 500,
+            message: format!("Ollama error: {}", error),
+        }
+        .into());
+    }
+
+    Ok(Some(StreamChunk {
+        delta: chunk
+            .message
+            .as_ref()
+            .map(|m| m.content.clone())
+            .unwrap_or_default(),
+        finish_reason: chunk.finish_reason(false),
+        usage: chunk.usage(),
+    }))
+}
@@ -1,14 +1,24 @@
-use crate::registry::Adapter;
+use crate::chat::{ChatMessage, ChatRequest, OpenAIContent, OpenAIMediaSupport};
+use crate::embedding::{openai_embeddings, EmbeddingAdapter, EmbeddingResponse};
+use crate::error::{check_response, AdapterError};
+use crate::health::HealthProbe;
+use crate::registry::{Adapter, InvokeOptions};
+use crate::response::{InvokeResponse, OpenAIUsage, Usage};
+use crate::stream::{parse_openai_chunk, sse_stream, ChatStream, StreamOptions};
+use crate::tool::{tool_choice_value, OpenAITool, OpenAIToolCall, ToolCall};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use tracing::{error, info};
+use tracing::info;
 
 #[derive(Clone)]
 pub struct OpenAIAdapter {
+    name: String,
     api_key: String,
     model: String,
+    embedding_model: String,
     base_url: String,
     client: reqwest::Client,
+    health_probe: HealthProbe,
 }
 
 #[derive(Serialize)]
@@ -16,33 +26,102 @@ struct OpenAIRequest {
     model: String,
     messages: Vec<Message>,
     temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    presence_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frequency_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<OpenAITool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
 }
 
 #[derive(Serialize)]
 struct Message {
     role: String,
-    content: String,
+    content: OpenAIContent,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<OpenAIToolCall>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+}
+
+impl Message {
+    fn new(message: &ChatMessage) -> Result<Self, AdapterError> {
+        Ok(Self {
+            role: message.role.as_str().to_string(),
+            content: OpenAIContent::new(
+                "OpenAI",
+                message,
+                message.content.clone(),
+                OpenAIMediaSupport::OPENAI,
+            )?,
+            name: message.name.clone(),
+            tool_calls: message
+                .tool_calls
+                .iter()
+                .map(OpenAIToolCall::from)
+                .collect(),
+            tool_call_id: message.tool_call_id.clone(),
+        })
+    }
 }
 
 #[derive(Deserialize)]
 struct OpenAIResponse {
     choices: Vec<Choice>,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    usage: Option<OpenAIUsage>,
 }
 
 #[derive(Deserialize)]
 struct Choice {
     message: MessageResponse,
+    #[serde(default)]
+    finish_reason: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct MessageResponse {
-    content: String,
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<OpenAIToolCall>,
+}
+
+#[derive(Deserialize)]
+struct ModelList {
+    #[serde(default)]
+    data: Vec<ModelEntry>,
+}
+
+#[derive(Deserialize)]
+struct ModelEntry {
+    id: String,
 }
 
 #[async_trait]
 impl Adapter for OpenAIAdapter {
     fn name(&self) -> &str {
-        "openai"
+        &self.name
     }
 
     async fn describe(&self) -> String {
@@ -50,57 +129,213 @@ impl Adapter for OpenAIAdapter {
     }
 
     async fn invoke(&self, prompt: &str) -> anyhow::Result<String> {
-        info!("Calling OpenAI with model: {}", self.model);
+        self.chat(&ChatRequest::from_prompt(prompt), &InvokeOptions::default())
+            .await
+            .map(|response| response.content)
+    }
 
-        let req = OpenAIRequest {
-            model: self.model.clone(),
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: prompt.to_string(),
-            }],
-            temperature: 0.7,
-        };
+    async fn invoke_with_options(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<String> {
+        self.chat(&ChatRequest::from_prompt(prompt), options)
+            .await
+            .map(|response| response.content)
+    }
 
-        let response = self
-            .client
-            .post(format!("{}/v1/chat/completions", self.base_url))
-            .bearer_auth(&self.api_key)
-            .json(&req)
-            .send()
-            .await?;
+    async fn invoke_stream(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        self.chat_stream(&ChatRequest::from_prompt(prompt), options)
+            .await
+    }
 
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            error!("OpenAI API error: {}", text);
-            anyhow::bail!("OpenAI API error: {}", status);
-        }
+    async fn chat(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<InvokeResponse> {
+        let req = self.build_request(request, options, false)?;
+        info!("Calling OpenAI with model: {}", req.model);
+        let response = self.send(&req).await?;
+
+        let result: OpenAIResponse = response.json().await.map_err(AdapterError::from)?;
+        let choice = result
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| AdapterError::no_choices("OpenAI"))?;
+
+        let tool_calls = choice
+            .message
+            .tool_calls
+            .into_iter()
+            .map(ToolCall::from)
+            .collect();
+        let content = choice.message.content.unwrap_or_default();
+
+        Ok(InvokeResponse::new(content)
+            .with_tool_calls(tool_calls)
+            .with_usage(result.usage.map(Usage::from))
+            .with_model(result.model.unwrap_or(req.model))
+            .with_finish_reason(choice.finish_reason))
+    }
+
+    async fn chat_stream(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let req = self.build_request(request, options, true)?;
+        info!("Streaming OpenAI with model: {}", req.model);
 
-        let result: OpenAIResponse = response.json().await?;
-        Ok(result.choices[0].message.content.clone())
+        let response = self.send(&req).await?;
+        Ok(sse_stream(response, parse_openai_chunk))
     }
 
     async fn health(&self) -> bool {
-        true // TODO: 实现健康检查
+        let models = Some(self.authorize(self.client.get(format!("{}/v1/models", self.base_url))));
+        self.health_probe.run(self, "OpenAI", models).await
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<String>> {
+        let response = self
+            .authorize(self.client.get(format!("{}/v1/models", self.base_url)))
+            .send()
+            .await
+            .map_err(AdapterError::from)?;
+        let models: ModelList = check_response("OpenAI", response)
+            .await?
+            .json()
+            .await
+            .map_err(AdapterError::from)?;
+        Ok(models.data.into_iter().map(|model| model.id).collect())
+    }
+
+    fn as_embedding(&self) -> Option<&dyn EmbeddingAdapter> {
+        Some(self)
+    }
+}
+
+#[async_trait]
+impl EmbeddingAdapter for OpenAIAdapter {
+    async fn embed(
+        &self,
+        texts: &[String],
+        model: Option<&str>,
+    ) -> anyhow::Result<EmbeddingResponse> {
+        let model = model.unwrap_or(&self.embedding_model);
+        info!(
+            "Embedding {} texts with OpenAI model: {}",
+            texts.len(),
+            model
+        );
+        let url = format!("{}/v1/embeddings", self.base_url);
+        openai_embeddings(
+            "OpenAI",
+            || self.authorize(self.client.post(&url)),
+            model,
+            texts,
+            2048,
+        )
+        .await
     }
 }
 
 impl OpenAIAdapter {
     pub fn new(api_key: String, model: Option<String>) -> Self {
         Self {
+            name: "openai".to_string(),
             api_key,
             model: model.unwrap_or_else(|| "gpt-4o-mini".to_string()),
+            embedding_model: "text-embedding-3-small".to_string(),
             base_url: "https://api.openai.com".to_string(),
             client: reqwest::Client::new(),
+            health_probe: HealthProbe::default(),
         }
     }
 
     pub fn new_with_base(api_key: String, model: String, base_url: String) -> Self {
         Self {
+            name: "openai".to_string(),
             api_key,
             model,
+            embedding_model: "text-embedding-3-small".to_string(),
             base_url,
             client: reqwest::Client::new(),
+            health_probe: HealthProbe::default(),
         }
     }
+
+    /// 兼容服务以自己的名称注册，计费、限流和预算都按该名称归属
+    pub fn with_name(mut self, name: String) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn with_embedding_model(mut self, embedding_model: String) -> Self {
+        self.embedding_model = embedding_model;
+        self
+    }
+
+    pub fn with_health_probe(mut self, health_probe: HealthProbe) -> Self {
+        self.health_probe = health_probe;
+        self
+    }
+
+    fn build_request(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+        stream: bool,
+    ) -> Result<OpenAIRequest, AdapterError> {
+        Ok(OpenAIRequest {
+            model: options.model.clone().unwrap_or_else(|| self.model.clone()),
+            messages: request
+                .messages
+                .iter()
+                .map(Message::new)
+                .collect::<Result<_, _>>()?,
+            temperature: options.temperature.unwrap_or(0.7),
+            max_tokens: options.max_tokens,
+            top_p: options.top_p,
+            stop: options.stop.clone(),
+            seed: options.seed,
+            presence_penalty: options.presence_penalty,
+            frequency_penalty: options.frequency_penalty,
+            n: options.n,
+            tools: request.tools.iter().map(OpenAITool::from).collect(),
+            tool_choice: request.tool_choice.as_ref().map(tool_choice_value),
+            stream,
+            stream_options: stream.then_some(StreamOptions {
+                include_usage: true,
+            }),
+        })
+    }
+
+    /// 本地 OpenAI 兼容服务（llama.cpp、vLLM）未配置 Key 时不发送认证头
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if self.api_key.is_empty() {
+            request
+        } else {
+            request.bearer_auth(&self.api_key)
+        }
+    }
+
+    async fn send(&self, req: &OpenAIRequest) -> anyhow::Result<reqwest::Response> {
+        let response = self
+            .authorize(
+                self.client
+                    .post(format!("{}/v1/chat/completions", self.base_url)),
+            )
+            .json(req)
+            .send()
+            .await
+            .map_err(AdapterError::from)?;
+
+        Ok(check_response("OpenAI", response).await?)
+    }
 }
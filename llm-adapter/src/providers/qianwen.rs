@@ -1,14 +1,23 @@
-use crate::registry::Adapter;
+use crate::chat::{ChatMessage, ChatRequest, ContentPart};
+use crate::embedding::{EmbeddingAdapter, EmbeddingResponse};
+use crate::error::{check_response, AdapterError};
+use crate::health::HealthProbe;
+use crate::registry::{Adapter, InvokeOptions};
+use crate::response::{InvokeResponse, Usage};
+use crate::stream::{sse_stream, ChatStream, StreamChunk};
+use crate::tool::{tool_choice_value, OpenAITool, OpenAIToolCall, ToolCall};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use tracing::{error, info};
+use tracing::info;
 
 #[derive(Clone)]
 pub struct QianwenAdapter {
     api_key: String,
     model: String,
+    embedding_model: String,
     base_url: String,
     client: reqwest::Client,
+    health_probe: HealthProbe,
 }
 
 #[derive(Serialize)]
@@ -16,6 +25,9 @@ struct QianwenRequest {
     model: String,
     input: QianwenInput,
     parameters: QianwenParameters,
+    /// 含图片或音频时改用多模态接口（Qwen-VL、Qwen-Audio）
+    #[serde(skip)]
+    multimodal: bool,
 }
 
 #[derive(Serialize)]
@@ -26,18 +38,95 @@ struct QianwenInput {
 #[derive(Serialize)]
 struct Message {
     role: String,
-    content: String,
+    content: QianwenContent,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<OpenAIToolCall>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+}
+
+/// 多模态接口要求每条消息的内容都是数组
+#[derive(Serialize)]
+#[serde(untagged)]
+enum QianwenContent {
+    Text(String),
+    Parts(Vec<QianwenContentPart>),
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+enum QianwenContentPart {
+    Text(String),
+    Image(String),
+    Audio(String),
+}
+
+impl From<ContentPart> for QianwenContentPart {
+    fn from(part: ContentPart) -> Self {
+        match part {
+            ContentPart::Text { text } => QianwenContentPart::Text(text),
+            ContentPart::Image { source, .. } => QianwenContentPart::Image(source.to_url()),
+            ContentPart::Audio { source } => QianwenContentPart::Audio(source.to_url()),
+        }
+    }
+}
+
+impl Message {
+    fn new(message: &ChatMessage, multimodal: bool) -> Self {
+        let text = message.content_with_name();
+        let content = if multimodal {
+            QianwenContent::Parts(
+                message
+                    .content_parts(text)
+                    .into_iter()
+                    .map(QianwenContentPart::from)
+                    .collect(),
+            )
+        } else {
+            QianwenContent::Text(text)
+        };
+
+        Self {
+            role: message.role.as_str().to_string(),
+            content,
+            tool_calls: message
+                .tool_calls
+                .iter()
+                .map(OpenAIToolCall::from)
+                .collect(),
+            tool_call_id: message.tool_call_id.clone(),
+        }
+    }
 }
 
 #[derive(Serialize)]
 struct QianwenParameters {
     temperature: f32,
     top_p: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    presence_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<OpenAITool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<serde_json::Value>,
+    result_format: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    incremental_output: bool,
 }
 
 #[derive(Deserialize)]
 struct QianwenResponse {
     output: QianwenOutput,
+    #[serde(default)]
+    usage: Option<QianwenUsage>,
 }
 
 #[derive(Deserialize)]
@@ -48,11 +137,87 @@ struct QianwenOutput {
 #[derive(Deserialize)]
 struct Choice {
     message: MessageResponse,
+    #[serde(default)]
+    finish_reason: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct MessageResponse {
-    content: String,
+    #[serde(default)]
+    content: Option<ResponseContent>,
+    #[serde(default)]
+    tool_calls: Vec<OpenAIToolCall>,
+}
+
+/// 多模态接口返回 `[{"text": ...}]` 形式的内容
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ResponseContent {
+    Text(String),
+    Parts(Vec<ResponsePart>),
+}
+
+#[derive(Deserialize)]
+struct ResponsePart {
+    #[serde(default)]
+    text: Option<String>,
+}
+
+impl ResponseContent {
+    fn into_text(self) -> String {
+        match self {
+            ResponseContent::Text(text) => text,
+            ResponseContent::Parts(parts) => parts.into_iter().filter_map(|p| p.text).collect(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct QianwenStreamResponse {
+    output: QianwenStreamOutput,
+    #[serde(default)]
+    usage: Option<QianwenUsage>,
+}
+
+#[derive(Deserialize)]
+struct QianwenStreamOutput {
+    #[serde(default)]
+    choices: Vec<StreamChoice>,
+}
+
+#[derive(Deserialize)]
+struct StreamChoice {
+    #[serde(default)]
+    message: Option<MessageResponse>,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct QianwenUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    prompt_tokens_details: Option<QianwenPromptTokensDetails>,
+}
+
+#[derive(Deserialize)]
+struct QianwenPromptTokensDetails {
+    #[serde(default)]
+    cached_tokens: u64,
+}
+
+impl From<QianwenUsage> for Usage {
+    fn from(usage: QianwenUsage) -> Self {
+        Usage::new(usage.input_tokens, usage.output_tokens).with_cached_tokens(
+            usage
+                .prompt_tokens_details
+                .map(|d| d.cached_tokens)
+                .unwrap_or(0),
+        )
+    }
 }
 
 #[async_trait]
@@ -66,56 +231,297 @@ impl Adapter for QianwenAdapter {
     }
 
     async fn invoke(&self, prompt: &str) -> anyhow::Result<String> {
-        info!("Calling Qianwen with model: {}", self.model);
+        self.chat(&ChatRequest::from_prompt(prompt), &InvokeOptions::default())
+            .await
+            .map(|response| response.content)
+    }
 
-        let req = QianwenRequest {
-            model: self.model.clone(),
-            input: QianwenInput {
-                messages: vec![Message {
-                    role: "user".to_string(),
-                    content: prompt.to_string(),
-                }],
-            },
-            parameters: QianwenParameters {
-                temperature: 0.7,
-                top_p: 0.9,
-            },
-        };
+    async fn invoke_with_options(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<String> {
+        self.chat(&ChatRequest::from_prompt(prompt), options)
+            .await
+            .map(|response| response.content)
+    }
 
-        let response = self
-            .client
-            .post(format!(
-                "{}/v1/services/aigc/text-generation/generation",
-                self.base_url
-            ))
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&req)
-            .send()
-            .await?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            error!("Qianwen API error: {}", text);
-            anyhow::bail!("Qianwen API error: {}", status);
-        }
+    async fn invoke_stream(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        self.chat_stream(&ChatRequest::from_prompt(prompt), options)
+            .await
+    }
+
+    async fn chat(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<InvokeResponse> {
+        let req = self.build_request(request, options, false);
+        info!("Calling Qianwen with model: {}", req.model);
+
+        let response = self.send(&req).await?;
+
+        let result: QianwenResponse = response.json().await.map_err(AdapterError::from)?;
+        let choice = result
+            .output
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| AdapterError::no_choices("Qianwen"))?;
 
-        let result: QianwenResponse = response.json().await?;
-        Ok(result.output.choices[0].message.content.clone())
+        let tool_calls = choice
+            .message
+            .tool_calls
+            .into_iter()
+            .map(ToolCall::from)
+            .collect();
+        let content = choice
+            .message
+            .content
+            .map(ResponseContent::into_text)
+            .unwrap_or_default();
+
+        Ok(InvokeResponse::new(content)
+            .with_tool_calls(tool_calls)
+            .with_usage(result.usage.map(Usage::from))
+            .with_model(req.model)
+            .with_finish_reason(choice.finish_reason))
+    }
+
+    async fn chat_stream(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let req = self.build_request(request, options, true);
+        info!("Streaming Qianwen with model: {}", req.model);
+
+        let response = self.send(&req).await?;
+        Ok(sse_stream(response, parse_stream_chunk))
     }
 
     async fn health(&self) -> bool {
-        true
+        // 没有模型列表接口，models 方式也使用最小补全请求探测
+        self.health_probe.run(self, "Qianwen", None).await
+    }
+
+    fn as_embedding(&self) -> Option<&dyn EmbeddingAdapter> {
+        Some(self)
+    }
+}
+
+#[derive(Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: EmbeddingInput<'a>,
+}
+
+#[derive(Serialize)]
+struct EmbeddingInput<'a> {
+    texts: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResult {
+    output: EmbeddingOutput,
+    #[serde(default)]
+    usage: Option<EmbeddingUsage>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingOutput {
+    #[serde(default)]
+    embeddings: Vec<TextEmbedding>,
+}
+
+#[derive(Deserialize)]
+struct TextEmbedding {
+    #[serde(default)]
+    text_index: usize,
+    embedding: Vec<f32>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingUsage {
+    #[serde(default)]
+    total_tokens: u64,
+}
+
+#[async_trait]
+impl EmbeddingAdapter for QianwenAdapter {
+    async fn embed(
+        &self,
+        texts: &[String],
+        model: Option<&str>,
+    ) -> anyhow::Result<EmbeddingResponse> {
+        let model = model.unwrap_or(&self.embedding_model);
+        info!(
+            "Embedding {} texts with Qianwen model: {}",
+            texts.len(),
+            model
+        );
+
+        // text-embedding-v3 单次最多 10 条
+        let mut result = EmbeddingResponse::default().with_model(model);
+        for batch in texts.chunks(10) {
+            let response = self
+                .client
+                .post(format!(
+                    "{}/v1/services/embeddings/text-embedding/text-embedding",
+                    self.base_url
+                ))
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .json(&EmbeddingRequest {
+                    model,
+                    input: EmbeddingInput { texts: batch },
+                })
+                .send()
+                .await
+                .map_err(AdapterError::from)?;
+            let mut body: EmbeddingResult = check_response("Qianwen", response)
+                .await?
+                .json()
+                .await
+                .map_err(AdapterError::from)?;
+
+            if body.output.embeddings.len() != batch.len() {
+                return Err(AdapterError::Decode(format!(
+                    "Qianwen returned {} embeddings for {} inputs",
+                    body.output.embeddings.len(),
+                    batch.len()
+                ))
+                .into());
+            }
+            body.output.embeddings.sort_by_key(|item| item.text_index);
+
+            result.append(
+                EmbeddingResponse::new(
+                    body.output
+                        .embeddings
+                        .into_iter()
+                        .map(|item| item.embedding)
+                        .collect(),
+                )
+                .with_usage(body.usage.map(|usage| Usage::new(usage.total_tokens, 0))),
+            );
+        }
+        Ok(result)
     }
 }
 
 impl QianwenAdapter {
     pub fn new(api_key: String, model: Option<String>) -> Self {
+        Self::new_with_base(
+            api_key,
+            model.unwrap_or_else(|| "qwen-turbo".to_string()),
+            "https://dashscope.aliyuncs.com/api".to_string(),
+        )
+    }
+
+    pub fn new_with_base(api_key: String, model: String, base_url: String) -> Self {
         Self {
             api_key,
-            model: model.unwrap_or_else(|| "qwen-turbo".to_string()),
-            base_url: "https://dashscope.aliyuncs.com/api".to_string(),
+            model,
+            embedding_model: "text-embedding-v3".to_string(),
+            base_url,
             client: reqwest::Client::new(),
+            health_probe: HealthProbe::default(),
+        }
+    }
+
+    pub fn with_embedding_model(mut self, embedding_model: String) -> Self {
+        self.embedding_model = embedding_model;
+        self
+    }
+
+    pub fn with_health_probe(mut self, health_probe: HealthProbe) -> Self {
+        self.health_probe = health_probe;
+        self
+    }
+
+    fn build_request(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+        stream: bool,
+    ) -> QianwenRequest {
+        let multimodal = request.has_media();
+        QianwenRequest {
+            model: options.model.clone().unwrap_or_else(|| self.model.clone()),
+            input: QianwenInput {
+                messages: request
+                    .messages
+                    .iter()
+                    .map(|m| Message::new(m, multimodal))
+                    .collect(),
+            },
+            parameters: QianwenParameters {
+                temperature: options.temperature.unwrap_or(0.7),
+                top_p: options.top_p.unwrap_or(0.9),
+                max_tokens: options.max_tokens,
+                stop: options.stop.clone(),
+                seed: options.seed,
+                presence_penalty: options.presence_penalty,
+                n: options.n,
+                tools: request.tools.iter().map(OpenAITool::from).collect(),
+                tool_choice: request.tool_choice.as_ref().map(tool_choice_value),
+                result_format: "message".to_string(),
+                incremental_output: stream,
+            },
+            multimodal,
         }
     }
+
+    async fn send(&self, req: &QianwenRequest) -> anyhow::Result<reqwest::Response> {
+        let endpoint = if req.multimodal {
+            "multimodal-generation"
+        } else {
+            "text-generation"
+        };
+        let mut request = self
+            .client
+            .post(format!(
+                "{}/v1/services/aigc/{}/generation",
+                self.base_url, endpoint
+            ))
+            .header("Authorization", format!("Bearer {}", self.api_key));
+
+        if req.parameters.incremental_output {
+            request = request.header("X-DashScope-SSE", "enable");
+        }
+
+        let response = request.json(req).send().await.map_err(AdapterError::from)?;
+
+        Ok(check_response("Qianwen", response).await?)
+    }
+}
+
+fn parse_stream_chunk(data: &str) -> anyhow::Result<Option<StreamChunk>> {
+    let chunk: QianwenStreamResponse = serde_json::from_str(data).map_err(|e| {
+        AdapterError::Decode(format!("Failed to parse Qianwen stream chunk: {}", e))
+    })?;
+
+    let choice = chunk.output.choices.into_iter().next();
+    let (delta, finish_reason) = match choice {
+        Some(choice) => (
+            choice
+                .message
+                .and_then(|m| m.content)
+                .map(ResponseContent::into_text)
+                .unwrap_or_default(),
+            // DashScope 在未结束时返回字符串 "null"
+            choice.finish_reason.filter(|r| r != "null"),
+        ),
+        None => (String::new(), None),
+    };
+
+    Ok(Some(StreamChunk {
+        delta,
+        finish_reason,
+        usage: chunk.usage.map(Usage::from),
+    }))
 }
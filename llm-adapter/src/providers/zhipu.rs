@@ -1,14 +1,23 @@
-use crate::registry::Adapter;
+use crate::chat::{ChatMessage, ChatRequest, OpenAIContent, OpenAIMediaSupport};
+use crate::embedding::{openai_embeddings, EmbeddingAdapter, EmbeddingResponse};
+use crate::error::{check_response, AdapterError};
+use crate::health::HealthProbe;
+use crate::registry::{Adapter, InvokeOptions};
+use crate::response::{InvokeResponse, OpenAIUsage, Usage};
+use crate::stream::{parse_openai_chunk, sse_stream, ChatStream};
+use crate::tool::{tool_choice_value, OpenAITool, OpenAIToolCall, ToolCall};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use tracing::{error, info};
+use tracing::info;
 
 #[derive(Clone)]
 pub struct ZhipuAdapter {
     api_key: String,
     model: String,
+    embedding_model: String,
     base_url: String,
     client: reqwest::Client,
+    health_probe: HealthProbe,
 }
 
 #[derive(Serialize)]
@@ -16,27 +25,75 @@ struct ZhipuRequest {
     model: String,
     messages: Vec<Message>,
     temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<OpenAITool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
 }
 
 #[derive(Serialize)]
 struct Message {
     role: String,
-    content: String,
+    content: OpenAIContent,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<OpenAIToolCall>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<String>,
+}
+
+impl Message {
+    fn new(message: &ChatMessage) -> Result<Self, AdapterError> {
+        Ok(Self {
+            role: message.role.as_str().to_string(),
+            content: OpenAIContent::new(
+                "Zhipu",
+                message,
+                message.content_with_name(),
+                OpenAIMediaSupport {
+                    audio: false,
+                    raw_base64_image: true,
+                },
+            )?,
+            tool_calls: message
+                .tool_calls
+                .iter()
+                .map(OpenAIToolCall::from)
+                .collect(),
+            tool_call_id: message.tool_call_id.clone(),
+        })
+    }
 }
 
 #[derive(Deserialize)]
 struct ZhipuResponse {
     choices: Vec<Choice>,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    usage: Option<OpenAIUsage>,
 }
 
 #[derive(Deserialize)]
 struct Choice {
     message: MessageResponse,
+    #[serde(default)]
+    finish_reason: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct MessageResponse {
-    content: String,
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<OpenAIToolCall>,
 }
 
 #[async_trait]
@@ -50,48 +107,172 @@ impl Adapter for ZhipuAdapter {
     }
 
     async fn invoke(&self, prompt: &str) -> anyhow::Result<String> {
-        info!("Calling Zhipu with model: {}", self.model);
+        self.chat(&ChatRequest::from_prompt(prompt), &InvokeOptions::default())
+            .await
+            .map(|response| response.content)
+    }
 
-        let req = ZhipuRequest {
-            model: self.model.clone(),
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: prompt.to_string(),
-            }],
-            temperature: 0.7,
-        };
+    async fn invoke_with_options(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<String> {
+        self.chat(&ChatRequest::from_prompt(prompt), options)
+            .await
+            .map(|response| response.content)
+    }
 
-        let response = self
-            .client
-            .post(format!("{}/v4/chat/completions", self.base_url))
-            .bearer_auth(&self.api_key)
-            .json(&req)
-            .send()
-            .await?;
+    async fn invoke_stream(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        self.chat_stream(&ChatRequest::from_prompt(prompt), options)
+            .await
+    }
 
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-            error!("Zhipu API error: {}", text);
-            anyhow::bail!("Zhipu API error: {}", status);
-        }
+    async fn chat(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<InvokeResponse> {
+        let req = self.build_request(request, options, false)?;
+        info!("Calling Zhipu with model: {}", req.model);
+        let response = self.send(&req).await?;
+
+        let result: ZhipuResponse = response.json().await.map_err(AdapterError::from)?;
+        let choice = result
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| AdapterError::no_choices("Zhipu"))?;
+
+        let tool_calls = choice
+            .message
+            .tool_calls
+            .into_iter()
+            .map(ToolCall::from)
+            .collect();
+        let content = choice.message.content.unwrap_or_default();
+
+        Ok(InvokeResponse::new(content)
+            .with_tool_calls(tool_calls)
+            .with_usage(result.usage.map(Usage::from))
+            .with_model(result.model.unwrap_or(req.model))
+            .with_finish_reason(choice.finish_reason))
+    }
+
+    async fn chat_stream(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let req = self.build_request(request, options, true)?;
+        info!("Streaming Zhipu with model: {}", req.model);
 
-        let result: ZhipuResponse = response.json().await?;
-        Ok(result.choices[0].message.content.clone())
+        let response = self.send(&req).await?;
+        Ok(sse_stream(response, parse_openai_chunk))
     }
 
     async fn health(&self) -> bool {
-        true
+        // 没有模型列表接口，models 方式也使用最小补全请求探测
+        self.health_probe.run(self, "Zhipu", None).await
+    }
+
+    fn as_embedding(&self) -> Option<&dyn EmbeddingAdapter> {
+        Some(self)
+    }
+}
+
+#[async_trait]
+impl EmbeddingAdapter for ZhipuAdapter {
+    async fn embed(
+        &self,
+        texts: &[String],
+        model: Option<&str>,
+    ) -> anyhow::Result<EmbeddingResponse> {
+        let model = model.unwrap_or(&self.embedding_model);
+        info!(
+            "Embedding {} texts with Zhipu model: {}",
+            texts.len(),
+            model
+        );
+        let url = format!("{}/v4/embeddings", self.base_url);
+        // embedding-3 单次最多 64 条
+        openai_embeddings(
+            "Zhipu",
+            || self.client.post(&url).bearer_auth(&self.api_key),
+            model,
+            texts,
+            64,
+        )
+        .await
     }
 }
 
 impl ZhipuAdapter {
     pub fn new(api_key: String, model: Option<String>) -> Self {
+        Self::new_with_base(
+            api_key,
+            model.unwrap_or_else(|| "glm-4".to_string()),
+            "https://open.bigmodel.cn/api/paas".to_string(),
+        )
+    }
+
+    pub fn new_with_base(api_key: String, model: String, base_url: String) -> Self {
         Self {
             api_key,
-            model: model.unwrap_or_else(|| "glm-4".to_string()),
-            base_url: "https://open.bigmodel.cn/api/paas".to_string(),
+            model,
+            embedding_model: "embedding-3".to_string(),
+            base_url,
             client: reqwest::Client::new(),
+            health_probe: HealthProbe::default(),
         }
     }
+
+    pub fn with_embedding_model(mut self, embedding_model: String) -> Self {
+        self.embedding_model = embedding_model;
+        self
+    }
+
+    pub fn with_health_probe(mut self, health_probe: HealthProbe) -> Self {
+        self.health_probe = health_probe;
+        self
+    }
+
+    fn build_request(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+        stream: bool,
+    ) -> Result<ZhipuRequest, AdapterError> {
+        Ok(ZhipuRequest {
+            model: options.model.clone().unwrap_or_else(|| self.model.clone()),
+            messages: request
+                .messages
+                .iter()
+                .map(Message::new)
+                .collect::<Result<_, _>>()?,
+            temperature: options.temperature.unwrap_or(0.7),
+            max_tokens: options.max_tokens,
+            top_p: options.top_p,
+            stop: options.stop.clone(),
+            tools: request.tools.iter().map(OpenAITool::from).collect(),
+            tool_choice: request.tool_choice.as_ref().map(tool_choice_value),
+            stream,
+        })
+    }
+
+    async fn send(&self, req: &ZhipuRequest) -> anyhow::Result<reqwest::Response> {
+        let response = self
+            .client
+            .post(format!("{}/v4/chat/completions", self.base_url))
+            .bearer_auth(&self.api_key)
+            .json(req)
+            .send()
+            .await
+            .map_err(AdapterError::from)?;
+
+        Ok(check_response("Zhipu", response).await?)
+    }
 }
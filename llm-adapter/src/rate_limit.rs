@@ -1,13 +1,16 @@
+use async_trait::async_trait;
 use dashmap::DashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::{Duration, Instant};
-use tracing::debug;
+use tracing::{debug, info};
 
 #[derive(Clone, Debug)]
 pub struct RateLimitConfig {
     pub requests_per_second: u32,
     pub requests_per_minute: u32,
     pub requests_per_hour: u32,
+    /// 每秒限额允许的突发请求数，未设置时等于 `requests_per_second`
+    pub burst: Option<u32>,
     pub enabled: bool,
 }
 
@@ -17,109 +20,259 @@ impl Default for RateLimitConfig {
             requests_per_second: 10,
             requests_per_minute: 60,
             requests_per_hour: 1000,
+            burst: None,
             enabled: true,
         }
     }
 }
 
-pub struct RateLimiter {
-    config: RateLimitConfig,
-    second_window: Arc<DashMap<String, Vec<Instant>>>,
-    minute_window: Arc<DashMap<String, Vec<Instant>>>,
-    hour_window: Arc<DashMap<String, Vec<Instant>>>,
+/// 单个时间窗口的 GCRA 参数：每次放行把理论到达时间（TAT）推后 `interval`，
+/// TAT 最多领先当前时间 `tolerance = interval * (容量 - 1)`，即允许连续放行“容量”个请求
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Tier {
+    pub(crate) name: &'static str,
+    pub(crate) limit: u32,
+    pub(crate) interval: Duration,
+    pub(crate) tolerance: Duration,
 }
 
-impl RateLimiter {
-    pub fn new(config: RateLimitConfig) -> Self {
+impl RateLimitConfig {
+    pub(crate) fn tiers(&self) -> [Option<Tier>; 3] {
+        let tier = |name, limit: u32, window: Duration, capacity: u32| {
+            (limit > 0).then(|| {
+                let interval = window / limit;
+                Tier {
+                    name,
+                    limit,
+                    interval,
+                    tolerance: interval * capacity.max(1).saturating_sub(1),
+                }
+            })
+        };
+
+        [
+            tier(
+                "second",
+                self.requests_per_second,
+                Duration::from_secs(1),
+                self.burst.unwrap_or(self.requests_per_second),
+            ),
+            tier(
+                "minute",
+                self.requests_per_minute,
+                Duration::from_secs(60),
+                self.requests_per_minute,
+            ),
+            tier(
+                "hour",
+                self.requests_per_hour,
+                Duration::from_secs(3600),
+                self.requests_per_hour,
+            ),
+        ]
+    }
+}
+
+/// 限流状态的存储后端
+#[async_trait]
+pub trait RateLimitBackend: Send + Sync {
+    fn kind(&self) -> &'static str;
+
+    async fn check(&self, key: &str, config: &RateLimitConfig) -> Result<(), RateLimitError>;
+
+    fn reconfigure(&self, _old: &RateLimitConfig, _new: &RateLimitConfig) {}
+}
+
+/// 各窗口的 TAT，None 表示桶是满的
+#[derive(Default)]
+struct KeyState {
+    tats: [Option<Instant>; 3],
+}
+
+impl KeyState {
+    fn is_idle(&self, now: Instant) -> bool {
+        self.tats.iter().flatten().all(|tat| *tat <= now)
+    }
+}
+
+pub struct InMemoryRateLimitBackend {
+    keys: DashMap<String, KeyState>,
+    cleanup_interval: Duration,
+    last_cleanup: Mutex<Instant>,
+}
+
+impl InMemoryRateLimitBackend {
+    pub fn new() -> Self {
         Self {
-            config,
-            second_window: Arc::new(DashMap::new()),
-            minute_window: Arc::new(DashMap::new()),
-            hour_window: Arc::new(DashMap::new()),
+            keys: DashMap::new(),
+            cleanup_interval: Duration::from_secs(60),
+            last_cleanup: Mutex::new(Instant::now()),
         }
     }
 
-    pub async fn check(&self, key: &str) -> Result<(), RateLimitError> {
-        if !self.config.enabled {
-            return Ok(());
+    pub fn with_cleanup_interval(mut self, cleanup_interval: Duration) -> Self {
+        self.cleanup_interval = cleanup_interval;
+        self
+    }
+
+    pub fn tracked_keys(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// 桶已回满的 key 与新 key 等价，可以直接删除
+    fn evict_idle(&self, now: Instant) {
+        {
+            let mut last_cleanup = self.last_cleanup.lock().unwrap();
+            if now.duration_since(*last_cleanup) < self.cleanup_interval {
+                return;
+            }
+            *last_cleanup = now;
         }
 
+        let before = self.keys.len();
+        self.keys.retain(|_, state| !state.is_idle(now));
+        debug!(
+            evicted = before.saturating_sub(self.keys.len()),
+            "Evicted idle rate limit keys"
+        );
+    }
+}
+
+impl Default for InMemoryRateLimitBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl RateLimitBackend for InMemoryRateLimitBackend {
+    fn kind(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn check(&self, key: &str, config: &RateLimitConfig) -> Result<(), RateLimitError> {
         let now = Instant::now();
+        self.evict_idle(now);
 
-        if self.config.requests_per_second > 0 {
-            let mut second_requests = self
-                .second_window
-                .entry(key.to_string())
-                .or_insert_with(Vec::new);
+        let mut state = self.keys.entry(key.to_string()).or_default();
+        let mut next = state.tats;
 
-            second_requests
-                .retain(|&timestamp| now.duration_since(timestamp) < Duration::from_secs(1));
+        // 先检查所有窗口，全部通过后再扣减，避免被拒绝的请求占用额度
+        for (i, tier) in config.tiers().into_iter().enumerate() {
+            let Some(tier) = tier else {
+                next[i] = None;
+                continue;
+            };
 
-            if second_requests.len() >= self.config.requests_per_second as usize {
-                return Err(RateLimitError::TooManyRequests(format!(
-                    "Rate limit exceeded: {} requests per second",
-                    self.config.requests_per_second
-                )));
+            let tat = state.tats[i].map_or(now, |tat| tat.max(now));
+            let allow_at = tat.checked_sub(tier.tolerance).unwrap_or(now);
+            if allow_at > now {
+                return Err(RateLimitError::exceeded(&tier, allow_at - now));
             }
-
-            second_requests.push(now);
+            next[i] = Some(tat + tier.interval);
         }
 
-        if self.config.requests_per_minute > 0 {
-            let mut minute_requests = self
-                .minute_window
-                .entry(key.to_string())
-                .or_insert_with(Vec::new);
-
-            minute_requests
-                .retain(|&timestamp| now.duration_since(timestamp) < Duration::from_secs(60));
+        state.tats = next;
+        Ok(())
+    }
 
-            if minute_requests.len() >= self.config.requests_per_minute as usize {
-                return Err(RateLimitError::TooManyRequests(format!(
-                    "Rate limit exceeded: {} requests per minute",
-                    self.config.requests_per_minute
-                )));
+    /// 保留已用额度的比例，按新速率继续计算
+    fn reconfigure(&self, old: &RateLimitConfig, new: &RateLimitConfig) {
+        let now = Instant::now();
+        let (old_tiers, new_tiers) = (old.tiers(), new.tiers());
+        for mut state in self.keys.iter_mut() {
+            for (i, tat) in state.tats.iter_mut().enumerate() {
+                *tat = match (*tat, &old_tiers[i], &new_tiers[i]) {
+                    (Some(t), Some(old_tier), Some(new_tier)) if t > now => {
+                        let scale = old_tier.limit as f64 / new_tier.limit as f64;
+                        Some(now + (t - now).mul_f64(scale))
+                    }
+                    _ => None,
+                };
             }
-
-            minute_requests.push(now);
         }
+    }
+}
 
-        if self.config.requests_per_hour > 0 {
-            let mut hour_requests = self
-                .hour_window
-                .entry(key.to_string())
-                .or_insert_with(Vec::new);
+pub struct RateLimiter {
+    config: RwLock<RateLimitConfig>,
+    backend: Arc<dyn RateLimitBackend>,
+}
 
-            hour_requests
-                .retain(|&timestamp| now.duration_since(timestamp) < Duration::from_secs(3600));
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self::with_backend(config, Arc::new(InMemoryRateLimitBackend::new()))
+    }
 
-            if hour_requests.len() >= self.config.requests_per_hour as usize {
-                return Err(RateLimitError::TooManyRequests(format!(
-                    "Rate limit exceeded: {} requests per hour",
-                    self.config.requests_per_hour
-                )));
-            }
+    pub fn with_backend(config: RateLimitConfig, backend: Arc<dyn RateLimitBackend>) -> Self {
+        Self {
+            config: RwLock::new(config),
+            backend,
+        }
+    }
 
-            hour_requests.push(now);
+    pub fn config(&self) -> RateLimitConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    pub fn backend_kind(&self) -> &'static str {
+        self.backend.kind()
+    }
+
+    pub async fn check(&self, key: &str) -> Result<(), RateLimitError> {
+        let config = self.config();
+        if !config.enabled {
+            return Ok(());
         }
 
-        Ok(())
+        self.backend.check(key, &config).await
     }
 
-    pub fn update_config(&self, _config: RateLimitConfig) {
-        debug!("Rate limiter config updated");
+    pub fn update_config(&self, config: RateLimitConfig) {
+        info!(
+            rps = config.requests_per_second,
+            rpm = config.requests_per_minute,
+            rph = config.requests_per_hour,
+            enabled = config.enabled,
+            "Rate limiter config updated"
+        );
+
+        let mut current = self.config.write().unwrap();
+        self.backend.reconfigure(&current, &config);
+        *current = config;
     }
 }
 
 #[derive(Debug, Clone)]
 pub enum RateLimitError {
-    TooManyRequests(String),
+    TooManyRequests {
+        message: String,
+        retry_after: Duration,
+    },
+}
+
+impl RateLimitError {
+    pub(crate) fn exceeded(tier: &Tier, retry_after: Duration) -> Self {
+        RateLimitError::TooManyRequests {
+            message: format!(
+                "Rate limit exceeded: {} requests per {}",
+                tier.limit, tier.name
+            ),
+            retry_after,
+        }
+    }
+
+    pub fn retry_after(&self) -> Duration {
+        match self {
+            RateLimitError::TooManyRequests { retry_after, .. } => *retry_after,
+        }
+    }
 }
 
 impl std::fmt::Display for RateLimitError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            RateLimitError::TooManyRequests(msg) => write!(f, "{}", msg),
+            RateLimitError::TooManyRequests { message, .. } => write!(f, "{}", message),
         }
     }
 }
@@ -0,0 +1,379 @@
+use crate::guard::{
+    ConcurrencyBackend, ConcurrencyError, ConcurrencyPermit, InMemoryConcurrencyBackend, Priority,
+};
+use crate::rate_limit::{
+    InMemoryRateLimitBackend, RateLimitBackend, RateLimitConfig, RateLimitError,
+};
+use async_trait::async_trait;
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+use redis::{ErrorKind, RedisResult, Script};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+use tokio::task::JoinHandle;
+use tracing::{error, warn};
+
+/// GCRA 检查所有窗口，全部通过后才写入，时间取 Redis 服务端时钟（微秒）
+const GCRA_SCRIPT: &str = r#"
+local t = redis.call('TIME')
+local now = tonumber(t[1]) * 1000000 + tonumber(t[2])
+local tats = {}
+for i = 1, #KEYS do
+    local interval = tonumber(ARGV[2 * i - 1])
+    local tolerance = tonumber(ARGV[2 * i])
+    local tat = tonumber(redis.call('GET', KEYS[i]) or now)
+    if tat < now then
+        tat = now
+    end
+    local allow_at = tat - tolerance
+    if allow_at > now then
+        return {0, allow_at - now, i}
+    end
+    tats[i] = tat + interval
+end
+for i = 1, #KEYS do
+    local ttl = math.ceil((tats[i] - now) / 1000)
+    redis.call('SET', KEYS[i], string.format('%.0f', tats[i]), 'PX', ttl)
+end
+return {1, 0, 0}
+"#;
+
+/// 清理过期租约后按数量判断是否还有名额，时间为毫秒
+const ACQUIRE_SCRIPT: &str = r#"
+local t = redis.call('TIME')
+local now = tonumber(t[1]) * 1000 + math.floor(tonumber(t[2]) / 1000)
+local limit = tonumber(ARGV[1])
+local lease = tonumber(ARGV[2])
+redis.call('ZREMRANGEBYSCORE', KEYS[1], '-inf', now)
+local count = redis.call('ZCARD', KEYS[1])
+if count >= limit then
+    return {0, 0}
+end
+redis.call('ZADD', KEYS[1], now + lease, ARGV[3])
+redis.call('PEXPIRE', KEYS[1], lease)
+return {1, limit - count - 1}
+"#;
+
+/// 仅续期仍然存在的租约
+const RENEW_SCRIPT: &str = r#"
+local t = redis.call('TIME')
+local now = tonumber(t[1]) * 1000 + math.floor(tonumber(t[2]) / 1000)
+local lease = tonumber(ARGV[1])
+if not redis.call('ZSCORE', KEYS[1], ARGV[2]) then
+    return 0
+end
+redis.call('ZADD', KEYS[1], now + lease, ARGV[2])
+redis.call('PEXPIRE', KEYS[1], lease)
+return 1
+"#;
+
+/// 连接失败后在这段时间内直接走本地实现，避免每个请求都等待连接超时
+const RECONNECT_BACKOFF: Duration = Duration::from_secs(5);
+
+/// 延迟建立的 Redis 连接，断开后由 ConnectionManager 自动重连
+pub struct RedisStore {
+    client: redis::Client,
+    connection: OnceCell<ConnectionManager>,
+    last_failure: Mutex<Option<Instant>>,
+    prefix: String,
+}
+
+impl RedisStore {
+    pub fn open(url: &str, prefix: impl Into<String>) -> RedisResult<Self> {
+        Ok(Self {
+            client: redis::Client::open(url)?,
+            connection: OnceCell::new(),
+            last_failure: Mutex::new(None),
+            prefix: prefix.into(),
+        })
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    async fn connection(&self) -> RedisResult<ConnectionManager> {
+        if let Some(connection) = self.connection.get() {
+            return Ok(connection.clone());
+        }
+
+        if self
+            .last_failure
+            .lock()
+            .unwrap()
+            .is_some_and(|at| at.elapsed() < RECONNECT_BACKOFF)
+        {
+            return Err((ErrorKind::IoError, "Redis unavailable, retrying later").into());
+        }
+
+        let config = ConnectionManagerConfig::new()
+            .set_number_of_retries(1)
+            .set_connection_timeout(Duration::from_secs(1))
+            .set_response_timeout(Duration::from_secs(1));
+        let result = self
+            .connection
+            .get_or_try_init(|| ConnectionManager::new_with_config(self.client.clone(), config))
+            .await
+            .cloned();
+        if result.is_err() {
+            *self.last_failure.lock().unwrap() = Some(Instant::now());
+        }
+        result
+    }
+}
+
+/// 多副本共享的 GCRA 限流，Redis 不可用时退回进程内限流
+pub struct RedisRateLimitBackend {
+    store: Arc<RedisStore>,
+    script: Script,
+    fallback: InMemoryRateLimitBackend,
+}
+
+impl RedisRateLimitBackend {
+    pub fn new(store: Arc<RedisStore>) -> Self {
+        Self {
+            store,
+            script: Script::new(GCRA_SCRIPT),
+            fallback: InMemoryRateLimitBackend::new(),
+        }
+    }
+
+    async fn check_remote(
+        &self,
+        key: &str,
+        config: &RateLimitConfig,
+    ) -> RedisResult<Result<(), RateLimitError>> {
+        let tiers: Vec<_> = config.tiers().into_iter().flatten().collect();
+        if tiers.is_empty() {
+            return Ok(Ok(()));
+        }
+
+        let mut invocation = self.script.prepare_invoke();
+        for tier in &tiers {
+            // hash tag 保证同一个 key 的各窗口落在同一个 slot
+            invocation
+                .key(format!(
+                    "{}:rate:{{{}}}:{}",
+                    self.store.prefix(),
+                    key,
+                    tier.name
+                ))
+                .arg(tier.interval.as_micros() as u64)
+                .arg(tier.tolerance.as_micros() as u64);
+        }
+
+        let mut connection = self.store.connection().await?;
+        let (allowed, retry_after_us, index): (i64, i64, usize) =
+            invocation.invoke_async(&mut connection).await?;
+        if allowed == 1 {
+            return Ok(Ok(()));
+        }
+
+        let tier = &tiers[index.saturating_sub(1).min(tiers.len() - 1)];
+        Ok(Err(RateLimitError::exceeded(
+            tier,
+            Duration::from_micros(retry_after_us.max(0) as u64),
+        )))
+    }
+}
+
+#[async_trait]
+impl RateLimitBackend for RedisRateLimitBackend {
+    fn kind(&self) -> &'static str {
+        "redis"
+    }
+
+    async fn check(&self, key: &str, config: &RateLimitConfig) -> Result<(), RateLimitError> {
+        match self.check_remote(key, config).await {
+            Ok(result) => result,
+            Err(e) => {
+                error!(key, error = %e, "Redis rate limit check failed, using local limiter");
+                self.fallback.check(key, config).await
+            }
+        }
+    }
+
+    /// Redis 中只保存理论到达时间，新限额在下一次检查时生效
+    fn reconfigure(&self, old: &RateLimitConfig, new: &RateLimitConfig) {
+        self.fallback.reconfigure(old, new);
+    }
+}
+
+/// 基于租约的分布式信号量，持有期间定期续期，进程崩溃时租约到期自动释放。
+/// 等待者轮询获取名额，优先级越低轮询间隔越长
+pub struct RedisConcurrencyBackend {
+    store: Arc<RedisStore>,
+    key: String,
+    max_concurrent: AtomicUsize,
+    lease: Duration,
+    poll_interval: Duration,
+    acquire_script: Arc<Script>,
+    renew_script: Arc<Script>,
+    available: AtomicUsize,
+    fallback: InMemoryConcurrencyBackend,
+}
+
+impl RedisConcurrencyBackend {
+    pub fn new(store: Arc<RedisStore>, name: &str, max_concurrent: usize) -> Self {
+        Self {
+            key: format!("{}:concurrency:{{{}}}", store.prefix(), name),
+            store,
+            max_concurrent: AtomicUsize::new(max_concurrent),
+            lease: Duration::from_secs(30),
+            poll_interval: Duration::from_millis(50),
+            acquire_script: Arc::new(Script::new(ACQUIRE_SCRIPT)),
+            renew_script: Arc::new(Script::new(RENEW_SCRIPT)),
+            available: AtomicUsize::new(max_concurrent),
+            fallback: InMemoryConcurrencyBackend::new(max_concurrent),
+        }
+    }
+
+    /// 设置租约时长，持有者每隔三分之一租约续期一次
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    async fn try_acquire_remote(&self) -> RedisResult<Option<RedisLease>> {
+        let mut connection = self.store.connection().await?;
+        let member = uuid::Uuid::new_v4().to_string();
+
+        let (acquired, remaining): (i64, usize) = self
+            .acquire_script
+            .key(&self.key)
+            .arg(self.max_concurrent.load(Ordering::Relaxed))
+            .arg(self.lease.as_millis() as u64)
+            .arg(&member)
+            .invoke_async(&mut connection)
+            .await?;
+
+        if acquired == 1 {
+            self.available.store(remaining, Ordering::Relaxed);
+            Ok(Some(self.spawn_lease(connection, member)))
+        } else {
+            self.available.store(0, Ordering::Relaxed);
+            Ok(None)
+        }
+    }
+
+    async fn acquire_remote(&self, priority: Priority) -> RedisResult<RedisLease> {
+        let poll_interval = self.poll_interval * (priority.lane() as u32 + 1);
+        loop {
+            if let Some(lease) = self.try_acquire_remote().await? {
+                return Ok(lease);
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    fn spawn_lease(&self, connection: ConnectionManager, member: String) -> RedisLease {
+        let renew = {
+            let mut connection = connection.clone();
+            let script = self.renew_script.clone();
+            let key = self.key.clone();
+            let member = member.clone();
+            let lease = self.lease;
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(lease / 3);
+                ticker.tick().await;
+                loop {
+                    ticker.tick().await;
+                    let renewed: RedisResult<i64> = script
+                        .key(&key)
+                        .arg(lease.as_millis() as u64)
+                        .arg(&member)
+                        .invoke_async(&mut connection)
+                        .await;
+                    match renewed {
+                        Ok(1) => {}
+                        Ok(_) => {
+                            warn!(key, "Concurrency lease expired before release");
+                            return;
+                        }
+                        Err(e) => warn!(key, error = %e, "Failed to renew concurrency lease"),
+                    }
+                }
+            })
+        };
+
+        RedisLease {
+            connection,
+            key: self.key.clone(),
+            member,
+            renew,
+        }
+    }
+}
+
+#[async_trait]
+impl ConcurrencyBackend for RedisConcurrencyBackend {
+    fn kind(&self) -> &'static str {
+        "redis"
+    }
+
+    async fn acquire(&self, priority: Priority) -> Result<ConcurrencyPermit, ConcurrencyError> {
+        match self.acquire_remote(priority).await {
+            Ok(lease) => Ok(ConcurrencyPermit::new(lease)),
+            Err(e) => {
+                error!(key = %self.key, error = %e, "Redis concurrency acquire failed, using local semaphore");
+                self.fallback.acquire(priority).await
+            }
+        }
+    }
+
+    async fn try_acquire(&self) -> Result<Option<ConcurrencyPermit>, ConcurrencyError> {
+        match self.try_acquire_remote().await {
+            Ok(lease) => Ok(lease.map(ConcurrencyPermit::new)),
+            Err(e) => {
+                error!(key = %self.key, error = %e, "Redis concurrency acquire failed, using local semaphore");
+                self.fallback.try_acquire().await
+            }
+        }
+    }
+
+    fn resize(&self, max_concurrent: usize) {
+        self.max_concurrent.store(max_concurrent, Ordering::Relaxed);
+        self.fallback.resize(max_concurrent);
+    }
+
+    fn available_permits(&self) -> usize {
+        self.available.load(Ordering::Relaxed)
+    }
+}
+
+/// 持有中的租约，drop 时停止续期并删除
+struct RedisLease {
+    connection: ConnectionManager,
+    key: String,
+    member: String,
+    renew: JoinHandle<()>,
+}
+
+impl Drop for RedisLease {
+    fn drop(&mut self) {
+        self.renew.abort();
+
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let mut connection = self.connection.clone();
+        let key = std::mem::take(&mut self.key);
+        let member = std::mem::take(&mut self.member);
+        runtime.spawn(async move {
+            let released: RedisResult<i64> = redis::cmd("ZREM")
+                .arg(&key)
+                .arg(&member)
+                .query_async(&mut connection)
+                .await;
+            if let Err(e) = released {
+                warn!(key, error = %e, "Failed to release concurrency lease");
+            }
+        });
+    }
+}
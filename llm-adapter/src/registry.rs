@@ -1,17 +1,40 @@
-use crate::billing::BillingTracker;
+use crate::billing::{BillingTracker, UsageRecord};
+use crate::billing_report::{aggregate, ReportBucket, ReportQuery};
+use crate::budget::{Budget, BudgetManager, BudgetPeriod};
+use crate::cache::{CacheStore, ResponseCache};
+use crate::chat::ChatRequest;
+use crate::circuit_breaker::{CircuitBreaker, CircuitState};
 use crate::config::AdapterConfig;
+use crate::embedding::EmbeddingAdapter;
 use crate::factory::AdapterFactory;
+use crate::fallback::FallbackAdapter;
+use crate::guard::{ConcurrencyConfig, ConcurrencyGuard, ConcurrencyStats, Priority};
+use crate::health::{HealthMonitor, HealthReport};
+use crate::pricing::PricingCatalog;
+use crate::rate_limit::{RateLimitConfig, RateLimiter};
+use crate::response::InvokeResponse;
+use crate::stream::{single_chunk_stream, ChatStream};
 use crate::wrapper::WrappedAdapter;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 pub struct AdapterRegistry {
     adapters: Arc<RwLock<HashMap<String, Arc<dyn Adapter + Send + Sync>>>>,
     billing_trackers: Arc<DashMap<String, Arc<BillingTracker>>>,
+    budget_manager: Arc<BudgetManager>,
+    pricing: Arc<PricingCatalog>,
+    cache_store: Option<Arc<dyn CacheStore>>,
+    circuit_breakers: Arc<DashMap<String, Arc<CircuitBreaker>>>,
+    concurrency_guards: Arc<DashMap<String, Arc<ConcurrencyGuard>>>,
+    embedding_adapters: Arc<DashMap<String, Arc<dyn EmbeddingAdapter>>>,
+    health_monitors: Arc<DashMap<String, Arc<HealthMonitor>>>,
+    rate_limiters: Arc<DashMap<String, Arc<RateLimiter>>>,
+    response_caches: Arc<DashMap<String, Arc<ResponseCache>>>,
 }
 
 impl AdapterRegistry {
@@ -19,12 +42,28 @@ impl AdapterRegistry {
         Self {
             adapters: Arc::new(RwLock::new(HashMap::new())),
             billing_trackers: Arc::new(DashMap::new()),
+            budget_manager: Arc::new(BudgetManager::new()),
+            pricing: Arc::new(PricingCatalog::default()),
+            cache_store: None,
+            circuit_breakers: Arc::new(DashMap::new()),
+            concurrency_guards: Arc::new(DashMap::new()),
+            embedding_adapters: Arc::new(DashMap::new()),
+            health_monitors: Arc::new(DashMap::new()),
+            rate_limiters: Arc::new(DashMap::new()),
+            response_caches: Arc::new(DashMap::new()),
         }
     }
 
+    /// 开启响应缓存的适配器共用该存储，未设置时各自使用进程内 LRU
+    pub fn with_cache_store(mut self, cache_store: Arc<dyn CacheStore>) -> Self {
+        self.cache_store = Some(cache_store);
+        self
+    }
+
     pub async fn register(&self, name: &str, adapter: Arc<dyn Adapter + Send + Sync>) {
         let mut adapters = self.adapters.write().await;
         adapters.insert(name.to_string(), adapter);
+        self.embedding_adapters.remove(name);
         info!("Registered adapter: {}", name);
     }
 
@@ -44,29 +83,149 @@ impl AdapterRegistry {
             return Ok(());
         }
 
+        // fallback 只组合已注册的适配器，自身不再包装限流与计费
+        if let Some(chain) = FallbackAdapter::chain_from_config(&config) {
+            for member in &chain {
+                if member == &config.name {
+                    anyhow::bail!("Fallback {} cannot reference itself", config.name);
+                }
+                if self.get(member).await.is_none() {
+                    anyhow::bail!(
+                        "Fallback {} references unknown adapter: {}",
+                        config.name,
+                        member
+                    );
+                }
+            }
+            if chain.is_empty() {
+                anyhow::bail!("Fallback {} has an empty chain", config.name);
+            }
+
+            // 成员在调用时按名称查找，重新加载成员后 fallback 使用新实例
+            let fallback =
+                FallbackAdapter::from_registry(config.name.clone(), chain, self.adapters.clone());
+            self.register(&config.name, Arc::new(fallback)).await;
+            return Ok(());
+        }
+
         let adapter = AdapterFactory::create_adapter(config.clone())?;
 
-        let rate_limiter = AdapterFactory::create_rate_limiter(&config.metadata);
+        // 重新注册时沿用原有限流器和并发控制，已消耗的额度与进行中的请求不受影响；
+        // 与配置请求的后端比较，后端变化时才新建
+        let requested = AdapterFactory::backend_kind(&config.metadata, "rate_limit_backend");
+        let (rate_limiter, reuse_limiter) = match self.get_rate_limiter(&config.name) {
+            Some(rate_limiter) if rate_limiter.backend_kind() == requested => (rate_limiter, true),
+            _ => {
+                let rate_limiter = AdapterFactory::create_rate_limiter(&config.metadata)?;
+                (rate_limiter, false)
+            }
+        };
+        let requested = AdapterFactory::backend_kind(&config.metadata, "concurrency_backend");
+        let (concurrency_guard, reuse_guard) = match self.get_concurrency_guard(&config.name) {
+            Some(guard) if guard.backend_kind() == requested => (guard, true),
+            _ => {
+                let guard =
+                    AdapterFactory::create_concurrency_guard(&config.name, &config.metadata)?;
+                (guard, false)
+            }
+        };
 
-        let concurrency_guard = AdapterFactory::create_concurrency_guard(&config.metadata);
+        let billing_tracker = AdapterFactory::create_billing_tracker(
+            &config.name,
+            &config.metadata,
+            self.pricing.clone(),
+        )?;
 
-        let billing_tracker = AdapterFactory::create_billing_tracker(&config.metadata);
-        self.billing_trackers.insert(config.name.clone(), billing_tracker.clone());
+        // 新建失败（如请求了 Redis 却没有可用地址、账单存储打不开）时已返回错误，原有配置保持不变
+        if reuse_limiter {
+            rate_limiter.update_config(AdapterFactory::rate_limit_config(&config.metadata));
+        }
+        if reuse_guard {
+            concurrency_guard.update_config(AdapterFactory::concurrency_config(&config.metadata));
+        }
+        self.rate_limiters
+            .insert(config.name.clone(), rate_limiter.clone());
+        self.concurrency_guards
+            .insert(config.name.clone(), concurrency_guard.clone());
+
+        match billing_tracker.rehydrate(&config.name).await {
+            Ok(_) => {
+                self.restore_budget_spend(&config.name, &billing_tracker)
+                    .await;
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to restore billing history for {}: {}",
+                    config.name, e
+                );
+            }
+        }
+        self.billing_trackers
+            .insert(config.name.clone(), billing_tracker.clone());
 
-        let wrapped = Arc::new(WrappedAdapter::new(
-            adapter,
-            rate_limiter,
-            billing_tracker,
-            concurrency_guard,
-        ));
+        let retry_policy = AdapterFactory::create_retry_policy(&config.metadata);
 
-        self.register(&config.name, wrapped).await;
+        let circuit_breaker =
+            AdapterFactory::create_circuit_breaker(&config.name, &config.metadata);
+        self.circuit_breakers
+            .insert(config.name.clone(), circuit_breaker.clone());
+
+        let health_monitor = AdapterFactory::create_health_monitor(&config.metadata);
+        let health_probe = AdapterFactory::health_probe(&config.metadata);
+        self.health_monitors
+            .insert(config.name.clone(), health_monitor.clone());
+
+        let mut wrapped =
+            WrappedAdapter::new(adapter, rate_limiter, billing_tracker, concurrency_guard)
+                .with_retry_policy(retry_policy)
+                .with_circuit_breaker(circuit_breaker)
+                .with_health_monitor(health_monitor)
+                .with_health_probe(health_probe)
+                .with_budget_manager(self.budget_manager.clone());
+
+        // 近似匹配默认用适配器自身生成向量，查询时再按名称取，不依赖注册顺序
+        match AdapterFactory::cache_config(&config.metadata) {
+            Some(cache_config) => {
+                let embedding_adapter = config
+                    .metadata
+                    .get("cache_embedding_adapter")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(&config.name)
+                    .to_string();
+                let cache = match &self.cache_store {
+                    Some(store) => {
+                        ResponseCache::with_store(config.name.clone(), cache_config, store.clone())
+                    }
+                    None => ResponseCache::new(config.name.clone(), cache_config),
+                }
+                .with_registry_embedder(self.embedding_adapters.clone(), embedding_adapter);
+                let cache = Arc::new(cache);
+                self.response_caches
+                    .insert(config.name.clone(), cache.clone());
+                wrapped = wrapped.with_response_cache(cache);
+            }
+            None => {
+                self.response_caches.remove(&config.name);
+            }
+        }
+        let wrapped = Arc::new(wrapped);
+
+        self.register(&config.name, wrapped.clone()).await;
+        // 向量接口与对话共用同一套限流、并发控制与计费
+        if wrapped.as_embedding().is_some() {
+            self.embedding_adapters.insert(config.name.clone(), wrapped);
+        }
 
         Ok(())
     }
 
     pub async fn register_from_configs(&self, configs: Vec<AdapterConfig>) -> anyhow::Result<()> {
-        for config in configs {
+        // fallback 依赖其成员，放在最后注册
+        let (fallbacks, configs): (Vec<_>, Vec<_>) = configs
+            .into_iter()
+            .partition(|c| FallbackAdapter::chain_from_config(c).is_some());
+
+        for config in configs.into_iter().chain(fallbacks) {
             if let Err(e) = self.register_from_config(config).await {
                 error!("Failed to register adapter: {}", e);
             }
@@ -79,13 +238,189 @@ impl AdapterRegistry {
         let removed = adapters.remove(name).is_some();
         if removed {
             self.billing_trackers.remove(name);
+            self.circuit_breakers.remove(name);
+            self.concurrency_guards.remove(name);
+            self.embedding_adapters.remove(name);
+            self.health_monitors.remove(name);
+            self.rate_limiters.remove(name);
+            self.response_caches.remove(name);
         }
         removed
     }
 
+    pub fn get_embedding_adapter(&self, name: &str) -> Option<Arc<dyn EmbeddingAdapter>> {
+        self.embedding_adapters.get(name).map(|e| e.value().clone())
+    }
+
+    /// 支持生成向量的适配器名称
+    pub fn list_embedding_adapters(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .embedding_adapters
+            .iter()
+            .map(|e| e.key().clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    pub fn get_response_cache(&self, name: &str) -> Option<Arc<ResponseCache>> {
+        self.response_caches.get(name).map(|e| e.value().clone())
+    }
+
     pub fn get_billing_tracker(&self, name: &str) -> Option<Arc<BillingTracker>> {
         self.billing_trackers.get(name).map(|e| e.value().clone())
     }
+
+    /// 适配器名称及其计费器，`adapter` 为 None 时包含所有适配器
+    pub fn billing_trackers(&self, adapter: Option<&str>) -> Vec<(String, Arc<BillingTracker>)> {
+        let mut trackers: Vec<_> = self
+            .billing_trackers
+            .iter()
+            .filter(|e| adapter.is_none_or(|name| name == e.key()))
+            .map(|e| (e.key().clone(), e.value().clone()))
+            .collect();
+        trackers.sort_by(|a, b| a.0.cmp(&b.0));
+        trackers
+    }
+
+    /// 各适配器在 `[from, to)` 内的账单记录，按时间排序；`adapter` 为 None 时包含所有适配器
+    pub async fn usage_records(
+        &self,
+        adapter: Option<&str>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<Vec<UsageRecord>> {
+        let mut records = Vec::new();
+        for (name, tracker) in self.billing_trackers(adapter) {
+            records.extend(tracker.records(&name, from, to).await?);
+        }
+        records.sort_by_key(|record| record.timestamp);
+        Ok(records)
+    }
+
+    pub async fn billing_report(&self, query: &ReportQuery) -> anyhow::Result<Vec<ReportBucket>> {
+        let records = self
+            .usage_records(query.adapter.as_deref(), query.from, query.to)
+            .await?;
+        Ok(aggregate(&records, query.granularity, query.group_by))
+    }
+
+    /// 所有适配器共用的预算
+    pub fn budget_manager(&self) -> Arc<BudgetManager> {
+        self.budget_manager.clone()
+    }
+
+    /// 替换预算定义，新增的预算从各适配器的账单记录补上当前周期已有的用量
+    pub async fn set_budgets(&self, budgets: Vec<Budget>) {
+        self.budget_manager.set_budgets(budgets);
+        let trackers: Vec<_> = self
+            .billing_trackers
+            .iter()
+            .map(|e| (e.key().clone(), e.value().clone()))
+            .collect();
+        for (name, billing_tracker) in trackers {
+            self.restore_budget_spend(&name, &billing_tracker).await;
+        }
+    }
+
+    /// 用账单记录重建当前周期的预算用量，避免预算随进程重启或后加的预算从零开始
+    async fn restore_budget_spend(&self, name: &str, billing_tracker: &BillingTracker) {
+        if !self.budget_manager.needs_restore(name) {
+            return;
+        }
+        let from = BudgetPeriod::Monthly.start(Utc::now());
+        match billing_tracker.records(name, Some(from), None).await {
+            Ok(records) => {
+                self.budget_manager.restore(name, &records);
+            }
+            Err(e) => warn!("Failed to restore budget spend for {}: {}", name, e),
+        }
+    }
+
+    /// 所有适配器共用的价格目录
+    pub fn pricing(&self) -> Arc<PricingCatalog> {
+        self.pricing.clone()
+    }
+
+    pub fn get_circuit_breaker(&self, name: &str) -> Option<Arc<CircuitBreaker>> {
+        self.circuit_breakers.get(name).map(|e| e.value().clone())
+    }
+
+    /// 各适配器的熔断状态，未配置熔断器的适配器不在其中
+    pub fn circuit_states(&self) -> HashMap<String, CircuitState> {
+        self.circuit_breakers
+            .iter()
+            .map(|e| (e.key().clone(), e.value().state()))
+            .collect()
+    }
+
+    pub fn get_concurrency_guard(&self, name: &str) -> Option<Arc<ConcurrencyGuard>> {
+        self.concurrency_guards.get(name).map(|e| e.value().clone())
+    }
+
+    /// 运行时调整适配器的并发上限；适配器不存在时返回 false
+    pub fn update_concurrency(&self, name: &str, config: ConcurrencyConfig) -> bool {
+        match self.get_concurrency_guard(name) {
+            Some(guard) => {
+                guard.update_config(config);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 各适配器的并发统计
+    pub fn concurrency_stats(&self) -> HashMap<String, ConcurrencyStats> {
+        self.concurrency_guards
+            .iter()
+            .map(|e| (e.key().clone(), e.value().stats()))
+            .collect()
+    }
+
+    pub fn get_rate_limiter(&self, name: &str) -> Option<Arc<RateLimiter>> {
+        self.rate_limiters.get(name).map(|e| e.value().clone())
+    }
+
+    /// 运行时调整适配器的限额，无需重新注册；适配器不存在时返回 false
+    pub fn update_rate_limit(&self, name: &str, config: RateLimitConfig) -> bool {
+        match self.get_rate_limiter(name) {
+            Some(rate_limiter) => {
+                rate_limiter.update_config(config);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn get_health_monitor(&self, name: &str) -> Option<Arc<HealthMonitor>> {
+        self.health_monitors.get(name).map(|e| e.value().clone())
+    }
+
+    /// 检查适配器健康状态，`refresh` 为 true 时忽略缓存的探测结果
+    pub async fn health_report(&self, name: &str, refresh: bool) -> Option<HealthReport> {
+        let adapter = self.get(name).await?;
+        let monitor = self.get_health_monitor(name);
+        if refresh {
+            if let Some(monitor) = &monitor {
+                monitor.invalidate();
+            }
+        }
+
+        let healthy = adapter.health().await;
+        let last_probe = monitor.as_ref().and_then(|m| m.last_probe());
+
+        Some(HealthReport {
+            adapter: name.to_string(),
+            healthy,
+            probe_healthy: last_probe.map(|(healthy, _)| healthy),
+            probe_checked_at: last_probe.map(|(_, checked_at)| checked_at),
+            passive_healthy: monitor.as_ref().is_none_or(|m| m.passive_healthy()),
+            error_rate: monitor.as_ref().and_then(|m| m.error_rate()),
+            circuit: self
+                .get_circuit_breaker(name)
+                .map(|breaker| breaker.state().as_str().to_string()),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -94,6 +429,16 @@ pub struct InvokeOptions {
     pub model: Option<String>,
     pub temperature: Option<f32>,
     pub max_tokens: Option<u32>,
+    pub top_p: Option<f32>,
+    pub stop: Option<Vec<String>>,
+    pub seed: Option<u64>,
+    pub presence_penalty: Option<f32>,
+    pub frequency_penalty: Option<f32>,
+    pub n: Option<u32>,
+    /// 并发名额不足时的排队优先级
+    pub priority: Priority,
+    /// 租户 id，用于按租户检查预算
+    pub tenant_id: Option<String>,
     pub metadata: std::collections::HashMap<String, serde_json::Value>,
 }
 
@@ -102,9 +447,48 @@ pub trait Adapter: Send + Sync {
     fn name(&self) -> &str;
     async fn describe(&self) -> String;
     async fn invoke(&self, prompt: &str) -> anyhow::Result<String>;
-    async fn invoke_with_options(&self, prompt: &str, options: &InvokeOptions) -> anyhow::Result<String> {
+    async fn invoke_with_options(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<String> {
         let _ = options;
         self.invoke(prompt).await
     }
+    async fn invoke_stream(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let content = self.invoke_with_options(prompt, options).await?;
+        Ok(single_chunk_stream(content))
+    }
+    async fn chat(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<InvokeResponse> {
+        request.ensure_text_only(self.name())?;
+        let content = self
+            .invoke_with_options(&request.to_prompt(), options)
+            .await?;
+        Ok(InvokeResponse::new(content))
+    }
+    async fn chat_stream(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        request.ensure_text_only(self.name())?;
+        self.invoke_stream(&request.to_prompt(), options).await
+    }
     async fn health(&self) -> bool;
+    /// 列出提供商当前可用的模型
+    async fn list_models(&self) -> anyhow::Result<Vec<String>> {
+        anyhow::bail!("Adapter {} does not support model listing", self.name())
+    }
+    /// 支持生成向量的适配器返回自身
+    fn as_embedding(&self) -> Option<&dyn EmbeddingAdapter> {
+        None
+    }
 }
@@ -0,0 +1,144 @@
+use crate::error::AdapterError;
+use std::future::Future;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+#[derive(Clone, Debug)]
+pub struct RetryConfig {
+    /// 总尝试次数，包含首次调用
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: bool,
+    /// 限流错误带有 Retry-After 时按其等待（不超过 max_delay）
+    pub respect_retry_after: bool,
+    /// 可重试的错误类别，取值为 [`AdapterError::code`]
+    pub retry_on: Vec<String>,
+    pub enabled: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+            respect_retry_after: true,
+            retry_on: ["rate_limited", "upstream_error", "timeout", "network_error"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            enabled: true,
+        }
+    }
+}
+
+pub struct RetryPolicy {
+    config: RetryConfig,
+}
+
+impl RetryPolicy {
+    pub fn new(config: RetryConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn disabled() -> Self {
+        Self::new(RetryConfig {
+            enabled: false,
+            ..Default::default()
+        })
+    }
+
+    pub fn config(&self) -> &RetryConfig {
+        &self.config
+    }
+
+    /// 第 `attempt` 次（从 1 开始）失败后是否重试，返回等待时间
+    pub fn retry_delay(&self, attempt: u32, err: &anyhow::Error) -> Option<Duration> {
+        if !self.config.enabled || attempt >= self.config.max_attempts {
+            return None;
+        }
+
+        // 未分类的错误不重试
+        let adapter_error = err.downcast_ref::<AdapterError>()?;
+        let code = adapter_error.code();
+        if !self.config.retry_on.iter().any(|c| c == code) {
+            return None;
+        }
+
+        if self.config.respect_retry_after {
+            if let Some(after) = adapter_error.retry_after() {
+                return Some(after.min(self.config.max_delay));
+            }
+        }
+
+        Some(self.backoff(attempt))
+    }
+
+    /// 指数退避，开启 jitter 时在 [delay/2, delay] 内随机
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exp = attempt.saturating_sub(1).min(16);
+        let delay = self
+            .config
+            .base_delay
+            .saturating_mul(1 << exp)
+            .min(self.config.max_delay);
+
+        if self.config.jitter && !delay.is_zero() {
+            let millis = delay.as_millis() as u64;
+            Duration::from_millis(rand::random_range(millis / 2..=millis))
+        } else {
+            delay
+        }
+    }
+
+    /// 按策略执行 `call`，返回最终结果与实际尝试次数
+    pub async fn run<T, F, Fut>(&self, adapter_name: &str, mut call: F) -> (anyhow::Result<T>, u32)
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            debug!(adapter = adapter_name, attempt, "Invoking adapter");
+
+            let err = match call().await {
+                Ok(value) => return (Ok(value), attempt),
+                Err(e) => e,
+            };
+
+            match self.retry_delay(attempt, &err) {
+                Some(delay) => {
+                    warn!(
+                        adapter = adapter_name,
+                        attempt,
+                        max_attempts = self.config.max_attempts,
+                        delay_ms = delay.as_millis() as u64,
+                        error = %err,
+                        "Adapter call failed, retrying"
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                None => {
+                    if attempt > 1 {
+                        warn!(
+                            adapter = adapter_name,
+                            attempt,
+                            error = %err,
+                            "Adapter call failed, giving up"
+                        );
+                    }
+                    return (Err(err), attempt);
+                }
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(RetryConfig::default())
+    }
+}
@@ -0,0 +1,293 @@
+use crate::error::AdapterError;
+use crate::response::{OpenAIUsage, Usage};
+use futures::stream::{self, Stream};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::pin::Pin;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StreamChunk {
+    pub delta: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+}
+
+impl StreamChunk {
+    pub fn text(delta: impl Into<String>) -> Self {
+        Self {
+            delta: delta.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn finished(finish_reason: impl Into<String>) -> Self {
+        Self {
+            finish_reason: Some(finish_reason.into()),
+            ..Default::default()
+        }
+    }
+}
+
+pub type ChatStream = Pin<Box<dyn Stream<Item = anyhow::Result<StreamChunk>> + Send>>;
+
+pub fn single_chunk_stream(content: String) -> ChatStream {
+    Box::pin(stream::iter(vec![Ok(StreamChunk {
+        delta: content,
+        finish_reason: Some("stop".to_string()),
+        usage: None,
+    })]))
+}
+
+/// 增量解析 Server-Sent Events，按字节缓冲以免多字节字符被网络分片截断
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    buffer: Vec<u8>,
+    data: Vec<String>,
+}
+
+impl SseDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 追加一段原始字节，返回其中已完整的事件的 data 内容
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(bytes);
+
+        let mut events = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+            self.process_line(line, &mut events);
+        }
+        events
+    }
+
+    /// 连接结束时调用，返回尚未以空行结尾的最后一个事件
+    pub fn finish(&mut self) -> Vec<String> {
+        let mut events = Vec::new();
+        if !self.buffer.is_empty() {
+            let rest = std::mem::take(&mut self.buffer);
+            let line = String::from_utf8_lossy(&rest)
+                .trim_end_matches('\r')
+                .to_string();
+            self.process_line(&line, &mut events);
+        }
+        self.dispatch(&mut events);
+        events
+    }
+
+    fn process_line(&mut self, line: &str, events: &mut Vec<String>) {
+        if line.is_empty() {
+            self.dispatch(events);
+        } else if let Some(value) = line.strip_prefix("data:") {
+            self.data
+                .push(value.strip_prefix(' ').unwrap_or(value).to_string());
+        }
+        // 注释行（以 ':' 开头）以及 event/id/retry 字段不影响 data 内容
+    }
+
+    fn dispatch(&mut self, events: &mut Vec<String>) {
+        if !self.data.is_empty() {
+            events.push(self.data.join("\n"));
+            self.data.clear();
+        }
+    }
+}
+
+/// 按行解析 NDJSON（如 Ollama 的流式响应），每个非空行为一个事件
+#[derive(Debug, Default)]
+pub struct NdjsonDecoder {
+    buffer: Vec<u8>,
+}
+
+impl NdjsonDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(bytes);
+
+        let mut events = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            push_line(&line, &mut events);
+        }
+        events
+    }
+
+    pub fn finish(&mut self) -> Vec<String> {
+        let mut events = Vec::new();
+        let rest = std::mem::take(&mut self.buffer);
+        push_line(&rest, &mut events);
+        events
+    }
+}
+
+fn push_line(line: &[u8], events: &mut Vec<String>) {
+    let line = String::from_utf8_lossy(line);
+    let line = line.trim();
+    if !line.is_empty() {
+        events.push(line.to_string());
+    }
+}
+
+trait EventDecoder: Send + 'static {
+    fn feed(&mut self, bytes: &[u8]) -> Vec<String>;
+    fn finish(&mut self) -> Vec<String>;
+}
+
+impl EventDecoder for SseDecoder {
+    fn feed(&mut self, bytes: &[u8]) -> Vec<String> {
+        SseDecoder::feed(self, bytes)
+    }
+
+    fn finish(&mut self) -> Vec<String> {
+        SseDecoder::finish(self)
+    }
+}
+
+impl EventDecoder for NdjsonDecoder {
+    fn feed(&mut self, bytes: &[u8]) -> Vec<String> {
+        NdjsonDecoder::feed(self, bytes)
+    }
+
+    fn finish(&mut self) -> Vec<String> {
+        NdjsonDecoder::finish(self)
+    }
+}
+
+struct DecodeState<D, F> {
+    response: reqwest::Response,
+    decoder: D,
+    pending: VecDeque<String>,
+    finished: bool,
+    parse: F,
+}
+
+/// 将 SSE 响应转换为 [`ChatStream`]，`parse` 返回 `Ok(None)` 表示跳过该事件
+pub fn sse_stream<F>(response: reqwest::Response, parse: F) -> ChatStream
+where
+    F: Fn(&str) -> anyhow::Result<Option<StreamChunk>> + Send + 'static,
+{
+    decode_stream(response, SseDecoder::new(), parse)
+}
+
+/// 将 NDJSON 响应转换为 [`ChatStream`]，`parse` 的约定与 [`sse_stream`] 相同
+pub fn ndjson_stream<F>(response: reqwest::Response, parse: F) -> ChatStream
+where
+    F: Fn(&str) -> anyhow::Result<Option<StreamChunk>> + Send + 'static,
+{
+    decode_stream(response, NdjsonDecoder::new(), parse)
+}
+
+fn decode_stream<D, F>(response: reqwest::Response, decoder: D, parse: F) -> ChatStream
+where
+    D: EventDecoder,
+    F: Fn(&str) -> anyhow::Result<Option<StreamChunk>> + Send + 'static,
+{
+    let state = DecodeState {
+        response,
+        decoder,
+        pending: VecDeque::new(),
+        finished: false,
+        parse,
+    };
+
+    Box::pin(stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(data) = state.pending.pop_front() {
+                match (state.parse)(&data) {
+                    Ok(Some(chunk)) => return Some((Ok(chunk), state)),
+                    Ok(None) => continue,
+                    Err(e) => {
+                        state.finished = true;
+                        state.pending.clear();
+                        return Some((Err(e), state));
+                    }
+                }
+            }
+
+            if state.finished {
+                return None;
+            }
+
+            match state.response.chunk().await {
+                Ok(Some(bytes)) => {
+                    let events = state.decoder.feed(&bytes);
+                    state.pending.extend(events);
+                }
+                Ok(None) => {
+                    state.finished = true;
+                    let events = state.decoder.finish();
+                    state.pending.extend(events);
+                }
+                Err(e) => {
+                    state.finished = true;
+                    return Some((Err(AdapterError::from(e).into()), state));
+                }
+            }
+        }
+    }))
+}
+
+#[derive(Serialize)]
+pub(crate) struct StreamOptions {
+    pub include_usage: bool,
+}
+
+#[derive(Deserialize)]
+struct OpenAIStreamChunk {
+    #[serde(default)]
+    choices: Vec<OpenAIStreamChoice>,
+    #[serde(default)]
+    usage: Option<OpenAIUsage>,
+}
+
+#[derive(Deserialize)]
+struct OpenAIStreamChoice {
+    #[serde(default)]
+    delta: Option<OpenAIStreamDelta>,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OpenAIStreamDelta {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+/// 解析 OpenAI 兼容接口（OpenAI、DeepSeek、智谱、豆包）的流式分片
+pub fn parse_openai_chunk(data: &str) -> anyhow::Result<Option<StreamChunk>> {
+    let data = data.trim();
+    if data.is_empty() || data == "[DONE]" {
+        return Ok(None);
+    }
+
+    let chunk: OpenAIStreamChunk = serde_json::from_str(data)
+        .map_err(|e| AdapterError::Decode(format!("Failed to parse stream chunk: {}", e)))?;
+
+    let choice = chunk.choices.into_iter().next();
+    if choice.is_none() && chunk.usage.is_none() {
+        return Ok(None);
+    }
+
+    let (delta, finish_reason) = match choice {
+        Some(choice) => (
+            choice.delta.and_then(|d| d.content).unwrap_or_default(),
+            choice.finish_reason,
+        ),
+        None => (String::new(), None),
+    };
+
+    Ok(Some(StreamChunk {
+        delta,
+        finish_reason,
+        usage: chunk.usage.map(Usage::from),
+    }))
+}
@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// JSON Schema 描述的参数
+    pub parameters: Value,
+}
+
+impl ToolDefinition {
+    pub fn new(name: impl Into<String>, description: impl Into<String>, parameters: Value) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            parameters,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    /// 模型生成的 JSON 参数字符串，可能不是合法 JSON
+    pub arguments: String,
+}
+
+impl ToolCall {
+    pub fn new(
+        id: impl Into<String>,
+        name: impl Into<String>,
+        arguments: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            arguments: arguments.into(),
+        }
+    }
+
+    pub fn parse_arguments(&self) -> anyhow::Result<Value> {
+        if self.arguments.trim().is_empty() {
+            return Ok(Value::Object(Default::default()));
+        }
+        serde_json::from_str(&self.arguments)
+            .map_err(|e| anyhow::anyhow!("Invalid arguments for tool {}: {}", self.name, e))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolChoice {
+    Auto,
+    None,
+    Required,
+    Tool(String),
+}
+
+#[derive(Serialize)]
+pub(crate) struct OpenAITool {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    function: OpenAIFunctionDefinition,
+}
+
+#[derive(Serialize)]
+struct OpenAIFunctionDefinition {
+    name: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    description: String,
+    parameters: Value,
+}
+
+impl From<&ToolDefinition> for OpenAITool {
+    fn from(tool: &ToolDefinition) -> Self {
+        Self {
+            kind: "function",
+            function: OpenAIFunctionDefinition {
+                name: tool.name.clone(),
+                description: tool.description.clone(),
+                parameters: tool.parameters.clone(),
+            },
+        }
+    }
+}
+
+/// OpenAI 兼容格式的工具调用，千问与智谱的接口也使用同样的结构
+#[derive(Serialize, Deserialize)]
+pub(crate) struct OpenAIToolCall {
+    #[serde(default)]
+    id: String,
+    #[serde(rename = "type", default = "default_tool_type")]
+    kind: String,
+    function: OpenAIFunctionCall,
+}
+
+#[derive(Serialize, Deserialize)]
+struct OpenAIFunctionCall {
+    name: String,
+    // 个别厂商直接返回 JSON 对象而不是字符串
+    #[serde(default)]
+    arguments: Value,
+}
+
+fn default_tool_type() -> String {
+    "function".to_string()
+}
+
+impl From<&ToolCall> for OpenAIToolCall {
+    fn from(call: &ToolCall) -> Self {
+        Self {
+            id: call.id.clone(),
+            kind: default_tool_type(),
+            function: OpenAIFunctionCall {
+                name: call.name.clone(),
+                arguments: Value::String(call.arguments.clone()),
+            },
+        }
+    }
+}
+
+impl From<OpenAIToolCall> for ToolCall {
+    fn from(call: OpenAIToolCall) -> Self {
+        let arguments = match call.function.arguments {
+            Value::String(s) => s,
+            Value::Null => String::new(),
+            other => other.to_string(),
+        };
+        Self {
+            id: call.id,
+            name: call.function.name,
+            arguments,
+        }
+    }
+}
+
+pub(crate) fn tool_choice_value(choice: &ToolChoice) -> Value {
+    match choice {
+        ToolChoice::Auto => Value::from("auto"),
+        ToolChoice::None => Value::from("none"),
+        ToolChoice::Required => Value::from("required"),
+        ToolChoice::Tool(name) => serde_json::json!({
+            "type": "function",
+            "function": {"name": name},
+        }),
+    }
+}
@@ -1,9 +1,26 @@
-use crate::billing::BillingTracker;
-use crate::guard::ConcurrencyGuard;
+use crate::adaptive::is_overload;
+use crate::billing::{BillingTracker, UsageRecord};
+use crate::budget::{BudgetManager, BudgetReservation, BudgetSubject};
+use crate::cache::{CacheHit, CacheLookup, ResponseCache};
+use crate::chat::{ChatRequest, ContentPart, MediaSource};
+use crate::circuit_breaker::{
+    is_failure, CircuitBreaker, CircuitBreakerConfig, CircuitGuard, CircuitState,
+};
+use crate::embedding::{EmbeddingAdapter, EmbeddingResponse};
+use crate::error::AdapterError;
+use crate::guard::{ConcurrencyError, ConcurrencyGuard, ConcurrencyPermit, Priority};
+use crate::health::{HealthMonitor, HealthProbe};
 use crate::rate_limit::RateLimiter;
 use crate::registry::{Adapter, InvokeOptions};
+use crate::response::{InvokeResponse, Usage};
+use crate::retry::RetryPolicy;
+use crate::stream::ChatStream;
 use async_trait::async_trait;
-use std::sync::Arc;
+use chrono::Utc;
+use futures::StreamExt;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tracing::{error, warn};
 use uuid::Uuid;
 
@@ -12,6 +29,12 @@ pub struct WrappedAdapter {
     rate_limiter: Arc<RateLimiter>,
     billing_tracker: Arc<BillingTracker>,
     concurrency_guard: Arc<ConcurrencyGuard>,
+    retry_policy: Arc<RetryPolicy>,
+    circuit_breaker: Arc<CircuitBreaker>,
+    health_monitor: Arc<HealthMonitor>,
+    health_probe: HealthProbe,
+    budget_manager: Option<Arc<BudgetManager>>,
+    response_cache: Option<Arc<ResponseCache>>,
     adapter_name: String,
 }
 
@@ -28,9 +51,218 @@ impl WrappedAdapter {
             rate_limiter,
             billing_tracker,
             concurrency_guard,
+            retry_policy: Arc::new(RetryPolicy::default()),
+            circuit_breaker: Arc::new(CircuitBreaker::new(
+                adapter_name.clone(),
+                CircuitBreakerConfig::default(),
+            )),
+            health_monitor: Arc::new(HealthMonitor::default()),
+            health_probe: HealthProbe::default(),
+            budget_manager: None,
+            response_cache: None,
             adapter_name,
         }
     }
+
+    pub fn with_retry_policy(mut self, retry_policy: Arc<RetryPolicy>) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn with_circuit_breaker(mut self, circuit_breaker: Arc<CircuitBreaker>) -> Self {
+        self.circuit_breaker = circuit_breaker;
+        self
+    }
+
+    pub fn circuit_breaker(&self) -> Arc<CircuitBreaker> {
+        self.circuit_breaker.clone()
+    }
+
+    pub fn with_health_monitor(mut self, health_monitor: Arc<HealthMonitor>) -> Self {
+        self.health_monitor = health_monitor;
+        self
+    }
+
+    pub fn health_monitor(&self) -> Arc<HealthMonitor> {
+        self.health_monitor.clone()
+    }
+
+    /// `Completion` 探测经由本层发送，与正常调用一样限流、计入预算与计费
+    pub fn with_health_probe(mut self, health_probe: HealthProbe) -> Self {
+        self.health_probe = health_probe;
+        self
+    }
+
+    async fn completion_probe(&self) -> bool {
+        let request = ChatRequest::from_prompt("ping");
+        match self
+            .chat(&request, &HealthProbe::completion_options())
+            .await
+        {
+            Ok(_) => true,
+            Err(e) => {
+                warn!(adapter = %self.adapter_name, error = %e, "Health probe failed");
+                false
+            }
+        }
+    }
+
+    pub fn with_budget_manager(mut self, budget_manager: Arc<BudgetManager>) -> Self {
+        self.budget_manager = Some(budget_manager);
+        self
+    }
+
+    /// 只缓存非流式对话的成功响应
+    pub fn with_response_cache(mut self, response_cache: Arc<ResponseCache>) -> Self {
+        self.response_cache = Some(response_cache);
+        self
+    }
+
+    pub fn response_cache(&self) -> Option<Arc<ResponseCache>> {
+        self.response_cache.clone()
+    }
+
+    /// 缓存命中不占用限流与预算，按原响应的用量记一条零费用账单
+    async fn serve_cached(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+        hit: CacheHit,
+    ) -> InvokeResponse {
+        let mut response = hit.response;
+        response.cached = true;
+
+        let usage = response.usage.clone().unwrap_or_else(|| {
+            Usage::new(
+                estimate_request_tokens(request),
+                estimate_tokens(&response.content),
+            )
+        });
+        let mut metadata = serde_json::json!({
+            "duration_ms": 0,
+            "success": true,
+            "cache_hit": true,
+        });
+        if let Some(similarity) = hit.similarity {
+            metadata["similarity"] = serde_json::json!(similarity);
+        }
+        if let Some(tenant_id) = &options.tenant_id {
+            metadata["tenant_id"] = serde_json::json!(tenant_id);
+        }
+
+        self.billing_tracker
+            .record_cache_hit(
+                self.adapter_name.clone(),
+                response.model.clone().or_else(|| options.model.clone()),
+                options.user_id.clone(),
+                Uuid::new_v4().to_string(),
+                &usage,
+                metadata,
+            )
+            .await;
+
+        response
+    }
+
+    fn budget_subject(&self, options: &InvokeOptions) -> BudgetSubject {
+        BudgetSubject {
+            adapter: self.adapter_name.clone(),
+            user_id: options.user_id.clone(),
+            tenant_id: options.tenant_id.clone(),
+        }
+    }
+
+    /// 按预估费用预留预算，额度不足时在占用并发许可之前拒绝
+    async fn reserve_budget(
+        &self,
+        options: &InvokeOptions,
+        input_tokens: u64,
+    ) -> anyhow::Result<Option<BudgetReservation>> {
+        let Some(budget_manager) = &self.budget_manager else {
+            return Ok(None);
+        };
+
+        let output_tokens = options.max_tokens.map_or(0, u64::from);
+        let cost = self
+            .billing_tracker
+            .price_at(&self.adapter_name, options.model.as_deref(), Utc::now())
+            .await
+            .cost(input_tokens, output_tokens, 0, 0);
+        let subject = self.budget_subject(options);
+        let reservation = budget_manager
+            .reserve(&subject, cost, input_tokens + output_tokens)
+            .map_err(|e| {
+                warn!("{}", e);
+                AdapterError::BudgetExceeded {
+                    retry_after: Some(e.retry_after()),
+                    message: e.to_string(),
+                }
+            })?;
+        Ok(Some(reservation))
+    }
+
+    /// 熔断时直接拒绝，不占用预算、并发许可和限流配额；放行的许可留给首次尝试
+    fn pass_circuit(&self) -> anyhow::Result<Mutex<Option<CircuitGuard<'_>>>> {
+        Ok(Mutex::new(Some(self.circuit_breaker.acquire()?)))
+    }
+
+    async fn admit(
+        &self,
+        user_id: Option<&str>,
+        priority: Priority,
+    ) -> anyhow::Result<ConcurrencyPermit> {
+        let permit = self
+            .concurrency_guard
+            .acquire(priority)
+            .await
+            .map_err(|e| {
+                error!("Concurrency limit exceeded: {}", e);
+                match e {
+                    ConcurrencyError::QueueFull => AdapterError::Overloaded {
+                        retry_after: Some(Duration::from_secs(1)),
+                        message: "Too many queued requests, please try again later".to_string(),
+                    },
+                    _ => AdapterError::RateLimited {
+                        retry_after: None,
+                        message: "Service busy, please try again later".to_string(),
+                    },
+                }
+            })?;
+
+        let rate_limit_key = format!("{}:{}", self.adapter_name, user_id.unwrap_or("anonymous"));
+        self.rate_limiter
+            .check(&rate_limit_key)
+            .await
+            .map_err(|e| {
+                warn!("Rate limit exceeded for {}: {}", rate_limit_key, e);
+                AdapterError::RateLimited {
+                    retry_after: Some(e.retry_after()),
+                    message: e.to_string(),
+                }
+            })?;
+
+        Ok(permit)
+    }
+
+    /// 经熔断器放行后调用，结果计入熔断与健康统计；重试时重新向熔断器申请
+    async fn guarded<T>(
+        &self,
+        circuit: &Mutex<Option<CircuitGuard<'_>>>,
+        call: impl Future<Output = anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        let first = circuit.lock().unwrap().take();
+        let guard = match first {
+            Some(guard) => guard,
+            None => self.circuit_breaker.acquire()?,
+        };
+        let start = Instant::now();
+        let result = call.await;
+        self.concurrency_guard
+            .record(start.elapsed(), is_overload(&result));
+        self.health_monitor.record(is_failure(&result));
+        guard.record(&result);
+        result
+    }
 }
 
 #[async_trait]
@@ -47,60 +279,415 @@ impl Adapter for WrappedAdapter {
     }
 
     async fn invoke(&self, prompt: &str) -> anyhow::Result<String> {
-        self.invoke_with_options(prompt, &InvokeOptions::default()).await
+        self.invoke_with_options(prompt, &InvokeOptions::default())
+            .await
+    }
+
+    async fn invoke_with_options(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<String> {
+        self.chat(&ChatRequest::from_prompt(prompt), options)
+            .await
+            .map(|response| response.content)
     }
 
-    async fn invoke_with_options(&self, prompt: &str, options: &InvokeOptions) -> anyhow::Result<String> {
+    async fn invoke_stream(
+        &self,
+        prompt: &str,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        self.chat_stream(&ChatRequest::from_prompt(prompt), options)
+            .await
+    }
+
+    async fn chat(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<InvokeResponse> {
+        let cache_miss = match &self.response_cache {
+            Some(cache) => match cache.lookup(request, options).await {
+                Some(CacheLookup::Hit(hit)) => {
+                    return Ok(self.serve_cached(request, options, hit).await)
+                }
+                Some(CacheLookup::Miss(miss)) => Some(miss),
+                None => None,
+            },
+            None => None,
+        };
+
         let request_id = Uuid::new_v4().to_string();
         let user_id = options.user_id.clone();
 
-        let _permit = self.concurrency_guard.acquire().await.map_err(|e| {
-            error!("Concurrency limit exceeded: {}", e);
-            anyhow::anyhow!("Service busy, please try again later")
-        })?;
-
-        let rate_limit_key = format!(
-            "{}:{}",
-            self.adapter_name,
-            user_id.as_deref().unwrap_or("anonymous")
-        );
-        self.rate_limiter
-            .check(&rate_limit_key)
-            .await
-            .map_err(|e| {
-                warn!("Rate limit exceeded for {}: {}", rate_limit_key, e);
-                anyhow::anyhow!("Rate limit exceeded: {}", e)
-            })?;
+        let circuit = self.pass_circuit()?;
+        let reservation = self
+            .reserve_budget(options, estimate_request_tokens(request))
+            .await?;
+        let _permit = self.admit(user_id.as_deref(), options.priority).await?;
 
+        // 许可与限流只在首次调用前检查，重试期间继续持有
         let start = std::time::Instant::now();
-        let result = self.inner.invoke_with_options(prompt, options).await;
+        let (result, attempts) = self
+            .retry_policy
+            .run(&self.adapter_name, || {
+                self.guarded(&circuit, self.inner.chat(request, options))
+            })
+            .await;
         let duration = start.elapsed();
 
-        let input_tokens = estimate_tokens(prompt);
-        let output_tokens = match &result {
-            Ok(ref output) => estimate_tokens(output),
-            Err(_) => 0,
+        // 优先使用提供商返回的真实用量，缺失时才估算
+        let (usage, usage_source) = match &result {
+            Ok(response) => match &response.usage {
+                Some(usage) => (usage.clone(), "provider"),
+                None => (
+                    Usage::new(
+                        estimate_request_tokens(request),
+                        estimate_tokens(&response.content),
+                    ),
+                    "estimated",
+                ),
+            },
+            Err(_) => (Usage::new(estimate_request_tokens(request), 0), "estimated"),
         };
 
-        self.billing_tracker
-            .record_usage(
+        // 按实际服务的模型定价，提供商未返回时使用请求指定的模型
+        let model = match &result {
+            Ok(response) => response.model.clone(),
+            Err(_) => None,
+        }
+        .or_else(|| options.model.clone());
+
+        let mut metadata = serde_json::json!({
+            "duration_ms": duration.as_millis(),
+            "success": result.is_ok(),
+            "usage_source": usage_source,
+            "attempts": attempts,
+        });
+        if let Some(tenant_id) = &options.tenant_id {
+            metadata["tenant_id"] = serde_json::json!(tenant_id);
+        }
+
+        let record = self
+            .billing_tracker
+            .record_model_usage(
                 self.adapter_name.clone(),
+                model,
                 user_id,
                 request_id,
-                input_tokens,
-                output_tokens,
-                serde_json::json!({
-                    "duration_ms": duration.as_millis(),
-                    "success": result.is_ok(),
-                }),
+                &usage,
+                metadata,
             )
             .await;
+        charge_budget(reservation, record);
+
+        if let (Some(cache), Some(miss), Ok(response)) = (&self.response_cache, cache_miss, &result)
+        {
+            cache.store(miss, response).await;
+        }
 
         result
     }
 
+    async fn chat_stream(
+        &self,
+        request: &ChatRequest,
+        options: &InvokeOptions,
+    ) -> anyhow::Result<ChatStream> {
+        let user_id = options.user_id.clone();
+
+        let circuit = self.pass_circuit()?;
+        let reservation = self
+            .reserve_budget(options, estimate_request_tokens(request))
+            .await?;
+        let permit = self.admit(user_id.as_deref(), options.priority).await?;
+
+        let mut billing = StreamBilling {
+            billing_tracker: self.billing_tracker.clone(),
+            adapter_name: self.adapter_name.clone(),
+            model: options.model.clone(),
+            user_id,
+            tenant_id: options.tenant_id.clone(),
+            request_id: Uuid::new_v4().to_string(),
+            input_tokens: estimate_request_tokens(request),
+            output: String::new(),
+            usage: None,
+            start: Instant::now(),
+            recorded: false,
+            budget: reservation,
+            _permit: permit,
+        };
+
+        // 只重试建立连接的阶段，已开始输出的流不再重试
+        let (result, _) = self
+            .retry_policy
+            .run(&self.adapter_name, || {
+                self.guarded(&circuit, self.inner.chat_stream(request, options))
+            })
+            .await;
+        let inner = match result {
+            Ok(inner) => inner,
+            Err(e) => {
+                billing.record(false).await;
+                return Err(e);
+            }
+        };
+
+        // 许可证随流一起持有，流结束（或被丢弃）后才释放并记账
+        Ok(Box::pin(futures::stream::unfold(
+            (inner, billing),
+            |(mut inner, mut billing)| async move {
+                if billing.recorded {
+                    return None;
+                }
+
+                match inner.next().await {
+                    Some(Ok(chunk)) => {
+                        billing.output.push_str(&chunk.delta);
+                        if chunk.usage.is_some() {
+                            billing.usage = chunk.usage.clone();
+                        }
+                        Some((Ok(chunk), (inner, billing)))
+                    }
+                    Some(Err(e)) => {
+                        billing.record(false).await;
+                        Some((Err(e), (inner, billing)))
+                    }
+                    None => {
+                        billing.record(true).await;
+                        None
+                    }
+                }
+            },
+        )))
+    }
+
     async fn health(&self) -> bool {
-        self.inner.health().await
+        // 熔断或近期错误率过高时不再主动探测
+        if self.circuit_breaker.state() == CircuitState::Open
+            || !self.health_monitor.passive_healthy()
+        {
+            return false;
+        }
+        self.health_monitor
+            .probe(|| async {
+                match self.health_probe {
+                    HealthProbe::Completion => self.completion_probe().await,
+                    _ => self.inner.health().await,
+                }
+            })
+            .await
+    }
+
+    async fn list_models(&self) -> anyhow::Result<Vec<String>> {
+        self.inner.list_models().await
+    }
+
+    fn as_embedding(&self) -> Option<&dyn EmbeddingAdapter> {
+        self.inner
+            .as_embedding()
+            .map(|_| self as &dyn EmbeddingAdapter)
+    }
+}
+
+#[async_trait]
+impl EmbeddingAdapter for WrappedAdapter {
+    async fn embed(
+        &self,
+        texts: &[String],
+        model: Option<&str>,
+    ) -> anyhow::Result<EmbeddingResponse> {
+        let options = InvokeOptions {
+            model: model.map(str::to_string),
+            ..Default::default()
+        };
+        self.embed_with_options(texts, &options).await
+    }
+
+    async fn embed_with_options(
+        &self,
+        texts: &[String],
+        options: &InvokeOptions,
+    ) -> anyhow::Result<EmbeddingResponse> {
+        let inner = self.inner.as_embedding().ok_or_else(|| {
+            AdapterError::InvalidRequest(format!(
+                "Adapter {} does not support embeddings",
+                self.adapter_name
+            ))
+        })?;
+        let request_id = Uuid::new_v4().to_string();
+        let user_id = options.user_id.clone();
+
+        let input_tokens = texts.iter().map(|text| estimate_tokens(text)).sum();
+        let circuit = self.pass_circuit()?;
+        let reservation = self.reserve_budget(options, input_tokens).await?;
+        let _permit = self.admit(user_id.as_deref(), options.priority).await?;
+
+        let start = Instant::now();
+        let (result, attempts) = self
+            .retry_policy
+            .run(&self.adapter_name, || {
+                self.guarded(&circuit, inner.embed_with_options(texts, options))
+            })
+            .await;
+        let duration = start.elapsed();
+
+        // 向量接口只有输入 token
+        let (usage, usage_source) = match result.as_ref().ok().and_then(|r| r.usage.clone()) {
+            Some(usage) => (usage, "provider"),
+            None => (Usage::new(input_tokens, 0), "estimated"),
+        };
+        let model = result
+            .as_ref()
+            .ok()
+            .and_then(|response| response.model.clone())
+            .or_else(|| options.model.clone());
+
+        let mut metadata = serde_json::json!({
+            "duration_ms": duration.as_millis(),
+            "success": result.is_ok(),
+            "usage_source": usage_source,
+            "attempts": attempts,
+            "embedding": true,
+            "inputs": texts.len(),
+        });
+        if let Some(tenant_id) = &options.tenant_id {
+            metadata["tenant_id"] = serde_json::json!(tenant_id);
+        }
+
+        let record = self
+            .billing_tracker
+            .record_model_usage(
+                self.adapter_name.clone(),
+                model,
+                user_id,
+                request_id,
+                &usage,
+                metadata,
+            )
+            .await;
+        charge_budget(reservation, record);
+
+        result
+    }
+}
+
+struct StreamBilling {
+    billing_tracker: Arc<BillingTracker>,
+    adapter_name: String,
+    model: Option<String>,
+    user_id: Option<String>,
+    tenant_id: Option<String>,
+    request_id: String,
+    input_tokens: u64,
+    output: String,
+    usage: Option<Usage>,
+    start: Instant,
+    recorded: bool,
+    budget: Option<BudgetReservation>,
+    _permit: ConcurrencyPermit,
+}
+
+impl StreamBilling {
+    fn usage_record(&mut self, success: bool) -> (Usage, serde_json::Value) {
+        self.recorded = true;
+
+        let (usage, usage_source) = match &self.usage {
+            Some(usage) => (usage.clone(), "provider"),
+            None => (
+                Usage::new(self.input_tokens, estimate_tokens(&self.output)),
+                "estimated",
+            ),
+        };
+
+        let mut metadata = serde_json::json!({
+            "duration_ms": self.start.elapsed().as_millis(),
+            "success": success,
+            "stream": true,
+            "usage_source": usage_source,
+        });
+        if let Some(tenant_id) = &self.tenant_id {
+            metadata["tenant_id"] = serde_json::json!(tenant_id);
+        }
+        (usage, metadata)
+    }
+
+    async fn record(&mut self, success: bool) {
+        let (usage, metadata) = self.usage_record(success);
+        let record = self
+            .billing_tracker
+            .record_model_usage(
+                self.adapter_name.clone(),
+                self.model.clone(),
+                self.user_id.clone(),
+                self.request_id.clone(),
+                &usage,
+                metadata,
+            )
+            .await;
+        charge_budget(self.budget.take(), record);
+    }
+}
+
+impl Drop for StreamBilling {
+    fn drop(&mut self) {
+        if self.recorded {
+            return;
+        }
+
+        // 调用方提前丢弃了流（例如客户端断开），按已收到的内容记账
+        let (usage, mut metadata) = self.usage_record(true);
+        metadata["cancelled"] = serde_json::json!(true);
+
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let billing_tracker = self.billing_tracker.clone();
+            let adapter_name = self.adapter_name.clone();
+            let model = self.model.clone();
+            let user_id = self.user_id.clone();
+            let request_id = self.request_id.clone();
+            let budget = self.budget.take();
+            handle.spawn(async move {
+                let record = billing_tracker
+                    .record_model_usage(adapter_name, model, user_id, request_id, &usage, metadata)
+                    .await;
+                charge_budget(budget, record);
+            });
+        }
+    }
+}
+
+/// 按实际费用结算预留的预算，没有账单记录时只释放预留
+fn charge_budget(reservation: Option<BudgetReservation>, record: Option<UsageRecord>) {
+    if let (Some(reservation), Some(record)) = (reservation, record) {
+        reservation.settle(
+            record.total_cost,
+            record.input_tokens + record.output_tokens,
+        );
+    }
+}
+
+fn estimate_request_tokens(request: &ChatRequest) -> u64 {
+    request
+        .messages
+        .iter()
+        .map(|m| {
+            estimate_tokens(&m.content) + m.parts.iter().map(estimate_part_tokens).sum::<u64>()
+        })
+        .sum()
+}
+
+/// 图片取 OpenAI 的典型值：low 精度 85，其余按 1024x1024 计 765；
+/// 音频按 16KB/s 的码率、每秒约 25 token 由数据大小推算，URL 按 30 秒计
+fn estimate_part_tokens(part: &ContentPart) -> u64 {
+    match part {
+        ContentPart::Text { text } => estimate_tokens(text),
+        ContentPart::Image { detail, .. } if detail.as_deref() == Some("low") => 85,
+        ContentPart::Image { .. } => 765,
+        ContentPart::Audio {
+            source: MediaSource::Base64 { data, .. },
+        } => data.len() as u64 * 3 / 4 * 25 / 16_000,
+        ContentPart::Audio { .. } => 750,
     }
 }
 
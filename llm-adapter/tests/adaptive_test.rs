@@ -0,0 +1,163 @@
+use llm_adapter::adaptive::AdaptiveLimit;
+use llm_adapter::guard::{ConcurrencyConfig, ConcurrencyError, ConcurrencyGuard};
+use llm_adapter::{
+    AdapterConfig, AdapterError, AdapterFactory, AdapterRegistry, AdaptiveConfig, Priority,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn adaptive(min_limit: usize, max_limit: usize) -> AdaptiveConfig {
+    AdaptiveConfig {
+        min_limit,
+        max_limit,
+        latency_threshold: Some(Duration::from_millis(100)),
+        cooldown: Duration::ZERO,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_aimd_decreases_on_congestion_and_recovers() {
+    let limit = AdaptiveLimit::new(adaptive(2, 10), 8);
+
+    assert_eq!(limit.record(Duration::from_millis(10), true, 8), Some(6));
+    assert_eq!(limit.record(Duration::from_millis(500), false, 6), Some(4));
+    assert_eq!(limit.record(Duration::from_millis(10), true, 4), Some(3));
+    assert_eq!(limit.record(Duration::from_millis(10), true, 3), Some(2));
+    // 不低于下限
+    assert_eq!(limit.record(Duration::from_millis(10), true, 2), None);
+
+    // 空闲时不扩容
+    assert_eq!(limit.record(Duration::from_millis(10), false, 0), None);
+
+    // 每轮（约 limit 次成功）加 1
+    assert_eq!(limit.record(Duration::from_millis(10), false, 2), None);
+    assert_eq!(limit.record(Duration::from_millis(10), false, 2), None);
+    assert_eq!(limit.record(Duration::from_millis(10), false, 2), Some(3));
+    for _ in 0..100 {
+        limit.record(Duration::from_millis(10), false, 10);
+    }
+    assert_eq!(limit.limit(), 10);
+}
+
+#[test]
+fn test_cooldown_limits_decrease_rate() {
+    let limit = AdaptiveLimit::new(
+        AdaptiveConfig {
+            cooldown: Duration::from_secs(60),
+            ..adaptive(1, 100)
+        },
+        20,
+    );
+
+    assert_eq!(limit.record(Duration::ZERO, true, 20), Some(15));
+    // 同一波错误只收缩一次
+    assert_eq!(limit.record(Duration::ZERO, true, 15), None);
+    assert_eq!(limit.limit(), 15);
+}
+
+#[tokio::test]
+async fn test_guard_resizes_from_recorded_calls() {
+    let guard = ConcurrencyGuard::new(ConcurrencyConfig {
+        max_concurrent: 4,
+        adaptive: Some(adaptive(1, 8)),
+        ..Default::default()
+    });
+    assert_eq!(guard.limit(), 4);
+
+    guard.record(Duration::from_millis(10), true);
+    assert_eq!(guard.limit(), 3);
+    assert_eq!(guard.available_permits(), 3);
+
+    let stats = guard.stats();
+    assert!(stats.adaptive);
+    assert_eq!(stats.limit, 3);
+
+    // 重新加载配置时保留已学到的上限
+    guard.update_config(ConcurrencyConfig {
+        max_concurrent: 4,
+        adaptive: Some(adaptive(1, 8)),
+        ..Default::default()
+    });
+    assert_eq!(guard.limit(), 3);
+
+    // 关闭自适应后恢复固定上限
+    guard.update_config(ConcurrencyConfig {
+        max_concurrent: 4,
+        ..Default::default()
+    });
+    guard.record(Duration::from_millis(10), true);
+    assert_eq!(guard.limit(), 4);
+    assert_eq!(guard.available_permits(), 4);
+}
+
+#[tokio::test]
+async fn test_queue_bound_sheds_load() {
+    let guard = Arc::new(ConcurrencyGuard::new(ConcurrencyConfig {
+        max_concurrent: 1,
+        max_queue: Some(1),
+        ..Default::default()
+    }));
+    let held = guard.acquire(Priority::Normal).await.unwrap();
+
+    let waiter = {
+        let guard = guard.clone();
+        tokio::spawn(async move { guard.acquire(Priority::Normal).await.is_ok() })
+    };
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    let result = guard.acquire(Priority::Interactive).await;
+    assert!(matches!(result, Err(ConcurrencyError::QueueFull)));
+    assert_eq!(guard.stats().shed, 1);
+
+    drop(held);
+    assert!(waiter.await.unwrap());
+}
+
+#[tokio::test]
+async fn test_shed_request_surfaces_as_overloaded() {
+    let registry = AdapterRegistry::new();
+    let config = AdapterConfig::new("mock".to_string())
+        .with_api_key("test-key".to_string())
+        .with_metadata("max_concurrent".to_string(), serde_json::json!(1))
+        .with_metadata("concurrency_max_queue".to_string(), serde_json::json!(0));
+    registry.register_from_config(config).await.unwrap();
+
+    let guard = registry.get_concurrency_guard("mock").unwrap();
+    let _held = guard.acquire(Priority::Normal).await.unwrap();
+
+    let adapter = registry.get("mock").await.unwrap();
+    let err = adapter.invoke("Hello").await.unwrap_err();
+    let err = err.downcast_ref::<AdapterError>().unwrap();
+    assert_eq!(err.code(), "overloaded");
+    assert!(err.is_retryable());
+    assert!(err.retry_after().is_some());
+}
+
+#[test]
+fn test_adaptive_config_from_metadata() {
+    let metadata = [
+        ("max_concurrent", serde_json::json!(16)),
+        ("concurrency_adaptive", serde_json::json!(true)),
+        ("concurrency_min_limit", serde_json::json!(4)),
+        ("concurrency_max_limit", serde_json::json!(64)),
+        ("concurrency_latency_threshold_ms", serde_json::json!(2000)),
+        ("concurrency_decrease_factor", serde_json::json!(0.5)),
+        ("concurrency_max_queue", serde_json::json!(32)),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value))
+    .collect();
+
+    let config = AdapterFactory::concurrency_config(&metadata);
+    assert_eq!(config.max_queue, Some(32));
+    let adaptive = config.adaptive.unwrap();
+    assert_eq!(adaptive.min_limit, 4);
+    assert_eq!(adaptive.max_limit, 64);
+    assert_eq!(adaptive.latency_threshold, Some(Duration::from_secs(2)));
+    assert_eq!(adaptive.decrease_factor, 0.5);
+
+    assert!(AdapterFactory::concurrency_config(&Default::default())
+        .adaptive
+        .is_none());
+}
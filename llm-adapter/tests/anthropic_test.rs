@@ -0,0 +1,195 @@
+mod common;
+
+use common::serve_once;
+use futures::StreamExt;
+use llm_adapter::providers::AnthropicAdapter;
+use llm_adapter::{
+    Adapter, AdapterConfig, AdapterError, AdapterFactory, ChatMessage, ChatRequest, InvokeOptions,
+    ToolCall, ToolChoice, ToolDefinition,
+};
+
+fn adapter(base_url: String) -> AnthropicAdapter {
+    AnthropicAdapter::new_with_base(
+        "sk-ant-test".to_string(),
+        "claude-test".to_string(),
+        base_url,
+    )
+}
+
+#[tokio::test]
+async fn test_chat_maps_messages_tools_and_usage() {
+    let (base_url, captured) = serve_once(
+        "200 OK",
+        "application/json",
+        concat!(
+            r#"{"id":"msg_1","type":"message","role":"assistant","model":"claude-test-20250101","#,
+            r#""content":[{"type":"text","text":"Checking."},{"type":"tool_use","id":"toolu_2","name":"weather","input":{"city":"Paris"}}],"#,
+            r#""stop_reason":"tool_use","#,
+            r#""usage":{"input_tokens":10,"output_tokens":7,"cache_creation_input_tokens":0,"cache_read_input_tokens":30}}"#
+        ),
+    )
+    .await;
+
+    let request = ChatRequest::new(vec![
+        ChatMessage::system("You are terse."),
+        ChatMessage::user("Weather in Berlin?"),
+        ChatMessage::assistant("").with_tool_calls(vec![ToolCall::new(
+            "toolu_1",
+            "weather",
+            r#"{"city":"Berlin"}"#,
+        )]),
+        ChatMessage::tool("toolu_1", "Sunny"),
+        ChatMessage::user("And Paris?"),
+    ])
+    .with_tools(vec![ToolDefinition::new(
+        "weather",
+        "Current weather",
+        serde_json::json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+    )])
+    .with_tool_choice(ToolChoice::Required);
+
+    let result = adapter(base_url)
+        .chat(&request, &InvokeOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(result.content, "Checking.");
+    assert_eq!(result.model.as_deref(), Some("claude-test-20250101"));
+    assert_eq!(result.finish_reason.as_deref(), Some("tool_calls"));
+    assert_eq!(result.tool_calls[0].name, "weather");
+    assert_eq!(
+        result.tool_calls[0].parse_arguments().unwrap(),
+        serde_json::json!({"city": "Paris"})
+    );
+    let usage = result.usage.unwrap();
+    assert_eq!(usage.prompt_tokens, 40);
+    assert_eq!(usage.cached_tokens, 30);
+    assert_eq!(usage.completion_tokens, 7);
+
+    let captured = captured.await.unwrap();
+    assert!(captured.head.starts_with("post /v1/messages "));
+    assert!(captured.head.contains("x-api-key: sk-ant-test"));
+    assert!(captured.head.contains("anthropic-version: 2023-06-01"));
+    assert!(!captured.head.contains("authorization"));
+
+    let body = captured.body;
+    assert_eq!(body["system"], "You are terse.");
+    assert_eq!(body["max_tokens"], 4096);
+    assert_eq!(body["tool_choice"], serde_json::json!({"type": "any"}));
+    assert_eq!(body["tools"][0]["input_schema"]["type"], "object");
+    let messages = body["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[1]["role"], "assistant");
+    assert_eq!(
+        messages[1]["content"][0],
+        serde_json::json!({"type": "tool_use", "id": "toolu_1", "name": "weather", "input": {"city": "Berlin"}})
+    );
+    // 工具结果与随后的用户消息合并为一条 user 消息
+    assert_eq!(messages[2]["role"], "user");
+    assert_eq!(
+        messages[2]["content"][0],
+        serde_json::json!({"type": "tool_result", "tool_use_id": "toolu_1", "content": "Sunny"})
+    );
+    assert_eq!(messages[2]["content"][1]["text"], "And Paris?");
+}
+
+#[tokio::test]
+async fn test_chat_stream_combines_usage() {
+    let (base_url, captured) = serve_once(
+        "200 OK",
+        "text/event-stream",
+        concat!(
+            "event: message_start\n",
+            r#"data: {"type":"message_start","message":{"id":"msg_1","model":"claude-test","usage":{"input_tokens":12,"output_tokens":1}}}"#,
+            "\n\n",
+            "event: content_block_start\n",
+            r#"data: {"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#,
+            "\n\n",
+            "event: ping\n",
+            r#"data: {"type":"ping"}"#,
+            "\n\n",
+            "event: content_block_delta\n",
+            r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hel"}}"#,
+            "\n\n",
+            "event: content_block_delta\n",
+            r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"lo"}}"#,
+            "\n\n",
+            "event: message_delta\n",
+            r#"data: {"type":"message_delta","delta":{"stop_reason":"max_tokens"},"usage":{"output_tokens":5}}"#,
+            "\n\n",
+            "event: message_stop\n",
+            r#"data: {"type":"message_stop"}"#,
+            "\n\n"
+        ),
+    )
+    .await;
+
+    let options = InvokeOptions {
+        max_tokens: Some(5),
+        ..Default::default()
+    };
+    let chunks: Vec<_> = adapter(base_url)
+        .invoke_stream("Hi", &options)
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+
+    let text: String = chunks.iter().map(|c| c.delta.as_str()).collect();
+    assert_eq!(text, "Hello");
+    let last = chunks.last().unwrap();
+    assert_eq!(last.finish_reason.as_deref(), Some("length"));
+    let usage = last.usage.clone().unwrap();
+    assert_eq!((usage.prompt_tokens, usage.completion_tokens), (12, 5));
+
+    let body = captured.await.unwrap().body;
+    assert_eq!(body["stream"], true);
+    assert_eq!(body["max_tokens"], 5);
+}
+
+#[tokio::test]
+async fn test_errors_are_classified() {
+    let (base_url, _) = serve_once(
+        "529 Overloaded",
+        "application/json",
+        r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#,
+    )
+    .await;
+    let err = adapter(base_url).invoke("Hi").await.unwrap_err();
+    let err = err.downcast_ref::<AdapterError>().unwrap();
+    assert!(err.is_retryable());
+    assert!(err.message().contains("Overloaded"));
+
+    // 流中途返回的错误事件
+    let (base_url, _) = serve_once(
+        "200 OK",
+        "text/event-stream",
+        concat!(
+            "event: error\n",
+            r#"data: {"type":"error","error":{"type":"rate_limit_error","message":"Slow down"}}"#,
+            "\n\n"
+        ),
+    )
+    .await;
+    let mut stream = adapter(base_url)
+        .invoke_stream("Hi", &InvokeOptions::default())
+        .await
+        .unwrap();
+    let err = stream.next().await.unwrap().unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<AdapterError>().unwrap().code(),
+        "rate_limited"
+    );
+}
+
+#[tokio::test]
+async fn test_factory_creates_anthropic_adapter() {
+    let adapter = AdapterFactory::create_adapter(
+        AdapterConfig::new("anthropic".to_string()).with_api_key("sk-ant-test".to_string()),
+    )
+    .unwrap();
+    assert_eq!(adapter.name(), "anthropic");
+    assert!(adapter.describe().await.contains("claude-sonnet-4-5"));
+}
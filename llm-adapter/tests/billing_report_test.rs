@@ -0,0 +1,186 @@
+mod common;
+
+use chrono::{DateTime, TimeZone, Utc};
+use common::wrap;
+use futures::TryStreamExt;
+use llm_adapter::billing::{BillingConfig, UsageRecord};
+use llm_adapter::billing_report::{aggregate, csv_row, CSV_HEADER};
+use llm_adapter::providers::MockAdapter;
+use llm_adapter::{
+    Adapter, BillingStore, InvokeOptions, JsonlBillingStore, ReportGranularity, ReportGroupBy,
+    SqliteBillingStore,
+};
+use std::sync::Arc;
+
+fn at(day: u32, hour: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 3, day, hour, 15, 0).unwrap()
+}
+
+fn record(
+    adapter: &str,
+    model: &str,
+    tenant: Option<&str>,
+    timestamp: DateTime<Utc>,
+) -> UsageRecord {
+    let mut metadata = serde_json::json!({});
+    if let Some(tenant) = tenant {
+        metadata["tenant_id"] = serde_json::json!(tenant);
+    }
+    UsageRecord {
+        adapter_name: adapter.to_string(),
+        user_id: Some("alice".to_string()),
+        request_id: uuid::Uuid::new_v4().to_string(),
+        model: Some(model.to_string()),
+        input_tokens: 100,
+        output_tokens: 50,
+        cached_tokens: 0,
+        total_cost: 0.5,
+        currency: "USD".to_string(),
+        timestamp,
+        metadata,
+    }
+}
+
+#[test]
+fn test_aggregate_by_granularity_and_group() {
+    let records = vec![
+        record("openai", "gpt-4o", Some("acme"), at(1, 9)),
+        record("openai", "gpt-4o-mini", Some("acme"), at(1, 9)),
+        record("openai", "gpt-4o", None, at(1, 10)),
+        record("claude", "sonnet", Some("globex"), at(2, 9)),
+    ];
+
+    let hourly = aggregate(&records, ReportGranularity::Hour, ReportGroupBy::Adapter);
+    assert_eq!(hourly.len(), 3);
+    assert_eq!(
+        hourly[0].period_start,
+        Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap()
+    );
+    assert_eq!(hourly[0].requests, 2);
+    assert_eq!(hourly[0].input_tokens, 200);
+
+    let daily = aggregate(&records, ReportGranularity::Day, ReportGroupBy::Model);
+    let keys: Vec<_> = daily.iter().map(|b| b.key.as_deref()).collect();
+    assert_eq!(
+        keys,
+        vec![Some("gpt-4o"), Some("gpt-4o-mini"), Some("sonnet")]
+    );
+    assert_eq!(daily[0].requests, 2);
+
+    let monthly = aggregate(&records, ReportGranularity::Month, ReportGroupBy::Tenant);
+    assert_eq!(monthly.len(), 3);
+    // 未带租户的请求单独成组
+    assert_eq!(monthly[0].key, None);
+    assert_eq!(monthly[1].key.as_deref(), Some("acme"));
+    assert!((monthly[1].total_cost - 1.0).abs() < 1e-9);
+    assert_eq!(
+        monthly[1].period_start,
+        Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap()
+    );
+}
+
+#[test]
+fn test_csv_row_escapes_fields() {
+    let mut record = record("openai", "gpt-4o", Some("acme, inc"), at(1, 9));
+    record.user_id = Some("say \"hi\"".to_string());
+
+    let row = csv_row(&record);
+    assert!(row.starts_with("2025-03-01T09:15:00+00:00,openai,gpt-4o,"));
+    assert!(row.contains(",\"say \"\"hi\"\"\",\"acme, inc\","));
+    assert!(row.ends_with(",0.5,USD\n"));
+
+    record.user_id = Some("=HYPERLINK(\"http://evil\")".to_string());
+    record.metadata["tenant_id"] = serde_json::json!("@acme");
+    let row = csv_row(&record);
+    assert!(row.contains(",\"'=HYPERLINK(\"\"http://evil\"\")\",'@acme,"));
+    assert!(CSV_HEADER.starts_with("timestamp,adapter,model,user_id,tenant_id,"));
+}
+
+#[tokio::test]
+async fn test_sqlite_store_queries_time_range() {
+    let store = SqliteBillingStore::open_in_memory().unwrap();
+    for day in 1..=5 {
+        store
+            .append(&record("openai", "gpt-4o", None, at(day, 9)))
+            .await
+            .unwrap();
+    }
+
+    let records = store
+        .query(
+            "openai",
+            Some(Utc.with_ymd_and_hms(2025, 3, 2, 0, 0, 0).unwrap()),
+            Some(at(4, 9)),
+        )
+        .await
+        .unwrap();
+    let days: Vec<_> = records.iter().map(|r| r.timestamp).collect();
+    assert_eq!(days, vec![at(2, 9), at(3, 9)]);
+
+    assert_eq!(store.query("openai", None, None).await.unwrap().len(), 5);
+    assert!(store.query("claude", None, None).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_stores_stream_records_across_pages() {
+    let path = std::env::temp_dir().join(format!("billing-{}.jsonl", uuid::Uuid::new_v4()));
+    let stores: Vec<Arc<dyn BillingStore>> = vec![
+        Arc::new(SqliteBillingStore::open_in_memory().unwrap()),
+        Arc::new(JsonlBillingStore::open(&path).unwrap()),
+    ];
+
+    for store in stores {
+        // 超过 SQLite 单页大小，并夹杂其他适配器的记录
+        for i in 0..1200u32 {
+            let adapter = if i % 4 == 0 { "claude" } else { "openai" };
+            let mut record = record(adapter, "gpt-4o", None, at(1 + i / 600, 9));
+            record.input_tokens = i as u64;
+            store.append(&record).await.unwrap();
+        }
+
+        let records: Vec<_> = store
+            .stream("openai", Some(at(2, 0)), None)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 450, "{}", store.kind());
+        assert_eq!(records[0].input_tokens, 601);
+        assert!(records
+            .windows(2)
+            .all(|w| w[0].input_tokens < w[1].input_tokens));
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_wrapped_adapter_records_tenant() {
+    let (wrapped, tracker) = wrap(
+        Arc::new(MockAdapter::new("mock".to_string())),
+        BillingConfig::default(),
+    );
+
+    let options = InvokeOptions {
+        tenant_id: Some("acme".to_string()),
+        ..Default::default()
+    };
+    wrapped
+        .invoke_with_options("Hello", &options)
+        .await
+        .unwrap();
+    wrapped.invoke("Hello").await.unwrap();
+
+    let records = tracker.records("mock", None, None).await.unwrap();
+    let report = aggregate(&records, ReportGranularity::Day, ReportGroupBy::Tenant);
+    let keys: Vec<_> = report.iter().map(|b| b.key.as_deref()).collect();
+    assert_eq!(keys, vec![None, Some("acme")]);
+
+    // 时间范围之外的记录不返回
+    let future = Utc::now() + chrono::Duration::hours(1);
+    assert!(tracker
+        .records("mock", Some(future), None)
+        .await
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,168 @@
+use llm_adapter::billing::{BillingConfig, BillingTracker};
+use llm_adapter::{
+    AdapterConfig, AdapterRegistry, BillingStore, JsonlBillingStore, SqliteBillingStore, Usage,
+};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+fn temp_path(extension: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "llm-adapter-billing-{}.{}",
+        uuid::Uuid::new_v4(),
+        extension
+    ))
+}
+
+fn config() -> BillingConfig {
+    BillingConfig {
+        input_price_per_1k: 1.0,
+        output_price_per_1k: 2.0,
+        ..Default::default()
+    }
+}
+
+async fn record(tracker: &BillingTracker, adapter: &str, user: &str, input: u64, output: u64) {
+    tracker
+        .record_token_usage(
+            adapter.to_string(),
+            Some(user.to_string()),
+            uuid::Uuid::new_v4().to_string(),
+            &Usage::new(input, output),
+            serde_json::json!({"model": "test"}),
+        )
+        .await;
+}
+
+async fn assert_survives_restart(open: impl Fn() -> Arc<dyn BillingStore>) {
+    let tracker = BillingTracker::new(config()).with_store(open());
+    record(&tracker, "openai", "alice", 1000, 500).await;
+    record(&tracker, "openai", "bob", 2000, 0).await;
+    record(&tracker, "claude", "alice", 3000, 3000).await;
+
+    let restored = BillingTracker::new(config()).with_store(open());
+    assert_eq!(restored.rehydrate("openai").await.unwrap(), 2);
+
+    let stats = restored.get_adapter_stats("openai").unwrap();
+    assert_eq!(stats.total_requests, 2);
+    assert_eq!(stats.total_input_tokens, 3000);
+    assert!((stats.total_cost - 4.0).abs() < 1e-9);
+    // 只恢复本适配器的记录
+    assert!(restored.get_adapter_stats("claude").is_none());
+    assert_eq!(restored.get_user_stats("alice").unwrap().total_requests, 1);
+
+    let records = restored.recent_records("openai");
+    assert_eq!(records[0].user_id.as_deref(), Some("alice"));
+    assert_eq!(records[0].metadata["model"], "test");
+}
+
+#[tokio::test]
+async fn test_jsonl_store_survives_restart() {
+    let path = temp_path("jsonl");
+    assert_survives_restart(|| Arc::new(JsonlBillingStore::open(&path).unwrap())).await;
+
+    // 写了一半的行被跳过
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap();
+    file.write_all(b"{\"adapter_name\":\"openai\",\"inp")
+        .unwrap();
+    let store = JsonlBillingStore::open(&path).unwrap();
+    assert_eq!(store.load("openai").await.unwrap().len(), 2);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn test_sqlite_store_survives_restart() {
+    let path = temp_path("db");
+    assert_survives_restart(|| Arc::new(SqliteBillingStore::open(&path).unwrap())).await;
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn test_memory_retention_is_bounded() {
+    let store = Arc::new(SqliteBillingStore::open_in_memory().unwrap());
+    let tracker = BillingTracker::new(BillingConfig {
+        max_records: 2,
+        ..config()
+    })
+    .with_store(store.clone());
+
+    for i in 1..=5 {
+        record(&tracker, "openai", "alice", i * 100, 0).await;
+    }
+
+    let records = tracker.recent_records("openai");
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1].input_tokens, 500);
+    assert_eq!(
+        tracker.get_adapter_stats("openai").unwrap().total_requests,
+        5
+    );
+    assert_eq!(store.load("openai").await.unwrap().len(), 5);
+}
+
+#[tokio::test]
+async fn test_registry_restores_billing_on_register() {
+    let path = temp_path("jsonl");
+    let config = AdapterConfig::new("mock".to_string())
+        .with_api_key("test-key".to_string())
+        .with_metadata("billing_store".to_string(), serde_json::json!("jsonl"))
+        .with_metadata(
+            "billing_path".to_string(),
+            serde_json::json!(path.to_str().unwrap()),
+        );
+
+    let registry = AdapterRegistry::new();
+    registry.register_from_config(config.clone()).await.unwrap();
+    let tracker = registry.get_billing_tracker("mock").unwrap();
+    assert_eq!(tracker.store().unwrap().kind(), "jsonl");
+    record(&tracker, "mock", "alice", 100, 100).await;
+
+    // 模拟进程重启
+    let registry = AdapterRegistry::new();
+    registry.register_from_config(config).await.unwrap();
+    let stats = registry
+        .get_billing_tracker("mock")
+        .unwrap()
+        .get_adapter_stats("mock")
+        .unwrap();
+    assert_eq!(stats.total_requests, 1);
+    assert_eq!(stats.total_output_tokens, 100);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn test_registry_rejects_unusable_billing_store() {
+    let registry = AdapterRegistry::new();
+    let base = AdapterConfig::new("mock".to_string()).with_api_key("test-key".to_string());
+    registry.register_from_config(base.clone()).await.unwrap();
+    let tracker = registry.get_billing_tracker("mock").unwrap();
+
+    // 父路径是普通文件，无法创建账单文件
+    let blocker = temp_path("txt");
+    std::fs::write(&blocker, "").unwrap();
+    let unwritable = base
+        .clone()
+        .with_metadata("billing_store".to_string(), serde_json::json!("jsonl"))
+        .with_metadata(
+            "billing_path".to_string(),
+            serde_json::json!(blocker.join("billing.jsonl").to_str().unwrap()),
+        );
+    let err = registry.register_from_config(unwritable).await.unwrap_err();
+    assert!(err.to_string().contains("Failed to open billing store"));
+
+    let unknown = base.with_metadata("billing_store".to_string(), serde_json::json!("postgres"));
+    assert!(registry.register_from_config(unknown).await.is_err());
+
+    // 原有的计费器保持不变
+    assert!(Arc::ptr_eq(
+        &registry.get_billing_tracker("mock").unwrap(),
+        &tracker
+    ));
+
+    std::fs::remove_file(blocker).unwrap();
+}
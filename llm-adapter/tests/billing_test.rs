@@ -0,0 +1,65 @@
+mod common;
+
+use common::{wrap, FixedUsageAdapter};
+use llm_adapter::billing::BillingConfig;
+use llm_adapter::{Adapter, ChatRequest, InvokeOptions, Usage};
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_wrapped_adapter_bills_provider_usage() {
+    let config = BillingConfig {
+        input_price_per_1k: 1.0,
+        output_price_per_1k: 2.0,
+        cached_input_price_per_1k: Some(0.5),
+        ..Default::default()
+    };
+    let (wrapped, billing_tracker) = wrap(
+        Arc::new(FixedUsageAdapter::new(Some(
+            Usage::new(1000, 500).with_cached_tokens(400),
+        ))),
+        config,
+    );
+
+    let options = InvokeOptions {
+        user_id: Some("user1".to_string()),
+        ..Default::default()
+    };
+    let result = wrapped
+        .invoke_with_options("Hello", &options)
+        .await
+        .unwrap();
+    assert_eq!(result, "echo: Hello");
+
+    let stats = billing_tracker.get_adapter_stats("fixed").unwrap();
+    assert_eq!(stats.total_input_tokens, 1000);
+    assert_eq!(stats.total_output_tokens, 500);
+    assert_eq!(stats.total_cached_tokens, 400);
+    // 600 * 1.0 + 400 * 0.5 + 500 * 2.0 (每千 token)
+    assert!((stats.total_cost - 1.8).abs() < 1e-9);
+
+    let user_stats = billing_tracker.get_user_stats("user1").unwrap();
+    assert_eq!(user_stats.total_cached_tokens, 400);
+}
+
+#[tokio::test]
+async fn test_wrapped_adapter_estimates_when_usage_missing() {
+    let (wrapped, billing_tracker) = wrap(
+        Arc::new(FixedUsageAdapter::new(None)),
+        BillingConfig::default(),
+    );
+
+    let response = wrapped
+        .chat(
+            &ChatRequest::from_prompt("Hello world, this is a test"),
+            &InvokeOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert!(response.usage.is_none());
+
+    let stats = billing_tracker.get_adapter_stats("fixed").unwrap();
+    assert_eq!(stats.total_requests, 1);
+    assert!(stats.total_input_tokens > 0);
+    assert!(stats.total_output_tokens > 0);
+    assert_eq!(stats.total_cached_tokens, 0);
+}
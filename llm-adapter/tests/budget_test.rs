@@ -0,0 +1,287 @@
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::{wrap, FixedUsageAdapter};
+use llm_adapter::billing::BillingConfig;
+use llm_adapter::budget::{BudgetEventKind, BudgetSubject};
+use llm_adapter::{
+    Adapter, AdapterConfig, AdapterError, AdapterRegistry, Budget, BudgetManager, BudgetPeriod,
+    BudgetScope, InvokeOptions, Usage,
+};
+use std::sync::Arc;
+
+fn budget(name: &str, scope: BudgetScope) -> Budget {
+    Budget {
+        name: name.to_string(),
+        scope,
+        period: BudgetPeriod::Daily,
+        max_cost: Some(1.0),
+        max_tokens: None,
+        warn_ratio: 0.5,
+        hard_stop: true,
+    }
+}
+
+fn user(id: &str) -> BudgetSubject {
+    BudgetSubject {
+        adapter: "openai".to_string(),
+        user_id: Some(id.to_string()),
+        tenant_id: None,
+    }
+}
+
+#[test]
+fn test_period_boundaries() {
+    let now = Utc.with_ymd_and_hms(2025, 12, 31, 15, 30, 0).unwrap();
+    assert_eq!(
+        BudgetPeriod::Daily.end(now),
+        Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()
+    );
+    assert_eq!(
+        BudgetPeriod::Monthly.start(now),
+        Utc.with_ymd_and_hms(2025, 12, 1, 0, 0, 0).unwrap()
+    );
+    assert_eq!(
+        BudgetPeriod::Monthly.end(now),
+        Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()
+    );
+}
+
+#[test]
+fn test_thresholds_emit_events_once() {
+    let manager = BudgetManager::new();
+    manager.set_budgets(vec![budget("per-user", BudgetScope::User("*".to_string()))]);
+    let mut events = manager.subscribe();
+
+    manager.record(&user("alice"), 0.6, 100);
+    manager.record(&user("alice"), 0.1, 100);
+    assert!(manager.check(&user("alice")).is_ok());
+
+    manager.record(&user("alice"), 0.4, 100);
+    let err = manager.check(&user("alice")).unwrap_err();
+    assert_eq!(err.budget, "per-user");
+    assert_eq!(err.subject, "alice");
+    assert!(err.retry_after().as_secs() <= 86400);
+
+    // 其他用户单独计算
+    assert!(manager.check(&user("bob")).is_ok());
+
+    let first = events.try_recv().unwrap();
+    assert_eq!(first.kind, BudgetEventKind::Warning);
+    assert_eq!(first.subject, "alice");
+    let second = events.try_recv().unwrap();
+    assert_eq!(second.kind, BudgetEventKind::Exceeded);
+    assert!((second.spent_cost - 1.1).abs() < 1e-9);
+    assert!(events.try_recv().is_err());
+}
+
+#[test]
+fn test_soft_budget_only_warns() {
+    let manager = BudgetManager::new();
+    manager.set_budgets(vec![Budget {
+        hard_stop: false,
+        max_cost: None,
+        max_tokens: Some(1000),
+        ..budget("tenant", BudgetScope::Tenant("acme".to_string()))
+    }]);
+    let mut events = manager.subscribe();
+
+    let subject = BudgetSubject {
+        tenant_id: Some("acme".to_string()),
+        ..user("alice")
+    };
+    manager.record(&subject, 0.0, 5000);
+    assert!(manager.check(&subject).is_ok());
+    assert_eq!(events.try_recv().unwrap().kind, BudgetEventKind::Exceeded);
+
+    // 不属于该租户的请求不计入
+    manager.record(&user("bob"), 0.0, 5000);
+    let usage = manager.usage();
+    assert_eq!(usage.len(), 1);
+    assert_eq!(usage[0].spent_tokens, 5000);
+
+    // 移除预算后清除用量
+    manager.set_budgets(Vec::new());
+    assert!(manager.usage().is_empty());
+}
+
+#[test]
+fn test_reservations_prevent_concurrent_overshoot() {
+    let manager = Arc::new(BudgetManager::new());
+    manager.set_budgets(vec![Budget {
+        max_cost: None,
+        max_tokens: Some(100),
+        ..budget("per-user", BudgetScope::User("*".to_string()))
+    }]);
+
+    // 两个并发请求各预估 60 token，只能放行一个
+    let first = manager.reserve(&user("alice"), 0.0, 60).unwrap();
+    assert!(manager.reserve(&user("alice"), 0.0, 60).is_err());
+    assert!(manager.reserve(&user("bob"), 0.0, 60).is_ok());
+
+    // 放弃的预留被释放
+    drop(first);
+    let second = manager.reserve(&user("alice"), 0.0, 60).unwrap();
+
+    // 按实际用量结算
+    second.settle(0.0, 30);
+    let usage = manager.usage();
+    let alice = usage.iter().find(|u| u.subject == "alice").unwrap();
+    assert_eq!(alice.spent_tokens, 30);
+    assert!(manager.reserve(&user("alice"), 0.0, 60).is_ok());
+}
+
+#[tokio::test]
+async fn test_registry_restores_budget_spend_after_restart() {
+    let path =
+        std::env::temp_dir().join(format!("llm-adapter-budget-{}.jsonl", uuid::Uuid::new_v4()));
+    let config = AdapterConfig::new("mock".to_string())
+        .with_api_key("test-key".to_string())
+        .with_metadata("billing_store".to_string(), serde_json::json!("jsonl"))
+        .with_metadata(
+            "billing_path".to_string(),
+            serde_json::json!(path.to_str().unwrap()),
+        );
+    let budgets = vec![Budget {
+        max_cost: None,
+        max_tokens: Some(150),
+        ..budget("per-user", BudgetScope::User("alice".to_string()))
+    }];
+
+    let registry = AdapterRegistry::new();
+    registry.register_from_config(config.clone()).await.unwrap();
+    let tracker = registry.get_billing_tracker("mock").unwrap();
+    for metadata in [
+        serde_json::json!({}),
+        serde_json::json!({"cache_hit": true}),
+    ] {
+        tracker
+            .record_token_usage(
+                "mock".to_string(),
+                Some("alice".to_string()),
+                uuid::Uuid::new_v4().to_string(),
+                &Usage::new(100, 100),
+                metadata,
+            )
+            .await;
+    }
+
+    // 模拟进程重启，预算在注册前加载
+    let registry = AdapterRegistry::new();
+    registry.budget_manager().set_budgets(budgets);
+    registry.register_from_config(config.clone()).await.unwrap();
+
+    // 命中缓存的记录不计入预算
+    let subject = BudgetSubject {
+        adapter: "mock".to_string(),
+        ..user("alice")
+    };
+    let manager = registry.budget_manager();
+    assert!(manager.check(&subject).is_err());
+    assert_eq!(manager.usage()[0].spent_tokens, 200);
+
+    // 重新注册不会重复累计
+    registry.register_from_config(config).await.unwrap();
+    assert_eq!(manager.usage()[0].spent_tokens, 200);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn test_budget_added_later_counts_existing_spend() {
+    let registry = AdapterRegistry::new();
+    registry
+        .register_from_config(
+            AdapterConfig::new("mock".to_string()).with_api_key("test-key".to_string()),
+        )
+        .await
+        .unwrap();
+    let tracker = registry.get_billing_tracker("mock").unwrap();
+    let call = |tokens: u64| {
+        let tracker = tracker.clone();
+        async move {
+            tracker
+                .record_token_usage(
+                    "mock".to_string(),
+                    Some("alice".to_string()),
+                    uuid::Uuid::new_v4().to_string(),
+                    &Usage::new(tokens, 0),
+                    serde_json::json!({}),
+                )
+                .await
+        }
+    };
+    call(100).await;
+
+    let cap = |name: &str| Budget {
+        max_cost: None,
+        max_tokens: Some(150),
+        ..budget(name, BudgetScope::User("alice".to_string()))
+    };
+    registry.set_budgets(vec![cap("first")]).await;
+    let manager = registry.budget_manager();
+    let spent = |name: &str| {
+        manager
+            .usage()
+            .into_iter()
+            .find(|u| u.budget == name)
+            .map_or(0, |u| u.spent_tokens)
+    };
+    assert_eq!(spent("first"), 100);
+
+    // 预算生效之后的调用由 record 计入，后续更新预算时不会再次累计
+    call(40).await;
+    let subject = BudgetSubject {
+        adapter: "mock".to_string(),
+        ..user("alice")
+    };
+    manager.record(&subject, 0.0, 40);
+    registry
+        .set_budgets(vec![cap("first"), cap("second")])
+        .await;
+    assert_eq!(spent("first"), 140);
+    assert_eq!(spent("second"), 140);
+    assert!(manager.reserve(&subject, 0.0, 20).is_err());
+}
+
+#[tokio::test]
+async fn test_wrapped_adapter_enforces_budget_before_call() {
+    let manager = Arc::new(BudgetManager::new());
+    manager.set_budgets(vec![Budget {
+        max_cost: None,
+        max_tokens: Some(100),
+        ..budget("tenant", BudgetScope::Tenant("acme".to_string()))
+    }]);
+
+    let (wrapped, _) = wrap(
+        Arc::new(FixedUsageAdapter::new(Some(Usage::new(60, 20)))),
+        BillingConfig::default(),
+    );
+    let wrapped = wrapped.with_budget_manager(manager.clone());
+
+    let options = InvokeOptions {
+        tenant_id: Some("acme".to_string()),
+        ..Default::default()
+    };
+    wrapped
+        .invoke_with_options("Hello", &options)
+        .await
+        .unwrap();
+    wrapped
+        .invoke_with_options("Hello", &options)
+        .await
+        .unwrap();
+
+    let err = wrapped
+        .invoke_with_options("Hello", &options)
+        .await
+        .unwrap_err();
+    let err = err.downcast_ref::<AdapterError>().unwrap();
+    assert_eq!(err.code(), "budget_exceeded");
+    assert!(!err.is_retryable());
+    assert!(err.retry_after().is_some());
+
+    // 未指定租户的请求不受影响
+    wrapped.invoke("Hello").await.unwrap();
+    assert_eq!(manager.usage()[0].spent_tokens, 160);
+}
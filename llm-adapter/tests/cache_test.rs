@@ -0,0 +1,347 @@
+mod common;
+
+use async_trait::async_trait;
+use common::serve_json_once;
+use llm_adapter::billing::{BillingConfig, BillingTracker};
+use llm_adapter::cache::CACHE_CONTROL;
+use llm_adapter::{
+    Adapter, AdapterConfig, AdapterRegistry, CacheConfig, CacheStore, ChatMessage, ChatRequest,
+    EmbeddingAdapter, EmbeddingResponse, InMemoryCacheStore, InvokeOptions, InvokeResponse,
+    ResponseCache, Usage, WrappedAdapter,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 记录上游调用次数，回复中带上调用序号
+struct CountingAdapter {
+    calls: AtomicUsize,
+}
+
+impl CountingAdapter {
+    fn new() -> Self {
+        Self {
+            calls: AtomicUsize::new(0),
+        }
+    }
+
+    fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl Adapter for CountingAdapter {
+    fn name(&self) -> &str {
+        "counting"
+    }
+
+    async fn describe(&self) -> String {
+        "counting adapter".to_string()
+    }
+
+    async fn invoke(&self, prompt: &str) -> anyhow::Result<String> {
+        let n = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        Ok(format!("answer {} to {}", n, prompt))
+    }
+
+    async fn chat(
+        &self,
+        request: &ChatRequest,
+        _options: &InvokeOptions,
+    ) -> anyhow::Result<InvokeResponse> {
+        let prompt = request
+            .last_user_message()
+            .map(|m| m.content.clone())
+            .unwrap_or_default();
+        let content = self.invoke(&prompt).await?;
+        Ok(InvokeResponse::new(content).with_usage(Some(Usage::new(100, 50))))
+    }
+
+    async fn health(&self) -> bool {
+        true
+    }
+}
+
+/// 提到天气的文本映射到同一方向，其他文本正交
+struct KeywordEmbedder;
+
+#[async_trait]
+impl Adapter for KeywordEmbedder {
+    fn name(&self) -> &str {
+        "keyword"
+    }
+
+    async fn describe(&self) -> String {
+        "keyword embedder".to_string()
+    }
+
+    async fn invoke(&self, _prompt: &str) -> anyhow::Result<String> {
+        anyhow::bail!("not a chat model")
+    }
+
+    async fn health(&self) -> bool {
+        true
+    }
+}
+
+#[async_trait]
+impl EmbeddingAdapter for KeywordEmbedder {
+    async fn embed(
+        &self,
+        texts: &[String],
+        _model: Option<&str>,
+    ) -> anyhow::Result<EmbeddingResponse> {
+        Ok(EmbeddingResponse::new(
+            texts
+                .iter()
+                .map(|t| {
+                    if t.to_lowercase().contains("weather") {
+                        vec![1.0, 0.1]
+                    } else {
+                        vec![0.0, 1.0]
+                    }
+                })
+                .collect(),
+        ))
+    }
+}
+
+fn wrap(
+    inner: Arc<CountingAdapter>,
+    cache: Arc<ResponseCache>,
+) -> (WrappedAdapter, Arc<BillingTracker>) {
+    let (wrapped, billing_tracker) = common::wrap(inner, BillingConfig::default());
+    (wrapped.with_response_cache(cache), billing_tracker)
+}
+
+fn ask(prompt: &str) -> ChatRequest {
+    ChatRequest::new(vec![
+        ChatMessage::system("You are helpful."),
+        ChatMessage::user(prompt),
+    ])
+}
+
+fn with_cache_control(directive: &str) -> InvokeOptions {
+    let mut options = InvokeOptions::default();
+    options
+        .metadata
+        .insert(CACHE_CONTROL.to_string(), serde_json::json!(directive));
+    options
+}
+
+#[tokio::test]
+async fn test_exact_hit_is_billed_at_zero_cost() {
+    let inner = Arc::new(CountingAdapter::new());
+    let cache = Arc::new(ResponseCache::new("counting", CacheConfig::default()));
+    let (wrapped, billing_tracker) = wrap(inner.clone(), cache.clone());
+    let options = InvokeOptions {
+        user_id: Some("alice".to_string()),
+        ..Default::default()
+    };
+
+    let first = wrapped.chat(&ask("What is Rust?"), &options).await.unwrap();
+    assert!(!first.cached);
+
+    // 空白差异不影响命中
+    let second = wrapped
+        .chat(&ask("  What   is Rust?\n"), &options)
+        .await
+        .unwrap();
+    assert!(second.cached);
+    assert_eq!(second.content, first.content);
+    assert_eq!(inner.calls(), 1);
+
+    let records = billing_tracker.recent_records("counting");
+    assert_eq!(records.len(), 2);
+    assert!(records[0].total_cost > 0.0);
+    assert_eq!(records[1].total_cost, 0.0);
+    assert_eq!(
+        (records[1].input_tokens, records[1].output_tokens),
+        (100, 50)
+    );
+    assert_eq!(records[1].metadata["cache_hit"], true);
+    assert_eq!(
+        billing_tracker
+            .get_user_stats("alice")
+            .unwrap()
+            .total_requests,
+        2
+    );
+
+    // 采样参数不同的请求不共用缓存
+    let hotter = InvokeOptions {
+        temperature: Some(1.2),
+        ..options.clone()
+    };
+    assert!(
+        !wrapped
+            .chat(&ask("What is Rust?"), &hotter)
+            .await
+            .unwrap()
+            .cached
+    );
+    assert_eq!(inner.calls(), 2);
+
+    let stats = cache.stats();
+    assert_eq!((stats.hits, stats.misses, stats.semantic_hits), (1, 2, 0));
+}
+
+#[tokio::test]
+async fn test_cache_control_bypass() {
+    let inner = Arc::new(CountingAdapter::new());
+    let cache = Arc::new(ResponseCache::new("counting", CacheConfig::default()));
+    let (wrapped, _) = wrap(inner.clone(), cache);
+
+    // no-store 既不读也不写
+    let response = wrapped
+        .chat(&ask("Hi"), &with_cache_control("no-store"))
+        .await
+        .unwrap();
+    assert_eq!(response.content, "answer 1 to Hi");
+    let response = wrapped
+        .chat(&ask("Hi"), &InvokeOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(response.content, "answer 2 to Hi");
+    assert!(!response.cached);
+
+    // no-cache 跳过读取，但用新结果覆盖缓存
+    let response = wrapped
+        .chat(&ask("Hi"), &with_cache_control("no-cache"))
+        .await
+        .unwrap();
+    assert_eq!(response.content, "answer 3 to Hi");
+    let response = wrapped
+        .chat(&ask("Hi"), &InvokeOptions::default())
+        .await
+        .unwrap();
+    assert!(response.cached);
+    assert_eq!(response.content, "answer 3 to Hi");
+    assert_eq!(inner.calls(), 3);
+}
+
+#[tokio::test]
+async fn test_in_memory_store_ttl_and_lru() {
+    let store = InMemoryCacheStore::new(2);
+    let ttl = Duration::from_secs(60);
+    store
+        .set("a", &InvokeResponse::new("a"), ttl)
+        .await
+        .unwrap();
+    store
+        .set("b", &InvokeResponse::new("b"), ttl)
+        .await
+        .unwrap();
+
+    // 读取 a 后 b 成为最久未访问的条目
+    assert!(store.get("a").await.unwrap().is_some());
+    store
+        .set("c", &InvokeResponse::new("c"), ttl)
+        .await
+        .unwrap();
+    assert_eq!(store.len(), 2);
+    assert!(store.get("b").await.unwrap().is_none());
+    assert_eq!(store.get("a").await.unwrap().unwrap().content, "a");
+    assert_eq!(store.get("c").await.unwrap().unwrap().content, "c");
+
+    store
+        .set("d", &InvokeResponse::new("d"), Duration::from_millis(20))
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(40)).await;
+    assert!(store.get("d").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_semantic_hit_within_same_conversation() {
+    let inner = Arc::new(CountingAdapter::new());
+    let config = CacheConfig {
+        similarity_threshold: Some(0.95),
+        ..Default::default()
+    };
+    let cache =
+        Arc::new(ResponseCache::new("counting", config).with_embedder(Arc::new(KeywordEmbedder)));
+    let (wrapped, billing_tracker) = wrap(inner.clone(), cache.clone());
+    let options = InvokeOptions::default();
+
+    let first = wrapped
+        .chat(&ask("What's the weather in Paris?"), &options)
+        .await
+        .unwrap();
+    let similar = wrapped
+        .chat(&ask("how is the weather in paris today"), &options)
+        .await
+        .unwrap();
+    assert!(similar.cached);
+    assert_eq!(similar.content, first.content);
+
+    let record = billing_tracker.recent_records("counting").pop().unwrap();
+    assert_eq!(record.total_cost, 0.0);
+    assert!(record.metadata["similarity"].as_f64().unwrap() >= 0.95);
+
+    // 向量不相近，或系统提示不同时都不命中
+    assert!(
+        !wrapped
+            .chat(&ask("Tell me a joke"), &options)
+            .await
+            .unwrap()
+            .cached
+    );
+    let other_system = ChatRequest::new(vec![
+        ChatMessage::system("You are a pirate."),
+        ChatMessage::user("What's the weather in Paris?"),
+    ]);
+    assert!(!wrapped.chat(&other_system, &options).await.unwrap().cached);
+    assert_eq!(inner.calls(), 3);
+
+    let stats = cache.stats();
+    assert_eq!((stats.hits, stats.semantic_hits), (1, 1));
+}
+
+#[tokio::test]
+async fn test_registry_enables_cache_from_metadata() {
+    // 只应答一次，第二次调用必须由缓存返回
+    let (base_url, _) = serve_json_once(
+        r#"{"model":"llama3.2","message":{"role":"assistant","content":"cached answer"},"done":true,"prompt_eval_count":1000,"eval_count":10}"#,
+    )
+    .await;
+
+    let registry = AdapterRegistry::new();
+    registry
+        .register_from_config(
+            AdapterConfig::new("ollama".to_string())
+                .with_base_url(base_url)
+                .with_metadata("cache".to_string(), serde_json::json!(true))
+                .with_metadata("cache_ttl_secs".to_string(), serde_json::json!(60))
+                .with_metadata("input_price_per_1k".to_string(), serde_json::json!(0.02))
+                .with_metadata("health_check".to_string(), serde_json::json!("none")),
+        )
+        .await
+        .unwrap();
+    let cache = registry.get_response_cache("ollama").unwrap();
+    assert_eq!(cache.config().ttl, Duration::from_secs(60));
+
+    let adapter = registry.get("ollama").await.unwrap();
+    let first = adapter
+        .chat(&ask("Hello"), &InvokeOptions::default())
+        .await
+        .unwrap();
+    let second = adapter
+        .chat(&ask("Hello"), &InvokeOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(second.content, "cached answer");
+    assert!(!first.cached && second.cached);
+
+    let records = registry
+        .get_billing_tracker("ollama")
+        .unwrap()
+        .recent_records("ollama");
+    assert!((records[0].total_cost - 0.02).abs() < 1e-9);
+    assert_eq!(records[1].total_cost, 0.0);
+    assert_eq!(cache.stats().hits, 1);
+
+    registry.unregister("ollama").await;
+    assert!(registry.get_response_cache("ollama").is_none());
+}
@@ -0,0 +1,307 @@
+mod common;
+
+use common::serve_json_once;
+use llm_adapter::providers::{MockAdapter, OpenAIAdapter};
+use llm_adapter::{
+    Adapter, AuthType, ChatMessage, ChatRequest, ChatRole, GenericAdapter, InvokeOptions,
+    RequestConfig, ToolCall, ToolChoice, ToolDefinition, Usage,
+};
+
+fn conversation() -> ChatRequest {
+    ChatRequest::new(vec![
+        ChatMessage::system("You are terse."),
+        ChatMessage::user("Hi").with_name("alice"),
+        ChatMessage::assistant("Hello!"),
+        ChatMessage::user("Bye"),
+    ])
+}
+
+#[test]
+fn test_chat_request_to_prompt() {
+    assert_eq!(ChatRequest::from_prompt("Hello").to_prompt(), "Hello");
+    assert_eq!(
+        conversation().to_prompt(),
+        "[system] You are terse.\n\n[user] alice: Hi\n\n[assistant] Hello!\n\n[user] Bye"
+    );
+    assert_eq!(conversation().last_user_message().unwrap().content, "Bye");
+    assert_eq!(
+        ChatMessage::user("Hi")
+            .with_name("alice")
+            .content_with_name(),
+        "alice: Hi"
+    );
+}
+
+#[test]
+fn test_chat_message_serialization() {
+    let value = serde_json::to_value(ChatMessage::user("Hi").with_name("alice")).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({"role": "user", "content": "Hi", "name": "alice"})
+    );
+
+    let message: ChatMessage =
+        serde_json::from_value(serde_json::json!({"role": "system", "content": "rules"})).unwrap();
+    assert_eq!(message.role, ChatRole::System);
+    assert!(message.name.is_none());
+}
+
+#[tokio::test]
+async fn test_mock_adapter_chat_answers_last_user_message() {
+    let adapter = MockAdapter::new("mock".to_string());
+    let result = adapter
+        .chat(&conversation(), &InvokeOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(result.content, "Mock response to: Bye");
+}
+
+#[tokio::test]
+async fn test_openai_adapter_sends_native_messages() {
+    let (base_url, body) =
+        serve_json_once(concat!(
+        r#"{"model":"gpt-test-0125","choices":[{"message":{"role":"assistant","content":"ok"},"finish_reason":"stop"}],"#,
+        r#""usage":{"prompt_tokens":20,"completion_tokens":3,"total_tokens":23,"prompt_tokens_details":{"cached_tokens":16}}}"#
+    ))
+    .await;
+
+    let adapter =
+        OpenAIAdapter::new_with_base("sk-test".to_string(), "gpt-test".to_string(), base_url);
+    let result = adapter
+        .chat(&conversation(), &InvokeOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(result.content, "ok");
+    assert_eq!(result.model.as_deref(), Some("gpt-test-0125"));
+    assert_eq!(result.finish_reason.as_deref(), Some("stop"));
+    assert_eq!(result.usage, Some(Usage::new(20, 3).with_cached_tokens(16)));
+
+    let body = body.await.unwrap().body;
+    assert_eq!(
+        body["messages"],
+        serde_json::json!([
+            {"role": "system", "content": "You are terse."},
+            {"role": "user", "content": "Hi", "name": "alice"},
+            {"role": "assistant", "content": "Hello!"},
+            {"role": "user", "content": "Bye"}
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_generic_adapter_sends_native_messages() {
+    let (base_url, body) = serve_json_once(r#"{"choices":[{"message":{"content":"ok"}}]}"#).await;
+
+    let adapter = GenericAdapter::new(
+        "custom".to_string(),
+        "sk-test".to_string(),
+        "custom-model".to_string(),
+        base_url,
+        RequestConfig {
+            endpoint_template: "/chat".to_string(),
+            body_template: Some(serde_json::json!({"temperature": 0.1})),
+            method: "POST".to_string(),
+            auth_type: AuthType::Bearer,
+            auth_header: None,
+            model_field: "model".to_string(),
+            message_field: "messages".to_string(),
+            response_path: "choices.0.message.content".to_string(),
+            stream_response_path: "choices.0.delta.content".to_string(),
+            finish_reason_path: "choices.0.finish_reason".to_string(),
+            usage_path: "usage".to_string(),
+            tool_calls_path: "choices.0.message.tool_calls".to_string(),
+            error_path: None,
+        },
+    );
+    let result = adapter
+        .chat(&conversation(), &InvokeOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(result.content, "ok");
+
+    let body = body.await.unwrap().body;
+    assert_eq!(body["model"], "custom-model");
+    assert_eq!(body["messages"].as_array().unwrap().len(), 4);
+    assert_eq!(body["messages"][1]["name"], "alice");
+    assert_eq!(body["messages"][2]["role"], "assistant");
+}
+
+#[tokio::test]
+async fn test_openai_adapter_sends_invoke_options() {
+    let (base_url, body) =
+        serve_json_once(r#"{"choices":[{"message":{"role":"assistant","content":"ok"}}]}"#).await;
+
+    let adapter =
+        OpenAIAdapter::new_with_base("sk-test".to_string(), "gpt-test".to_string(), base_url);
+    let options = InvokeOptions {
+        model: Some("gpt-override".to_string()),
+        temperature: Some(0.2),
+        max_tokens: Some(64),
+        top_p: Some(0.5),
+        stop: Some(vec!["END".to_string()]),
+        seed: Some(42),
+        n: Some(2),
+        ..Default::default()
+    };
+    let result = adapter.invoke_with_options("Hi", &options).await.unwrap();
+    assert_eq!(result, "ok");
+
+    let body = body.await.unwrap().body;
+    assert_eq!(body["model"], "gpt-override");
+    assert_eq!(body["temperature"], serde_json::json!(0.2));
+    assert_eq!(body["max_tokens"], 64);
+    assert_eq!(body["top_p"], serde_json::json!(0.5));
+    assert_eq!(body["stop"], serde_json::json!(["END"]));
+    assert_eq!(body["seed"], 42);
+    assert_eq!(body["n"], 2);
+    assert!(body.get("presence_penalty").is_none());
+}
+
+#[tokio::test]
+async fn test_generic_adapter_fills_option_placeholders() {
+    let (base_url, body) = serve_json_once(r#"{"choices":[{"message":{"content":"ok"}}]}"#).await;
+
+    let adapter = GenericAdapter::new(
+        "custom".to_string(),
+        "sk-test".to_string(),
+        "custom-model".to_string(),
+        base_url,
+        RequestConfig {
+            endpoint_template: "/chat".to_string(),
+            body_template: Some(serde_json::json!({
+                "model": "{model}",
+                "temperature": "{temperature}",
+                "max_tokens": "{max_tokens}",
+                "options": {"top_p": "{top_p}"}
+            })),
+            method: "POST".to_string(),
+            auth_type: AuthType::None,
+            auth_header: None,
+            model_field: "model".to_string(),
+            message_field: "messages".to_string(),
+            response_path: "choices.0.message.content".to_string(),
+            stream_response_path: "choices.0.delta.content".to_string(),
+            finish_reason_path: "choices.0.finish_reason".to_string(),
+            usage_path: "usage".to_string(),
+            tool_calls_path: "choices.0.message.tool_calls".to_string(),
+            error_path: None,
+        },
+    );
+    let options = InvokeOptions {
+        model: Some("override-model".to_string()),
+        temperature: Some(0.7),
+        ..Default::default()
+    };
+    adapter.invoke_with_options("Hi", &options).await.unwrap();
+
+    let body = body.await.unwrap().body;
+    assert_eq!(body["model"], "override-model");
+    assert_eq!(body["temperature"], serde_json::json!(0.7));
+    // 未设置的选项不会以占位符原样发送
+    assert!(body.get("max_tokens").is_none());
+    assert!(body["options"].get("top_p").is_none());
+    assert_eq!(body["messages"][0]["content"], "Hi");
+}
+
+fn weather_tool() -> ToolDefinition {
+    ToolDefinition::new(
+        "get_weather",
+        "查询城市天气",
+        serde_json::json!({
+            "type": "object",
+            "properties": {"city": {"type": "string"}},
+            "required": ["city"]
+        }),
+    )
+}
+
+#[tokio::test]
+async fn test_openai_adapter_tool_calls_round_trip() {
+    let (base_url, body) = serve_json_once(concat!(
+        r#"{"choices":[{"message":{"role":"assistant","content":null,"tool_calls":["#,
+        r#"{"id":"call_1","type":"function","function":{"name":"get_weather","arguments":"{\"city\":\"北京\"}"}},"#,
+        r#"{"id":"call_2","type":"function","function":{"name":"get_weather","arguments":{"city":"上海"}}}"#,
+        r#"]},"finish_reason":"tool_calls"}]}"#
+    ))
+    .await;
+
+    let adapter =
+        OpenAIAdapter::new_with_base("sk-test".to_string(), "gpt-test".to_string(), base_url);
+    let request = ChatRequest::new(vec![
+        ChatMessage::user("北京天气？"),
+        ChatMessage::assistant("").with_tool_calls(vec![ToolCall::new(
+            "call_0",
+            "get_weather",
+            r#"{"city":"北京"}"#,
+        )]),
+        ChatMessage::tool("call_0", "晴"),
+    ])
+    .with_tools(vec![weather_tool()])
+    .with_tool_choice(ToolChoice::Tool("get_weather".to_string()));
+
+    let response = adapter
+        .chat(&request, &InvokeOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(response.content, "");
+    assert_eq!(response.finish_reason.as_deref(), Some("tool_calls"));
+    assert_eq!(response.tool_calls.len(), 2);
+    assert_eq!(response.tool_calls[0].id, "call_1");
+    assert_eq!(
+        response.tool_calls[0].parse_arguments().unwrap()["city"],
+        "北京"
+    );
+    // 以 JSON 对象返回的参数也会被规整为字符串
+    assert_eq!(
+        response.tool_calls[1].parse_arguments().unwrap()["city"],
+        "上海"
+    );
+
+    let body = body.await.unwrap().body;
+    assert_eq!(body["tools"][0]["type"], "function");
+    assert_eq!(body["tools"][0]["function"]["name"], "get_weather");
+    assert_eq!(
+        body["tool_choice"],
+        serde_json::json!({"type": "function", "function": {"name": "get_weather"}})
+    );
+    assert_eq!(
+        body["messages"][1]["tool_calls"][0]["function"]["arguments"],
+        r#"{"city":"北京"}"#
+    );
+    assert_eq!(body["messages"][2]["role"], "tool");
+    assert_eq!(body["messages"][2]["tool_call_id"], "call_0");
+}
+
+#[tokio::test]
+async fn test_mock_adapter_tool_loop() {
+    let adapter = MockAdapter::new("mock".to_string());
+    let mut request = ChatRequest::from_prompt("北京天气？").with_tools(vec![weather_tool()]);
+
+    let first = adapter
+        .chat(&request, &InvokeOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(first.finish_reason.as_deref(), Some("tool_calls"));
+    let call = first.tool_calls[0].clone();
+    assert_eq!(call.name, "get_weather");
+
+    request = request
+        .with_message(ChatMessage::assistant("").with_tool_calls(first.tool_calls))
+        .with_message(ChatMessage::tool(call.id, "晴"));
+
+    let second = adapter
+        .chat(&request, &InvokeOptions::default())
+        .await
+        .unwrap();
+    assert!(second.tool_calls.is_empty());
+    assert_eq!(second.content, "Mock response to: 北京天气？");
+
+    let disabled = ChatRequest::from_prompt("北京天气？")
+        .with_tools(vec![weather_tool()])
+        .with_tool_choice(ToolChoice::None);
+    let response = adapter
+        .chat(&disabled, &InvokeOptions::default())
+        .await
+        .unwrap();
+    assert!(response.tool_calls.is_empty());
+}
@@ -0,0 +1,230 @@
+use async_trait::async_trait;
+use llm_adapter::billing::{BillingConfig, BillingTracker};
+use llm_adapter::circuit_breaker::CircuitBreakerConfig;
+use llm_adapter::guard::{ConcurrencyConfig, ConcurrencyGuard};
+use llm_adapter::rate_limit::{RateLimitConfig, RateLimiter};
+use llm_adapter::{
+    Adapter, AdapterError, AdapterFactory, CircuitBreaker, CircuitState, RetryPolicy,
+    WrappedAdapter,
+};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 可切换上游是否可用的适配器
+struct SwitchableAdapter {
+    down: AtomicBool,
+    calls: AtomicU32,
+}
+
+impl SwitchableAdapter {
+    fn new(down: bool) -> Arc<Self> {
+        Arc::new(Self {
+            down: AtomicBool::new(down),
+            calls: AtomicU32::new(0),
+        })
+    }
+}
+
+#[async_trait]
+impl Adapter for SwitchableAdapter {
+    fn name(&self) -> &str {
+        "switchable"
+    }
+
+    async fn describe(&self) -> String {
+        "Switchable adapter".to_string()
+    }
+
+    async fn invoke(&self, prompt: &str) -> anyhow::Result<String> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if self.down.load(Ordering::SeqCst) {
+            return Err(AdapterError::Upstream5xx {
+                status: 503,
+                message: "unavailable".to_string(),
+            }
+            .into());
+        }
+        if prompt.is_empty() {
+            return Err(AdapterError::InvalidRequest("empty prompt".to_string()).into());
+        }
+        Ok(format!("echo: {}", prompt))
+    }
+
+    async fn health(&self) -> bool {
+        true
+    }
+}
+
+fn breaker_config() -> CircuitBreakerConfig {
+    CircuitBreakerConfig {
+        failure_threshold: 2,
+        cool_down: Duration::from_millis(50),
+        ..Default::default()
+    }
+}
+
+fn wrap(adapter: Arc<SwitchableAdapter>, config: CircuitBreakerConfig) -> WrappedAdapter {
+    WrappedAdapter::new(
+        adapter,
+        Arc::new(RateLimiter::new(RateLimitConfig {
+            enabled: false,
+            ..Default::default()
+        })),
+        Arc::new(BillingTracker::new(BillingConfig::default())),
+        Arc::new(ConcurrencyGuard::new(ConcurrencyConfig::default())),
+    )
+    .with_retry_policy(Arc::new(RetryPolicy::disabled()))
+    .with_circuit_breaker(Arc::new(CircuitBreaker::new("switchable", config)))
+}
+
+#[tokio::test]
+async fn test_circuit_opens_after_consecutive_failures() {
+    let adapter = SwitchableAdapter::new(true);
+    let wrapped = wrap(adapter.clone(), breaker_config());
+
+    for _ in 0..2 {
+        assert!(wrapped.invoke("Hello").await.is_err());
+    }
+    assert_eq!(wrapped.circuit_breaker().state(), CircuitState::Open);
+    assert!(!wrapped.health().await);
+
+    // 熔断期间请求不再发往上游
+    let err = wrapped.invoke("Hello").await.unwrap_err();
+    let err = err.downcast_ref::<AdapterError>().unwrap();
+    assert_eq!(err.code(), "circuit_open");
+    assert!(err.retry_after().is_some());
+    assert_eq!(adapter.calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_half_open_probe_closes_or_reopens_circuit() {
+    let adapter = SwitchableAdapter::new(true);
+    let wrapped = wrap(adapter.clone(), breaker_config());
+    for _ in 0..2 {
+        let _ = wrapped.invoke("Hello").await;
+    }
+
+    // 探测失败重新熔断
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert_eq!(wrapped.circuit_breaker().state(), CircuitState::HalfOpen);
+    assert!(wrapped.invoke("Hello").await.is_err());
+    assert_eq!(wrapped.circuit_breaker().state(), CircuitState::Open);
+
+    // 上游恢复后探测成功即关闭
+    adapter.down.store(false, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert_eq!(wrapped.invoke("Hello").await.unwrap(), "echo: Hello");
+    assert_eq!(wrapped.circuit_breaker().state(), CircuitState::Closed);
+    assert!(wrapped.health().await);
+}
+
+#[tokio::test]
+async fn test_rejected_probe_takes_no_permit_or_rate_limit_token() {
+    let breaker = Arc::new(CircuitBreaker::new("switchable", breaker_config()));
+    for _ in 0..2 {
+        breaker
+            .acquire()
+            .unwrap()
+            .record::<()>(&Err(AdapterError::Upstream5xx {
+                status: 503,
+                message: "unavailable".to_string(),
+            }
+            .into()));
+    }
+    tokio::time::sleep(Duration::from_millis(60)).await;
+
+    let adapter = SwitchableAdapter::new(false);
+    let guard = Arc::new(ConcurrencyGuard::new(ConcurrencyConfig::default()));
+    let wrapped = WrappedAdapter::new(
+        adapter.clone(),
+        Arc::new(RateLimiter::new(RateLimitConfig {
+            requests_per_second: 1,
+            requests_per_minute: 1,
+            ..Default::default()
+        })),
+        Arc::new(BillingTracker::new(BillingConfig::default())),
+        guard.clone(),
+    )
+    .with_retry_policy(Arc::new(RetryPolicy::disabled()))
+    .with_circuit_breaker(breaker.clone());
+
+    // 探测名额已被占用，请求在占用并发许可和限流配额之前被拒绝
+    let probe = breaker.acquire().unwrap();
+    let err = wrapped.invoke("Hello").await.unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<AdapterError>().unwrap().code(),
+        "circuit_open"
+    );
+    assert_eq!(guard.stats().acquired, 0);
+
+    // 限流配额仍在，释放探测名额后的请求可以通过
+    drop(probe);
+    assert_eq!(wrapped.invoke("Hello").await.unwrap(), "echo: Hello");
+    assert_eq!(adapter.calls.load(Ordering::SeqCst), 1);
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
+
+#[tokio::test]
+async fn test_request_errors_do_not_trip_circuit() {
+    let adapter = SwitchableAdapter::new(false);
+    let wrapped = wrap(adapter, breaker_config());
+
+    for _ in 0..5 {
+        assert!(wrapped.invoke("").await.is_err());
+    }
+    assert_eq!(wrapped.circuit_breaker().state(), CircuitState::Closed);
+}
+
+#[test]
+fn test_failure_rate_threshold() {
+    let breaker = CircuitBreaker::new(
+        "rate",
+        CircuitBreakerConfig {
+            failure_threshold: 100,
+            failure_rate_threshold: 0.5,
+            min_requests: 4,
+            window_size: 4,
+            ..Default::default()
+        },
+    );
+    let failure: anyhow::Result<()> = Err(AdapterError::Timeout("slow".to_string()).into());
+
+    for result in [Ok(()), Err(()), Ok(())] {
+        let guard = breaker.acquire().unwrap();
+        match result {
+            Ok(()) => guard.record(&Ok::<(), anyhow::Error>(())),
+            Err(()) => guard.record(&failure),
+        }
+    }
+    assert_eq!(breaker.state(), CircuitState::Closed);
+
+    breaker.acquire().unwrap().record(&failure);
+    assert_eq!(breaker.state(), CircuitState::Open);
+}
+
+#[test]
+fn test_circuit_breaker_from_metadata() {
+    let mut metadata = HashMap::new();
+    metadata.insert(
+        "circuit_failure_threshold".to_string(),
+        serde_json::json!(3),
+    );
+    metadata.insert("circuit_cool_down_ms".to_string(), serde_json::json!(1500));
+    metadata.insert("circuit_half_open_probes".to_string(), serde_json::json!(2));
+
+    let breaker = AdapterFactory::create_circuit_breaker("deepseek", &metadata);
+    let config = breaker.config();
+    assert_eq!(config.failure_threshold, 3);
+    assert_eq!(config.cool_down, Duration::from_millis(1500));
+    assert_eq!(config.half_open_max_probes, 2);
+    assert!(config.enabled);
+
+    metadata.insert(
+        "circuit_breaker_enabled".to_string(),
+        serde_json::json!(false),
+    );
+    let disabled = AdapterFactory::create_circuit_breaker("deepseek", &metadata);
+    assert!(!disabled.config().enabled);
+}
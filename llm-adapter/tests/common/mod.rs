@@ -0,0 +1,189 @@
+// 各测试文件只用到其中一部分
+#![allow(dead_code)]
+
+use async_trait::async_trait;
+use llm_adapter::billing::{BillingConfig, BillingTracker};
+use llm_adapter::guard::{ConcurrencyConfig, ConcurrencyGuard};
+use llm_adapter::rate_limit::{RateLimitConfig, RateLimiter};
+use llm_adapter::{Adapter, ChatRequest, InvokeOptions, InvokeResponse, Usage, WrappedAdapter};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, oneshot};
+
+pub struct CapturedRequest {
+    pub head: String,
+    pub body: serde_json::Value,
+}
+
+/// 启动一次性 HTTP 服务，返回地址以及收到的请求头和请求体
+pub async fn serve_once(
+    status: &'static str,
+    content_type: &'static str,
+    body: &'static str,
+) -> (String, oneshot::Receiver<CapturedRequest>) {
+    serve_response_once(http_response(
+        status,
+        &format!("Content-Type: {}\r\n", content_type),
+        body,
+    ))
+    .await
+}
+
+pub async fn serve_json_once(body: &'static str) -> (String, oneshot::Receiver<CapturedRequest>) {
+    serve_once("200 OK", "application/json", body).await
+}
+
+/// 以给定状态行和额外 header 返回错误响应
+pub async fn serve_error_once(
+    status: &'static str,
+    headers: &'static str,
+    body: &'static str,
+) -> String {
+    let headers = format!("Content-Type: application/json\r\n{}", headers);
+    serve_response_once(http_response(status, &headers, body))
+        .await
+        .0
+}
+
+/// 返回不带 Content-Length 的事件流，连接关闭即结束
+pub async fn serve_sse_once(body: &'static str) -> String {
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n{}",
+        body
+    );
+    serve_response_once(response).await.0
+}
+
+/// 依次处理多个请求，每个连接返回一个 JSON 响应
+pub async fn serve(bodies: Vec<String>) -> (String, mpsc::UnboundedReceiver<CapturedRequest>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        for body in bodies {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let _ = tx.send(read_request(&mut socket).await);
+            let response = http_response("200 OK", "Content-Type: application/json\r\n", &body);
+            write_response(&mut socket, &response).await;
+        }
+    });
+
+    (format!("http://{}", addr), rx)
+}
+
+async fn serve_response_once(response: String) -> (String, oneshot::Receiver<CapturedRequest>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = oneshot::channel();
+
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let _ = tx.send(read_request(&mut socket).await);
+        write_response(&mut socket, &response).await;
+    });
+
+    (format!("http://{}", addr), rx)
+}
+
+fn http_response(status: &str, headers: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        headers,
+        body.len(),
+        body
+    )
+}
+
+/// 读到完整的请求头和 Content-Length 指定的请求体为止
+async fn read_request(socket: &mut TcpStream) -> CapturedRequest {
+    let mut request = Vec::new();
+    let mut buf = vec![0u8; 8192];
+    loop {
+        let n = socket.read(&mut buf).await.unwrap();
+        request.extend_from_slice(&buf[..n]);
+
+        let text = String::from_utf8_lossy(&request).to_string();
+        if let Some(pos) = text.find("\r\n\r\n") {
+            let content_length = text[..pos]
+                .lines()
+                .find_map(|l| {
+                    l.to_ascii_lowercase()
+                        .strip_prefix("content-length:")
+                        .map(|v| v.trim().parse::<usize>().unwrap())
+                })
+                .unwrap_or(0);
+            if request.len() >= pos + 4 + content_length {
+                return CapturedRequest {
+                    head: text[..pos].to_ascii_lowercase(),
+                    // GET 请求没有请求体
+                    body: serde_json::from_slice(&request[pos + 4..])
+                        .unwrap_or(serde_json::Value::Null),
+                };
+            }
+        }
+    }
+}
+
+async fn write_response(socket: &mut TcpStream, response: &str) {
+    socket.write_all(response.as_bytes()).await.unwrap();
+    socket.shutdown().await.unwrap();
+}
+
+/// 回显提示词并返回固定用量的适配器
+pub struct FixedUsageAdapter {
+    usage: Option<Usage>,
+}
+
+impl FixedUsageAdapter {
+    pub fn new(usage: Option<Usage>) -> Self {
+        Self { usage }
+    }
+}
+
+#[async_trait]
+impl Adapter for FixedUsageAdapter {
+    fn name(&self) -> &str {
+        "fixed"
+    }
+
+    async fn describe(&self) -> String {
+        "Fixed usage adapter".to_string()
+    }
+
+    async fn invoke(&self, prompt: &str) -> anyhow::Result<String> {
+        Ok(format!("echo: {}", prompt))
+    }
+
+    async fn chat(
+        &self,
+        request: &ChatRequest,
+        _options: &InvokeOptions,
+    ) -> anyhow::Result<InvokeResponse> {
+        Ok(
+            InvokeResponse::new(format!("echo: {}", request.to_prompt()))
+                .with_usage(self.usage.clone()),
+        )
+    }
+
+    async fn health(&self) -> bool {
+        true
+    }
+}
+
+/// 用默认限流与并发配置包装适配器，返回包装后的适配器和它的计费器
+pub fn wrap(
+    adapter: Arc<dyn Adapter + Send + Sync>,
+    config: BillingConfig,
+) -> (WrappedAdapter, Arc<BillingTracker>) {
+    let billing_tracker = Arc::new(BillingTracker::new(config));
+    let wrapped = WrappedAdapter::new(
+        adapter,
+        Arc::new(RateLimiter::new(RateLimitConfig::default())),
+        billing_tracker.clone(),
+        Arc::new(ConcurrencyGuard::new(ConcurrencyConfig::default())),
+    );
+    (wrapped, billing_tracker)
+}
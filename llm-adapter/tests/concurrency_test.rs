@@ -0,0 +1,153 @@
+use llm_adapter::guard::{ConcurrencyConfig, ConcurrencyError, ConcurrencyGuard};
+use llm_adapter::{AdapterConfig, AdapterError, AdapterFactory, AdapterRegistry, Priority};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn guard(max_concurrent: usize) -> Arc<ConcurrencyGuard> {
+    Arc::new(ConcurrencyGuard::new(ConcurrencyConfig {
+        max_concurrent,
+        ..Default::default()
+    }))
+}
+
+#[tokio::test]
+async fn test_higher_priority_waiters_go_first() {
+    let guard = guard(1);
+    let held = guard.acquire(Priority::Normal).await.unwrap();
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    let mut tasks = Vec::new();
+    for priority in [Priority::Batch, Priority::Normal, Priority::Interactive] {
+        let (guard, order) = (guard.clone(), order.clone());
+        tasks.push(tokio::spawn(async move {
+            let _permit = guard.acquire(priority).await.unwrap();
+            order.lock().unwrap().push(priority);
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let stats = guard.stats();
+    assert_eq!(stats.queue_depth(), 3);
+    assert_eq!(stats.queued["batch"], 1);
+
+    drop(held);
+    for task in tasks {
+        task.await.unwrap();
+    }
+    assert_eq!(
+        *order.lock().unwrap(),
+        vec![Priority::Interactive, Priority::Normal, Priority::Batch]
+    );
+    let stats = guard.stats();
+    assert!(stats.wait_seconds_total > 0.0);
+    assert_eq!(stats.wait_buckets.last().unwrap().1, stats.acquired);
+}
+
+#[tokio::test]
+async fn test_resize_takes_effect_immediately() {
+    let guard = guard(1);
+    let first = guard.acquire(Priority::Normal).await.unwrap();
+
+    let waiter = {
+        let guard = guard.clone();
+        tokio::spawn(async move { guard.acquire(Priority::Normal).await.is_ok() })
+    };
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    // 扩容后排队的请求立即获得许可
+    guard.update_config(ConcurrencyConfig {
+        max_concurrent: 2,
+        ..Default::default()
+    });
+    assert!(waiter.await.unwrap());
+
+    let second = guard.acquire(Priority::Normal).await.unwrap();
+    assert_eq!(guard.available_permits(), 0);
+
+    // 缩容不影响已发出的许可，归还后按新上限计算
+    guard.update_config(ConcurrencyConfig {
+        max_concurrent: 1,
+        acquire_timeout: Duration::from_millis(20),
+        ..Default::default()
+    });
+    drop(first);
+    assert_eq!(guard.available_permits(), 0);
+    assert!(guard.acquire(Priority::Interactive).await.is_err());
+    drop(second);
+    assert_eq!(guard.available_permits(), 1);
+}
+
+#[tokio::test]
+async fn test_timeout_and_fail_fast() {
+    let guard = Arc::new(ConcurrencyGuard::new(ConcurrencyConfig {
+        max_concurrent: 1,
+        acquire_timeout: Duration::from_millis(20),
+        ..Default::default()
+    }));
+    let held = guard.acquire(Priority::Normal).await.unwrap();
+
+    let result = guard.acquire(Priority::Normal).await;
+    assert!(matches!(result, Err(ConcurrencyError::Timeout)));
+
+    guard.update_config(ConcurrencyConfig {
+        max_concurrent: 1,
+        fail_fast: true,
+        ..Default::default()
+    });
+    let result = guard.acquire(Priority::Interactive).await;
+    assert!(matches!(result, Err(ConcurrencyError::Busy)));
+
+    // 超时退出的等待者不占用名额
+    drop(held);
+    assert_eq!(guard.available_permits(), 1);
+    let stats = guard.stats();
+    assert_eq!(stats.rejected, 2);
+    assert_eq!(stats.queue_depth(), 0);
+}
+
+#[tokio::test]
+async fn test_registry_updates_concurrency_in_place() {
+    let registry = AdapterRegistry::new();
+    let config = AdapterConfig::new("busy".to_string())
+        .with_api_key("test-key".to_string())
+        .with_metadata("max_concurrent".to_string(), serde_json::json!(2))
+        .with_metadata("acquire_timeout_ms".to_string(), serde_json::json!(500));
+    registry.register_from_config(config.clone()).await.unwrap();
+
+    let guard = registry.get_concurrency_guard("busy").unwrap();
+    assert_eq!(guard.config().acquire_timeout, Duration::from_millis(500));
+
+    let mut metadata = config.metadata.clone();
+    metadata.insert("max_concurrent".to_string(), serde_json::json!(8));
+    metadata.insert("concurrency_fail_fast".to_string(), serde_json::json!(true));
+    assert!(registry.update_concurrency("busy", AdapterFactory::concurrency_config(&metadata)));
+    assert_eq!(guard.available_permits(), 8);
+    assert!(guard.config().fail_fast);
+    assert_eq!(registry.concurrency_stats()["busy"].limit, 8);
+    assert!(!registry.update_concurrency("missing", ConcurrencyConfig::default()));
+
+    // 重新注册沿用同一个并发控制
+    registry.register_from_config(config).await.unwrap();
+    let reused = registry.get_concurrency_guard("busy").unwrap();
+    assert!(Arc::ptr_eq(&guard, &reused));
+    assert_eq!(reused.available_permits(), 2);
+}
+
+#[tokio::test]
+async fn test_fail_fast_surfaces_as_rate_limited() {
+    let registry = AdapterRegistry::new();
+    let config = AdapterConfig::new("mock".to_string())
+        .with_api_key("test-key".to_string())
+        .with_metadata("max_concurrent".to_string(), serde_json::json!(1))
+        .with_metadata("concurrency_fail_fast".to_string(), serde_json::json!(true));
+    registry.register_from_config(config).await.unwrap();
+
+    let guard = registry.get_concurrency_guard("mock").unwrap();
+    let _held = guard.acquire(Priority::Normal).await.unwrap();
+
+    let adapter = registry.get("mock").await.unwrap();
+    let err = adapter.invoke("Hello").await.unwrap_err();
+    let err = err.downcast_ref::<AdapterError>().unwrap();
+    assert_eq!(err.code(), "rate_limited");
+}
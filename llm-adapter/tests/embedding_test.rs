@@ -0,0 +1,173 @@
+mod common;
+
+use common::serve;
+use llm_adapter::providers::{OllamaAdapter, OpenAIAdapter, QianwenAdapter};
+use llm_adapter::{
+    AdapterConfig, AdapterError, AdapterRegistry, EmbeddingAdapter, EmbeddingResponse,
+    InvokeOptions,
+};
+
+fn texts(count: usize) -> Vec<String> {
+    (0..count).map(|i| format!("text {}", i)).collect()
+}
+
+#[tokio::test]
+async fn test_openai_embeddings_keep_input_order() {
+    let (base_url, mut captured) = serve(vec![concat!(
+        r#"{"object":"list","model":"text-embedding-3-small","#,
+        r#""data":[{"object":"embedding","index":1,"embedding":[0.0,1.0,0.0]},"#,
+        r#"{"object":"embedding","index":0,"embedding":[1.0,0.0,0.0]}],"#,
+        r#""usage":{"prompt_tokens":8,"total_tokens":8}}"#
+    )
+    .to_string()])
+    .await;
+
+    let adapter =
+        OpenAIAdapter::new_with_base("sk-test".to_string(), "gpt-4o-mini".to_string(), base_url);
+    let result = adapter.embed(&texts(2), None).await.unwrap();
+    assert_eq!(result.embeddings[0], vec![1.0, 0.0, 0.0]);
+    assert_eq!(result.embeddings[1], vec![0.0, 1.0, 0.0]);
+    assert_eq!(result.dimensions(), 3);
+    assert_eq!(result.model.as_deref(), Some("text-embedding-3-small"));
+    let usage = result.usage.unwrap();
+    assert_eq!((usage.prompt_tokens, usage.completion_tokens), (8, 0));
+
+    let captured = captured.recv().await.unwrap();
+    assert!(captured.head.starts_with("post /v1/embeddings "));
+    assert!(captured.head.contains("authorization: bearer sk-test"));
+    assert_eq!(
+        captured.body,
+        serde_json::json!({"model": "text-embedding-3-small", "input": ["text 0", "text 1"]})
+    );
+}
+
+#[tokio::test]
+async fn test_dashscope_embeddings_are_batched() {
+    let batch = |count: usize, tokens: u64| {
+        let embeddings: Vec<_> = (0..count)
+            .rev()
+            .map(|i| serde_json::json!({"text_index": i, "embedding": [i as f32, 0.5]}))
+            .collect();
+        serde_json::json!({
+            "output": {"embeddings": embeddings},
+            "usage": {"total_tokens": tokens},
+            "request_id": "req-1"
+        })
+        .to_string()
+    };
+    let (base_url, mut captured) = serve(vec![batch(10, 40), batch(2, 8)]).await;
+
+    let adapter =
+        QianwenAdapter::new_with_base("sk-test".to_string(), "qwen-turbo".to_string(), base_url);
+    let result = adapter.embed(&texts(12), None).await.unwrap();
+    assert_eq!(result.embeddings.len(), 12);
+    assert_eq!(result.embeddings[9], vec![9.0, 0.5]);
+    assert_eq!(result.embeddings[10], vec![0.0, 0.5]);
+    assert_eq!(result.model.as_deref(), Some("text-embedding-v3"));
+    assert_eq!(result.usage.unwrap().prompt_tokens, 48);
+
+    let first = captured.recv().await.unwrap();
+    assert!(first
+        .head
+        .starts_with("post /v1/services/embeddings/text-embedding/text-embedding "));
+    assert_eq!(first.body["model"], "text-embedding-v3");
+    assert_eq!(first.body["input"]["texts"].as_array().unwrap().len(), 10);
+    let second = captured.recv().await.unwrap();
+    assert_eq!(
+        second.body["input"]["texts"],
+        serde_json::json!(["text 10", "text 11"])
+    );
+}
+
+#[tokio::test]
+async fn test_ollama_embeddings() {
+    let (base_url, mut captured) = serve(vec![
+        r#"{"model":"bge-m3","embeddings":[[0.1,0.2],[0.3,0.4]],"prompt_eval_count":6}"#
+            .to_string(),
+    ])
+    .await;
+
+    let adapter = OllamaAdapter::new_with_base("llama3.2".to_string(), base_url)
+        .with_keep_alive(serde_json::json!(-1));
+    let result = adapter.embed(&texts(2), Some("bge-m3")).await.unwrap();
+    assert_eq!(
+        result,
+        EmbeddingResponse::new(vec![vec![0.1, 0.2], vec![0.3, 0.4]])
+            .with_model("bge-m3")
+            .with_usage(Some(llm_adapter::Usage::new(6, 0)))
+    );
+
+    let captured = captured.recv().await.unwrap();
+    assert!(captured.head.starts_with("post /api/embed "));
+    assert_eq!(captured.body["model"], "bge-m3");
+    assert_eq!(captured.body["keep_alive"], -1);
+    assert_eq!(
+        captured.body["input"],
+        serde_json::json!(["text 0", "text 1"])
+    );
+}
+
+#[tokio::test]
+async fn test_registry_wraps_embeddings_with_limits_and_billing() {
+    let (base_url, mut captured) = serve(vec![
+        r#"{"model":"bge-small","embeddings":[[0.5,0.5]],"prompt_eval_count":1000}"#.to_string(),
+    ])
+    .await;
+
+    let registry = AdapterRegistry::new();
+    registry
+        .register_from_config(
+            AdapterConfig::new("ollama".to_string())
+                .with_base_url(base_url)
+                .with_metadata(
+                    "embedding_model".to_string(),
+                    serde_json::json!("bge-small"),
+                )
+                .with_metadata("input_price_per_1k".to_string(), serde_json::json!(0.02))
+                .with_metadata("rate_limit_rps".to_string(), serde_json::json!(1))
+                .with_metadata("health_check".to_string(), serde_json::json!("none")),
+        )
+        .await
+        .unwrap();
+    registry
+        .register_from_config(
+            AdapterConfig::new("custom".to_string()).with_api_key("test-key".to_string()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(registry.list_embedding_adapters(), vec!["ollama"]);
+    assert!(registry.get_embedding_adapter("custom").is_none());
+
+    let adapter = registry.get_embedding_adapter("ollama").unwrap();
+    let options = InvokeOptions {
+        user_id: Some("alice".to_string()),
+        ..Default::default()
+    };
+    let result = adapter
+        .embed_with_options(&texts(1), &options)
+        .await
+        .unwrap();
+    assert_eq!(result.dimensions(), 2);
+    assert_eq!(captured.recv().await.unwrap().body["model"], "bge-small");
+
+    let tracker = registry.get_billing_tracker("ollama").unwrap();
+    let record = tracker.recent_records("ollama").pop().unwrap();
+    assert_eq!(record.model.as_deref(), Some("bge-small"));
+    assert_eq!((record.input_tokens, record.output_tokens), (1000, 0));
+    assert!((record.total_cost - 0.02).abs() < 1e-9);
+    assert_eq!(record.metadata["embedding"], true);
+    assert_eq!(tracker.get_user_stats("alice").unwrap().total_requests, 1);
+
+    // 与对话接口共用同一个限流器
+    let err = adapter
+        .embed_with_options(&texts(1), &options)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<AdapterError>().unwrap().code(),
+        "rate_limited"
+    );
+
+    registry.unregister("ollama").await;
+    assert!(registry.get_embedding_adapter("ollama").is_none());
+}
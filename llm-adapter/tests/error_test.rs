@@ -0,0 +1,109 @@
+mod common;
+
+use common::serve_error_once;
+use llm_adapter::providers::OpenAIAdapter;
+use llm_adapter::{Adapter, AdapterError, AuthType, GenericAdapter, RequestConfig};
+use std::time::Duration;
+
+#[test]
+fn test_classify_status_codes() {
+    let auth = AdapterError::from_status(401, r#"{"error":{"message":"bad key"}}"#, None);
+    assert_eq!(auth, AdapterError::Auth("bad key".to_string()));
+    assert!(!auth.is_retryable());
+
+    let limited = AdapterError::from_status(429, "", Some(Duration::from_secs(3)));
+    assert_eq!(limited.retry_after(), Some(Duration::from_secs(3)));
+    assert!(limited.is_retryable());
+
+    let upstream = AdapterError::from_status(503, "overloaded", None);
+    assert!(matches!(
+        upstream,
+        AdapterError::Upstream5xx { status: 503, .. }
+    ));
+    assert!(upstream.is_retryable());
+
+    assert!(matches!(
+        AdapterError::from_status(504, "", None),
+        AdapterError::Timeout(_)
+    ));
+    assert!(matches!(
+        AdapterError::from_status(400, r#"{"error":{"message":"missing field"}}"#, None),
+        AdapterError::InvalidRequest(_)
+    ));
+}
+
+#[test]
+fn test_classify_context_length_and_content_filter() {
+    let openai = r#"{"error":{"message":"This model's maximum context length is 8192 tokens","code":"context_length_exceeded"}}"#;
+    let err = AdapterError::from_status(400, openai, None);
+    assert_eq!(err.code(), "context_length_exceeded");
+    assert!(err.message().contains("8192"));
+    assert!(!err.is_retryable());
+
+    let dashscope = r#"{"code":"DataInspectionFailed","message":"Input data may contain inappropriate content."}"#;
+    assert_eq!(
+        AdapterError::from_status(400, dashscope, None),
+        AdapterError::ContentFiltered("Input data may contain inappropriate content.".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_openai_rate_limit_is_typed() {
+    let base_url = serve_error_once(
+        "429 Too Many Requests",
+        "Retry-After: 7\r\n",
+        r#"{"error":{"message":"Rate limit reached","type":"requests"}}"#,
+    )
+    .await;
+
+    let adapter =
+        OpenAIAdapter::new_with_base("sk-test".to_string(), "gpt-test".to_string(), base_url);
+    let err = adapter.invoke("hi").await.unwrap_err();
+
+    let err = err
+        .downcast_ref::<AdapterError>()
+        .expect("typed adapter error");
+    assert_eq!(err.retry_after(), Some(Duration::from_secs(7)));
+    assert!(err.message().contains("Rate limit reached"));
+    assert!(err.is_retryable());
+}
+
+#[tokio::test]
+async fn test_generic_adapter_uses_error_path() {
+    let base_url = serve_error_once(
+        "422 Unprocessable Entity",
+        "",
+        r#"{"detail":{"reason":"unknown model"}}"#,
+    )
+    .await;
+
+    let adapter = GenericAdapter::new(
+        "custom".to_string(),
+        "sk-test".to_string(),
+        "custom-model".to_string(),
+        base_url,
+        RequestConfig {
+            endpoint_template: "/chat".to_string(),
+            body_template: None,
+            method: "POST".to_string(),
+            auth_type: AuthType::None,
+            auth_header: None,
+            model_field: "model".to_string(),
+            message_field: "messages".to_string(),
+            response_path: "choices.0.message.content".to_string(),
+            stream_response_path: "choices.0.delta.content".to_string(),
+            finish_reason_path: "choices.0.finish_reason".to_string(),
+            usage_path: "usage".to_string(),
+            tool_calls_path: "choices.0.message.tool_calls".to_string(),
+            error_path: Some("detail.reason".to_string()),
+        },
+    );
+
+    let err = adapter.invoke("hi").await.unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<AdapterError>(),
+        Some(&AdapterError::InvalidRequest(
+            "custom API error: unknown model".to_string()
+        ))
+    );
+}
@@ -0,0 +1,239 @@
+use async_trait::async_trait;
+use llm_adapter::billing::{BillingConfig, BillingTracker};
+use llm_adapter::budget::BudgetSubject;
+use llm_adapter::guard::{ConcurrencyConfig, ConcurrencyGuard};
+use llm_adapter::providers::MockAdapter;
+use llm_adapter::rate_limit::{RateLimitConfig, RateLimiter};
+use llm_adapter::{
+    Adapter, AdapterConfig, AdapterError, AdapterRegistry, Budget, BudgetManager, BudgetPeriod,
+    BudgetScope, ChatRequest, FallbackAdapter, InvokeOptions, RetryPolicy, WrappedAdapter,
+};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// 每次调用都返回同一个错误
+struct FailingAdapter {
+    name: String,
+    error: AdapterError,
+    calls: AtomicU32,
+}
+
+impl FailingAdapter {
+    fn new(name: &str, error: AdapterError) -> Arc<Self> {
+        Arc::new(Self {
+            name: name.to_string(),
+            error,
+            calls: AtomicU32::new(0),
+        })
+    }
+}
+
+#[async_trait]
+impl Adapter for FailingAdapter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn describe(&self) -> String {
+        "Failing adapter".to_string()
+    }
+
+    async fn invoke(&self, _prompt: &str) -> anyhow::Result<String> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Err(self.error.clone().into())
+    }
+
+    async fn health(&self) -> bool {
+        false
+    }
+}
+
+fn mock(name: &str) -> Arc<MockAdapter> {
+    Arc::new(MockAdapter::new(name.to_string()))
+}
+
+#[tokio::test]
+async fn test_fallback_switches_on_retryable_error() {
+    let primary = FailingAdapter::new(
+        "primary",
+        AdapterError::Upstream5xx {
+            status: 503,
+            message: "overloaded".to_string(),
+        },
+    );
+    let fallback = FallbackAdapter::new("chain".to_string(), vec![primary.clone(), mock("backup")]);
+
+    let response = fallback
+        .chat(
+            &ChatRequest::from_prompt("Hello"),
+            &InvokeOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.content, "Mock response to: Hello");
+    assert_eq!(response.served_by.as_deref(), Some("backup"));
+    assert_eq!(primary.calls.load(Ordering::SeqCst), 1);
+    assert!(fallback.health().await);
+}
+
+#[tokio::test]
+async fn test_fallback_stops_on_non_retryable_error() {
+    let primary = FailingAdapter::new(
+        "primary",
+        AdapterError::ContextLengthExceeded("too long".to_string()),
+    );
+    let fallback = FallbackAdapter::new("chain".to_string(), vec![primary, mock("backup")]);
+
+    let err = fallback.invoke("Hello").await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<AdapterError>(),
+        Some(AdapterError::ContextLengthExceeded(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_fallback_skips_member_over_budget_or_with_bad_credentials() {
+    let budget_manager = Arc::new(BudgetManager::new());
+    budget_manager.set_budgets(vec![Budget {
+        name: "primary-daily".to_string(),
+        scope: BudgetScope::Adapter("primary".to_string()),
+        period: BudgetPeriod::Daily,
+        max_cost: Some(1.0),
+        max_tokens: None,
+        warn_ratio: 0.8,
+        hard_stop: true,
+    }]);
+    budget_manager.record(
+        &BudgetSubject {
+            adapter: "primary".to_string(),
+            ..Default::default()
+        },
+        1.0,
+        0,
+    );
+    let over_budget = Arc::new(
+        WrappedAdapter::new(
+            mock("primary"),
+            Arc::new(RateLimiter::new(RateLimitConfig::default())),
+            Arc::new(BillingTracker::new(BillingConfig::default())),
+            Arc::new(ConcurrencyGuard::new(ConcurrencyConfig::default())),
+        )
+        .with_budget_manager(budget_manager)
+        .with_retry_policy(Arc::new(RetryPolicy::disabled())),
+    );
+    let unauthorized = FailingAdapter::new("secondary", AdapterError::Auth("bad key".to_string()));
+    let fallback = FallbackAdapter::new(
+        "chain".to_string(),
+        vec![over_budget, unauthorized.clone(), mock("backup")],
+    );
+
+    let response = fallback
+        .chat(
+            &ChatRequest::from_prompt("Hello"),
+            &InvokeOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.served_by.as_deref(), Some("backup"));
+    assert_eq!(unauthorized.calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_fallback_respects_member_rate_limiter() {
+    let limited = Arc::new(
+        WrappedAdapter::new(
+            mock("primary"),
+            Arc::new(RateLimiter::new(RateLimitConfig {
+                requests_per_second: 1,
+                ..Default::default()
+            })),
+            Arc::new(BillingTracker::new(BillingConfig::default())),
+            Arc::new(ConcurrencyGuard::new(ConcurrencyConfig::default())),
+        )
+        .with_retry_policy(Arc::new(RetryPolicy::disabled())),
+    );
+    let fallback = FallbackAdapter::new("chain".to_string(), vec![limited, mock("backup")]);
+
+    let request = ChatRequest::from_prompt("Hello");
+    let options = InvokeOptions::default();
+    let first = fallback.chat(&request, &options).await.unwrap();
+    let second = fallback.chat(&request, &options).await.unwrap();
+    assert_eq!(first.served_by.as_deref(), Some("primary"));
+    assert_eq!(second.served_by.as_deref(), Some("backup"));
+}
+
+#[tokio::test]
+async fn test_registry_builds_fallback_from_config() {
+    let registry = AdapterRegistry::new();
+    registry.register("deepseek", mock("deepseek")).await;
+    registry.register("qianwen", mock("qianwen")).await;
+
+    let config = AdapterConfig::new("resilient".to_string())
+        .with_metadata("type".to_string(), serde_json::json!("fallback"))
+        .with_metadata(
+            "chain".to_string(),
+            serde_json::json!(["deepseek", "qianwen"]),
+        );
+    registry.register_from_config(config).await.unwrap();
+
+    let adapter = registry.get("resilient").await.unwrap();
+    assert_eq!(
+        adapter.describe().await,
+        "Fallback adapter resilient: deepseek -> qianwen"
+    );
+
+    let missing = AdapterConfig::new("broken".to_string())
+        .with_metadata("type".to_string(), serde_json::json!("fallback"))
+        .with_metadata("chain".to_string(), serde_json::json!(["unknown"]));
+    assert!(registry.register_from_config(missing).await.is_err());
+}
+
+#[tokio::test]
+async fn test_registry_fallback_follows_reloaded_members() {
+    let registry = AdapterRegistry::new();
+    let failing = FailingAdapter::new(
+        "deepseek",
+        AdapterError::Upstream5xx {
+            status: 503,
+            message: "overloaded".to_string(),
+        },
+    );
+    registry.register("deepseek", failing.clone()).await;
+    registry.register("qianwen", mock("qianwen")).await;
+    registry
+        .register_from_config(
+            AdapterConfig::new("resilient".to_string())
+                .with_metadata("type".to_string(), serde_json::json!("fallback"))
+                .with_metadata(
+                    "chain".to_string(),
+                    serde_json::json!(["deepseek", "qianwen"]),
+                ),
+        )
+        .await
+        .unwrap();
+
+    let fallback = registry.get("resilient").await.unwrap();
+    let request = ChatRequest::from_prompt("Hello");
+    let options = InvokeOptions::default();
+    let response = fallback.chat(&request, &options).await.unwrap();
+    assert_eq!(response.served_by.as_deref(), Some("qianwen"));
+
+    // 重新加载后的成员立即生效，旧实例不再收到请求
+    registry.register("deepseek", mock("deepseek")).await;
+    let response = fallback.chat(&request, &options).await.unwrap();
+    assert_eq!(response.served_by.as_deref(), Some("deepseek"));
+    assert_eq!(failing.calls.load(Ordering::SeqCst), 1);
+
+    // 注销的成员被跳过
+    registry.unregister("deepseek").await;
+    let response = fallback.chat(&request, &options).await.unwrap();
+    assert_eq!(response.served_by.as_deref(), Some("qianwen"));
+
+    let looping = AdapterConfig::new("resilient".to_string())
+        .with_metadata("type".to_string(), serde_json::json!("fallback"))
+        .with_metadata(
+            "chain".to_string(),
+            serde_json::json!(["resilient", "qianwen"]),
+        );
+    assert!(registry.register_from_config(looping).await.is_err());
+}
@@ -0,0 +1,195 @@
+mod common;
+
+use common::serve_once;
+use futures::StreamExt;
+use llm_adapter::providers::GeminiAdapter;
+use llm_adapter::{
+    Adapter, AdapterConfig, AdapterError, AdapterFactory, ChatMessage, ChatRequest, InvokeOptions,
+    ToolCall, ToolChoice, ToolDefinition,
+};
+
+fn adapter(base_url: String) -> GeminiAdapter {
+    GeminiAdapter::new_with_base("gm-test".to_string(), "gemini-test".to_string(), base_url)
+}
+
+#[tokio::test]
+async fn test_chat_maps_contents_tools_and_usage() {
+    let (base_url, captured) = serve_once(
+        "200 OK",
+        "application/json",
+        concat!(
+            r#"{"candidates":[{"content":{"role":"model","parts":[{"text":"Checking."},"#,
+            r#"{"functionCall":{"name":"weather","args":{"city":"Paris"}}}]},"finishReason":"STOP"}],"#,
+            r#""usageMetadata":{"promptTokenCount":40,"candidatesTokenCount":5,"thoughtsTokenCount":2,"#,
+            r#""cachedContentTokenCount":30,"totalTokenCount":47},"modelVersion":"gemini-test-001"}"#
+        ),
+    )
+    .await;
+
+    let request = ChatRequest::new(vec![
+        ChatMessage::system("You are terse."),
+        ChatMessage::user("Weather in Berlin?"),
+        ChatMessage::assistant("").with_tool_calls(vec![ToolCall::new(
+            "call_0",
+            "weather",
+            r#"{"city":"Berlin"}"#,
+        )]),
+        ChatMessage::tool("call_0", "Sunny"),
+        ChatMessage::user("And Paris?"),
+    ])
+    .with_tools(vec![ToolDefinition::new(
+        "weather",
+        "Current weather",
+        serde_json::json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+    )])
+    .with_tool_choice(ToolChoice::Required);
+    let options = InvokeOptions {
+        max_tokens: Some(256),
+        ..Default::default()
+    };
+
+    let result = adapter(base_url).chat(&request, &options).await.unwrap();
+    assert_eq!(result.content, "Checking.");
+    assert_eq!(result.model.as_deref(), Some("gemini-test-001"));
+    assert_eq!(result.finish_reason.as_deref(), Some("tool_calls"));
+    assert_eq!(result.tool_calls[0].name, "weather");
+    assert_eq!(
+        result.tool_calls[0].parse_arguments().unwrap(),
+        serde_json::json!({"city": "Paris"})
+    );
+    let usage = result.usage.unwrap();
+    assert_eq!(usage.prompt_tokens, 40);
+    assert_eq!(usage.cached_tokens, 30);
+    assert_eq!(usage.completion_tokens, 7);
+
+    let captured = captured.await.unwrap();
+    assert!(captured
+        .head
+        .starts_with("post /v1beta/models/gemini-test:generatecontent "));
+    assert!(captured.head.contains("x-goog-api-key: gm-test"));
+
+    let body = captured.body;
+    assert_eq!(
+        body["systemInstruction"],
+        serde_json::json!({"parts": [{"text": "You are terse."}]})
+    );
+    assert_eq!(body["generationConfig"]["maxOutputTokens"], 256);
+    assert_eq!(
+        body["toolConfig"],
+        serde_json::json!({"functionCallingConfig": {"mode": "ANY"}})
+    );
+    assert_eq!(
+        body["tools"][0]["functionDeclarations"][0]["name"],
+        "weather"
+    );
+    let contents = body["contents"].as_array().unwrap();
+    assert_eq!(contents.len(), 3);
+    assert_eq!(contents[1]["role"], "model");
+    assert_eq!(
+        contents[1]["parts"][0],
+        serde_json::json!({"functionCall": {"name": "weather", "args": {"city": "Berlin"}}})
+    );
+    // 工具结果按调用 ID 找回函数名，并与随后的用户消息合并
+    assert_eq!(contents[2]["role"], "user");
+    assert_eq!(
+        contents[2]["parts"][0],
+        serde_json::json!({"functionResponse": {"name": "weather", "response": {"result": "Sunny"}}})
+    );
+    assert_eq!(contents[2]["parts"][1]["text"], "And Paris?");
+}
+
+#[tokio::test]
+async fn test_safety_blocks_are_content_filtered() {
+    let (base_url, _) = serve_once(
+        "200 OK",
+        "application/json",
+        r#"{"promptFeedback":{"blockReason":"SAFETY","safetyRatings":[]},"usageMetadata":{"promptTokenCount":8}}"#,
+    )
+    .await;
+    let err = adapter(base_url).invoke("Hi").await.unwrap_err();
+    let err = err.downcast_ref::<AdapterError>().unwrap();
+    assert_eq!(err.code(), "content_filtered");
+    assert!(err.message().contains("SAFETY"));
+
+    let (base_url, _) = serve_once(
+        "200 OK",
+        "application/json",
+        r#"{"candidates":[{"finishReason":"PROHIBITED_CONTENT","safetyRatings":[]}]}"#,
+    )
+    .await;
+    let err = adapter(base_url).invoke("Hi").await.unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<AdapterError>().unwrap().code(),
+        "content_filtered"
+    );
+
+    let (base_url, _) = serve_once(
+        "400 Bad Request",
+        "application/json",
+        r#"{"error":{"code":400,"message":"API key not valid","status":"INVALID_ARGUMENT"}}"#,
+    )
+    .await;
+    let err = adapter(base_url).invoke("Hi").await.unwrap_err();
+    assert!(err
+        .downcast_ref::<AdapterError>()
+        .unwrap()
+        .message()
+        .contains("API key not valid"));
+}
+
+#[tokio::test]
+async fn test_chat_stream_uses_sse_and_query_key() {
+    let (base_url, captured) = serve_once(
+        "200 OK",
+        "text/event-stream",
+        concat!(
+            r#"data: {"candidates":[{"content":{"role":"model","parts":[{"text":"Hel"}]}}],"usageMetadata":{"promptTokenCount":12,"candidatesTokenCount":1}}"#,
+            "\n\n",
+            r#"data: {"candidates":[{"content":{"role":"model","parts":[{"text":"lo"}]},"finishReason":"MAX_TOKENS"}],"usageMetadata":{"promptTokenCount":12,"candidatesTokenCount":5}}"#,
+            "\n\n"
+        ),
+    )
+    .await;
+
+    let adapter = AdapterFactory::create_adapter(
+        AdapterConfig::new("gemini".to_string())
+            .with_api_key("gm-test".to_string())
+            .with_model("gemini-test".to_string())
+            .with_base_url(base_url)
+            .with_metadata("auth_type".to_string(), serde_json::json!("query")),
+    )
+    .unwrap();
+    let chunks: Vec<_> = adapter
+        .invoke_stream("Hi", &InvokeOptions::default())
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+
+    let text: String = chunks.iter().map(|c| c.delta.as_str()).collect();
+    assert_eq!(text, "Hello");
+    let last = chunks.last().unwrap();
+    assert_eq!(last.finish_reason.as_deref(), Some("length"));
+    let usage = last.usage.clone().unwrap();
+    assert_eq!((usage.prompt_tokens, usage.completion_tokens), (12, 5));
+
+    let captured = captured.await.unwrap();
+    assert!(captured
+        .head
+        .starts_with("post /v1beta/models/gemini-test:streamgeneratecontent?alt=sse&key=gm-test "));
+    assert!(!captured.head.contains("x-goog-api-key"));
+    assert_eq!(captured.body["contents"][0]["parts"][0]["text"], "Hi");
+}
+
+#[tokio::test]
+async fn test_factory_creates_gemini_adapter() {
+    let adapter = AdapterFactory::create_adapter(
+        AdapterConfig::new("gemini".to_string()).with_api_key("gm-test".to_string()),
+    )
+    .unwrap();
+    assert_eq!(adapter.name(), "gemini");
+    assert!(adapter.describe().await.contains("gemini-2.0-flash"));
+}
@@ -0,0 +1,239 @@
+mod common;
+
+use async_trait::async_trait;
+use llm_adapter::billing::{BillingConfig, BillingTracker};
+use llm_adapter::guard::{ConcurrencyConfig, ConcurrencyGuard};
+use llm_adapter::providers::ZhipuAdapter;
+use llm_adapter::rate_limit::{RateLimitConfig, RateLimiter};
+use llm_adapter::{
+    Adapter, AdapterConfig, AdapterError, AdapterFactory, AdapterRegistry, HealthCheckConfig,
+    HealthMonitor, HealthProbe, RetryPolicy, WrappedAdapter,
+};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 记录探测次数的适配器
+struct ProbedAdapter {
+    healthy: AtomicBool,
+    failing: AtomicBool,
+    probe_delay: Duration,
+    probes: AtomicU32,
+}
+
+impl ProbedAdapter {
+    fn new(probe_delay: Duration) -> Arc<Self> {
+        Arc::new(Self {
+            healthy: AtomicBool::new(true),
+            failing: AtomicBool::new(false),
+            probe_delay,
+            probes: AtomicU32::new(0),
+        })
+    }
+}
+
+#[async_trait]
+impl Adapter for ProbedAdapter {
+    fn name(&self) -> &str {
+        "probed"
+    }
+
+    async fn describe(&self) -> String {
+        "Probed adapter".to_string()
+    }
+
+    async fn invoke(&self, prompt: &str) -> anyhow::Result<String> {
+        if self.failing.load(Ordering::SeqCst) {
+            return Err(AdapterError::Timeout("slow upstream".to_string()).into());
+        }
+        Ok(format!("echo: {}", prompt))
+    }
+
+    async fn health(&self) -> bool {
+        self.probes.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(self.probe_delay).await;
+        self.healthy.load(Ordering::SeqCst)
+    }
+}
+
+fn wrap(adapter: Arc<ProbedAdapter>, config: HealthCheckConfig) -> WrappedAdapter {
+    WrappedAdapter::new(
+        adapter,
+        Arc::new(RateLimiter::new(RateLimitConfig {
+            enabled: false,
+            ..Default::default()
+        })),
+        Arc::new(BillingTracker::new(BillingConfig::default())),
+        Arc::new(ConcurrencyGuard::new(ConcurrencyConfig::default())),
+    )
+    .with_retry_policy(Arc::new(RetryPolicy::disabled()))
+    .with_health_monitor(Arc::new(HealthMonitor::new(config)))
+}
+
+#[tokio::test]
+async fn test_probe_result_is_cached_for_ttl() {
+    let adapter = ProbedAdapter::new(Duration::ZERO);
+    let wrapped = wrap(
+        adapter.clone(),
+        HealthCheckConfig {
+            ttl: Duration::from_millis(50),
+            ..Default::default()
+        },
+    );
+
+    assert!(wrapped.health().await);
+    adapter.healthy.store(false, Ordering::SeqCst);
+    assert!(wrapped.health().await);
+    assert_eq!(adapter.probes.load(Ordering::SeqCst), 1);
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert!(!wrapped.health().await);
+    assert_eq!(adapter.probes.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_probe_timeout_is_unhealthy() {
+    let adapter = ProbedAdapter::new(Duration::from_secs(10));
+    let wrapped = wrap(
+        adapter,
+        HealthCheckConfig {
+            timeout: Duration::from_secs(1),
+            ..Default::default()
+        },
+    );
+
+    assert!(!wrapped.health().await);
+    let (healthy, _) = wrapped.health_monitor().last_probe().unwrap();
+    assert!(!healthy);
+}
+
+#[tokio::test]
+async fn test_recent_errors_mark_adapter_unhealthy() {
+    let adapter = ProbedAdapter::new(Duration::ZERO);
+    let wrapped = wrap(
+        adapter.clone(),
+        HealthCheckConfig {
+            min_requests: 4,
+            error_rate_threshold: 0.5,
+            window: Duration::from_millis(100),
+            ..Default::default()
+        },
+    );
+
+    wrapped.invoke("Hello").await.unwrap();
+    adapter.failing.store(true, Ordering::SeqCst);
+    for _ in 0..3 {
+        assert!(wrapped.invoke("Hello").await.is_err());
+    }
+
+    let monitor = wrapped.health_monitor();
+    assert_eq!(monitor.error_rate(), Some(0.75));
+    assert!(!wrapped.health().await);
+    assert_eq!(adapter.probes.load(Ordering::SeqCst), 0);
+
+    // 窗口过期后恢复
+    tokio::time::sleep(Duration::from_millis(120)).await;
+    assert_eq!(monitor.error_rate(), None);
+    assert!(wrapped.health().await);
+}
+
+#[tokio::test]
+async fn test_generic_adapter_models_probe() {
+    // 未配置 health_endpoint 时不主动探测
+    let config = AdapterConfig::new("unreachable".to_string())
+        .with_api_key("test-key".to_string())
+        .with_base_url("http://127.0.0.1:1".to_string());
+    let adapter = AdapterFactory::create_adapter(config.clone()).unwrap();
+    assert!(adapter.health().await);
+
+    let config = config
+        .with_metadata(
+            "health_endpoint".to_string(),
+            serde_json::json!("/v1/models"),
+        )
+        .with_metadata("health_check".to_string(), serde_json::json!("models"));
+    let adapter = AdapterFactory::create_adapter(config.clone()).unwrap();
+    assert!(!adapter.health().await);
+
+    // 无法识别的取值不主动探测
+    let adapter = AdapterFactory::create_adapter(
+        config
+            .clone()
+            .with_metadata("health_check".to_string(), serde_json::json!("model")),
+    )
+    .unwrap();
+    assert!(adapter.health().await);
+
+    let config = config.with_metadata("health_check".to_string(), serde_json::json!("none"));
+    let adapter = AdapterFactory::create_adapter(config).unwrap();
+    assert!(adapter.health().await);
+}
+
+#[tokio::test]
+async fn test_builtin_provider_is_passive_by_default() {
+    let config = AdapterConfig::new("anthropic".to_string())
+        .with_api_key("test-key".to_string())
+        .with_base_url("http://127.0.0.1:1".to_string());
+    let adapter = AdapterFactory::create_adapter(config.clone()).unwrap();
+    assert!(adapter.health().await);
+
+    let config = config.with_metadata("health_check".to_string(), serde_json::json!("models"));
+    let adapter = AdapterFactory::create_adapter(config).unwrap();
+    assert!(!adapter.health().await);
+}
+
+#[tokio::test]
+async fn test_provider_without_models_endpoint_is_passive_by_default() {
+    // 智谱没有模型列表接口，默认不发送补全请求
+    let adapter = ZhipuAdapter::new_with_base(
+        "sk-test".to_string(),
+        "glm-4".to_string(),
+        "http://127.0.0.1:1".to_string(),
+    );
+    assert!(adapter.health().await);
+    assert!(
+        !adapter
+            .with_health_probe(HealthProbe::Completion)
+            .health()
+            .await
+    );
+}
+
+#[tokio::test]
+async fn test_completion_probe_goes_through_wrapper() {
+    let adapter = ProbedAdapter::new(Duration::ZERO);
+    let (wrapped, billing_tracker) = common::wrap(adapter.clone(), BillingConfig::default());
+    let wrapped = wrapped.with_health_probe(HealthProbe::Completion);
+
+    assert!(wrapped.health().await);
+    // 补全探测不调用内层的 health()，按正常调用计费
+    assert_eq!(adapter.probes.load(Ordering::SeqCst), 0);
+    let record = billing_tracker.recent_records("probed").pop().unwrap();
+    assert!(record.total_cost > 0.0);
+
+    adapter.failing.store(true, Ordering::SeqCst);
+    wrapped.health_monitor().invalidate();
+    assert!(!wrapped.health().await);
+}
+
+#[tokio::test]
+async fn test_registry_health_report() {
+    let registry = AdapterRegistry::new();
+    let config = AdapterConfig::new("probe".to_string())
+        .with_api_key("test-key".to_string())
+        .with_base_url("http://127.0.0.1:1".to_string())
+        .with_metadata("health_check".to_string(), serde_json::json!("none"))
+        .with_metadata("health_check_ttl_ms".to_string(), serde_json::json!(1000));
+    registry.register_from_config(config).await.unwrap();
+
+    let monitor = registry.get_health_monitor("probe").unwrap();
+    assert_eq!(monitor.config().ttl, Duration::from_millis(1000));
+
+    let report = registry.health_report("probe", false).await.unwrap();
+    assert!(report.healthy);
+    assert_eq!(report.probe_healthy, Some(true));
+    assert!(report.passive_healthy);
+    assert_eq!(report.circuit.as_deref(), Some("closed"));
+
+    assert!(registry.health_report("missing", true).await.is_none());
+}
@@ -0,0 +1,219 @@
+mod common;
+
+use common::{serve_json_once, wrap};
+use llm_adapter::billing::BillingConfig;
+use llm_adapter::providers::{
+    DeepSeekAdapter, DoubaoAdapter, MockAdapter, OpenAIAdapter, QianwenAdapter, ZhipuAdapter,
+};
+use llm_adapter::{
+    Adapter, AdapterError, ChatMessage, ChatRequest, ContentPart, InvokeOptions, MediaSource,
+};
+use std::sync::Arc;
+
+const OPENAI_OK: &str = r#"{"choices":[{"message":{"role":"assistant","content":"a scanned invoice"},"finish_reason":"stop"}]}"#;
+
+fn screenshot_request() -> ChatRequest {
+    ChatRequest::new(vec![
+        ChatMessage::system("You review documents."),
+        ChatMessage::user("What is on this page?")
+            .with_part(ContentPart::image_url("https://example.com/page.png").with_detail("high"))
+            .with_part(ContentPart::image_base64("image/png", "iVBORw0KGgo=")),
+    ])
+}
+
+fn assert_invalid_request(err: anyhow::Error, message: &str) {
+    match err.downcast_ref::<AdapterError>() {
+        Some(AdapterError::InvalidRequest(m)) => assert!(m.contains(message), "{}", m),
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_content_part_serialization() {
+    let message = ChatMessage::user("Listen").with_parts(vec![
+        ContentPart::image_url("https://example.com/a.png").with_detail("low"),
+        ContentPart::audio_base64("audio/wav", "UklGRg=="),
+    ]);
+    let value = serde_json::to_value(&message).unwrap();
+    assert_eq!(
+        value["parts"],
+        serde_json::json!([
+            {"type": "image", "source": {"type": "url", "url": "https://example.com/a.png"}, "detail": "low"},
+            {"type": "audio", "source": {"type": "base64", "media_type": "audio/wav", "data": "UklGRg=="}}
+        ])
+    );
+    let parsed: ChatMessage = serde_json::from_value(value).unwrap();
+    assert_eq!(parsed, message);
+    assert!(parsed.has_media());
+
+    // 没有附带内容时不输出 parts 字段
+    let value = serde_json::to_value(ChatMessage::user("Hi")).unwrap();
+    assert!(value.get("parts").is_none());
+
+    let source = MediaSource::Base64 {
+        media_type: "image/jpeg".to_string(),
+        data: "/9j/".to_string(),
+    };
+    assert_eq!(source.to_url(), "data:image/jpeg;base64,/9j/");
+}
+
+#[tokio::test]
+async fn test_openai_adapter_sends_content_parts() {
+    let (base_url, captured) = serve_json_once(OPENAI_OK).await;
+
+    let adapter =
+        OpenAIAdapter::new_with_base("sk-test".to_string(), "gpt-4o".to_string(), base_url);
+    let request = screenshot_request().with_message(
+        ChatMessage::user("").with_part(ContentPart::audio_base64("audio/mpeg", "SUQz")),
+    );
+    let result = adapter
+        .chat(&request, &InvokeOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(result.content, "a scanned invoice");
+
+    let body = captured.await.unwrap().body;
+    assert_eq!(
+        body["messages"],
+        serde_json::json!([
+            {"role": "system", "content": "You review documents."},
+            {"role": "user", "content": [
+                {"type": "text", "text": "What is on this page?"},
+                {"type": "image_url", "image_url": {"url": "https://example.com/page.png", "detail": "high"}},
+                {"type": "image_url", "image_url": {"url": "data:image/png;base64,iVBORw0KGgo="}}
+            ]},
+            {"role": "user", "content": [
+                {"type": "input_audio", "input_audio": {"data": "SUQz", "format": "mp3"}}
+            ]}
+        ])
+    );
+
+    // OpenAI 只接受 base64 音频
+    let err = adapter
+        .chat(
+            &ChatRequest::new(vec![ChatMessage::user("Transcribe")
+                .with_part(ContentPart::audio_url("https://example.com/a.wav"))]),
+            &InvokeOptions::default(),
+        )
+        .await
+        .unwrap_err();
+    assert_invalid_request(err, "only accepts base64 audio");
+}
+
+#[tokio::test]
+async fn test_doubao_and_zhipu_vision_requests() {
+    let (base_url, captured) = serve_json_once(OPENAI_OK).await;
+    let adapter = DoubaoAdapter::new_with_base(
+        "sk-test".to_string(),
+        "doubao-1.5-vision-pro".to_string(),
+        base_url,
+    );
+    adapter
+        .chat(&screenshot_request(), &InvokeOptions::default())
+        .await
+        .unwrap();
+    let captured = captured.await.unwrap();
+    assert!(captured.head.starts_with("post /v1/chat/completions "));
+    assert_eq!(
+        captured.body["messages"][1]["content"][2]["image_url"]["url"],
+        "data:image/png;base64,iVBORw0KGgo="
+    );
+
+    // 智谱 GLM-4V 的 base64 图片不带 data URL 前缀
+    let (base_url, captured) = serve_json_once(OPENAI_OK).await;
+    let adapter =
+        ZhipuAdapter::new_with_base("sk-test".to_string(), "glm-4v-plus".to_string(), base_url);
+    adapter
+        .chat(&screenshot_request(), &InvokeOptions::default())
+        .await
+        .unwrap();
+    let captured = captured.await.unwrap();
+    assert!(captured.head.starts_with("post /v4/chat/completions "));
+    assert_eq!(
+        captured.body["messages"][1]["content"][1]["image_url"]["url"],
+        "https://example.com/page.png"
+    );
+    assert_eq!(
+        captured.body["messages"][1]["content"][2]["image_url"]["url"],
+        "iVBORw0KGgo="
+    );
+    assert_eq!(
+        captured.body["messages"][0]["content"],
+        "You review documents."
+    );
+
+    let err = adapter
+        .chat(
+            &ChatRequest::new(vec![ChatMessage::user("Hi")
+                .with_part(ContentPart::audio_base64("audio/wav", "UklGRg=="))]),
+            &InvokeOptions::default(),
+        )
+        .await
+        .unwrap_err();
+    assert_invalid_request(err, "Zhipu does not support audio");
+}
+
+#[tokio::test]
+async fn test_qianwen_uses_multimodal_endpoint() {
+    let (base_url, captured) = serve_json_once(concat!(
+        r#"{"output":{"choices":[{"finish_reason":"stop","message":{"role":"assistant","#,
+        r#""content":[{"text":"a scanned "},{"text":"invoice"}]}}]},"#,
+        r#""usage":{"input_tokens":1210,"output_tokens":4},"request_id":"req-1"}"#
+    ))
+    .await;
+
+    let adapter =
+        QianwenAdapter::new_with_base("sk-test".to_string(), "qwen-vl-max".to_string(), base_url);
+    let result = adapter
+        .chat(&screenshot_request(), &InvokeOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(result.content, "a scanned invoice");
+    assert_eq!(result.usage.unwrap().prompt_tokens, 1210);
+
+    let captured = captured.await.unwrap();
+    assert!(captured
+        .head
+        .starts_with("post /v1/services/aigc/multimodal-generation/generation "));
+    assert_eq!(
+        captured.body["input"]["messages"],
+        serde_json::json!([
+            {"role": "system", "content": [{"text": "You review documents."}]},
+            {"role": "user", "content": [
+                {"text": "What is on this page?"},
+                {"image": "https://example.com/page.png"},
+                {"image": "data:image/png;base64,iVBORw0KGgo="}
+            ]}
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_text_only_adapters_reject_media() {
+    let adapter = DeepSeekAdapter::new("sk-test".to_string(), None);
+    let err = adapter
+        .chat(&screenshot_request(), &InvokeOptions::default())
+        .await
+        .unwrap_err();
+    assert_invalid_request(err, "DeepSeek does not support image or audio input");
+}
+
+#[tokio::test]
+async fn test_wrapped_adapter_estimates_image_tokens() {
+    let (wrapped, billing_tracker) = wrap(
+        Arc::new(MockAdapter::new("mock".to_string())),
+        BillingConfig::default(),
+    );
+
+    let request = ChatRequest::new(vec![ChatMessage::user("")
+        .with_part(ContentPart::image_url("https://example.com/page.png"))
+        .with_part(ContentPart::image_url("https://example.com/thumb.png").with_detail("low"))]);
+    let response = wrapped
+        .chat(&request, &InvokeOptions::default())
+        .await
+        .unwrap();
+    assert!(response.content.ends_with("[2 attachments]"));
+
+    let stats = billing_tracker.get_adapter_stats("mock").unwrap();
+    assert_eq!(stats.total_input_tokens, 765 + 85);
+}
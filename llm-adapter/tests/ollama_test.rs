@@ -0,0 +1,240 @@
+mod common;
+
+use common::{serve_json_once, serve_once};
+use futures::StreamExt;
+use llm_adapter::providers::OllamaAdapter;
+use llm_adapter::{
+    Adapter, AdapterConfig, AdapterFactory, AdapterRegistry, ChatMessage, ChatRequest,
+    InvokeOptions, ModelPrice, ToolCall, ToolDefinition,
+};
+
+#[tokio::test]
+async fn test_chat_maps_messages_options_and_keep_alive() {
+    let (base_url, captured) = serve_once(
+        "200 OK",
+        "application/json",
+        concat!(
+            r#"{"model":"qwen2.5:7b","created_at":"2025-01-01T00:00:00Z","#,
+            r#""message":{"role":"assistant","content":"","tool_calls":[{"function":{"name":"weather","arguments":{"city":"Paris"}}}]},"#,
+            r#""done":true,"done_reason":"stop","prompt_eval_count":26,"eval_count":9}"#
+        ),
+    )
+    .await;
+
+    let request = ChatRequest::new(vec![
+        ChatMessage::system("You are terse."),
+        ChatMessage::user("Weather in Berlin?"),
+        ChatMessage::assistant("").with_tool_calls(vec![ToolCall::new(
+            "call_0",
+            "weather",
+            r#"{"city":"Berlin"}"#,
+        )]),
+        ChatMessage::tool("call_0", "Sunny"),
+        ChatMessage::user("And Paris?"),
+    ])
+    .with_tools(vec![ToolDefinition::new(
+        "weather",
+        "Current weather",
+        serde_json::json!({"type": "object", "properties": {"city": {"type": "string"}}}),
+    )]);
+    let options = InvokeOptions {
+        max_tokens: Some(64),
+        temperature: Some(0.2),
+        ..Default::default()
+    };
+
+    let adapter = OllamaAdapter::new_with_base("qwen2.5:7b".to_string(), base_url)
+        .with_keep_alive(serde_json::json!("10m"));
+    let result = adapter.chat(&request, &options).await.unwrap();
+    assert_eq!(result.model.as_deref(), Some("qwen2.5:7b"));
+    assert_eq!(result.finish_reason.as_deref(), Some("tool_calls"));
+    assert_eq!(result.tool_calls[0].id, "call_0");
+    assert_eq!(
+        result.tool_calls[0].parse_arguments().unwrap(),
+        serde_json::json!({"city": "Paris"})
+    );
+    let usage = result.usage.unwrap();
+    assert_eq!((usage.prompt_tokens, usage.completion_tokens), (26, 9));
+
+    let captured = captured.await.unwrap();
+    assert!(captured.head.starts_with("post /api/chat "));
+    assert!(!captured.head.contains("authorization"));
+
+    let body = captured.body;
+    assert_eq!(body["stream"], false);
+    assert_eq!(body["keep_alive"], "10m");
+    assert_eq!(body["options"]["num_predict"], 64);
+    assert_eq!(body["tools"][0]["function"]["name"], "weather");
+    let messages = body["messages"].as_array().unwrap();
+    assert_eq!(messages[0]["role"], "system");
+    assert_eq!(
+        messages[2]["tool_calls"][0],
+        serde_json::json!({"function": {"name": "weather", "arguments": {"city": "Berlin"}}})
+    );
+    assert_eq!(messages[3]["role"], "tool");
+    assert_eq!(messages[3]["tool_name"], "weather");
+}
+
+#[tokio::test]
+async fn test_chat_stream_parses_ndjson() {
+    let (base_url, captured) = serve_once(
+        "200 OK",
+        "application/x-ndjson",
+        concat!(
+            r#"{"model":"llama3.2","message":{"role":"assistant","content":"Hel"},"done":false}"#,
+            "\n",
+            r#"{"model":"llama3.2","message":{"role":"assistant","content":"lo"},"done":false}"#,
+            "\n",
+            r#"{"model":"llama3.2","message":{"role":"assistant","content":""},"done":true,"done_reason":"length","prompt_eval_count":12,"eval_count":5}"#,
+            "\n"
+        ),
+    )
+    .await;
+
+    let chunks: Vec<_> = OllamaAdapter::new_with_base("llama3.2".to_string(), base_url)
+        .invoke_stream("Hi", &InvokeOptions::default())
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+
+    let text: String = chunks.iter().map(|c| c.delta.as_str()).collect();
+    assert_eq!(text, "Hello");
+    assert!(chunks[0].usage.is_none());
+    let last = chunks.last().unwrap();
+    assert_eq!(last.finish_reason.as_deref(), Some("length"));
+    let usage = last.usage.clone().unwrap();
+    assert_eq!((usage.prompt_tokens, usage.completion_tokens), (12, 5));
+
+    assert_eq!(captured.await.unwrap().body["stream"], true);
+}
+
+#[tokio::test]
+async fn test_list_models() {
+    let (base_url, captured) = serve_once(
+        "200 OK",
+        "application/json",
+        r#"{"models":[{"name":"llama3.2:latest","size":2019393189},{"name":"qwen2.5:7b"}]}"#,
+    )
+    .await;
+    let models = OllamaAdapter::new_with_base("llama3.2".to_string(), base_url)
+        .list_models()
+        .await
+        .unwrap();
+    assert_eq!(models, vec!["llama3.2:latest", "qwen2.5:7b"]);
+    assert!(captured.await.unwrap().head.starts_with("get /api/tags "));
+
+    // llama.cpp / vLLM 走 OpenAI 兼容的 /v1/models，未配置 Key 时不带认证头
+    let (base_url, captured) = serve_once(
+        "200 OK",
+        "application/json",
+        r#"{"object":"list","data":[{"id":"Qwen/Qwen2.5-7B-Instruct","object":"model"}]}"#,
+    )
+    .await;
+    let adapter = AdapterFactory::create_adapter(
+        AdapterConfig::new("vllm".to_string()).with_base_url(base_url),
+    )
+    .unwrap();
+    assert_eq!(
+        adapter.list_models().await.unwrap(),
+        vec!["Qwen/Qwen2.5-7B-Instruct"]
+    );
+    let captured = captured.await.unwrap();
+    assert!(captured.head.starts_with("get /v1/models "));
+    assert!(!captured.head.contains("authorization"));
+
+    let adapter = AdapterFactory::create_adapter(
+        AdapterConfig::new("custom".to_string())
+            .with_api_key("test-key".to_string())
+            .with_metadata("health_check".to_string(), serde_json::json!("none")),
+    )
+    .unwrap();
+    assert!(adapter.list_models().await.is_err());
+}
+
+#[tokio::test]
+async fn test_local_adapters_need_no_key_and_bill_zero() {
+    let (base_url, _) = serve_once(
+        "200 OK",
+        "application/json",
+        r#"{"model":"llama3.2","message":{"role":"assistant","content":"Hi"},"done":true,"prompt_eval_count":100,"eval_count":50}"#,
+    )
+    .await;
+
+    let registry = AdapterRegistry::new();
+    registry
+        .register_from_config(
+            AdapterConfig::new("ollama".to_string())
+                .with_base_url(base_url)
+                .with_metadata("health_check".to_string(), serde_json::json!("none")),
+        )
+        .await
+        .unwrap();
+    let adapter = registry.get("ollama").await.unwrap();
+    assert_eq!(adapter.invoke("Hello").await.unwrap(), "Hi");
+
+    let stats = registry
+        .get_billing_tracker("ollama")
+        .unwrap()
+        .get_adapter_stats("ollama")
+        .unwrap();
+    assert_eq!(stats.total_input_tokens, 100);
+    assert_eq!(stats.total_cost, 0.0);
+
+    // 通用适配器在 auth_type 为 none 时同样可以不配置 Key
+    assert!(AdapterFactory::create_adapter(
+        AdapterConfig::new("self-hosted".to_string())
+            .with_metadata("auth_type".to_string(), serde_json::json!("none")),
+    )
+    .is_ok());
+    assert!(AdapterFactory::create_adapter(AdapterConfig::new("self-hosted".to_string())).is_err());
+}
+
+#[tokio::test]
+async fn test_openai_compatible_local_adapter_bills_under_own_name() {
+    let (base_url, _) = serve_json_once(
+        r#"{"model":"gpt-4o","choices":[{"message":{"content":"Hi"},"finish_reason":"stop"}],"usage":{"prompt_tokens":100,"completion_tokens":50,"total_tokens":150}}"#,
+    )
+    .await;
+
+    let registry = AdapterRegistry::new();
+    // openai 的目录价格不应套用到本地服务上
+    registry.pricing().set_prices(vec![ModelPrice {
+        adapter: "openai".to_string(),
+        model: "gpt-4o".to_string(),
+        input_price_per_1k: 2.5,
+        output_price_per_1k: 10.0,
+        cached_input_price_per_1k: None,
+        currency: "USD".to_string(),
+        effective_from: None,
+        effective_until: None,
+    }]);
+    registry
+        .register_from_config(
+            AdapterConfig::new("vllm".to_string())
+                .with_model("gpt-4o".to_string())
+                .with_base_url(base_url)
+                .with_metadata("health_check".to_string(), serde_json::json!("none")),
+        )
+        .await
+        .unwrap();
+    let adapter = registry.get("vllm").await.unwrap();
+    assert_eq!(adapter.name(), "vllm");
+    assert_eq!(adapter.invoke("Hello").await.unwrap(), "Hi");
+
+    let tracker = registry.get_billing_tracker("vllm").unwrap();
+    let stats = tracker.get_adapter_stats("vllm").unwrap();
+    assert_eq!(stats.total_input_tokens, 100);
+    assert_eq!(stats.total_cost, 0.0);
+    assert!(tracker.get_adapter_stats("openai").is_none());
+
+    let records = registry
+        .usage_records(Some("vllm"), None, None)
+        .await
+        .unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].adapter_name, "vllm");
+}
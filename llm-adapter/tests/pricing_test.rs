@@ -0,0 +1,173 @@
+use chrono::{Duration, TimeZone, Utc};
+use llm_adapter::billing::{BillingConfig, BillingTracker};
+use llm_adapter::{BillingStore, ModelPrice, PricingCatalog, SqliteBillingStore, Usage};
+use std::sync::Arc;
+
+fn price(adapter: &str, model: &str, input: f64, output: f64) -> ModelPrice {
+    ModelPrice {
+        adapter: adapter.to_string(),
+        model: model.to_string(),
+        input_price_per_1k: input,
+        output_price_per_1k: output,
+        cached_input_price_per_1k: None,
+        currency: "USD".to_string(),
+        effective_from: None,
+        effective_until: None,
+    }
+}
+
+#[test]
+fn test_lookup_prefers_most_specific_match() {
+    let catalog = PricingCatalog::new(vec![
+        price("*", "*", 1.0, 1.0),
+        price("*", "gpt-4o*", 2.0, 2.0),
+        price("*", "gpt-4o-mini*", 3.0, 3.0),
+        price("openai", "gpt-4o-mini", 4.0, 4.0),
+        price("azure", "gpt-4o-mini", 5.0, 5.0),
+    ]);
+    let now = Utc::now();
+    let input_price = |adapter: &str, model: Option<&str>| {
+        catalog
+            .lookup(adapter, model, now)
+            .map(|price| price.input_price_per_1k)
+    };
+
+    assert_eq!(input_price("openai", Some("gpt-4o-mini")), Some(4.0));
+    assert_eq!(input_price("other", Some("gpt-4o-mini-2024")), Some(3.0));
+    assert_eq!(input_price("other", Some("gpt-4o")), Some(2.0));
+    assert_eq!(input_price("other", Some("claude")), Some(1.0));
+    assert_eq!(input_price("other", None), Some(1.0));
+
+    assert!(PricingCatalog::default()
+        .lookup("openai", Some("gpt-4o"), now)
+        .is_none());
+}
+
+#[test]
+fn test_lookup_respects_effective_dates() {
+    let cutover = Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap();
+    let catalog = PricingCatalog::new(vec![
+        ModelPrice {
+            effective_until: Some(cutover),
+            ..price("openai", "gpt-4o", 5.0, 15.0)
+        },
+        ModelPrice {
+            effective_from: Some(cutover),
+            ..price("openai", "gpt-4o", 2.5, 10.0)
+        },
+    ]);
+
+    let before = catalog
+        .lookup("openai", Some("gpt-4o"), cutover - Duration::seconds(1))
+        .unwrap();
+    assert_eq!(before.input_price_per_1k, 5.0);
+    let after = catalog.lookup("openai", Some("gpt-4o"), cutover).unwrap();
+    assert_eq!(after.input_price_per_1k, 2.5);
+}
+
+#[test]
+fn test_min_charge_tokens() {
+    let price = ModelPrice {
+        cached_input_price_per_1k: Some(0.5),
+        ..price("*", "*", 1.0, 2.0)
+    };
+    // 不足部分按输入价格补足
+    assert!((price.cost(100, 100, 0, 1000) - 1.1).abs() < 1e-9);
+    assert!((price.cost(1000, 1000, 500, 1000) - 2.75).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_tracker_uses_catalog_price_for_model() {
+    let catalog = Arc::new(PricingCatalog::new(vec![ModelPrice {
+        currency: "CNY".to_string(),
+        ..price("qianwen", "qwen-max", 20.0, 60.0)
+    }]));
+    let store = Arc::new(SqliteBillingStore::open_in_memory().unwrap());
+    let tracker = BillingTracker::new(BillingConfig {
+        input_price_per_1k: 1.0,
+        output_price_per_1k: 1.0,
+        min_charge_tokens: 100,
+        ..Default::default()
+    })
+    .with_pricing(catalog.clone())
+    .with_store(store.clone());
+
+    let record = tracker
+        .record_model_usage(
+            "qianwen".to_string(),
+            Some("qwen-max".to_string()),
+            None,
+            "req-1".to_string(),
+            &Usage::new(1000, 1000),
+            serde_json::json!({}),
+        )
+        .await
+        .unwrap();
+    assert!((record.total_cost - 80.0).abs() < 1e-9);
+    assert_eq!(record.currency, "CNY");
+
+    // 没有匹配价格的模型使用统一价格，并应用最低计费 token 数
+    let record = tracker
+        .record_model_usage(
+            "qianwen".to_string(),
+            Some("qwen-turbo".to_string()),
+            None,
+            "req-2".to_string(),
+            &Usage::new(10, 10),
+            serde_json::json!({}),
+        )
+        .await
+        .unwrap();
+    assert!((record.total_cost - 0.1).abs() < 1e-9);
+    assert_eq!(record.currency, "USD");
+
+    // 调价不影响已记录的费用
+    catalog.set_prices(vec![price("qianwen", "qwen-max", 1.0, 1.0)]);
+    let records = store.load("qianwen").await.unwrap();
+    assert_eq!(records[0].model.as_deref(), Some("qwen-max"));
+    assert_eq!(records[0].currency, "CNY");
+    assert!((records[0].total_cost - 80.0).abs() < 1e-9);
+    assert_eq!(
+        tracker
+            .price_at("qianwen", Some("qwen-max"), Utc::now())
+            .await
+            .input_price_per_1k,
+        1.0
+    );
+}
+
+#[tokio::test]
+async fn test_sqlite_store_migrates_old_schema() {
+    let path =
+        std::env::temp_dir().join(format!("llm-adapter-pricing-{}.db", uuid::Uuid::new_v4()));
+    {
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE usage_records (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    adapter_name TEXT NOT NULL,
+                    user_id TEXT,
+                    request_id TEXT NOT NULL,
+                    input_tokens INTEGER NOT NULL,
+                    output_tokens INTEGER NOT NULL,
+                    cached_tokens INTEGER NOT NULL DEFAULT 0,
+                    total_cost REAL NOT NULL,
+                    timestamp TEXT NOT NULL,
+                    metadata TEXT NOT NULL
+                );
+                INSERT INTO usage_records (adapter_name, request_id, input_tokens, output_tokens,
+                    total_cost, timestamp, metadata)
+                VALUES ('openai', 'old', 10, 10, 0.5, '2025-01-01T00:00:00.000000Z', '{}');",
+            )
+            .unwrap();
+    }
+
+    let store = SqliteBillingStore::open(&path).unwrap();
+    let records = store.load("openai").await.unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].model, None);
+    assert_eq!(records[0].currency, "USD");
+
+    std::fs::remove_file(path).unwrap();
+}
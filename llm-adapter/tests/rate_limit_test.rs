@@ -0,0 +1,117 @@
+use llm_adapter::rate_limit::{InMemoryRateLimitBackend, RateLimitConfig, RateLimiter};
+use llm_adapter::{AdapterConfig, AdapterFactory, AdapterRegistry};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn per_second(rps: u32, burst: Option<u32>) -> RateLimitConfig {
+    RateLimitConfig {
+        requests_per_second: rps,
+        requests_per_minute: 0,
+        requests_per_hour: 0,
+        burst,
+        enabled: true,
+    }
+}
+
+#[tokio::test]
+async fn test_burst_then_refill() {
+    let limiter = RateLimiter::new(per_second(10, Some(2)));
+
+    assert!(limiter.check("user").await.is_ok());
+    assert!(limiter.check("user").await.is_ok());
+    let err = limiter.check("user").await.unwrap_err();
+    assert!(err.retry_after() <= Duration::from_millis(100));
+    assert!(err.to_string().contains("10 requests per second"));
+
+    // 其他 key 不受影响
+    assert!(limiter.check("other").await.is_ok());
+
+    tokio::time::sleep(err.retry_after() + Duration::from_millis(5)).await;
+    assert!(limiter.check("user").await.is_ok());
+}
+
+#[tokio::test]
+async fn test_rejected_requests_do_not_consume_quota() {
+    let limiter = RateLimiter::new(RateLimitConfig {
+        requests_per_second: 20,
+        requests_per_minute: 2,
+        requests_per_hour: 0,
+        burst: Some(1),
+        enabled: true,
+    });
+
+    assert!(limiter.check("user").await.is_ok());
+    for _ in 0..5 {
+        assert!(limiter.check("user").await.is_err());
+    }
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert!(limiter.check("user").await.is_ok());
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    let err = limiter.check("user").await.unwrap_err();
+    assert!(err.to_string().contains("2 requests per minute"));
+}
+
+#[tokio::test]
+async fn test_update_config_applies_new_limits() {
+    let limiter = RateLimiter::new(per_second(1, None));
+    assert!(limiter.check("user").await.is_ok());
+    assert!(limiter.check("user").await.is_err());
+
+    limiter.update_config(per_second(100, Some(100)));
+    assert_eq!(limiter.config().requests_per_second, 100);
+
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    for _ in 0..50 {
+        assert!(limiter.check("user").await.is_ok());
+    }
+
+    limiter.update_config(RateLimitConfig {
+        enabled: false,
+        ..per_second(1, None)
+    });
+    assert!(limiter.check("user").await.is_ok());
+}
+
+#[tokio::test]
+async fn test_idle_keys_are_evicted() {
+    let backend =
+        Arc::new(InMemoryRateLimitBackend::new().with_cleanup_interval(Duration::from_millis(10)));
+    let limiter = RateLimiter::with_backend(per_second(100, Some(1)), backend.clone());
+
+    assert!(limiter.check("a").await.is_ok());
+    assert!(limiter.check("b").await.is_ok());
+    assert_eq!(backend.tracked_keys(), 2);
+
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert!(limiter.check("c").await.is_ok());
+    assert_eq!(backend.tracked_keys(), 1);
+}
+
+#[tokio::test]
+async fn test_registry_updates_limits_in_place() {
+    let registry = AdapterRegistry::new();
+    let config = AdapterConfig::new("limited".to_string())
+        .with_api_key("test-key".to_string())
+        .with_metadata("rate_limit_rps".to_string(), serde_json::json!(5));
+    registry.register_from_config(config.clone()).await.unwrap();
+
+    let limiter = registry.get_rate_limiter("limited").unwrap();
+    assert_eq!(limiter.config().requests_per_second, 5);
+
+    let mut metadata = HashMap::new();
+    metadata.insert("rate_limit_rps".to_string(), serde_json::json!(50));
+    metadata.insert("rate_limit_burst".to_string(), serde_json::json!(80));
+    assert!(registry.update_rate_limit("limited", AdapterFactory::rate_limit_config(&metadata)));
+    assert_eq!(limiter.config().requests_per_second, 50);
+    assert_eq!(limiter.config().burst, Some(80));
+    assert!(!registry.update_rate_limit("missing", RateLimitConfig::default()));
+
+    // 重新注册沿用同一个限流器
+    registry.register_from_config(config).await.unwrap();
+    let reused = registry.get_rate_limiter("limited").unwrap();
+    assert!(Arc::ptr_eq(&limiter, &reused));
+    assert_eq!(reused.config().requests_per_second, 5);
+}
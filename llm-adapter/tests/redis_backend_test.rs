@@ -0,0 +1,206 @@
+use llm_adapter::guard::{ConcurrencyConfig, ConcurrencyGuard};
+use llm_adapter::rate_limit::{RateLimitConfig, RateLimiter};
+use llm_adapter::redis_backend::{RedisConcurrencyBackend, RedisRateLimitBackend, RedisStore};
+use llm_adapter::{AdapterConfig, AdapterFactory, AdapterRegistry, Priority};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 需要本地 Redis，未设置 REDIS_URL 时跳过
+fn redis_store() -> Option<Arc<RedisStore>> {
+    let Ok(url) = std::env::var("REDIS_URL") else {
+        eprintln!("REDIS_URL not set, skipping");
+        return None;
+    };
+    let prefix = format!("llm_adapter_test:{}", uuid::Uuid::new_v4());
+    Some(Arc::new(RedisStore::open(&url, prefix).unwrap()))
+}
+
+fn per_second(rps: u32, burst: u32) -> RateLimitConfig {
+    RateLimitConfig {
+        requests_per_second: rps,
+        requests_per_minute: 0,
+        requests_per_hour: 0,
+        burst: Some(burst),
+        enabled: true,
+    }
+}
+
+#[tokio::test]
+async fn test_backend_selected_from_metadata() {
+    let mut metadata = HashMap::new();
+    metadata.insert("rate_limit_rps".to_string(), serde_json::json!(1));
+    assert_eq!(
+        AdapterFactory::create_rate_limiter(&metadata)
+            .unwrap()
+            .backend_kind(),
+        "memory"
+    );
+
+    metadata.insert("rate_limit_backend".to_string(), serde_json::json!("redis"));
+    metadata.insert(
+        "redis_url".to_string(),
+        serde_json::json!("redis://127.0.0.1:1"),
+    );
+    let limiter = AdapterFactory::create_rate_limiter(&metadata).unwrap();
+    assert_eq!(limiter.backend_kind(), "redis");
+
+    // Redis 不可达时退回进程内限流，限额仍然生效
+    assert!(limiter.check("user").await.is_ok());
+    assert!(limiter.check("user").await.is_err());
+}
+
+#[tokio::test]
+async fn test_registry_rejects_unusable_redis_backend() {
+    let registry = AdapterRegistry::new();
+    let config = |backend: &str, redis_url: &str| {
+        AdapterConfig::new("mock".to_string())
+            .with_api_key("test-key".to_string())
+            .with_metadata("rate_limit_rps".to_string(), serde_json::json!(2))
+            .with_metadata("rate_limit_backend".to_string(), serde_json::json!(backend))
+            .with_metadata("redis_url".to_string(), serde_json::json!(redis_url))
+    };
+
+    registry
+        .register_from_config(config("memory", "redis://127.0.0.1:1"))
+        .await
+        .unwrap();
+    let limiter = registry.get_rate_limiter("mock").unwrap();
+
+    // 地址无效或后端未知时注册失败，不会悄悄换成进程内限流
+    let err = registry
+        .register_from_config(config("redis", "not a url"))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("rate_limit_backend is redis"));
+    let err = registry
+        .register_from_config(config("memcached", "redis://127.0.0.1:1"))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Unknown rate_limit_backend"));
+    assert!(Arc::ptr_eq(
+        &registry.get_rate_limiter("mock").unwrap(),
+        &limiter
+    ));
+
+    // 请求的后端未变时重新注册沿用原有限流器
+    registry
+        .register_from_config(config("memory", "redis://127.0.0.1:1"))
+        .await
+        .unwrap();
+    assert!(Arc::ptr_eq(
+        &registry.get_rate_limiter("mock").unwrap(),
+        &limiter
+    ));
+}
+
+#[tokio::test]
+async fn test_concurrency_falls_back_when_redis_unavailable() {
+    let mut metadata = HashMap::new();
+    metadata.insert("max_concurrent".to_string(), serde_json::json!(1));
+    metadata.insert(
+        "concurrency_backend".to_string(),
+        serde_json::json!("redis"),
+    );
+    metadata.insert(
+        "redis_url".to_string(),
+        serde_json::json!("redis://127.0.0.1:1"),
+    );
+    let guard = AdapterFactory::create_concurrency_guard("unreachable", &metadata).unwrap();
+
+    let permit = guard.acquire(Priority::Normal).await.unwrap();
+    assert!(
+        tokio::time::timeout(Duration::from_millis(100), guard.acquire(Priority::Normal))
+            .await
+            .is_err()
+    );
+    drop(permit);
+    assert!(guard.acquire(Priority::Normal).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_redis_rate_limit_shared_across_instances() {
+    let Some(store) = redis_store() else {
+        return;
+    };
+    let first = RateLimiter::with_backend(
+        per_second(10, 2),
+        Arc::new(RedisRateLimitBackend::new(store.clone())),
+    );
+    let second = RateLimiter::with_backend(
+        per_second(10, 2),
+        Arc::new(RedisRateLimitBackend::new(store)),
+    );
+
+    assert!(first.check("user").await.is_ok());
+    assert!(second.check("user").await.is_ok());
+    let err = first.check("user").await.unwrap_err();
+    assert!(err.retry_after() <= Duration::from_millis(100));
+    assert!(second.check("other").await.is_ok());
+
+    tokio::time::sleep(err.retry_after() + Duration::from_millis(10)).await;
+    assert!(second.check("user").await.is_ok());
+}
+
+#[tokio::test]
+async fn test_redis_semaphore_shared_across_instances() {
+    let Some(store) = redis_store() else {
+        return;
+    };
+    let config = ConcurrencyConfig {
+        max_concurrent: 1,
+        ..Default::default()
+    };
+    let guard = |store: Arc<RedisStore>| {
+        let backend = RedisConcurrencyBackend::new(store, "shared", 1)
+            .with_poll_interval(Duration::from_millis(10));
+        ConcurrencyGuard::with_backend(config.clone(), Arc::new(backend))
+    };
+    let (first, second) = (guard(store.clone()), guard(store));
+
+    let permit = first.acquire(Priority::Normal).await.unwrap();
+    assert_eq!(first.available_permits(), 0);
+    assert!(
+        tokio::time::timeout(Duration::from_millis(100), second.acquire(Priority::Normal))
+            .await
+            .is_err()
+    );
+
+    drop(permit);
+    let permit = tokio::time::timeout(Duration::from_secs(1), second.acquire(Priority::Normal))
+        .await
+        .unwrap();
+    assert!(permit.is_ok());
+}
+
+#[tokio::test]
+async fn test_redis_expired_leases_are_reclaimed() {
+    let Some(store) = redis_store() else {
+        return;
+    };
+    let backend = RedisConcurrencyBackend::new(store.clone(), "crashed", 1)
+        .with_poll_interval(Duration::from_millis(10));
+    let guard = ConcurrencyGuard::with_backend(
+        ConcurrencyConfig {
+            max_concurrent: 1,
+            ..Default::default()
+        },
+        Arc::new(backend),
+    );
+
+    // 模拟崩溃进程遗留的过期租约
+    let client = redis::Client::open(std::env::var("REDIS_URL").unwrap()).unwrap();
+    let mut connection = client.get_multiplexed_async_connection().await.unwrap();
+    let _: i64 = redis::cmd("ZADD")
+        .arg(format!("{}:concurrency:{{crashed}}", store.prefix()))
+        .arg(0)
+        .arg("stale")
+        .query_async(&mut connection)
+        .await
+        .unwrap();
+
+    let permit = tokio::time::timeout(Duration::from_secs(1), guard.acquire(Priority::Normal))
+        .await
+        .unwrap();
+    assert!(permit.is_ok());
+}
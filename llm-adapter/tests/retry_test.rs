@@ -0,0 +1,165 @@
+mod common;
+
+use async_trait::async_trait;
+use llm_adapter::billing::{BillingConfig, BillingTracker};
+use llm_adapter::{
+    Adapter, AdapterError, AdapterFactory, RetryConfig, RetryPolicy, WrappedAdapter,
+};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 依次返回预设错误，用完后成功
+struct FlakyAdapter {
+    errors: Mutex<Vec<AdapterError>>,
+    calls: AtomicU32,
+}
+
+impl FlakyAdapter {
+    fn new(mut errors: Vec<AdapterError>) -> Self {
+        errors.reverse();
+        Self {
+            errors: Mutex::new(errors),
+            calls: AtomicU32::new(0),
+        }
+    }
+}
+
+#[async_trait]
+impl Adapter for FlakyAdapter {
+    fn name(&self) -> &str {
+        "flaky"
+    }
+
+    async fn describe(&self) -> String {
+        "Flaky adapter".to_string()
+    }
+
+    async fn invoke(&self, prompt: &str) -> anyhow::Result<String> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        match self.errors.lock().unwrap().pop() {
+            Some(err) => Err(err.into()),
+            None => Ok(format!("echo: {}", prompt)),
+        }
+    }
+
+    async fn health(&self) -> bool {
+        true
+    }
+}
+
+fn upstream_error() -> AdapterError {
+    AdapterError::Upstream5xx {
+        status: 502,
+        message: "bad gateway".to_string(),
+    }
+}
+
+fn wrap(adapter: Arc<FlakyAdapter>, config: RetryConfig) -> (WrappedAdapter, Arc<BillingTracker>) {
+    let (wrapped, billing_tracker) = common::wrap(adapter, BillingConfig::default());
+    (
+        wrapped.with_retry_policy(Arc::new(RetryPolicy::new(config))),
+        billing_tracker,
+    )
+}
+
+fn fast_retry() -> RetryConfig {
+    RetryConfig {
+        base_delay: Duration::from_millis(1),
+        jitter: false,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_retries_transient_errors_and_bills_once() {
+    let adapter = Arc::new(FlakyAdapter::new(vec![
+        upstream_error(),
+        AdapterError::Network("connection reset".to_string()),
+    ]));
+    let (wrapped, billing_tracker) = wrap(adapter.clone(), fast_retry());
+
+    let result = wrapped.invoke("Hello").await.unwrap();
+    assert_eq!(result, "echo: Hello");
+    assert_eq!(adapter.calls.load(Ordering::SeqCst), 3);
+
+    let stats = billing_tracker.get_adapter_stats("flaky").unwrap();
+    assert_eq!(stats.total_requests, 1);
+}
+
+#[tokio::test]
+async fn test_does_not_retry_non_retryable_errors() {
+    let adapter = Arc::new(FlakyAdapter::new(vec![AdapterError::Auth(
+        "bad key".to_string(),
+    )]));
+    let (wrapped, _) = wrap(adapter.clone(), fast_retry());
+
+    let err = wrapped.invoke("Hello").await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<AdapterError>(),
+        Some(AdapterError::Auth(_))
+    ));
+    assert_eq!(adapter.calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_gives_up_after_max_attempts() {
+    let adapter = Arc::new(FlakyAdapter::new(vec![upstream_error(); 5]));
+    let (wrapped, _) = wrap(
+        adapter.clone(),
+        RetryConfig {
+            max_attempts: 2,
+            ..fast_retry()
+        },
+    );
+
+    assert!(wrapped.invoke("Hello").await.is_err());
+    assert_eq!(adapter.calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_honors_retry_after() {
+    let adapter = Arc::new(FlakyAdapter::new(vec![AdapterError::RateLimited {
+        retry_after: Some(Duration::from_secs(5)),
+        message: "slow down".to_string(),
+    }]));
+    let (wrapped, _) = wrap(adapter.clone(), fast_retry());
+
+    let start = tokio::time::Instant::now();
+    wrapped.invoke("Hello").await.unwrap();
+    assert!(start.elapsed() >= Duration::from_secs(5));
+    assert_eq!(adapter.calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_backoff_grows_and_is_capped() {
+    let policy = RetryPolicy::new(RetryConfig {
+        base_delay: Duration::from_millis(100),
+        max_delay: Duration::from_millis(300),
+        jitter: false,
+        ..Default::default()
+    });
+    assert_eq!(policy.backoff(1), Duration::from_millis(100));
+    assert_eq!(policy.backoff(2), Duration::from_millis(200));
+    assert_eq!(policy.backoff(3), Duration::from_millis(300));
+}
+
+#[test]
+fn test_retry_policy_from_metadata() {
+    let mut metadata = HashMap::new();
+    metadata.insert("retry_max_attempts".to_string(), serde_json::json!(5));
+    metadata.insert("retry_base_delay_ms".to_string(), serde_json::json!(200));
+    metadata.insert("retry_jitter".to_string(), serde_json::json!(false));
+    metadata.insert("retry_on".to_string(), serde_json::json!(["rate_limited"]));
+
+    let policy = AdapterFactory::create_retry_policy(&metadata);
+    let config = policy.config();
+    assert_eq!(config.max_attempts, 5);
+    assert_eq!(config.base_delay, Duration::from_millis(200));
+    assert!(!config.jitter);
+    assert_eq!(config.retry_on, vec!["rate_limited".to_string()]);
+
+    let err = anyhow::Error::from(upstream_error());
+    assert_eq!(policy.retry_delay(1, &err), None);
+}
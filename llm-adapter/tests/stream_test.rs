@@ -0,0 +1,147 @@
+mod common;
+
+use common::serve_sse_once;
+use futures::StreamExt;
+use llm_adapter::billing::{BillingConfig, BillingTracker};
+use llm_adapter::guard::{ConcurrencyConfig, ConcurrencyGuard};
+use llm_adapter::providers::{MockAdapter, OpenAIAdapter};
+use llm_adapter::rate_limit::{RateLimitConfig, RateLimiter};
+use llm_adapter::stream::{parse_openai_chunk, SseDecoder};
+use llm_adapter::{Adapter, InvokeOptions, WrappedAdapter};
+use std::sync::Arc;
+
+#[test]
+fn test_sse_decoder_handles_split_events() {
+    let mut decoder = SseDecoder::new();
+
+    assert!(decoder.feed(b"data: {\"a\":").is_empty());
+    let events = decoder.feed(b"1}\n\n: keep-alive\n\ndata: second\r\n\r\n");
+    assert_eq!(events, vec!["{\"a\":1}".to_string(), "second".to_string()]);
+
+    assert!(decoder.feed(b"data: tail").is_empty());
+    assert_eq!(decoder.finish(), vec!["tail".to_string()]);
+}
+
+#[test]
+fn test_sse_decoder_keeps_multibyte_chars_across_chunks() {
+    let mut decoder = SseDecoder::new();
+    let bytes = "data: 你好\n\n".as_bytes();
+
+    assert!(decoder.feed(&bytes[..7]).is_empty());
+    let events = decoder.feed(&bytes[7..]);
+    assert_eq!(events, vec!["你好".to_string()]);
+}
+
+#[test]
+fn test_parse_openai_chunk() {
+    let chunk =
+        parse_openai_chunk(r#"{"choices":[{"delta":{"content":"Hi"},"finish_reason":null}]}"#)
+            .unwrap()
+            .unwrap();
+    assert_eq!(chunk.delta, "Hi");
+    assert!(chunk.finish_reason.is_none());
+
+    let usage_only = parse_openai_chunk(
+        r#"{"choices":[],"usage":{"prompt_tokens":3,"completion_tokens":5,"total_tokens":8}}"#,
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(usage_only.delta, "");
+    assert_eq!(usage_only.usage.unwrap().completion_tokens, 5);
+
+    assert!(parse_openai_chunk("[DONE]").unwrap().is_none());
+    assert!(parse_openai_chunk("not json").is_err());
+}
+
+#[tokio::test]
+async fn test_mock_adapter_stream_matches_invoke() {
+    let adapter = MockAdapter::new("mock".to_string());
+    let full = adapter.invoke("Hello there").await.unwrap();
+
+    let mut stream = adapter
+        .invoke_stream("Hello there", &InvokeOptions::default())
+        .await
+        .unwrap();
+
+    let mut collected = String::new();
+    let mut finish_reason = None;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.unwrap();
+        collected.push_str(&chunk.delta);
+        if chunk.finish_reason.is_some() {
+            finish_reason = chunk.finish_reason;
+        }
+    }
+
+    assert_eq!(collected, full);
+    assert_eq!(finish_reason.as_deref(), Some("stop"));
+}
+
+#[tokio::test]
+async fn test_wrapped_adapter_bills_after_stream_finishes() {
+    let billing_tracker = Arc::new(BillingTracker::new(BillingConfig::default()));
+    let guard = Arc::new(ConcurrencyGuard::new(ConcurrencyConfig {
+        max_concurrent: 1,
+        ..Default::default()
+    }));
+    let wrapped = WrappedAdapter::new(
+        Arc::new(MockAdapter::new("mock".to_string())),
+        Arc::new(RateLimiter::new(RateLimitConfig::default())),
+        billing_tracker.clone(),
+        guard.clone(),
+    );
+
+    let options = InvokeOptions {
+        user_id: Some("user1".to_string()),
+        ..Default::default()
+    };
+    let mut stream = wrapped.invoke_stream("Hello", &options).await.unwrap();
+
+    assert_eq!(guard.available_permits(), 0);
+    assert!(billing_tracker.get_adapter_stats("mock").is_none());
+
+    while let Some(chunk) = stream.next().await {
+        chunk.unwrap();
+    }
+
+    let stats = billing_tracker.get_adapter_stats("mock").unwrap();
+    assert_eq!(stats.total_requests, 1);
+    assert!(stats.total_output_tokens > 0);
+    assert!(billing_tracker.get_user_stats("user1").is_some());
+
+    drop(stream);
+    assert_eq!(guard.available_permits(), 1);
+}
+
+#[tokio::test]
+async fn test_openai_adapter_streams_sse_response() {
+    let base_url = serve_sse_once(concat!(
+        "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"},\"finish_reason\":null}]}\n\n",
+        "data: {\"choices\":[{\"delta\":{\"content\":\"Hel\"},\"finish_reason\":null}]}\n\n",
+        "data: {\"choices\":[{\"delta\":{\"content\":\"lo\"},\"finish_reason\":\"stop\"}]}\n\n",
+        "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":4,\"completion_tokens\":2,\"total_tokens\":6}}\n\n",
+        "data: [DONE]\n\n",
+    ))
+    .await;
+
+    let adapter =
+        OpenAIAdapter::new_with_base("sk-test".to_string(), "gpt-test".to_string(), base_url);
+    let mut stream = adapter
+        .invoke_stream("Hi", &InvokeOptions::default())
+        .await
+        .unwrap();
+
+    let mut content = String::new();
+    let mut finish_reason = None;
+    let mut usage = None;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.unwrap();
+        content.push_str(&chunk.delta);
+        finish_reason = chunk.finish_reason.or(finish_reason);
+        usage = chunk.usage.or(usage);
+    }
+
+    assert_eq!(content, "Hello");
+    assert_eq!(finish_reason.as_deref(), Some("stop"));
+    assert_eq!(usage.unwrap().total_tokens, 6);
+}
@@ -86,6 +86,12 @@ pub trait Postprocessor: Send + Sync {
         let _ = context;
         Ok(())
     }
+
+    /// 流式输出时对单个分片做处理，默认原样返回
+    fn post_process_chunk(&self, context: &ProcessingContext, chunk: &str) -> String {
+        let _ = context;
+        chunk.to_string()
+    }
 }
 
 pub struct AuditPostprocessor {
@@ -367,6 +373,17 @@ impl Postprocessor for PiiRedactionPostprocessor {
 
         Ok(())
     }
+
+    fn post_process_chunk(&self, context: &ProcessingContext, chunk: &str) -> String {
+        let redacted = self.redact_text(chunk);
+        if redacted != chunk {
+            debug!(
+                "PII redaction applied to stream chunk for request {}",
+                context.request_id
+            );
+        }
+        redacted
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -614,6 +631,14 @@ impl PostprocessorChain {
         Ok(())
     }
 
+    pub fn post_process_chunk(&self, context: &ProcessingContext, chunk: &str) -> String {
+        self.processors
+            .iter()
+            .fold(chunk.to_string(), |acc, processor| {
+                processor.post_process_chunk(context, &acc)
+            })
+    }
+
     pub fn list_processors(&self) -> Vec<String> {
         self.processors
             .iter()
@@ -627,3 +652,67 @@ impl Default for PostprocessorChain {
         Self::new()
     }
 }
+
+/// 流式输出分片缓冲：只在不可能属于敏感信息的字符处切分，
+/// 避免邮箱、手机号等被分片截断后漏过脱敏
+pub struct StreamingOutputBuffer {
+    pending: String,
+    max_pending_chars: usize,
+}
+
+impl StreamingOutputBuffer {
+    pub fn new() -> Self {
+        Self::with_max_pending(256)
+    }
+
+    pub fn with_max_pending(max_pending_chars: usize) -> Self {
+        Self {
+            pending: String::new(),
+            max_pending_chars,
+        }
+    }
+
+    /// 追加一个增量，返回可以安全下发的片段
+    pub fn push(&mut self, delta: &str) -> Option<String> {
+        self.pending.push_str(delta);
+
+        let split_at = match self.pending.rfind(|c| !is_pii_token_char(c)) {
+            Some(idx) => {
+                idx + self.pending[idx..]
+                    .chars()
+                    .next()
+                    .map(char::len_utf8)
+                    .unwrap_or(0)
+            }
+            None if self.pending.chars().count() > self.max_pending_chars => self.pending.len(),
+            None => return None,
+        };
+
+        let rest = self.pending.split_off(split_at);
+        let ready = std::mem::replace(&mut self.pending, rest);
+        if ready.is_empty() {
+            None
+        } else {
+            Some(ready)
+        }
+    }
+
+    /// 流结束时取出剩余内容
+    pub fn finish(&mut self) -> Option<String> {
+        if self.pending.is_empty() {
+            None
+        } else {
+            Some(std::mem::take(&mut self.pending))
+        }
+    }
+}
+
+fn is_pii_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '%' | '+' | '-' | '@')
+}
+
+impl Default for StreamingOutputBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -6,7 +6,7 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::info;
 
-pub use llm_adapter::AdapterConfig;
+pub use llm_adapter::{AdapterConfig, Budget, ModelPrice};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Config {
@@ -15,6 +15,10 @@ pub struct Config {
     pub prompts: HashMap<String, PromptConfig>,
     pub feature_flags: HashMap<String, serde_json::Value>,
     pub routing_rules: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub budgets: Vec<Budget>,
+    #[serde(default)]
+    pub pricing: Vec<ModelPrice>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -41,6 +45,8 @@ impl ConfigManager {
             prompts: HashMap::new(),
             feature_flags: HashMap::new(),
             routing_rules: Vec::new(),
+            budgets: Vec::new(),
+            pricing: Vec::new(),
         };
 
         Self {
@@ -1,6 +1,6 @@
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
@@ -39,6 +39,8 @@ pub struct ModelRouter {
     rules: Arc<RwLock<Vec<RoutingRule>>>,
     round_robin_index: Arc<DashMap<String, usize>>,
     connection_counts: Arc<RwLock<HashMap<String, u64>>>,
+    /// 健康检查失败的适配器，路由时跳过
+    unhealthy_adapters: Arc<RwLock<HashSet<String>>>,
 }
 
 impl ModelRouter {
@@ -47,6 +49,7 @@ impl ModelRouter {
             rules: Arc::new(RwLock::new(Vec::new())),
             round_robin_index: Arc::new(DashMap::new()),
             connection_counts: Arc::new(RwLock::new(HashMap::new())),
+            unhealthy_adapters: Arc::new(RwLock::new(HashSet::new())),
         }
     }
 
@@ -63,6 +66,7 @@ impl ModelRouter {
         context: Option<&HashMap<String, serde_json::Value>>,
     ) -> Option<(String, String)> {
         let rules = self.rules.read().await;
+        let unhealthy = self.unhealthy_adapters.read().await;
 
         for rule in rules.iter() {
             if let Some(ref condition) = rule.condition {
@@ -71,8 +75,11 @@ impl ModelRouter {
                 }
             }
 
-            let enabled_models: Vec<&ModelWeight> =
-                rule.models.iter().filter(|m| m.enabled).collect();
+            let enabled_models: Vec<&ModelWeight> = rule
+                .models
+                .iter()
+                .filter(|m| m.enabled && !unhealthy.contains(&m.adapter_name))
+                .collect();
 
             if enabled_models.is_empty() {
                 continue;
@@ -226,6 +233,32 @@ impl ModelRouter {
         let rules = self.rules.read().await;
         rules.clone()
     }
+
+    /// 路由规则中引用的适配器
+    pub async fn adapter_names(&self) -> Vec<String> {
+        let rules = self.rules.read().await;
+        let names: HashSet<&String> = rules
+            .iter()
+            .flat_map(|r| r.models.iter().map(|m| &m.adapter_name))
+            .collect();
+        names.into_iter().cloned().collect()
+    }
+
+    pub async fn set_adapter_health(&self, adapter_name: &str, healthy: bool) {
+        let mut unhealthy = self.unhealthy_adapters.write().await;
+        let changed = if healthy {
+            unhealthy.remove(adapter_name)
+        } else {
+            unhealthy.insert(adapter_name.to_string())
+        };
+        if changed {
+            info!(
+                "Adapter {} marked {} for routing",
+                adapter_name,
+                if healthy { "healthy" } else { "unhealthy" }
+            );
+        }
+    }
 }
 
 impl Default for ModelRouter {
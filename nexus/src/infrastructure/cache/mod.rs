@@ -1,7 +1,9 @@
 pub mod embedding;
 pub mod redis;
+pub mod response;
 pub mod session;
 
 pub use embedding::EmbeddingCache;
 pub use redis::RedisCache;
+pub use response::ResponseCacheStore;
 pub use session::SessionCache;
@@ -0,0 +1,32 @@
+use crate::infrastructure::cache::RedisCache;
+use anyhow::Result;
+use async_trait::async_trait;
+use llm_adapter::{CacheStore, InvokeResponse};
+use std::time::Duration;
+
+/// 适配器响应缓存的 Redis 存储，多个实例共享命中结果
+#[derive(Clone)]
+pub struct ResponseCacheStore {
+    redis: RedisCache,
+}
+
+impl ResponseCacheStore {
+    pub fn new(redis_url: Option<&str>) -> Self {
+        Self {
+            redis: RedisCache::new(redis_url, "llm_response"),
+        }
+    }
+}
+
+#[async_trait]
+impl CacheStore for ResponseCacheStore {
+    async fn get(&self, key: &str) -> Result<Option<InvokeResponse>> {
+        self.redis.get::<InvokeResponse>(key).await
+    }
+
+    async fn set(&self, key: &str, response: &InvokeResponse, ttl: Duration) -> Result<()> {
+        self.redis
+            .set(key, response, Some(ttl.as_secs().max(1)))
+            .await
+    }
+}
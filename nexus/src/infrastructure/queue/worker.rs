@@ -1,7 +1,7 @@
 use crate::application::Planner;
 use crate::infrastructure::queue::manager::TaskQueue;
 use crate::infrastructure::queue::task::{Task, TaskStatus};
-use llm_adapter::AdapterRegistry;
+use llm_adapter::{AdapterRegistry, InvokeOptions, Priority};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
@@ -118,7 +118,12 @@ impl TaskWorker {
                 let registry = adapter_registry.read().await;
 
                 if let Some(adapter) = registry.get(adapter_name).await {
-                    let result = adapter.invoke(input).await?;
+                    // 队列任务让位于交互请求
+                    let options = InvokeOptions {
+                        priority: Priority::Batch,
+                        ..Default::default()
+                    };
+                    let result = adapter.invoke_with_options(input, &options).await?;
                     Ok(serde_json::json!({
                         "result": result,
                         "adapter": adapter_name
@@ -1,6 +1,6 @@
-use agentflow::{LLMInvokeOptions, LLMProvider};
+use agentflow::{LLMInvokeOptions, LLMMessage, LLMMessageRole, LLMProvider};
 use async_trait::async_trait;
-use llm_adapter::Adapter;
+use llm_adapter::{Adapter, ChatMessage, ChatRequest, ChatRole, InvokeOptions, Priority};
 use std::sync::Arc;
 
 pub struct LLMAdapterProvider {
@@ -17,16 +17,13 @@ impl LLMAdapterProvider {
 #[async_trait]
 impl LLMProvider for LLMAdapterProvider {
     async fn invoke(&self, prompt: &str, options: &LLMInvokeOptions) -> anyhow::Result<String> {
-        use llm_adapter::InvokeOptions;
-        let invoke_options = InvokeOptions {
-            user_id: options.user_id.clone(),
-            model: options.model.clone(),
-            temperature: options.temperature,
-            max_tokens: options.max_tokens,
-            metadata: options.metadata.clone(),
-        };
+        self.adapter.invoke_with_options(prompt, &to_invoke_options(options)).await
+    }
 
-        self.adapter.invoke_with_options(prompt, &invoke_options).await
+    async fn chat(&self, messages: &[LLMMessage], options: &LLMInvokeOptions) -> anyhow::Result<String> {
+        let request = ChatRequest::new(messages.iter().map(to_chat_message).collect());
+        let response = self.adapter.chat(&request, &to_invoke_options(options)).await?;
+        Ok(response.content)
     }
 
     fn name(&self) -> &str {
@@ -34,3 +31,42 @@ impl LLMProvider for LLMAdapterProvider {
     }
 }
 
+fn to_invoke_options(options: &LLMInvokeOptions) -> InvokeOptions {
+    InvokeOptions {
+        user_id: options.user_id.clone(),
+        model: options.model.clone(),
+        temperature: options.temperature,
+        max_tokens: options.max_tokens,
+        top_p: options.top_p,
+        stop: options.stop.clone(),
+        seed: options.seed,
+        presence_penalty: options.presence_penalty,
+        frequency_penalty: options.frequency_penalty,
+        n: options.n,
+        priority: options
+            .metadata
+            .get("priority")
+            .and_then(|v| v.as_str())
+            .and_then(Priority::parse)
+            .unwrap_or_default(),
+        tenant_id: options
+            .metadata
+            .get("tenant_id")
+            .and_then(|v| v.as_str())
+            .map(str::to_string),
+        metadata: options.metadata.clone(),
+    }
+}
+
+fn to_chat_message(message: &LLMMessage) -> ChatMessage {
+    let role = match message.role {
+        LLMMessageRole::System => ChatRole::System,
+        LLMMessageRole::User => ChatRole::User,
+        LLMMessageRole::Assistant => ChatRole::Assistant,
+    };
+    let chat_message = ChatMessage::new(role, message.content.clone());
+    match &message.name {
+        Some(name) => chat_message.with_name(name.clone()),
+        None => chat_message,
+    }
+}
@@ -1,4 +1,4 @@
-use agentflow::{Agent, AgentConfig, AgentContext, AgentMessage, AgentResponse, LLMInvokeOptions, LLMProvider, MessageType};
+use agentflow::{Agent, AgentConfig, AgentContext, AgentMessage, AgentResponse, LLMInvokeOptions, LLMMessage, LLMProvider, MessageType};
 use async_trait::async_trait;
 use std::sync::Arc;
 
@@ -12,86 +12,64 @@ impl LLMAgent {
         Self { config, llm_provider }
     }
 
-    fn build_prompt(&self, message: &AgentMessage, context: &AgentContext) -> String {
-        let role_name = match &self.config.role {
-            agentflow::AgentRole::User => "User",
-            agentflow::AgentRole::Assistant => "Assistant",
-            agentflow::AgentRole::Planner => "Planner",
-            agentflow::AgentRole::Executor => "Executor",
-            agentflow::AgentRole::Reviewer => "Reviewer",
-            agentflow::AgentRole::Coordinator => "Coordinator",
-            agentflow::AgentRole::Expert { domain } => return self.build_expert_prompt(message, context, domain),
-            agentflow::AgentRole::Custom { role_name } => return self.build_custom_prompt(message, context, role_name),
-        };
+    fn build_messages(&self, message: &AgentMessage, context: &AgentContext) -> Vec<LLMMessage> {
+        let mut messages = vec![LLMMessage::system(self.system_prompt())];
 
-        let mut prompt = format!("=== {} Agent: {} ===\n", role_name, self.config.name);
-        prompt.push_str(&format!("System Instructions: {}\n\n", self.config.system_prompt));
-
-        let recent_messages = context.get_last_n_messages(10);
-        if !recent_messages.is_empty() {
-            prompt.push_str("=== Conversation History ===\n");
-            for msg in recent_messages {
-                let sender_role = if msg.sender_id == "user" {
-                    "User"
-                } else {
-                    "Agent"
-                };
-                prompt.push_str(&format!("[{}] {}: {}\n", sender_role, msg.sender_name, msg.content));
-            }
-            prompt.push_str("\n");
+        for msg in context.get_last_n_messages(10) {
+            messages.push(self.to_llm_message(msg));
         }
+        messages.push(self.to_llm_message(message));
 
-        prompt.push_str("=== Current Message ===\n");
-        prompt.push_str(&format!("From: {}\n", message.sender_name));
-        prompt.push_str(&format!("Content: {}\n\n", message.content));
-        prompt.push_str(&format!("=== Your Response as {} ===\n", role_name));
-
-        prompt
+        messages
     }
 
-    fn build_expert_prompt(&self, message: &AgentMessage, context: &AgentContext, domain: &str) -> String {
-        let mut prompt = format!("=== Expert Agent: {} (Domain: {}) ===\n", self.config.name, domain);
-        prompt.push_str(&format!("System Instructions: {}\n\n", self.config.system_prompt));
-
-        let recent_messages = context.get_last_n_messages(10);
-        if !recent_messages.is_empty() {
-            prompt.push_str("=== Conversation History ===\n");
-            for msg in recent_messages {
-                prompt.push_str(&format!("[{}]: {}\n", msg.sender_name, msg.content));
-            }
-            prompt.push_str("\n");
-        }
-
-        prompt.push_str("=== Current Request ===\n");
-        prompt.push_str(&format!("From: {}\n", message.sender_name));
-        prompt.push_str(&format!("Request: {}\n\n", message.content));
-        prompt.push_str(&format!("=== Your Expert Response (Domain: {}) ===\n", domain));
+    fn system_prompt(&self) -> String {
+        let identity = match &self.config.role {
+            agentflow::AgentRole::User => format!("You are {}, the User agent.", self.config.name),
+            agentflow::AgentRole::Assistant => format!("You are {}, the Assistant agent.", self.config.name),
+            agentflow::AgentRole::Planner => format!("You are {}, the Planner agent.", self.config.name),
+            agentflow::AgentRole::Executor => format!("You are {}, the Executor agent.", self.config.name),
+            agentflow::AgentRole::Reviewer => format!("You are {}, the Reviewer agent.", self.config.name),
+            agentflow::AgentRole::Coordinator => format!("You are {}, the Coordinator agent.", self.config.name),
+            agentflow::AgentRole::Expert { domain } => format!("You are {}, an Expert agent in the {} domain.", self.config.name, domain),
+            agentflow::AgentRole::Custom { role_name } => format!("You are {}, the {} agent.", self.config.name, role_name),
+        };
 
-        prompt
+        format!("{}\n\n{}", identity, self.config.system_prompt)
     }
 
-    fn build_custom_prompt(&self, message: &AgentMessage, context: &AgentContext, role_name: &str) -> String {
-        let mut prompt = format!("=== {} Agent: {} ===\n", role_name, self.config.name);
-        prompt.push_str(&format!("System Instructions: {}\n\n", self.config.system_prompt));
-
-        let recent_messages = context.get_last_n_messages(10);
-        if !recent_messages.is_empty() {
-            prompt.push_str("=== Conversation History ===\n");
-            for msg in recent_messages {
-                prompt.push_str(&format!("[{}]: {}\n", msg.sender_name, msg.content));
-            }
-            prompt.push_str("\n");
+    /// 本 Agent 的历史发言作为 assistant，其他参与者的发言作为带名称的 user 消息
+    fn to_llm_message(&self, msg: &AgentMessage) -> LLMMessage {
+        if msg.sender_id == self.config.id {
+            return LLMMessage::assistant(msg.content.clone());
         }
 
-        prompt.push_str("=== Current Message ===\n");
-        prompt.push_str(&format!("From: {}\n", message.sender_name));
-        prompt.push_str(&format!("Content: {}\n\n", message.content));
-        prompt.push_str(&format!("=== Your Response as {} ===\n", role_name));
+        if msg.message_type == MessageType::System {
+            return LLMMessage::system(msg.content.clone());
+        }
 
-        prompt
+        let llm_message = LLMMessage::user(msg.content.clone());
+        match participant_name(msg) {
+            Some(name) => llm_message.with_name(name),
+            None => llm_message,
+        }
     }
 }
 
+/// 多数接口要求 name 只包含 ASCII 字母、数字、下划线和连字符
+fn participant_name(msg: &AgentMessage) -> Option<String> {
+    let is_valid = |name: &str| {
+        !name.is_empty()
+            && name.len() <= 64
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    };
+
+    [msg.sender_name.as_str(), msg.sender_id.as_str()]
+        .into_iter()
+        .find(|name| is_valid(name))
+        .map(|name| name.to_string())
+}
+
 #[async_trait]
 impl Agent for LLMAgent {
     fn config(&self) -> &AgentConfig {
@@ -139,7 +117,7 @@ impl Agent for LLMAgent {
         message: AgentMessage,
         context: &mut AgentContext,
     ) -> anyhow::Result<AgentResponse> {
-        let prompt = self.build_prompt(&message, context);
+        let messages = self.build_messages(&message, context);
 
         context.add_message(message.clone());
 
@@ -148,10 +126,16 @@ impl Agent for LLMAgent {
             model: self.config.metadata.get("model").and_then(|v| v.as_str().map(|s| s.to_string())),
             temperature: self.config.temperature,
             max_tokens: self.config.metadata.get("max_tokens").and_then(|v| v.as_u64().map(|u| u as u32)),
+            top_p: self.config.metadata.get("top_p").and_then(|v| v.as_f64().map(|f| f as f32)),
+            stop: self.config.metadata.get("stop").and_then(|v| serde_json::from_value(v.clone()).ok()),
+            seed: self.config.metadata.get("seed").and_then(|v| v.as_u64()),
+            presence_penalty: self.config.metadata.get("presence_penalty").and_then(|v| v.as_f64().map(|f| f as f32)),
+            frequency_penalty: self.config.metadata.get("frequency_penalty").and_then(|v| v.as_f64().map(|f| f as f32)),
+            n: None,
             metadata: self.config.metadata.clone(),
         };
 
-        let result = self.llm_provider.invoke(&prompt, &options).await?;
+        let result = self.llm_provider.chat(&messages, &options).await?;
 
         let response_msg = AgentMessage::new(
             self.config.id.clone(),
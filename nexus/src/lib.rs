@@ -48,7 +48,47 @@ pub fn create_test_app() -> Router {
 }
 
 /// Prometheus metrics endpoint
-pub async fn metrics_handler(Extension(metrics): Extension<Arc<PrometheusMetrics>>) -> String {
+pub async fn metrics_handler(
+    Extension(state): Extension<Arc<AppState>>,
+    Extension(metrics): Extension<Arc<PrometheusMetrics>>,
+) -> String {
+    // 熔断状态在采集时读取，删除的适配器不再上报
+    metrics.adapter_circuit_state.reset();
+    let registry = state.adapter_registry.read().await;
+    for (adapter, circuit) in registry.circuit_states() {
+        metrics
+            .adapter_circuit_state
+            .with_label_values(&[adapter.as_str()])
+            .set(circuit.as_gauge());
+    }
+
+    for gauge in [
+        &metrics.adapter_concurrency_limit,
+        &metrics.adapter_concurrency_in_use,
+        &metrics.adapter_concurrency_queue_depth,
+    ] {
+        gauge.reset();
+    }
+    for (adapter, stats) in registry.concurrency_stats() {
+        let labels = [adapter.as_str()];
+        metrics
+            .adapter_concurrency_limit
+            .with_label_values(&labels)
+            .set(stats.limit as f64);
+        metrics
+            .adapter_concurrency_in_use
+            .with_label_values(&labels)
+            .set(stats.limit.saturating_sub(stats.available) as f64);
+        metrics.record_concurrency(&adapter, &stats);
+        for (priority, queued) in &stats.queued {
+            metrics
+                .adapter_concurrency_queue_depth
+                .with_label_values(&[adapter.as_str(), priority])
+                .set(*queued as f64);
+        }
+    }
+    drop(registry);
+
     metrics.gather().unwrap_or_else(|e| {
         tracing::error!("Failed to gather metrics: {}", e);
         String::new()
@@ -1,4 +1,12 @@
-use prometheus::{Counter, Encoder, Gauge, Histogram, HistogramOpts, Opts, Registry, TextEncoder};
+use llm_adapter::guard::{ConcurrencyStats, WAIT_BUCKETS};
+use prometheus::core::{Collector, Desc};
+use prometheus::proto::{self, MetricFamily, MetricType};
+use prometheus::{
+    Counter, Encoder, Gauge, GaugeVec, Histogram, HistogramOpts, IntCounterVec, Opts, Registry,
+    TextEncoder,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
 
 /// Prometheus 指标收集器
 pub struct PrometheusMetrics {
@@ -12,6 +20,18 @@ pub struct PrometheusMetrics {
     pub adapter_calls_total: Counter,
     pub adapter_duration_seconds: Histogram,
     pub adapter_errors_total: Counter,
+    /// 熔断状态：0 关闭，1 半开，2 熔断
+    pub adapter_circuit_state: GaugeVec,
+    pub adapter_concurrency_limit: GaugeVec,
+    pub adapter_concurrency_in_use: GaugeVec,
+    /// 按优先级统计的排队请求数
+    pub adapter_concurrency_queue_depth: GaugeVec,
+    pub adapter_concurrency_wait_seconds: WaitHistograms,
+    pub adapter_concurrency_acquired_total: IntCounterVec,
+    pub adapter_concurrency_rejected_total: IntCounterVec,
+    pub adapter_concurrency_shed_total: IntCounterVec,
+    /// 上次采集时各适配器的统计，用于计算增量
+    concurrency_totals: Mutex<HashMap<String, ConcurrencyStats>>,
 
     pub task_queue_size: Gauge,
     pub tasks_processed_total: Counter,
@@ -71,6 +91,63 @@ impl PrometheusMetrics {
         )?;
         registry.register(Box::new(adapter_errors_total.clone()))?;
 
+        let adapter_circuit_state = GaugeVec::new(
+            Opts::new(
+                "adapter_circuit_state",
+                "Adapter circuit breaker state (0=closed, 1=half-open, 2=open)",
+            )
+            .namespace("nexus"),
+            &["adapter"],
+        )?;
+        registry.register(Box::new(adapter_circuit_state.clone()))?;
+
+        let concurrency_gauge =
+            |name: &str, help: &str, labels: &[&str]| -> anyhow::Result<GaugeVec> {
+                let gauge = GaugeVec::new(Opts::new(name, help).namespace("nexus"), labels)?;
+                registry.register(Box::new(gauge.clone()))?;
+                Ok(gauge)
+            };
+        let adapter_concurrency_limit = concurrency_gauge(
+            "adapter_concurrency_limit",
+            "Adapter concurrency limit",
+            &["adapter"],
+        )?;
+        let adapter_concurrency_in_use = concurrency_gauge(
+            "adapter_concurrency_in_use",
+            "Adapter concurrency permits in use",
+            &["adapter"],
+        )?;
+        let adapter_concurrency_queue_depth = concurrency_gauge(
+            "adapter_concurrency_queue_depth",
+            "Requests waiting for an adapter concurrency permit",
+            &["adapter", "priority"],
+        )?;
+
+        let adapter_concurrency_wait_seconds = WaitHistograms::new(
+            "nexus_adapter_concurrency_wait_seconds",
+            "Time spent waiting for adapter concurrency permits",
+        )?;
+        registry.register(Box::new(adapter_concurrency_wait_seconds.clone()))?;
+
+        let concurrency_counter = |name: &str, help: &str| -> anyhow::Result<IntCounterVec> {
+            let counter =
+                IntCounterVec::new(Opts::new(name, help).namespace("nexus"), &["adapter"])?;
+            registry.register(Box::new(counter.clone()))?;
+            Ok(counter)
+        };
+        let adapter_concurrency_acquired_total = concurrency_counter(
+            "adapter_concurrency_acquired_total",
+            "Adapter concurrency permits granted",
+        )?;
+        let adapter_concurrency_rejected_total = concurrency_counter(
+            "adapter_concurrency_rejected_total",
+            "Requests rejected by adapter concurrency control (timeout or fail-fast)",
+        )?;
+        let adapter_concurrency_shed_total = concurrency_counter(
+            "adapter_concurrency_shed_total",
+            "Requests shed because the adapter concurrency queue was full",
+        )?;
+
         let task_queue_size = Gauge::with_opts(
             Opts::new("task_queue_size", "Current size of task queue").namespace("nexus"),
         )?;
@@ -106,6 +183,15 @@ impl PrometheusMetrics {
             adapter_calls_total,
             adapter_duration_seconds,
             adapter_errors_total,
+            adapter_circuit_state,
+            adapter_concurrency_limit,
+            adapter_concurrency_in_use,
+            adapter_concurrency_queue_depth,
+            adapter_concurrency_wait_seconds,
+            adapter_concurrency_acquired_total,
+            adapter_concurrency_rejected_total,
+            adapter_concurrency_shed_total,
+            concurrency_totals: Mutex::new(HashMap::new()),
             task_queue_size,
             tasks_processed_total,
             tasks_failed_total,
@@ -114,6 +200,45 @@ impl PrometheusMetrics {
         })
     }
 
+    /// 把并发控制自上次采集以来的新增计数和等待时间计入计数器与直方图
+    pub fn record_concurrency(&self, adapter: &str, stats: &ConcurrencyStats) {
+        let previous = self
+            .concurrency_totals
+            .lock()
+            .unwrap()
+            .insert(adapter.to_string(), stats.clone());
+        // 适配器重新注册后统计从零开始，此时整个当前值都是新增
+        let previous = previous.filter(|p| {
+            stats.acquired >= p.acquired && stats.rejected >= p.rejected && stats.shed >= p.shed
+        });
+        let previous = previous.as_ref();
+
+        for (counter, current, previous) in [
+            (
+                &self.adapter_concurrency_acquired_total,
+                stats.acquired,
+                previous.map_or(0, |p| p.acquired),
+            ),
+            (
+                &self.adapter_concurrency_rejected_total,
+                stats.rejected,
+                previous.map_or(0, |p| p.rejected),
+            ),
+            (
+                &self.adapter_concurrency_shed_total,
+                stats.shed,
+                previous.map_or(0, |p| p.shed),
+            ),
+        ] {
+            counter
+                .with_label_values(&[adapter])
+                .inc_by(current.saturating_sub(previous));
+        }
+
+        self.adapter_concurrency_wait_seconds
+            .add(adapter, stats, previous);
+    }
+
     /// 收集所有指标并格式化为 Prometheus 文本格式
     pub fn gather(&self) -> anyhow::Result<String> {
         let metric_families = self.registry.gather();
@@ -128,6 +253,108 @@ impl PrometheusMetrics {
     }
 }
 
+/// 各适配器获得并发许可前的等待时间直方图，按 ConcurrencyGuard 的分桶计数累加
+#[derive(Clone)]
+pub struct WaitHistograms {
+    desc: Desc,
+    histograms: Arc<Mutex<BTreeMap<String, WaitHistogram>>>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct WaitHistogram {
+    pub count: u64,
+    pub sum: f64,
+    /// 与 `WAIT_BUCKETS` 对应的累计次数
+    pub buckets: Vec<u64>,
+}
+
+impl WaitHistograms {
+    fn new(name: &str, help: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            desc: Desc::new(
+                name.to_string(),
+                help.to_string(),
+                vec!["adapter".to_string()],
+                HashMap::new(),
+            )?,
+            histograms: Arc::new(Mutex::new(BTreeMap::new())),
+        })
+    }
+
+    pub fn get(&self, adapter: &str) -> Option<WaitHistogram> {
+        self.histograms.lock().unwrap().get(adapter).cloned()
+    }
+
+    fn add(&self, adapter: &str, stats: &ConcurrencyStats, previous: Option<&ConcurrencyStats>) {
+        let mut histograms = self.histograms.lock().unwrap();
+        let histogram = histograms
+            .entry(adapter.to_string())
+            .or_insert_with(|| WaitHistogram {
+                buckets: vec![0; WAIT_BUCKETS.len()],
+                ..Default::default()
+            });
+
+        histogram.count += stats.acquired - previous.map_or(0, |p| p.acquired);
+        histogram.sum +=
+            (stats.wait_seconds_total - previous.map_or(0.0, |p| p.wait_seconds_total)).max(0.0);
+        for (i, (total, (_, current))) in histogram
+            .buckets
+            .iter_mut()
+            .zip(&stats.wait_buckets)
+            .enumerate()
+        {
+            let previous = previous
+                .and_then(|p| p.wait_buckets.get(i))
+                .map_or(0, |b| b.1);
+            *total += current.saturating_sub(previous);
+        }
+    }
+}
+
+impl Collector for WaitHistograms {
+    fn desc(&self) -> Vec<&Desc> {
+        vec![&self.desc]
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let histograms = self.histograms.lock().unwrap();
+        if histograms.is_empty() {
+            return Vec::new();
+        }
+
+        let mut family = MetricFamily::default();
+        family.set_name(self.desc.fq_name.clone());
+        family.set_help(self.desc.help.clone());
+        family.set_field_type(MetricType::HISTOGRAM);
+        for (adapter, histogram) in histograms.iter() {
+            let mut label = proto::LabelPair::default();
+            label.set_name("adapter".to_string());
+            label.set_value(adapter.clone());
+
+            let mut h = proto::Histogram::default();
+            h.set_sample_count(histogram.count);
+            h.set_sample_sum(histogram.sum);
+            h.set_bucket(
+                WAIT_BUCKETS
+                    .iter()
+                    .zip(&histogram.buckets)
+                    .map(|(bound, count)| {
+                        let mut bucket = proto::Bucket::default();
+                        bucket.set_upper_bound(*bound);
+                        bucket.set_cumulative_count(*count);
+                        bucket
+                    })
+                    .collect(),
+            );
+
+            let mut metric = proto::Metric::from_label(vec![label]);
+            metric.set_histogram(h);
+            family.mut_metric().push(metric);
+        }
+        vec![family]
+    }
+}
+
 impl Default for PrometheusMetrics {
     fn default() -> Self {
         Self::new().expect("Failed to create Prometheus metrics")
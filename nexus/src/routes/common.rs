@@ -1,4 +1,7 @@
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
 use axum::Json;
+use llm_adapter::AdapterError;
 use serde::Serialize;
 use utoipa::ToSchema;
 
@@ -41,3 +44,47 @@ pub fn error_response(message: &str) -> Json<serde_json::Value> {
         "message": message
     }))
 }
+
+/// 将适配器调用错误转换为对应的 HTTP 状态码，未分类的错误返回 500
+pub fn adapter_error_response(err: &anyhow::Error) -> Response {
+    let Some(adapter_error) = err.downcast_ref::<AdapterError>() else {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            error_response(&err.to_string()),
+        )
+            .into_response();
+    };
+
+    let status = match adapter_error {
+        AdapterError::Auth(_) => StatusCode::UNAUTHORIZED,
+        AdapterError::RateLimited { .. } | AdapterError::BudgetExceeded { .. } => {
+            StatusCode::TOO_MANY_REQUESTS
+        }
+        AdapterError::ContextLengthExceeded(_) => StatusCode::PAYLOAD_TOO_LARGE,
+        AdapterError::ContentFiltered(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        AdapterError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+        AdapterError::Upstream5xx { .. } | AdapterError::Network(_) | AdapterError::Decode(_) => {
+            StatusCode::BAD_GATEWAY
+        }
+        AdapterError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+        AdapterError::CircuitOpen { .. } | AdapterError::Overloaded { .. } => {
+            StatusCode::SERVICE_UNAVAILABLE
+        }
+    };
+
+    let body = Json(serde_json::json!({
+        "status": "error",
+        "code": adapter_error.code(),
+        "message": adapter_error.to_string()
+    }));
+
+    match adapter_error.retry_after() {
+        Some(after) => (
+            status,
+            [(header::RETRY_AFTER, after.as_secs().to_string())],
+            body,
+        )
+            .into_response(),
+        None => (status, body).into_response(),
+    }
+}
@@ -13,6 +13,8 @@ pub fn adapters_routes() -> Router {
         .route("/adapters/{name}", get(get_adapter))
         .route("/adapters/{name}", delete(delete_adapter))
         .route("/adapters/{name}/billing", get(get_billing_stats))
+        .route("/adapters/{name}/health", get(get_adapter_health))
+        .route("/adapters/{name}/models", get(list_adapter_models))
 }
 
 #[utoipa::path(
@@ -85,6 +87,51 @@ pub async fn get_billing_stats(
     handlers::get_billing_stats(Extension(state), axum::extract::Path(adapter_name)).await
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/config/adapters/{name}/health",
+    tag = "config-adapters",
+    params(
+        ("name" = String, Path, description = "适配器名称"),
+        ("refresh" = Option<bool>, Query, description = "忽略缓存，立即重新探测")
+    ),
+    responses(
+        (status = 200, description = "适配器健康状态", content_type = "application/json"),
+        (status = 404, description = "适配器不存在", body = crate::routes::common::ErrorResponse)
+    )
+)]
+pub async fn get_adapter_health(
+    Extension(state): Extension<Arc<AppState>>,
+    axum::extract::Path(name): axum::extract::Path<String>,
+    axum::extract::Query(query): axum::extract::Query<handlers::AdapterHealthQuery>,
+) -> axum::Json<serde_json::Value> {
+    handlers::get_adapter_health(
+        Extension(state),
+        axum::extract::Path(name),
+        axum::extract::Query(query),
+    )
+    .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/config/adapters/{name}/models",
+    tag = "config-adapters",
+    params(
+        ("name" = String, Path, description = "适配器名称")
+    ),
+    responses(
+        (status = 200, description = "提供商当前可用的模型列表", content_type = "application/json"),
+        (status = 404, description = "适配器不存在", body = crate::routes::common::ErrorResponse)
+    )
+)]
+pub async fn list_adapter_models(
+    Extension(state): Extension<Arc<AppState>>,
+    axum::extract::Path(name): axum::extract::Path<String>,
+) -> axum::Json<serde_json::Value> {
+    handlers::list_adapter_models(Extension(state), axum::extract::Path(name)).await
+}
+
 #[utoipa::path(
     get,
     path = "/api/config/adapters/stats",
@@ -125,6 +172,8 @@ pub async fn get_adapter_by_model(
         get_adapter,
         delete_adapter,
         get_billing_stats,
+        get_adapter_health,
+        list_adapter_models,
         get_models_stats,
         get_adapter_by_model,
     ),
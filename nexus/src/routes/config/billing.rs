@@ -0,0 +1,74 @@
+use crate::routes::handlers::config::billing as handlers;
+use crate::state::AppState;
+use axum::response::Response;
+use axum::routing::get;
+use axum::{Extension, Router};
+use llm_adapter::ReportQuery;
+use std::sync::Arc;
+use utoipa::OpenApi;
+
+#[utoipa::path(
+    get,
+    path = "/api/config/billing/report",
+    tag = "config-billing",
+    params(
+        ("from" = Option<String>, Query, description = "开始时间（RFC3339，含）"),
+        ("to" = Option<String>, Query, description = "结束时间（RFC3339，不含）"),
+        ("granularity" = Option<String>, Query, description = "时间粒度：hour / day / month，默认 day"),
+        ("group_by" = Option<String>, Query, description = "分组：adapter / model / user / tenant，默认 adapter"),
+        ("adapter" = Option<String>, Query, description = "只统计该适配器")
+    ),
+    responses(
+        (status = 200, description = "按时间段与分组汇总的用量", content_type = "application/json"),
+        (status = 500, description = "读取账单失败", body = crate::routes::common::ErrorResponse)
+    )
+)]
+pub async fn get_billing_report(
+    Extension(state): Extension<Arc<AppState>>,
+    axum::extract::Query(query): axum::extract::Query<ReportQuery>,
+) -> axum::Json<serde_json::Value> {
+    handlers::get_billing_report(Extension(state), axum::extract::Query(query)).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/config/billing/export",
+    tag = "config-billing",
+    params(
+        ("format" = Option<String>, Query, description = "导出格式：csv / jsonl，默认 csv"),
+        ("from" = Option<String>, Query, description = "开始时间（RFC3339，含）"),
+        ("to" = Option<String>, Query, description = "结束时间（RFC3339，不含）"),
+        ("adapter" = Option<String>, Query, description = "只导出该适配器")
+    ),
+    responses(
+        (status = 200, description = "账单明细", content_type = "text/csv"),
+        (status = 500, description = "读取账单失败", body = crate::routes::common::ErrorResponse)
+    )
+)]
+pub async fn export_billing_records(
+    Extension(state): Extension<Arc<AppState>>,
+    axum::extract::Query(query): axum::extract::Query<handlers::BillingExportQuery>,
+) -> Response {
+    handlers::export_billing_records(Extension(state), axum::extract::Query(query)).await
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        get_billing_report,
+        export_billing_records,
+    ),
+    components(schemas(
+        crate::routes::common::ErrorResponse,
+    )),
+    tags(
+        (name = "config-billing", description = "账单报表与导出"),
+    )
+)]
+pub struct BillingApiDoc;
+
+pub fn billing_routes() -> Router {
+    Router::new()
+        .route("/billing/report", get(get_billing_report))
+        .route("/billing/export", get(export_billing_records))
+}
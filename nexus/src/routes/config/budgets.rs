@@ -0,0 +1,56 @@
+use crate::routes::handlers::config::budgets as handlers;
+use crate::state::AppState;
+use axum::routing::get;
+use axum::{Extension, Router};
+use std::sync::Arc;
+use utoipa::OpenApi;
+
+#[utoipa::path(
+    get,
+    path = "/api/config/budgets",
+    tag = "config-budgets",
+    responses(
+        (status = 200, description = "预算定义与当前周期用量", content_type = "application/json")
+    )
+)]
+pub async fn list_budgets(
+    Extension(state): Extension<Arc<AppState>>,
+) -> axum::Json<serde_json::Value> {
+    handlers::list_budgets(Extension(state)).await
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/config/budgets",
+    tag = "config-budgets",
+    request_body(content = serde_json::Value, description = "预算定义列表，替换现有预算", content_type = "application/json"),
+    responses(
+        (status = 200, description = "成功更新预算", content_type = "application/json"),
+        (status = 500, description = "预算定义无效", body = crate::routes::common::ErrorResponse)
+    )
+)]
+pub async fn update_budgets(
+    Extension(state): Extension<Arc<AppState>>,
+    axum::Json(payload): axum::Json<serde_json::Value>,
+) -> axum::Json<serde_json::Value> {
+    handlers::update_budgets(Extension(state), axum::Json(payload)).await
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        list_budgets,
+        update_budgets,
+    ),
+    components(schemas(
+        crate::routes::common::ErrorResponse,
+    )),
+    tags(
+        (name = "config-budgets", description = "预算管理"),
+    )
+)]
+pub struct BudgetsApiDoc;
+
+pub fn budgets_routes() -> Router {
+    Router::new().route("/budgets", get(list_budgets).put(update_budgets))
+}
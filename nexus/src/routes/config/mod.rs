@@ -1,6 +1,9 @@
 pub mod adapters;
+pub mod billing;
+pub mod budgets;
 pub mod flags;
 pub mod import_export;
+pub mod pricing;
 pub mod prompts;
 pub mod reload;
 pub mod routing;
@@ -19,6 +22,9 @@ pub fn config_routes() -> Router {
         .merge(prompts::prompts_routes())
         .merge(reload::reload_routes())
         .merge(import_export::import_export_routes())
+        .merge(budgets::budgets_routes())
+        .merge(pricing::pricing_routes())
+        .merge(billing::billing_routes())
 }
 
 use utoipa::openapi::OpenApi as OpenApiStruct;
@@ -31,6 +37,9 @@ pub fn config_openapi() -> OpenApiStruct {
     openapi.merge(<prompts::PromptsApiDoc as utoipa::OpenApi>::openapi());
     openapi.merge(<reload::ReloadApiDoc as utoipa::OpenApi>::openapi());
     openapi.merge(<import_export::ImportExportApiDoc as utoipa::OpenApi>::openapi());
+    openapi.merge(<budgets::BudgetsApiDoc as utoipa::OpenApi>::openapi());
+    openapi.merge(<pricing::PricingApiDoc as utoipa::OpenApi>::openapi());
+    openapi.merge(<billing::BillingApiDoc as utoipa::OpenApi>::openapi());
 
     openapi
 }
@@ -0,0 +1,56 @@
+use crate::routes::handlers::config::pricing as handlers;
+use crate::state::AppState;
+use axum::routing::get;
+use axum::{Extension, Router};
+use std::sync::Arc;
+use utoipa::OpenApi;
+
+#[utoipa::path(
+    get,
+    path = "/api/config/pricing",
+    tag = "config-pricing",
+    responses(
+        (status = 200, description = "模型价格表", content_type = "application/json")
+    )
+)]
+pub async fn list_pricing(
+    Extension(state): Extension<Arc<AppState>>,
+) -> axum::Json<serde_json::Value> {
+    handlers::list_pricing(Extension(state)).await
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/config/pricing",
+    tag = "config-pricing",
+    request_body(content = serde_json::Value, description = "模型价格列表，替换现有价格表", content_type = "application/json"),
+    responses(
+        (status = 200, description = "成功更新价格表", content_type = "application/json"),
+        (status = 500, description = "价格定义无效", body = crate::routes::common::ErrorResponse)
+    )
+)]
+pub async fn update_pricing(
+    Extension(state): Extension<Arc<AppState>>,
+    axum::Json(payload): axum::Json<serde_json::Value>,
+) -> axum::Json<serde_json::Value> {
+    handlers::update_pricing(Extension(state), axum::Json(payload)).await
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        list_pricing,
+        update_pricing,
+    ),
+    components(schemas(
+        crate::routes::common::ErrorResponse,
+    )),
+    tags(
+        (name = "config-pricing", description = "模型价格管理"),
+    )
+)]
+pub struct PricingApiDoc;
+
+pub fn pricing_routes() -> Router {
+    Router::new().route("/pricing", get(list_pricing).put(update_pricing))
+}
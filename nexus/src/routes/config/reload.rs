@@ -51,6 +51,10 @@ pub struct ReloadAdapterRequest {
     pub base_url: Option<String>,
     #[schema(example = true)]
     pub enabled: bool,
+    /// 适配器元数据，如 fallback 链：`{"type": "fallback", "chain": ["deepseek", "qianwen"]}`
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub metadata: std::collections::HashMap<String, serde_json::Value>,
 }
 
 #[derive(serde::Deserialize, utoipa::ToSchema)]
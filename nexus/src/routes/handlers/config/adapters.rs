@@ -81,6 +81,43 @@ pub async fn get_billing_stats(
     }
 }
 
+pub async fn get_adapter_health(
+    Extension(state): Extension<Arc<AppState>>,
+    axum::extract::Path(name): axum::extract::Path<String>,
+    axum::extract::Query(query): axum::extract::Query<AdapterHealthQuery>,
+) -> Json<serde_json::Value> {
+    let registry = state.adapter_registry.read().await;
+    match registry.health_report(&name, query.refresh).await {
+        Some(report) => {
+            let router = state.config_manager.router();
+            router.set_adapter_health(&name, report.healthy).await;
+            ok_response(serde_json::json!({ "health": report }))
+        }
+        None => error_response(&format!("Adapter {} not found", name)),
+    }
+}
+
+pub async fn list_adapter_models(
+    Extension(state): Extension<Arc<AppState>>,
+    axum::extract::Path(name): axum::extract::Path<String>,
+) -> Json<serde_json::Value> {
+    let adapter = state.adapter_registry.read().await.get(&name).await;
+    match adapter {
+        Some(adapter) => match adapter.list_models().await {
+            Ok(models) => ok_response(serde_json::json!({ "models": models })),
+            Err(e) => error_response(&format!("Failed to list models for {}: {}", name, e)),
+        },
+        None => error_response(&format!("Adapter {} not found", name)),
+    }
+}
+
+#[derive(serde::Deserialize, Default)]
+pub struct AdapterHealthQuery {
+    /// 忽略缓存，立即重新探测
+    #[serde(default)]
+    pub refresh: bool,
+}
+
 pub async fn get_models_stats(Extension(state): Extension<Arc<AppState>>) -> Json<serde_json::Value> {
     let config = state.config_manager.get_config().await;
     let mut total = 0;
@@ -0,0 +1,100 @@
+use crate::routes::common::{error_response, ok_response};
+use crate::state::AppState;
+use axum::body::Body;
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use llm_adapter::billing_report::{csv_row, CSV_HEADER};
+use llm_adapter::ReportQuery;
+use std::sync::Arc;
+use tracing::warn;
+
+pub async fn get_billing_report(
+    Extension(state): Extension<Arc<AppState>>,
+    axum::extract::Query(query): axum::extract::Query<ReportQuery>,
+) -> Json<serde_json::Value> {
+    let registry = state.adapter_registry.read().await;
+    match registry.billing_report(&query).await {
+        Ok(buckets) => ok_response(serde_json::json!({
+            "granularity": query.granularity,
+            "group_by": query.group_by,
+            "buckets": buckets,
+        })),
+        Err(e) => error_response(&format!("Failed to build billing report: {}", e)),
+    }
+}
+
+#[derive(serde::Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Jsonl,
+}
+
+#[derive(serde::Deserialize, Default)]
+pub struct BillingExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+    #[serde(default)]
+    pub from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub adapter: Option<String>,
+}
+
+pub async fn export_billing_records(
+    Extension(state): Extension<Arc<AppState>>,
+    axum::extract::Query(query): axum::extract::Query<BillingExportQuery>,
+) -> Response {
+    // 只在复制计费器句柄时持有注册表读锁，下载期间不阻塞配置变更
+    let trackers = state
+        .adapter_registry
+        .read()
+        .await
+        .billing_trackers(query.adapter.as_deref());
+
+    let mut streams = Vec::with_capacity(trackers.len());
+    for (name, tracker) in trackers {
+        match tracker.record_stream(&name, query.from, query.to).await {
+            Ok(stream) => streams.push(stream),
+            Err(e) => {
+                return error_response(&format!("Failed to export billing records: {}", e))
+                    .into_response()
+            }
+        }
+    }
+
+    let (content_type, filename, header_line) = match query.format {
+        ExportFormat::Csv => ("text/csv; charset=utf-8", "usage.csv", Some(CSV_HEADER)),
+        ExportFormat::Jsonl => ("application/x-ndjson", "usage.jsonl", None),
+    };
+    let format = query.format;
+    let rows = stream::iter(streams)
+        .flatten()
+        .map_ok(move |record| match format {
+            ExportFormat::Csv => csv_row(&record),
+            ExportFormat::Jsonl => {
+                let mut line = serde_json::to_string(&record).unwrap_or_default();
+                line.push('\n');
+                line
+            }
+        })
+        .inspect_err(|e| warn!("Billing export aborted: {}", e));
+    let lines = stream::iter(header_line.map(|line| Ok(line.to_string()))).chain(rows);
+
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        Body::from_stream(lines),
+    )
+        .into_response()
+}
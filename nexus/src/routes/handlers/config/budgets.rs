@@ -0,0 +1,57 @@
+use crate::routes::common::{error_response, ok_response, ok_response_with_message};
+use crate::state::AppState;
+use axum::{Extension, Json};
+use llm_adapter::Budget;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+pub async fn list_budgets(Extension(state): Extension<Arc<AppState>>) -> Json<serde_json::Value> {
+    let budget_manager = state.adapter_registry.read().await.budget_manager();
+    ok_response(serde_json::json!({
+        "budgets": budget_manager.budgets(),
+        "usage": budget_manager.usage(),
+    }))
+}
+
+pub async fn update_budgets(
+    Extension(state): Extension<Arc<AppState>>,
+    Json(payload): Json<serde_json::Value>,
+) -> Json<serde_json::Value> {
+    let budgets: Vec<Budget> = match serde_json::from_value(payload) {
+        Ok(budgets) => budgets,
+        Err(e) => return error_response(&format!("Invalid budgets: {}", e)),
+    };
+    if let Err(e) = validate_budgets(&budgets) {
+        return error_response(&e);
+    }
+
+    let mut config = state.config_manager.get_config().await;
+    config.budgets = budgets.clone();
+    state.config_manager.update_config(config).await;
+
+    let count = budgets.len();
+    state
+        .adapter_registry
+        .read()
+        .await
+        .set_budgets(budgets)
+        .await;
+
+    ok_response_with_message(&format!("{} budgets updated", count), serde_json::json!({}))
+}
+
+fn validate_budgets(budgets: &[Budget]) -> Result<(), String> {
+    let mut names = HashSet::new();
+    for budget in budgets {
+        if !names.insert(budget.name.as_str()) {
+            return Err(format!("Duplicate budget name: {}", budget.name));
+        }
+        if budget.max_cost.is_none() && budget.max_tokens.is_none() {
+            return Err(format!(
+                "Budget {} needs max_cost or max_tokens",
+                budget.name
+            ));
+        }
+    }
+    Ok(())
+}
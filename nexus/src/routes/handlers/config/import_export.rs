@@ -34,6 +34,11 @@ pub async fn import_config(
 
             state.config_manager.sync_prompts_to_store(&state.prompt_store).await;
 
+            let config = state.config_manager.get_config().await;
+            let registry = state.adapter_registry.read().await;
+            registry.set_budgets(config.budgets).await;
+            registry.pricing().set_prices(config.pricing);
+
             match adapter_result {
                 Ok(_) => ok_response_with_message(
                     "Configuration imported and adapters registered successfully",
@@ -1,6 +1,9 @@
 pub mod adapters;
+pub mod billing;
+pub mod budgets;
 pub mod flags;
 pub mod import_export;
+pub mod pricing;
 pub mod prompts;
 pub mod reload;
 pub mod routing;
@@ -0,0 +1,60 @@
+use crate::routes::common::{error_response, ok_response, ok_response_with_message};
+use crate::state::AppState;
+use axum::{Extension, Json};
+use llm_adapter::ModelPrice;
+use std::sync::Arc;
+
+pub async fn list_pricing(Extension(state): Extension<Arc<AppState>>) -> Json<serde_json::Value> {
+    let pricing = state.adapter_registry.read().await.pricing();
+    ok_response(serde_json::json!({ "prices": pricing.prices() }))
+}
+
+pub async fn update_pricing(
+    Extension(state): Extension<Arc<AppState>>,
+    Json(payload): Json<serde_json::Value>,
+) -> Json<serde_json::Value> {
+    let prices: Vec<ModelPrice> = match serde_json::from_value(payload) {
+        Ok(prices) => prices,
+        Err(e) => return error_response(&format!("Invalid pricing: {}", e)),
+    };
+    if let Err(e) = validate_prices(&prices) {
+        return error_response(&e);
+    }
+
+    let mut config = state.config_manager.get_config().await;
+    config.pricing = prices.clone();
+    state.config_manager.update_config(config).await;
+
+    let count = prices.len();
+    state
+        .adapter_registry
+        .read()
+        .await
+        .pricing()
+        .set_prices(prices);
+
+    ok_response_with_message(&format!("{} prices updated", count), serde_json::json!({}))
+}
+
+fn validate_prices(prices: &[ModelPrice]) -> Result<(), String> {
+    for price in prices {
+        let negative = price.input_price_per_1k < 0.0
+            || price.output_price_per_1k < 0.0
+            || price.cached_input_price_per_1k.is_some_and(|p| p < 0.0);
+        if negative {
+            return Err(format!(
+                "Price for {}/{} must not be negative",
+                price.adapter, price.model
+            ));
+        }
+        if let (Some(from), Some(until)) = (price.effective_from, price.effective_until) {
+            if from >= until {
+                return Err(format!(
+                    "Price for {}/{} has effective_from after effective_until",
+                    price.adapter, price.model
+                ));
+            }
+        }
+    }
+    Ok(())
+}
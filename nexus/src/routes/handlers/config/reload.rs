@@ -4,6 +4,7 @@ use crate::routes::config::reload::{ReloadAdapterRequest, ReloadPromptRequest};
 use crate::routes::handlers::adapter_helpers::register_adapter_dynamically;
 use crate::state::AppState;
 use axum::{Extension, Json};
+use llm_adapter::AdapterFactory;
 use std::sync::Arc;
 use tracing::error;
 
@@ -17,23 +18,82 @@ pub async fn hot_reload_adapter(
         model: payload.model,
         base_url: payload.base_url,
         enabled: payload.enabled,
-        metadata: std::collections::HashMap::new(),
+        metadata: payload.metadata,
     };
 
-    match state
+    let previous = state.config_manager.get_adapter_config(&config.name).await;
+
+    if let Err(e) = state
         .config_manager
         .hot_reload_adapter(config.clone())
         .await
     {
-        Ok(_) => match register_adapter_dynamically(&state, config).await {
-            Ok(_) => ok_response_with_message(
-                &format!("Adapter {} reloaded and registered", payload.name),
+        return error_response(&e.to_string());
+    }
+
+    // 只修改了限流或并发参数时原地更新，保留适配器、已消耗的额度和进行中的请求
+    if previous.is_some_and(|prev| only_limits_changed(&prev, &config)) {
+        let registry = state.adapter_registry.read().await;
+        let updated = registry.update_rate_limit(
+            &config.name,
+            AdapterFactory::rate_limit_config(&config.metadata),
+        ) && registry.update_concurrency(
+            &config.name,
+            AdapterFactory::concurrency_config(&config.metadata),
+        );
+        if updated {
+            return ok_response_with_message(
+                &format!("Adapter {} limits updated", payload.name),
                 serde_json::json!({}),
-            ),
-            Err(e) => error_response(&e),
-        },
-        Err(e) => error_response(&e.to_string()),
+            );
+        }
     }
+
+    match register_adapter_dynamically(&state, config).await {
+        Ok(_) => ok_response_with_message(
+            &format!("Adapter {} reloaded and registered", payload.name),
+            serde_json::json!({}),
+        ),
+        Err(e) => error_response(&e),
+    }
+}
+
+/// 可以在运行时调整的并发参数
+const CONCURRENCY_KEYS: [&str; 11] = [
+    "max_concurrent",
+    "acquire_timeout_ms",
+    "concurrency_fail_fast",
+    "concurrency_enabled",
+    "concurrency_max_queue",
+    "concurrency_adaptive",
+    "concurrency_min_limit",
+    "concurrency_max_limit",
+    "concurrency_latency_threshold_ms",
+    "concurrency_decrease_factor",
+    "concurrency_cooldown_ms",
+];
+
+fn only_limits_changed(previous: &AdapterConfig, config: &AdapterConfig) -> bool {
+    // 切换限流后端需要重新创建限流器
+    let without_limits = |config: &AdapterConfig| {
+        let mut metadata: Vec<(String, String)> = config
+            .metadata
+            .iter()
+            .filter(|(key, _)| {
+                (!key.starts_with("rate_limit_") || *key == "rate_limit_backend")
+                    && !CONCURRENCY_KEYS.contains(&key.as_str())
+            })
+            .map(|(key, value)| (key.clone(), value.to_string()))
+            .collect();
+        metadata.sort();
+        metadata
+    };
+
+    previous.api_key == config.api_key
+        && previous.model == config.model
+        && previous.base_url == config.base_url
+        && previous.enabled == config.enabled
+        && without_limits(previous) == without_limits(config)
 }
 
 pub async fn hot_reload_prompt(
@@ -12,10 +12,40 @@ pub async fn health_handler() -> Json<serde_json::Value> {
 pub async fn readiness_handler(
     Extension(state): Extension<Arc<AppState>>,
 ) -> Json<serde_json::Value> {
-    let adapter_count = state.adapter_registry.read().await.list().await.len();
-    let ready = adapter_count > 0;
+    let (adapters, circuits) = {
+        let registry = state.adapter_registry.read().await;
+        let mut adapters = Vec::new();
+        for name in registry.list().await {
+            if let Some(adapter) = registry.get(&name).await {
+                adapters.push((name, adapter));
+            }
+        }
+        (adapters, registry.circuit_states())
+    };
+
+    // 主动探测结果有缓存，熔断中的适配器直接视为不健康
+    let checks = adapters.into_iter().map(|(name, adapter)| async move {
+        let healthy = adapter.health().await;
+        (name, serde_json::Value::from(healthy))
+    });
+    let health: serde_json::Map<String, serde_json::Value> = futures::future::join_all(checks)
+        .await
+        .into_iter()
+        .collect();
+
+    // 没有任何健康的适配器时视为未就绪
+    let ready = health
+        .values()
+        .any(|healthy| healthy.as_bool() == Some(true));
+
+    let circuits: serde_json::Map<String, serde_json::Value> = circuits
+        .into_iter()
+        .map(|(name, state)| (name, serde_json::Value::from(state.as_str())))
+        .collect();
 
     ok_response(serde_json::json!({
-        "ready": ready
+        "ready": ready,
+        "adapters": health,
+        "circuits": circuits
     }))
 }
@@ -1,14 +1,24 @@
-use crate::application::postprocessor::ProcessingContext;
+use crate::application::postprocessor::{ProcessingContext, StreamingOutputBuffer};
 use crate::infrastructure::queue::task::{Task, TaskPriority};
 use crate::monitor::event::{Event, EventLevel};
 use crate::routes::handlers::adapter_helpers::{
     record_adapter_call, record_adapter_error, record_adapter_success, register_adapter_dynamically,
 };
 use crate::state::AppState;
+use axum::http::{header, HeaderMap};
+use axum::response::sse::{Event as SseEvent, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
 use axum::{Extension, Json};
+use futures::StreamExt;
+use llm_adapter::cache::CACHE_CONTROL;
 use llm_adapter::config::AdapterConfig;
+use llm_adapter::{
+    Adapter, AdapterError, ChatMessage, ChatRequest, ContentPart, InvokeOptions, Priority, Usage,
+};
 use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
 use std::sync::Arc;
+use tokio_stream::wrappers::ReceiverStream;
 use tracing::warn;
 use tracing::{error, info};
 use utoipa::ToSchema;
@@ -20,7 +30,8 @@ use utoipa::ToSchema;
     "api_key": "sk-xxxx",
     "model": "gpt-4o-mini",
     "user_id": "user123",
-    "prompt_name": "default"
+    "prompt_name": "default",
+    "stream": false
 }))]
 pub struct InvokeRequest {
     #[schema(example = "Hello, world!")]
@@ -40,9 +51,74 @@ pub struct InvokeRequest {
     #[serde(default)]
     #[schema(example = "user123")]
     pub user_id: Option<String>,
+    /// 租户 id，用于按租户检查预算
+    #[serde(default)]
+    #[schema(example = "acme")]
+    pub tenant_id: Option<String>,
     #[serde(default)]
     #[schema(example = "default")]
     pub prompt_name: Option<String>,
+    /// 为 true 时以 Server-Sent Events 逐段返回结果
+    #[serde(default)]
+    #[schema(example = false)]
+    pub stream: bool,
+    #[serde(default)]
+    #[schema(example = 0.7)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    #[schema(example = 1024)]
+    pub max_tokens: Option<u32>,
+    #[serde(default)]
+    #[schema(example = 0.9)]
+    pub top_p: Option<f32>,
+    #[serde(default)]
+    pub stop: Option<Vec<String>>,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub presence_penalty: Option<f32>,
+    #[serde(default)]
+    pub frequency_penalty: Option<f32>,
+    #[serde(default)]
+    pub n: Option<u32>,
+    /// 并发名额不足时的排队优先级：interactive（默认）、normal、batch
+    #[serde(default)]
+    #[schema(example = "interactive")]
+    pub priority: Option<String>,
+    /// 随输入一起发送的图片、音频，需要适配器支持多模态
+    #[serde(default)]
+    #[schema(value_type = Vec<Object>, example = json!([{"type": "image", "source": {"type": "url", "url": "https://example.com/page.png"}}]))]
+    pub parts: Vec<ContentPart>,
+}
+
+impl InvokeRequest {
+    fn invoke_options(&self) -> InvokeOptions {
+        InvokeOptions {
+            user_id: self.user_id.clone(),
+            model: self.model.clone(),
+            temperature: self.temperature,
+            max_tokens: self.max_tokens,
+            top_p: self.top_p,
+            stop: self.stop.clone(),
+            seed: self.seed,
+            presence_penalty: self.presence_penalty,
+            frequency_penalty: self.frequency_penalty,
+            n: self.n,
+            priority: self
+                .priority
+                .as_deref()
+                .and_then(Priority::parse)
+                .unwrap_or(Priority::Interactive),
+            tenant_id: self.tenant_id.clone(),
+            metadata: std::collections::HashMap::new(),
+        }
+    }
+
+    fn chat_request(&self, prompt: &str) -> ChatRequest {
+        ChatRequest::new(vec![
+            ChatMessage::user(prompt).with_parts(self.parts.clone())
+        ])
+    }
 }
 
 #[derive(Serialize, ToSchema)]
@@ -53,15 +129,29 @@ pub struct InvokeResponse {
     pub tasks: Vec<serde_json::Value>,
     #[schema(example = "mock")]
     pub adapter_used: String,
+    /// 提供商返回的 token 用量，未返回时省略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>, example = json!({"prompt_tokens": 12, "completion_tokens": 30, "total_tokens": 42, "cached_tokens": 0}))]
+    pub usage: Option<Usage>,
+    /// 经 fallback 适配器调用时实际处理请求的成员
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "qianwen")]
+    pub served_by: Option<String>,
+    /// 由适配器的响应缓存返回
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub cached: bool,
 }
 
 pub async fn invoke_handler(
     Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
     Json(payload): Json<InvokeRequest>,
-) -> Json<serde_json::Value> {
-    use crate::routes::common::{error_response, ok_response};
+) -> Response {
+    use crate::routes::common::{adapter_error_response, error_response, ok_response};
     info!("Processing invoke request: {}", payload.input);
 
+    let stream_requested = payload.stream || accepts_event_stream(&headers);
+
     state.metrics.increment("invoke_requests_total");
 
     state.audit_log.log_action(
@@ -111,7 +201,8 @@ pub async fn invoke_handler(
                         warn!("No adapter available from routing configuration");
                         return error_response(
                             "No adapter available. Please specify an adapter or configure routing.",
-                        );
+                        )
+                        .into_response();
                     }
                 }
             }
@@ -131,7 +222,7 @@ pub async fn invoke_handler(
         }
 
         if let Err(e) = register_adapter_dynamically(&state, config).await {
-            return error_response(&e);
+            return error_response(&e).into_response();
         }
     }
 
@@ -235,7 +326,7 @@ pub async fn invoke_handler(
         Ok(id) => id,
         Err(e) => {
             error!("Failed to enqueue task: {}", e);
-            return error_response("Failed to queue task");
+            return error_response("Failed to queue task").into_response();
         }
     };
 
@@ -259,6 +350,22 @@ pub async fn invoke_handler(
 
     let prompt_to_use = context.processed_input.as_ref().unwrap_or(&final_prompt);
 
+    if stream_requested {
+        let adapter = state.adapter_registry.read().await.get(&adapter_name).await;
+        let Some(adapter) = adapter else {
+            error!("Adapter not found: {}", adapter_name);
+            return error_response("No adapter available").into_response();
+        };
+
+        let options = payload.invoke_options();
+        let request = payload.chat_request(prompt_to_use);
+
+        return stream_invoke(state, adapter, &request, options, context, task_messages).await;
+    }
+
+    let mut usage = None;
+    let mut served_by = None;
+    let mut cached = false;
     match state.adapter_registry.read().await.get(&adapter_name).await {
         Some(adapter) => {
             info!("Using adapter: {}", adapter_name);
@@ -266,24 +373,30 @@ pub async fn invoke_handler(
 
             let start = std::time::Instant::now();
 
-            let options = llm_adapter::InvokeOptions {
-                user_id: payload.user_id.clone(),
-                model: payload.model.clone(),
-                temperature: None,
-                max_tokens: None,
-                metadata: std::collections::HashMap::new(),
-            };
+            let mut options = payload.invoke_options();
+            if let Some(directive) = cache_control(&headers) {
+                options
+                    .metadata
+                    .insert(CACHE_CONTROL.to_string(), serde_json::json!(directive));
+            }
 
-            let res = match adapter.invoke_with_options(prompt_to_use, &options).await {
-                Ok(res) => {
+            let request = payload.chat_request(prompt_to_use);
+            let res = match adapter.chat(&request, &options).await {
+                Ok(response) => {
                     let duration = start.elapsed().as_secs_f64();
                     record_adapter_success(&state.metrics, &adapter_name, duration);
-                    res
+                    usage = response.usage;
+                    served_by = response.served_by;
+                    cached = response.cached;
+                    if cached {
+                        state.metrics.increment("invoke_cache_hits_total");
+                    }
+                    response.content
                 }
                 Err(e) => {
                     error!("Adapter invocation failed: {}", e);
                     record_adapter_error(&state.metrics, &adapter_name);
-                    format!("Error: {}", e)
+                    return adapter_error_response(&e);
                 }
             };
 
@@ -308,7 +421,157 @@ pub async fn invoke_handler(
         result: final_result,
         tasks: task_messages,
         adapter_used: adapter_name.to_string(),
+        usage,
+        served_by,
+        cached,
     })
+    .into_response()
+}
+
+async fn stream_invoke(
+    state: Arc<AppState>,
+    adapter: Arc<dyn Adapter + Send + Sync>,
+    request: &ChatRequest,
+    options: InvokeOptions,
+    mut context: ProcessingContext,
+    tasks: Vec<serde_json::Value>,
+) -> Response {
+    use crate::routes::common::adapter_error_response;
+
+    let adapter_name = context.adapter_name.clone();
+    info!("Streaming with adapter: {}", adapter_name);
+    record_adapter_call(&state.metrics, &adapter_name);
+
+    let start = std::time::Instant::now();
+
+    let mut upstream = match adapter.chat_stream(request, &options).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            error!("Adapter stream invocation failed: {}", e);
+            record_adapter_error(&state.metrics, &adapter_name);
+            return adapter_error_response(&e);
+        }
+    };
+
+    let (tx, rx) = tokio::sync::mpsc::channel::<Result<SseEvent, Infallible>>(32);
+
+    tokio::spawn(async move {
+        let mut buffer = StreamingOutputBuffer::new();
+        let mut output = String::new();
+        let mut usage = None;
+        let mut finish_reason = None;
+        let mut failed = false;
+
+        while let Some(item) = upstream.next().await {
+            match item {
+                Ok(chunk) => {
+                    output.push_str(&chunk.delta);
+                    if chunk.usage.is_some() {
+                        usage = chunk.usage;
+                    }
+                    if chunk.finish_reason.is_some() {
+                        finish_reason = chunk.finish_reason;
+                    }
+
+                    if let Some(ready) = buffer.push(&chunk.delta) {
+                        let delta = state
+                            .postprocessor_chain
+                            .post_process_chunk(&context, &ready);
+                        if tx.send(Ok(chunk_event(&delta))).await.is_err() {
+                            info!("Stream client disconnected: {}", context.request_id);
+                            return;
+                        }
+                    }
+                }
+                Err(e) => {
+                    error!("Adapter stream failed: {}", e);
+                    failed = true;
+                    let event = SseEvent::default()
+                        .event("error")
+                        .json_data(stream_error_data(&e))
+                        .unwrap_or_default();
+                    let _ = tx.send(Ok(event)).await;
+                    break;
+                }
+            }
+        }
+
+        if let Some(rest) = buffer.finish() {
+            let delta = state
+                .postprocessor_chain
+                .post_process_chunk(&context, &rest);
+            let _ = tx.send(Ok(chunk_event(&delta))).await;
+        }
+
+        if failed {
+            record_adapter_error(&state.metrics, &adapter_name);
+        } else {
+            record_adapter_success(&state.metrics, &adapter_name, start.elapsed().as_secs_f64());
+        }
+
+        context = context.with_output(output);
+        if let Err(e) = state.postprocessor_chain.post_process(&mut context).await {
+            error!("Post-processing failed: {}", e);
+        }
+
+        let done = SseEvent::default()
+            .event("done")
+            .json_data(serde_json::json!({
+                "request_id": context.request_id,
+                "adapter_used": adapter_name,
+                "usage": usage,
+                "finish_reason": finish_reason,
+                "tasks": tasks,
+            }))
+            .unwrap_or_default();
+        let _ = tx.send(Ok(done)).await;
+    });
+
+    Sse::new(ReceiverStream::new(rx))
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+fn chunk_event(delta: &str) -> SseEvent {
+    SseEvent::default()
+        .event("chunk")
+        .json_data(serde_json::json!({ "delta": delta }))
+        .unwrap_or_default()
+}
+
+/// 流式错误事件携带与非流式响应相同的错误码
+fn stream_error_data(err: &anyhow::Error) -> serde_json::Value {
+    match err.downcast_ref::<AdapterError>() {
+        Some(adapter_error) => serde_json::json!({
+            "code": adapter_error.code(),
+            "message": adapter_error.to_string()
+        }),
+        None => serde_json::json!({ "message": err.to_string() }),
+    }
+}
+
+fn accepts_event_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.contains("text/event-stream"))
+        .unwrap_or(false)
+}
+
+/// 请求头 `Cache-Control` 中的 no-store / no-cache 指令，no-store 优先
+fn cache_control(headers: &HeaderMap) -> Option<&'static str> {
+    let value = headers.get(header::CACHE_CONTROL)?.to_str().ok()?;
+    let directives: Vec<String> = value
+        .split(',')
+        .map(|d| d.trim().to_ascii_lowercase())
+        .collect();
+    if directives.iter().any(|d| d == "no-store") {
+        Some("no-store")
+    } else if directives.iter().any(|d| d == "no-cache") {
+        Some("no-cache")
+    } else {
+        None
+    }
 }
 
 fn is_potential_api_key(value: &str) -> bool {
@@ -9,15 +9,24 @@ use utoipa::OpenApi;
     tag = "invoke",
     request_body = handlers::InvokeRequest,
     responses(
-        (status = 200, description = "成功处理请求", content_type = "application/json"),
+        (status = 200, description = "成功处理请求（stream 为 true 或 Accept 为 text/event-stream 时以 SSE 返回 chunk/error/done 事件）", content_type = "application/json"),
+        (status = 400, description = "请求参数无效", body = crate::routes::common::ErrorResponse),
+        (status = 401, description = "提供商鉴权失败", body = crate::routes::common::ErrorResponse),
+        (status = 413, description = "超出模型上下文长度", body = crate::routes::common::ErrorResponse),
+        (status = 422, description = "内容被提供商审核拦截", body = crate::routes::common::ErrorResponse),
+        (status = 429, description = "被限流，可能带有 Retry-After", body = crate::routes::common::ErrorResponse),
+        (status = 503, description = "适配器熔断中，可能带有 Retry-After", body = crate::routes::common::ErrorResponse),
+        (status = 502, description = "上游服务错误或响应无法解析", body = crate::routes::common::ErrorResponse),
+        (status = 504, description = "上游请求超时", body = crate::routes::common::ErrorResponse),
         (status = 500, description = "服务器内部错误", body = crate::routes::common::ErrorResponse)
     )
 )]
 pub async fn invoke_handler(
     axum::Extension(state): axum::Extension<std::sync::Arc<crate::state::AppState>>,
+    headers: axum::http::HeaderMap,
     axum::Json(payload): axum::Json<handlers::InvokeRequest>,
-) -> axum::Json<serde_json::Value> {
-    handlers::invoke_handler(axum::Extension(state), axum::Json(payload)).await
+) -> axum::response::Response {
+    handlers::invoke_handler(axum::Extension(state), headers, axum::Json(payload)).await
 }
 
 pub fn invoke_routes() -> Router {
@@ -4,14 +4,19 @@ use crate::application::Planner;
 use crate::application::PostprocessorChain;
 use crate::application::PromptStore;
 use crate::domain::config::manager::ConfigManager;
-use crate::infrastructure::cache::{EmbeddingCache, SessionCache};
+use crate::infrastructure::cache::{EmbeddingCache, ResponseCacheStore, SessionCache};
 use crate::infrastructure::messaging::mcp::bus::McpBus;
 use crate::infrastructure::queue::{TaskQueue, TaskWorker};
+use crate::monitor::event::{Event, EventLevel};
 use crate::monitor::{AuditLog, EventBus, Metrics};
+use llm_adapter::budget::{BudgetEvent, BudgetEventKind};
 use llm_adapter::AdapterRegistry;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
+const ROUTING_HEALTH_INTERVAL: Duration = Duration::from_secs(10);
+
 pub struct AppState {
     pub adapter_registry: Arc<RwLock<AdapterRegistry>>,
     pub mcp_bus: McpBus,
@@ -31,7 +36,17 @@ pub struct AppState {
 
 impl AppState {
     pub fn new() -> Self {
-        let registry = AdapterRegistry::new();
+        let redis_url = std::env::var("REDIS_URL").ok();
+        let redis_url_str = redis_url.as_deref();
+
+        // 配置了 Redis 时各实例共享适配器的响应缓存
+        let mut registry = AdapterRegistry::new();
+        if redis_url.is_some() {
+            registry = registry.with_cache_store(Arc::new(ResponseCacheStore::new(redis_url_str)));
+        }
+        let budget_manager = registry.budget_manager();
+        let budget_events = budget_manager.subscribe();
+        let pricing = registry.pricing();
 
         let config_manager = Arc::new(ConfigManager::new());
 
@@ -50,6 +65,8 @@ impl AppState {
             }
 
             let config = config_manager_clone.get_config().await;
+            budget_manager.set_budgets(config.budgets.clone());
+            pricing.set_prices(config.pricing.clone());
             if !config.adapters.is_empty() {
                 let configs: Vec<_> = config.adapters.values().cloned().collect();
                 if let Err(e) = registry_for_spawn
@@ -63,9 +80,6 @@ impl AppState {
             }
         });
 
-        let redis_url = std::env::var("REDIS_URL").ok();
-        let redis_url_str = redis_url.as_deref();
-
         let session_cache = SessionCache::new(redis_url_str);
         let embedding_cache = EmbeddingCache::new(redis_url_str);
 
@@ -86,6 +100,11 @@ impl AppState {
 
         let event_bus = Arc::new(EventBus::new());
         let audit_log = Arc::new(AuditLog::new(event_bus.clone()));
+        tokio::spawn(forward_budget_events(budget_events, event_bus.clone()));
+        tokio::spawn(refresh_routing_health(
+            registry_clone.clone(),
+            config_manager.clone(),
+        ));
         let metrics = Arc::new(Metrics::new());
 
         let postprocessor_chain = Arc::new(PostprocessorChain::with_defaults(
@@ -113,3 +132,64 @@ impl AppState {
         }
     }
 }
+
+/// 定期探测路由规则引用的适配器，请求路径只读取标记好的健康状态
+async fn refresh_routing_health(
+    registry: Arc<RwLock<AdapterRegistry>>,
+    config_manager: Arc<ConfigManager>,
+) {
+    let mut interval = tokio::time::interval(ROUTING_HEALTH_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+
+        let router = config_manager.router();
+        let mut adapters = Vec::new();
+        {
+            let registry = registry.read().await;
+            for name in router.adapter_names().await {
+                if let Some(adapter) = registry.get(&name).await {
+                    adapters.push((name, adapter));
+                }
+            }
+        }
+
+        let checks = adapters.into_iter().map(|(name, adapter)| async move {
+            let healthy = adapter.health().await;
+            (name, healthy)
+        });
+        for (name, healthy) in futures::future::join_all(checks).await {
+            router.set_adapter_health(&name, healthy).await;
+        }
+    }
+}
+
+/// 将预算阈值事件转发到 EventBus
+async fn forward_budget_events(
+    mut events: tokio::sync::broadcast::Receiver<BudgetEvent>,
+    event_bus: Arc<EventBus>,
+) {
+    use tokio::sync::broadcast::error::RecvError;
+
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!("Dropped {} budget events", skipped);
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+
+        let (event_type, level) = match event.kind {
+            BudgetEventKind::Warning => ("budget.warning", EventLevel::Warn),
+            BudgetEventKind::Exceeded => ("budget.exceeded", EventLevel::Error),
+        };
+        event_bus.publish(Event::new(
+            event_type.to_string(),
+            "budget".to_string(),
+            serde_json::to_value(&event).unwrap_or_default(),
+            level,
+        ));
+    }
+}
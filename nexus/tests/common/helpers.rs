@@ -1,6 +1,10 @@
+use crate::common::mocks::create_mock_adapter;
 use axum_test::TestServer;
 use nexus::create_test_app;
+use nexus::monitor::PrometheusMetrics;
+use nexus::state::AppState;
 use std::env;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TestMode {
@@ -29,6 +33,22 @@ pub fn create_test_server() -> TestServer {
     TestServer::new(app).expect("Failed to create test server")
 }
 
+/// 创建预先注册了 mock 适配器的测试服务器
+pub async fn create_test_server_with_mock() -> TestServer {
+    let state = Arc::new(AppState::new());
+    state
+        .adapter_registry
+        .read()
+        .await
+        .register("mock", create_mock_adapter("mock"))
+        .await;
+
+    let prometheus_metrics =
+        Arc::new(PrometheusMetrics::new().expect("Failed to create PrometheusMetrics"));
+    let app = nexus::create_app(state, prometheus_metrics, false);
+    TestServer::new(app).expect("Failed to create test server")
+}
+
 /// 等待适配器注册完成
 /// 在 Mock 模式下等待时间较短，真实模式下等待时间较长
 pub async fn wait_for_adapters() {
@@ -12,3 +12,110 @@ async fn test_list_adapters() {
     assert_eq!(json_response["status"], "ok");
     assert!(json_response["data"]["adapters"].is_array());
 }
+
+#[tokio::test]
+async fn test_adapter_health_endpoint() {
+    let server = create_test_server();
+    wait_for_adapters().await;
+
+    let register_response = server
+        .put("/api/config/reload/adapter")
+        .json(&serde_json::json!({
+            "name": "health-probe",
+            "api_key": "test-key",
+            "base_url": "http://127.0.0.1:1",
+            "enabled": true,
+            "metadata": { "health_check": "none" }
+        }))
+        .await;
+    register_response.assert_status_ok();
+
+    let response = server.get("/api/config/adapters/health-probe/health").await;
+    response.assert_status_ok();
+    let json_response: serde_json::Value = response.json();
+    assert_eq!(json_response["status"], "ok");
+    assert_eq!(json_response["data"]["health"]["healthy"], true);
+    assert_eq!(json_response["data"]["health"]["circuit"], "closed");
+
+    let response = server.get("/api/config/adapters/missing/health").await;
+    let json_response: serde_json::Value = response.json();
+    assert_eq!(json_response["status"], "error");
+}
+
+#[tokio::test]
+async fn test_reload_updates_rate_limits_in_place() {
+    let server = create_test_server();
+    wait_for_adapters().await;
+
+    let config = |rps: u64, max_concurrent: u64| {
+        serde_json::json!({
+            "name": "limits-reload",
+            "api_key": "test-key",
+            "base_url": "http://127.0.0.1:1",
+            "enabled": true,
+            "metadata": {
+                "rate_limit_rps": rps,
+                "max_concurrent": max_concurrent,
+                "health_check": "none"
+            }
+        })
+    };
+
+    let response = server
+        .put("/api/config/reload/adapter")
+        .json(&config(5, 2))
+        .await;
+    let json_response: serde_json::Value = response.json();
+    assert_eq!(
+        json_response["message"],
+        "Adapter limits-reload reloaded and registered"
+    );
+
+    let response = server
+        .put("/api/config/reload/adapter")
+        .json(&config(50, 4))
+        .await;
+    let json_response: serde_json::Value = response.json();
+    assert_eq!(
+        json_response["message"],
+        "Adapter limits-reload limits updated"
+    );
+
+    let metrics = server.get("/metrics").await.text();
+    assert!(metrics.contains(r#"nexus_adapter_concurrency_limit{adapter="limits-reload"} 4"#));
+}
+
+#[tokio::test]
+async fn test_adapter_models_endpoint() {
+    let server = create_test_server();
+    wait_for_adapters().await;
+
+    // auth_type 为 none 的自建服务无需 API Key
+    let register_response = server
+        .put("/api/config/reload/adapter")
+        .json(&serde_json::json!({
+            "name": "keyless-models",
+            "base_url": "http://127.0.0.1:1",
+            "enabled": true,
+            "metadata": { "auth_type": "none", "health_check": "none" }
+        }))
+        .await;
+    register_response.assert_status_ok();
+    let json_response: serde_json::Value = register_response.json();
+    assert_eq!(json_response["status"], "ok");
+
+    // 通用适配器不支持列出模型
+    let response = server
+        .get("/api/config/adapters/keyless-models/models")
+        .await;
+    let json_response: serde_json::Value = response.json();
+    assert_eq!(json_response["status"], "error");
+    assert!(json_response["message"]
+        .as_str()
+        .unwrap()
+        .contains("does not support model listing"));
+
+    let response = server.get("/api/config/adapters/missing/models").await;
+    let json_response: serde_json::Value = response.json();
+    assert_eq!(json_response["status"], "error");
+}
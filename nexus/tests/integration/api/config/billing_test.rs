@@ -0,0 +1,86 @@
+use llm_adapter::{AdapterConfig, Usage};
+use nexus::monitor::PrometheusMetrics;
+use nexus::state::AppState;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_billing_report_and_export() {
+    let state = Arc::new(AppState::new());
+    let tracker = {
+        let registry = state.adapter_registry.read().await;
+        registry
+            .register_from_config(
+                AdapterConfig::new("reporting".to_string()).with_api_key("test-key".to_string()),
+            )
+            .await
+            .unwrap();
+        registry.get_billing_tracker("reporting").unwrap()
+    };
+    for (user, tenant) in [("alice", "acme"), ("bob", "acme"), ("carol", "globex")] {
+        tracker
+            .record_model_usage(
+                "reporting".to_string(),
+                Some("gpt-4o".to_string()),
+                Some(user.to_string()),
+                uuid::Uuid::new_v4().to_string(),
+                &Usage::new(1000, 1000),
+                serde_json::json!({ "tenant_id": tenant }),
+            )
+            .await;
+    }
+
+    let prometheus_metrics =
+        Arc::new(PrometheusMetrics::new().expect("Failed to create PrometheusMetrics"));
+    let server =
+        axum_test::TestServer::new(nexus::create_app(state.clone(), prometheus_metrics, false))
+            .expect("Failed to create test server");
+
+    let json_response: serde_json::Value = server
+        .get("/api/config/billing/report")
+        .add_query_param("group_by", "tenant")
+        .add_query_param("granularity", "hour")
+        .await
+        .json();
+    let buckets = json_response["data"]["buckets"].as_array().unwrap();
+    assert_eq!(buckets.len(), 2);
+    assert_eq!(buckets[0]["key"], "acme");
+    assert_eq!(buckets[0]["requests"], 2);
+    assert_eq!(buckets[1]["key"], "globex");
+
+    // 时间范围之外没有数据
+    let json_response: serde_json::Value = server
+        .get("/api/config/billing/report")
+        .add_query_param("to", "2020-01-01T00:00:00Z")
+        .await
+        .json();
+    assert!(json_response["data"]["buckets"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+
+    let response = server.get("/api/config/billing/export").await;
+    response.assert_status_ok();
+    assert!(response
+        .header("content-type")
+        .to_str()
+        .unwrap()
+        .starts_with("text/csv"));
+    let csv = response.text();
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("timestamp,adapter,model"));
+    assert!(lines[1].contains(",reporting,gpt-4o,alice,acme,"));
+
+    let response = server
+        .get("/api/config/billing/export")
+        .add_query_param("format", "jsonl")
+        .add_query_param("adapter", "reporting")
+        .await;
+    let records: Vec<serde_json::Value> = response
+        .text()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[2]["user_id"], "carol");
+}
@@ -0,0 +1,78 @@
+use crate::common::{create_mock_adapter, wait_for_adapters};
+use llm_adapter::guard::{ConcurrencyConfig, ConcurrencyGuard};
+use llm_adapter::rate_limit::{RateLimitConfig, RateLimiter};
+use llm_adapter::{BillingTracker, WrappedAdapter};
+use nexus::monitor::PrometheusMetrics;
+use nexus::state::AppState;
+use serde_json::json;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_budget_blocks_invocations_and_publishes_events() {
+    let state = Arc::new(AppState::new());
+    wait_for_adapters().await;
+    {
+        let registry = state.adapter_registry.read().await;
+        let wrapped = WrappedAdapter::new(
+            create_mock_adapter("mock"),
+            Arc::new(RateLimiter::new(RateLimitConfig::default())),
+            Arc::new(BillingTracker::default()),
+            Arc::new(ConcurrencyGuard::new(ConcurrencyConfig::default())),
+        )
+        .with_budget_manager(registry.budget_manager());
+        registry.register("mock", Arc::new(wrapped)).await;
+    }
+    let prometheus_metrics =
+        Arc::new(PrometheusMetrics::new().expect("Failed to create PrometheusMetrics"));
+    let server =
+        axum_test::TestServer::new(nexus::create_app(state.clone(), prometheus_metrics, false))
+            .expect("Failed to create test server");
+
+    let response = server
+        .put("/api/config/budgets")
+        .json(&json!([{ "name": "empty", "scope": { "type": "user", "id": "*" }, "period": "daily" }]))
+        .await;
+    let json_response: serde_json::Value = response.json();
+    assert_eq!(json_response["status"], "error");
+
+    let response = server
+        .put("/api/config/budgets")
+        .json(&json!([{
+            "name": "per-user",
+            "scope": { "type": "user", "id": "*" },
+            "period": "daily",
+            "max_tokens": 1
+        }]))
+        .await;
+    response.assert_status_ok();
+
+    let invoke = |user_id: &'static str| {
+        server
+            .post("/api/invoke")
+            .json(&json!({ "input": "Hello", "adapter": "mock", "user_id": user_id }))
+    };
+    invoke("alice").await.assert_status_ok();
+
+    let response = invoke("alice").await;
+    response.assert_status(axum::http::StatusCode::TOO_MANY_REQUESTS);
+    let json_response: serde_json::Value = response.json();
+    assert_eq!(json_response["code"], "budget_exceeded");
+
+    // 每个用户单独计算
+    invoke("bob").await.assert_status_ok();
+
+    let json_response: serde_json::Value = server.get("/api/config/budgets").await.json();
+    assert_eq!(json_response["data"]["budgets"][0]["name"], "per-user");
+    assert_eq!(json_response["data"]["usage"].as_array().unwrap().len(), 2);
+
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let events = state.event_bus.get_events("budget", &today);
+    assert!(events
+        .iter()
+        .any(|event| event.event_type == "budget.exceeded" && event.data["subject"] == "alice"));
+
+    // 预算写入配置，导出后可以恢复
+    let exported: serde_json::Value = server.get("/api/config/export").await.json();
+    assert_eq!(exported["data"]["budgets"][0]["max_tokens"], 1);
+}
@@ -1,3 +1,6 @@
 pub mod adapters_test;
+pub mod billing_test;
+pub mod budgets_test;
 pub mod flags_test;
+pub mod pricing_test;
 pub mod routing_test;
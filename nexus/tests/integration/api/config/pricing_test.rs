@@ -0,0 +1,50 @@
+use nexus::monitor::PrometheusMetrics;
+use nexus::state::AppState;
+use serde_json::json;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_update_and_list_pricing() {
+    let state = Arc::new(AppState::new());
+    let prometheus_metrics =
+        Arc::new(PrometheusMetrics::new().expect("Failed to create PrometheusMetrics"));
+    let server =
+        axum_test::TestServer::new(nexus::create_app(state.clone(), prometheus_metrics, false))
+            .expect("Failed to create test server");
+
+    let response = server
+        .put("/api/config/pricing")
+        .json(&json!([{ "model": "gpt-4o", "input_price_per_1k": -1.0, "output_price_per_1k": 0.01 }]))
+        .await;
+    let json_response: serde_json::Value = response.json();
+    assert_eq!(json_response["status"], "error");
+
+    let response = server
+        .put("/api/config/pricing")
+        .json(&json!([{
+            "adapter": "openai",
+            "model": "gpt-4o*",
+            "input_price_per_1k": 0.0025,
+            "output_price_per_1k": 0.01,
+            "effective_from": "2025-01-01T00:00:00Z"
+        }]))
+        .await;
+    response.assert_status_ok();
+
+    let json_response: serde_json::Value = server.get("/api/config/pricing").await.json();
+    let price = &json_response["data"]["prices"][0];
+    assert_eq!(price["model"], "gpt-4o*");
+    assert_eq!(price["currency"], "USD");
+
+    let price = state
+        .adapter_registry
+        .read()
+        .await
+        .pricing()
+        .lookup("openai", Some("gpt-4o-mini"), chrono::Utc::now())
+        .unwrap();
+    assert_eq!(price.output_price_per_1k, 0.01);
+
+    let exported: serde_json::Value = server.get("/api/config/export").await.json();
+    assert_eq!(exported["data"]["pricing"][0]["adapter"], "openai");
+}
@@ -20,6 +20,7 @@ async fn test_readiness_endpoint() {
     let json_response: serde_json::Value = response.json();
     assert_eq!(json_response["status"], "ok");
     assert!(json_response["data"]["ready"].is_boolean());
+    assert!(json_response["data"]["circuits"].is_object());
 }
 
 #[tokio::test]
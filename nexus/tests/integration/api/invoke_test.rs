@@ -1,6 +1,40 @@
 use crate::common::fixtures::create_test_invoke_payload;
-use crate::common::{create_test_server, get_test_mode, wait_for_adapters, TestMode};
+use crate::common::{
+    create_test_server, create_test_server_with_mock, get_test_mode, wait_for_adapters, TestMode,
+};
+use async_trait::async_trait;
+use llm_adapter::{Adapter, AdapterError};
+use nexus::monitor::PrometheusMetrics;
+use nexus::state::AppState;
 use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 总是返回限流错误的适配器
+struct RateLimitedAdapter;
+
+#[async_trait]
+impl Adapter for RateLimitedAdapter {
+    fn name(&self) -> &str {
+        "limited"
+    }
+
+    async fn describe(&self) -> String {
+        "Rate limited adapter".to_string()
+    }
+
+    async fn invoke(&self, _prompt: &str) -> anyhow::Result<String> {
+        Err(AdapterError::RateLimited {
+            retry_after: Some(Duration::from_secs(12)),
+            message: "limited API error: slow down".to_string(),
+        }
+        .into())
+    }
+
+    async fn health(&self) -> bool {
+        true
+    }
+}
 
 #[tokio::test]
 async fn test_invoke_endpoint_success() {
@@ -60,3 +94,156 @@ async fn test_invoke_endpoint_no_adapter() {
     assert_eq!(json_response["status"], "ok");
     assert!(json_response["data"]["result"].is_string());
 }
+
+#[tokio::test]
+async fn test_invoke_endpoint_stream() {
+    let server = create_test_server_with_mock().await;
+
+    let response = server
+        .post("/api/invoke")
+        .json(&json!({
+            "input": "Hello, world!",
+            "adapter": "mock",
+            "user_id": "test_user",
+            "stream": true
+        }))
+        .await;
+
+    response.assert_status_ok();
+    let content_type = response.header("content-type");
+    assert!(content_type
+        .to_str()
+        .unwrap()
+        .starts_with("text/event-stream"));
+
+    let body = response.text();
+    let mut deltas = String::new();
+    let mut done = None;
+    for event in body.split("\n\n").filter(|e| !e.trim().is_empty()) {
+        let name = event
+            .lines()
+            .find_map(|l| l.strip_prefix("event: "))
+            .unwrap_or_default();
+        let data: serde_json::Value = event
+            .lines()
+            .find_map(|l| l.strip_prefix("data: "))
+            .map(|d| serde_json::from_str(d).unwrap())
+            .unwrap_or_default();
+        match name {
+            "chunk" => deltas.push_str(data["delta"].as_str().unwrap()),
+            "done" => done = Some(data),
+            _ => {}
+        }
+    }
+
+    assert!(deltas.starts_with("Mock response to: Hello, world!"));
+    let done = done.expect("missing done event");
+    assert_eq!(done["adapter_used"], "mock");
+    assert!(done["request_id"].is_string());
+    assert!(done["tasks"].is_array());
+}
+
+#[tokio::test]
+async fn test_invoke_endpoint_stream_via_accept_header() {
+    let server = create_test_server_with_mock().await;
+
+    let response = server
+        .post("/api/invoke")
+        .add_header("accept", "text/event-stream")
+        .json(&json!({
+            "input": "Hello",
+            "adapter": "mock"
+        }))
+        .await;
+
+    response.assert_status_ok();
+    let body = response.text();
+    assert!(body.contains("event: chunk"));
+    assert!(body.contains("event: done"));
+}
+
+#[tokio::test]
+async fn test_invoke_endpoint_maps_adapter_errors() {
+    let state = Arc::new(AppState::new());
+    state
+        .adapter_registry
+        .read()
+        .await
+        .register("limited", Arc::new(RateLimitedAdapter))
+        .await;
+    let prometheus_metrics =
+        Arc::new(PrometheusMetrics::new().expect("Failed to create PrometheusMetrics"));
+    let server = axum_test::TestServer::new(nexus::create_app(state, prometheus_metrics, false))
+        .expect("Failed to create test server");
+
+    for stream in [false, true] {
+        let response = server
+            .post("/api/invoke")
+            .json(&json!({
+                "input": "Hello",
+                "adapter": "limited",
+                "stream": stream
+            }))
+            .await;
+
+        response.assert_status(axum::http::StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.header("retry-after"), "12");
+        let json_response: serde_json::Value = response.json();
+        assert_eq!(json_response["status"], "error");
+        assert_eq!(json_response["code"], "rate_limited");
+        assert!(json_response["message"]
+            .as_str()
+            .unwrap()
+            .contains("slow down"));
+    }
+}
+
+#[tokio::test]
+async fn test_invoke_endpoint_accepts_content_parts() {
+    let server = create_test_server_with_mock().await;
+
+    let response = server
+        .post("/api/invoke")
+        .json(&json!({
+            "input": "Describe this page",
+            "adapter": "mock",
+            "parts": [
+                {"type": "image", "source": {"type": "url", "url": "https://example.com/page.png"}},
+                {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "iVBORw0KGgo="}, "detail": "low"}
+            ]
+        }))
+        .await;
+
+    response.assert_status_ok();
+    let json_response: serde_json::Value = response.json();
+    assert!(json_response["data"]["result"]
+        .as_str()
+        .unwrap()
+        .contains("[2 attachments]"));
+
+    // 只支持文本的适配器拒绝图片，而不是静默丢弃
+    let state = Arc::new(AppState::new());
+    state
+        .adapter_registry
+        .read()
+        .await
+        .register("limited", Arc::new(RateLimitedAdapter))
+        .await;
+    let prometheus_metrics =
+        Arc::new(PrometheusMetrics::new().expect("Failed to create PrometheusMetrics"));
+    let server = axum_test::TestServer::new(nexus::create_app(state, prometheus_metrics, false))
+        .expect("Failed to create test server");
+
+    let response = server
+        .post("/api/invoke")
+        .json(&json!({
+            "input": "Describe this page",
+            "adapter": "limited",
+            "parts": [{"type": "image", "source": {"type": "url", "url": "https://example.com/page.png"}}]
+        }))
+        .await;
+
+    response.assert_status(axum::http::StatusCode::BAD_REQUEST);
+    let json_response: serde_json::Value = response.json();
+    assert_eq!(json_response["code"], "invalid_request");
+}
@@ -0,0 +1,109 @@
+use agentflow::{Agent, AgentConfig, AgentContext, AgentMessage, AgentRole, MessageType};
+use async_trait::async_trait;
+use llm_adapter::{Adapter, ChatRequest, ChatRole, InvokeOptions, InvokeResponse};
+use nexus::integration::{LLMAdapterProvider, LLMAgent};
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct RecordingAdapter {
+    requests: Mutex<Vec<ChatRequest>>,
+}
+
+#[async_trait]
+impl Adapter for RecordingAdapter {
+    fn name(&self) -> &str {
+        "recording"
+    }
+
+    async fn describe(&self) -> String {
+        "Recording adapter".to_string()
+    }
+
+    async fn invoke(&self, _prompt: &str) -> anyhow::Result<String> {
+        anyhow::bail!("LLMAgent should use the chat API")
+    }
+
+    async fn chat(
+        &self,
+        request: &ChatRequest,
+        _options: &InvokeOptions,
+    ) -> anyhow::Result<InvokeResponse> {
+        self.requests.lock().unwrap().push(request.clone());
+        Ok(InvokeResponse::new("好的，已完成"))
+    }
+
+    async fn health(&self) -> bool {
+        true
+    }
+}
+
+/// 测试 LLMAgent 以 system/user/assistant 角色发送对话历史
+#[tokio::test]
+async fn test_llm_agent_sends_role_based_messages() {
+    let adapter = Arc::new(RecordingAdapter::default());
+    let provider = Arc::new(LLMAdapterProvider::new(
+        "recording".to_string(),
+        adapter.clone(),
+    ));
+    let agent = LLMAgent::new(
+        AgentConfig::new(
+            "assistant_1".to_string(),
+            "Helper".to_string(),
+            AgentRole::Assistant,
+            "测试助手".to_string(),
+            "你是一个乐于助人的助手".to_string(),
+            "recording".to_string(),
+        ),
+        provider,
+    );
+
+    let mut context = AgentContext::new();
+    context.add_message(AgentMessage::new(
+        "user".to_string(),
+        "用户".to_string(),
+        None,
+        "你好".to_string(),
+        MessageType::Text,
+    ));
+    context.add_message(AgentMessage::new(
+        "assistant_1".to_string(),
+        "Helper".to_string(),
+        Some("user".to_string()),
+        "你好，有什么可以帮你？".to_string(),
+        MessageType::Result,
+    ));
+
+    let response = agent
+        .process(
+            AgentMessage::new(
+                "user".to_string(),
+                "用户".to_string(),
+                None,
+                "帮我写个计划".to_string(),
+                MessageType::Text,
+            ),
+            &mut context,
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.message.content, "好的，已完成");
+
+    let requests = adapter.requests.lock().unwrap();
+    let messages = &requests[0].messages;
+    let roles: Vec<ChatRole> = messages.iter().map(|m| m.role).collect();
+    assert_eq!(
+        roles,
+        vec![
+            ChatRole::System,
+            ChatRole::User,
+            ChatRole::Assistant,
+            ChatRole::User
+        ]
+    );
+    assert!(messages[0].content.contains("你是一个乐于助人的助手"));
+    assert_eq!(messages[1].content, "你好");
+    // 非 ASCII 的发言者名称回退为 sender_id
+    assert_eq!(messages[1].name.as_deref(), Some("user"));
+    assert_eq!(messages[2].content, "你好，有什么可以帮你？");
+    assert_eq!(messages[3].content, "帮我写个计划");
+}
@@ -1 +1 @@
-
+pub mod llm_agent_test;
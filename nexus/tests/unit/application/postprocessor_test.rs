@@ -1,5 +1,6 @@
 use nexus::application::postprocessor::{
-    FormatMode, MergeStrategy, PostprocessorChain, ProcessingContext, RedactionMode,
+    FormatMode, MergeStrategy, PiiRedactionPostprocessor, PostprocessorChain, ProcessingContext,
+    RedactionMode, StreamingOutputBuffer,
 };
 use nexus::monitor::AuditLog;
 use nexus::monitor::EventBus;
@@ -32,3 +33,25 @@ async fn test_postprocessor_chain() {
     let result = chain.post_process(&mut context).await;
     assert!(result.is_ok());
 }
+
+/// 测试流式分片不会把邮箱截断在两个分片之间
+#[tokio::test]
+async fn test_streaming_chunk_redaction() {
+    let chain = PostprocessorChain::new().add(Arc::new(PiiRedactionPostprocessor::new(
+        RedactionMode::Remove,
+    )));
+    let context = ProcessingContext::new(None, "mock".to_string(), "input".to_string());
+
+    let mut buffer = StreamingOutputBuffer::new();
+    let mut output = String::new();
+    for delta in ["联系邮箱 user@exa", "mple.com 谢谢"] {
+        if let Some(ready) = buffer.push(delta) {
+            output.push_str(&chain.post_process_chunk(&context, &ready));
+        }
+    }
+    if let Some(rest) = buffer.finish() {
+        output.push_str(&chain.post_process_chunk(&context, &rest));
+    }
+
+    assert_eq!(output, "联系邮箱 [已脱敏] 谢谢");
+}
@@ -22,3 +22,38 @@ async fn test_routing_rules() {
 
     let _result = router.select_model(Some("user1"), None).await;
 }
+
+/// 不健康的适配器不参与路由
+#[tokio::test]
+async fn test_routing_skips_unhealthy_adapters() {
+    let router = ModelRouter::new();
+
+    let model = |model_name: &str, adapter_name: &str| ModelWeight {
+        model_name: model_name.to_string(),
+        adapter_name: adapter_name.to_string(),
+        weight: 1,
+        enabled: true,
+    };
+    router
+        .add_rule(RoutingRule {
+            name: "failover".to_string(),
+            strategy: RoutingStrategy::RoundRobin,
+            models: vec![model("model1", "primary"), model("model2", "backup")],
+            condition: None,
+            priority: 10,
+        })
+        .await;
+
+    router.set_adapter_health("primary", false).await;
+    for _ in 0..3 {
+        let (_, adapter) = router.select_model(None, None).await.unwrap();
+        assert_eq!(adapter, "backup");
+    }
+
+    router.set_adapter_health("backup", false).await;
+    assert!(router.select_model(None, None).await.is_none());
+
+    router.set_adapter_health("primary", true).await;
+    let (_, adapter) = router.select_model(None, None).await.unwrap();
+    assert_eq!(adapter, "primary");
+}
@@ -0,0 +1,62 @@
+use llm_adapter::guard::{ConcurrencyStats, WAIT_BUCKETS};
+use nexus::monitor::PrometheusMetrics;
+
+/// `waits` 为每次获得许可的等待秒数
+fn stats(acquired: u64, rejected: u64, shed: u64, waits: &[f64]) -> ConcurrencyStats {
+    ConcurrencyStats {
+        limit: 4,
+        available: 4,
+        queued: Default::default(),
+        acquired,
+        rejected,
+        shed,
+        adaptive: false,
+        wait_seconds_total: waits.iter().sum(),
+        wait_buckets: WAIT_BUCKETS
+            .iter()
+            .map(|bound| (*bound, waits.iter().filter(|w| *w <= bound).count() as u64))
+            .collect(),
+    }
+}
+
+/// 测试并发计数按增量累加，等待时间计入直方图
+#[test]
+fn test_concurrency_counters_accumulate_deltas() {
+    let metrics = PrometheusMetrics::new().unwrap();
+    let acquired = |adapter: &str| {
+        metrics
+            .adapter_concurrency_acquired_total
+            .with_label_values(&[adapter])
+            .get()
+    };
+
+    metrics.record_concurrency("openai", &stats(3, 1, 0, &[0.0, 0.0, 0.02]));
+    metrics.record_concurrency("openai", &stats(5, 1, 2, &[0.0, 0.0, 0.02, 0.0, 0.0]));
+    assert_eq!(acquired("openai"), 5);
+    assert_eq!(
+        metrics
+            .adapter_concurrency_shed_total
+            .with_label_values(&["openai"])
+            .get(),
+        2
+    );
+
+    // 重新注册后计数从零开始，计数器仍单调递增
+    metrics.record_concurrency("openai", &stats(2, 0, 0, &[0.0, 2.0]));
+    assert_eq!(acquired("openai"), 7);
+
+    // 直方图的样本数与获得许可的次数一致
+    let histogram = metrics
+        .adapter_concurrency_wait_seconds
+        .get("openai")
+        .unwrap();
+    assert_eq!(histogram.count, 7);
+    assert!((histogram.sum - 2.02).abs() < 1e-9);
+
+    let text = metrics.gather().unwrap();
+    assert!(text.contains("# TYPE nexus_adapter_concurrency_acquired_total counter"));
+    assert!(text.contains(
+        r#"nexus_adapter_concurrency_wait_seconds_bucket{adapter="openai",le="0.05"} 6"#
+    ));
+    assert!(text.contains(r#"nexus_adapter_concurrency_wait_seconds_count{adapter="openai"} 7"#));
+}